@@ -0,0 +1,196 @@
+use crate::{match_candidate, Candidate, FieldQueryResult, PresentationDefinition, Rejection};
+
+/// The evaluation of a single candidate against an Input Descriptor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateMatch {
+    /// The index of the candidate.
+    pub candidate: usize,
+    /// The Field Query Results, holding the matched values and the JSONPath expressions that located them, or the
+    /// reason why the candidate is rejected.
+    pub result: Result<Vec<FieldQueryResult>, Rejection>,
+}
+
+impl CandidateMatch {
+    pub fn is_accepted(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// The evaluation of every candidate against an Input Descriptor, from which the Holder can choose the candidate to
+/// submit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDescriptorMatches {
+    /// The `id` of the Input Descriptor.
+    pub id: String,
+    pub matches: Vec<CandidateMatch>,
+}
+
+impl InputDescriptorMatches {
+    /// Returns the indices of the candidates that can be submitted for the Input Descriptor.
+    pub fn accepted(&self) -> impl Iterator<Item = usize> + '_ {
+        self.matches
+            .iter()
+            .filter(|candidate_match| candidate_match.is_accepted())
+            .map(|candidate_match| candidate_match.candidate)
+    }
+}
+
+/// Evaluates every candidate against every Input Descriptor of the Presentation Definition, as described in
+/// [`match_candidate`].
+pub fn match_candidates(
+    presentation_definition: &PresentationDefinition,
+    candidates: &[Candidate],
+) -> Vec<InputDescriptorMatches> {
+    presentation_definition
+        .input_descriptors()
+        .iter()
+        .map(|input_descriptor| InputDescriptorMatches {
+            id: input_descriptor.id().clone(),
+            matches: candidates
+                .iter()
+                .enumerate()
+                .map(|(index, candidate)| CandidateMatch {
+                    candidate: index,
+                    result: match_candidate(presentation_definition, input_descriptor, candidate),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClaimFormatDesignation;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_candidates() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "definition",
+            "input_descriptors": [
+                {
+                    "id": "adult",
+                    "constraints": {
+                        "fields": [
+                            { "path": ["$.vc.credentialSubject.given_name"] },
+                            { "path": ["$.vc.credentialSubject.age"], "filter": { "type": "number", "minimum": 18 } },
+                            { "path": ["$.vc.credentialSubject.email"], "optional": true }
+                        ]
+                    }
+                },
+                {
+                    "id": "limited",
+                    "constraints": {
+                        "fields": [{ "path": ["$.vc.credentialSubject.given_name"] }],
+                        "limit_disclosure": "required"
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        let candidate = |claim: serde_json::Value| Candidate {
+            format: ClaimFormatDesignation::JwtVcJson,
+            alg: None,
+            claim,
+        };
+        let candidates = [
+            candidate(json!({ "vc": { "credentialSubject": { "given_name": "Ferris", "age": 21 } } })),
+            candidate(json!({ "vc": { "credentialSubject": { "given_name": "Ferris", "age": 12 } } })),
+            candidate(json!({ "vc": { "credentialSubject": { "age": 21 } } })),
+        ];
+
+        let [adult, limited] = match_candidates(&presentation_definition, &candidates)
+            .try_into()
+            .unwrap();
+        assert_eq!(adult.id, "adult");
+        assert_eq!(adult.accepted().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(
+            adult.matches[0].result,
+            Ok(vec![
+                FieldQueryResult::Some {
+                    value: json!("Ferris"),
+                    path: "$.vc.credentialSubject.given_name".to_string()
+                },
+                FieldQueryResult::Some {
+                    value: json!(21),
+                    path: "$.vc.credentialSubject.age".to_string()
+                },
+                FieldQueryResult::None,
+            ])
+        );
+        assert_eq!(
+            adult.matches[1].result,
+            Err(Rejection::FilterFailed {
+                path: "$.vc.credentialSubject.age".to_string(),
+                value: json!(12)
+            })
+        );
+        assert_eq!(
+            adult.matches[2].result,
+            Err(Rejection::MissingField {
+                paths: vec!["$.vc.credentialSubject.given_name".to_string()]
+            })
+        );
+
+        // The disclosure of `jwt_vc_json` Credentials cannot be limited.
+        assert_eq!(limited.accepted().count(), 0);
+        assert_eq!(limited.matches[0].result, Err(Rejection::LimitDisclosure));
+    }
+
+    #[test]
+    fn test_match_candidates_rejections() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "definition",
+            "input_descriptors": [
+                {
+                    "id": "sd_jwt",
+                    "format": { "vc+sd-jwt": { "alg": ["ES256"] } },
+                    "constraints": {
+                        "fields": [{ "path": ["$.given_name"] }],
+                        "limit_disclosure": "required"
+                    }
+                },
+                {
+                    "id": "no_fields",
+                    "constraints": {}
+                }
+            ]
+        }))
+        .unwrap();
+        let candidate = |format: ClaimFormatDesignation, alg: &str| Candidate {
+            format,
+            alg: Some(alg.to_string()),
+            claim: json!({ "given_name": "Ferris" }),
+        };
+        let candidates = [
+            candidate(ClaimFormatDesignation::VcSdJwt, "ES256"),
+            candidate(ClaimFormatDesignation::VcSdJwt, "EdDSA"),
+            candidate(ClaimFormatDesignation::JwtVcJson, "ES256"),
+        ];
+
+        let [sd_jwt, no_fields] = match_candidates(&presentation_definition, &candidates)
+            .try_into()
+            .unwrap();
+
+        // The disclosure of `vc+sd-jwt` Credentials can be limited, but only the accepted algorithm is allowed.
+        assert_eq!(sd_jwt.accepted().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(sd_jwt.matches[1].result, Err(Rejection::Format));
+        assert_eq!(sd_jwt.matches[2].result, Err(Rejection::Format));
+
+        // An Input Descriptor without fields cannot be matched by any candidate.
+        assert_eq!(no_fields.accepted().count(), 0);
+        assert!(no_fields
+            .matches
+            .iter()
+            .all(|candidate_match| candidate_match.result == Err(Rejection::NoFields)));
+
+        // Every Input Descriptor is evaluated, even when there are no candidates.
+        let matches = match_candidates(&presentation_definition, &[]);
+        assert_eq!(
+            matches.iter().map(|matches| matches.id.as_str()).collect::<Vec<_>>(),
+            vec!["sd_jwt", "no_fields"]
+        );
+        assert!(matches.iter().all(|matches| matches.matches.is_empty()));
+    }
+}
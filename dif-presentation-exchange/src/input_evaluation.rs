@@ -1,4 +1,8 @@
-use crate::InputDescriptor;
+use crate::{
+    presentation_definition::Directive,
+    relational_constraints::{issuer_of, same_identifier, subject_of},
+    ClaimFormatDesignation, InputDescriptor, PresentationDefinition,
+};
 use jsonpath_lib as jsonpath;
 use jsonschema::JSONSchema;
 
@@ -19,67 +23,172 @@ impl FieldQueryResult {
     }
 }
 
+/// The reason why a Claim does not satisfy an Input Descriptor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    /// The format of the Claim, or the algorithm with which it is secured, is not accepted.
+    Format,
+    /// The Input Descriptor requires limited disclosure, which the format of the Claim does not support.
+    LimitDisclosure,
+    /// The Claim is not issued by its subject, which the `subject_is_issuer` constraint requires.
+    SubjectIsIssuer,
+    /// The Input Descriptor has no `fields`.
+    NoFields,
+    /// The field has a `predicate`, but no `filter`.
+    PredicateWithoutFilter { paths: Vec<String> },
+    /// No value is located for any of the `paths` of a required field.
+    MissingField { paths: Vec<String> },
+    /// The `value` that is located at `path` does not pass the `filter` of a required field.
+    FilterFailed { path: String, value: serde_json::Value },
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Format => write!(f, "The format of the Claim is not accepted."),
+            Rejection::LimitDisclosure => write!(f, "The disclosure of the Claim cannot be limited."),
+            Rejection::SubjectIsIssuer => write!(f, "The Claim is not issued by its subject."),
+            Rejection::NoFields => write!(f, "The Input Descriptor has no fields."),
+            Rejection::PredicateWithoutFilter { paths } => {
+                write!(f, "The field `{}` has a predicate but no filter.", paths.join("`, `"))
+            }
+            Rejection::MissingField { paths } => write!(f, "The field `{}` is missing.", paths.join("`, `")),
+            Rejection::FilterFailed { path, value } => {
+                write!(f, "The value `{value}` of the field `{path}` does not pass the filter.")
+            }
+        }
+    }
+}
+
+/// A Claim that can be submitted for an Input Descriptor, tagged with its format and the algorithm with which it is
+/// secured. For Data Integrity proofs, the `alg` is the proof type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub format: ClaimFormatDesignation,
+    pub alg: Option<String>,
+    /// The decoded Claim, against which the `fields` of the Input Descriptor are evaluated.
+    pub claim: serde_json::Value,
+}
+
+/// Evaluates whether the `candidate` can be submitted for the Input Descriptor: its format must be accepted, its
+/// disclosure must be limitable when the Input Descriptor requires so, and it must meet the constraints as evaluated by
+/// [`match_input`]. Returns the Field Query Results, or the reason why the `candidate` is rejected.
+pub fn match_candidate(
+    presentation_definition: &PresentationDefinition,
+    input_descriptor: &InputDescriptor,
+    candidate: &Candidate,
+) -> Result<Vec<FieldQueryResult>, Rejection> {
+    if !presentation_definition.accepts_format(input_descriptor, &candidate.format, candidate.alg.as_deref()) {
+        return Err(Rejection::Format);
+    }
+    if input_descriptor.requires_limited_disclosure() && !candidate.format.supports_selective_disclosure() {
+        return Err(Rejection::LimitDisclosure);
+    }
+    match_input(input_descriptor, &candidate.claim)
+}
+
+/// Returns whether the `candidate` can be submitted for the Input Descriptor, as evaluated by [`match_candidate`].
+pub fn evaluate_candidate(
+    presentation_definition: &PresentationDefinition,
+    input_descriptor: &InputDescriptor,
+    candidate: &Candidate,
+) -> bool {
+    match_candidate(presentation_definition, input_descriptor, candidate).is_ok()
+}
+
 /// Input Evaluation as described in section [8. Input
 /// Evaluation](https://identity.foundation/presentation-exchange/spec/v2.0.0/#input-evaluation) of the DIF
 /// Presentation Exchange specification.
 pub fn evaluate_input(input_descriptor: &InputDescriptor, value: &serde_json::Value) -> bool {
+    match_input(input_descriptor, value).is_ok()
+}
+
+/// Evaluates the `value` as described in [`evaluate_input`]. Returns the Field Query Result of every field, in the
+/// order of the `fields`, or the reason why the `value` does not satisfy the Input Descriptor.
+pub fn match_input(
+    input_descriptor: &InputDescriptor,
+    value: &serde_json::Value,
+) -> Result<Vec<FieldQueryResult>, Rejection> {
     let selector = &mut jsonpath::selector(value);
 
+    // If the `subject_is_issuer` property is set to `required`, the Claim must be issued by its subject.
+    if input_descriptor.constraints().subject_is_issuer() == &Some(Directive::Required)
+        && !subject_of(value)
+            .zip(issuer_of(value))
+            .is_some_and(|(subject, issuer)| same_identifier(subject, issuer))
+    {
+        return Err(Rejection::SubjectIsIssuer);
+    }
+
     input_descriptor
         .constraints()
         .fields()
         .as_ref()
-        .map(|fields| {
-            let results: Vec<FieldQueryResult> = fields
+        .ok_or(Rejection::NoFields)?
+        .iter()
+        .map(|field| {
+            // A field with a `predicate` must have a `filter` to evaluate the predicate against.
+            if field.predicate().is_some() && field.filter().is_none() {
+                return Err(Rejection::PredicateWithoutFilter {
+                    paths: field.path().clone(),
+                });
+            }
+
+            let filter = field
+                .filter()
+                .as_ref()
+                .map(JSONSchema::compile)
+                .transpose()
+                .ok()
+                .flatten();
+
+            // The first candidate that does not validate against the `filter`, which explains why the field is
+            // rejected.
+            let mut filter_failure = None;
+
+            // For each JSONPath expression in the `path` array (incrementing from the 0-index), evaluate the JSONPath
+            // expression against the candidate input and repeat the following subsequence on the result.
+            field
+                .path()
                 .iter()
-                .map(|field| {
-                    let filter = field
-                        .filter()
-                        .as_ref()
-                        .map(JSONSchema::compile)
-                        .transpose()
-                        .ok()
-                        .flatten();
-
-                    // For each JSONPath expression in the `path` array (incrementing from the 0-index),
-                    // evaluate the JSONPath expression against the candidate input and repeat the following
-                    // subsequence on the result.
-                    field
-                        .path()
-                        .iter()
-                        // Repeat until a Field Query Result is found, or the path array elements are exhausted:
-                        .find_map(|path| {
-                            // If the result returned no JSONPath match, skip to the next path array element.
-                            // Else, evaluate the first JSONPath match (candidate) as follows:
-                            selector(path).ok().and_then(|values| {
-                                values.into_iter().find_map(|result| {
-                                    // If the fields object has no `filter`, or if candidate validates against
-                                    // the JSON Schema descriptor specified in `filter`, then:
-                                    filter
-                                        .as_ref()
-                                        .map(|filter| filter.is_valid(result))
-                                        .unwrap_or(true)
-                                        // set Field Query Result to be candidate
-                                        .then(|| FieldQueryResult::Some {
-                                            value: result.to_owned(),
-                                            path: path.to_owned(),
-                                        })
-                                    // Else, skip to the next `path` array element.
+                // Repeat until a Field Query Result is found, or the path array elements are exhausted:
+                .find_map(|path| {
+                    // If the result returned no JSONPath match, skip to the next path array element.
+                    // Else, evaluate the first JSONPath match (candidate) as follows:
+                    selector(path).ok().and_then(|values| {
+                        values.into_iter().find_map(|result| {
+                            // If the fields object has no `filter`, or if candidate validates against the JSON
+                            // Schema descriptor specified in `filter`, then:
+                            if filter.as_ref().map_or(true, |filter| filter.is_valid(result)) {
+                                // set Field Query Result to be candidate
+                                Some(FieldQueryResult::Some {
+                                    value: result.to_owned(),
+                                    path: path.to_owned(),
                                 })
-                            })
+                            } else {
+                                // Else, skip to the next `path` array element.
+                                filter_failure.get_or_insert_with(|| Rejection::FilterFailed {
+                                    path: path.to_owned(),
+                                    value: result.to_owned(),
+                                });
+                                None
+                            }
                         })
-                        // If no value is located for any of the specified `path` queries, and the fields
-                        // object DOES NOT contain the `optional` property or it is set to `false`, reject the
-                        // field as invalid. If no value is located for any of the specified `path` queries and
-                        // the fields object DOES contain the `optional` property set to the value `true`,
-                        // treat the field as valid and proceed to the next fields object.
-                        .or_else(|| field.optional().and_then(|opt| opt.then(|| FieldQueryResult::None)))
-                        .unwrap_or(FieldQueryResult::Invalid)
+                    })
+                })
+                // If no value is located for any of the specified `path` queries, and the fields object DOES NOT
+                // contain the `optional` property or it is set to `false`, reject the field as invalid. If no value
+                // is located for any of the specified `path` queries and the fields object DOES contain the
+                // `optional` property set to the value `true`, treat the field as valid and proceed to the next
+                // fields object.
+                .or_else(|| field.optional().and_then(|opt| opt.then_some(FieldQueryResult::None)))
+                .ok_or_else(|| {
+                    filter_failure.unwrap_or_else(|| Rejection::MissingField {
+                        paths: field.path().clone(),
+                    })
                 })
-                .collect();
-            results.iter().all(FieldQueryResult::is_valid)
         })
-        .unwrap_or(false)
+        .collect()
 }
 
 #[cfg(test)]
@@ -109,6 +218,7 @@ mod tests {
             format: None,
             constraints,
             schema: None,
+            group: None,
         }
     }
 
@@ -316,5 +426,114 @@ mod tests {
             }),
             &credential
         ));
+
+        // Has a predicate, but NO filter.
+        assert!(!evaluate_input(
+            &input_descriptor(Constraints {
+                fields: Some(vec![Field {
+                    path: vec!["$.vc.type".to_string()],
+                    predicate: Some(Directive::Required),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            &credential
+        ));
+    }
+
+    #[test]
+    fn test_subject_is_issuer() {
+        let constraints = Constraints {
+            fields: Some(vec![Field {
+                path: vec!["$.credentialSubject.name".to_string()],
+                ..Default::default()
+            }]),
+            subject_is_issuer: Some(Directive::Required),
+            ..Default::default()
+        };
+
+        assert!(evaluate_input(
+            &input_descriptor(constraints.clone()),
+            &serde_json::json!({
+                "issuer": "did:example:holder",
+                "credentialSubject": { "id": "did:example:holder", "name": "Jane" }
+            })
+        ));
+        assert!(!evaluate_input(
+            &input_descriptor(constraints),
+            &serde_json::json!({
+                "issuer": { "id": "did:example:issuer" },
+                "credentialSubject": { "id": "did:example:holder", "name": "Jane" }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_candidate() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(serde_json::json!({
+            "id": "definition",
+            "format": { "jwt_vc_json": { "alg": ["EdDSA"] }, "vc+sd-jwt": { "alg": ["ES256"] } },
+            "input_descriptors": [
+                { "id": "any", "constraints": { "fields": [{ "path": ["$.vc.type"] }] } },
+                {
+                    "id": "ldp",
+                    "format": { "ldp_vp": { "proof_type": ["DataIntegrityProof"] } },
+                    "constraints": { "fields": [{ "path": ["$.vc.type"] }] }
+                },
+                {
+                    "id": "limited",
+                    "constraints": { "fields": [{ "path": ["$.vc.type"] }], "limit_disclosure": "required" }
+                }
+            ]
+        }))
+        .unwrap();
+        let [any, ldp, limited] = presentation_definition.input_descriptors().as_slice() else {
+            panic!("Expected three Input Descriptors.");
+        };
+        let candidate = |format: ClaimFormatDesignation, alg: &str| Candidate {
+            format,
+            alg: Some(alg.to_string()),
+            claim: json_example::<serde_json::Value>("../oid4vp/tests/examples/credentials/jwt_vc.json"),
+        };
+
+        assert!(evaluate_candidate(
+            &presentation_definition,
+            any,
+            &candidate(ClaimFormatDesignation::JwtVcJson, "EdDSA")
+        ));
+        // The algorithm is not accepted.
+        assert!(!evaluate_candidate(
+            &presentation_definition,
+            any,
+            &candidate(ClaimFormatDesignation::JwtVcJson, "ES256")
+        ));
+        // The format is not accepted.
+        assert!(!evaluate_candidate(
+            &presentation_definition,
+            any,
+            &candidate(ClaimFormatDesignation::LdpVc, "DataIntegrityProof")
+        ));
+        // The formats of the Input Descriptor override those of the Presentation Definition.
+        assert!(evaluate_candidate(
+            &presentation_definition,
+            ldp,
+            &candidate(ClaimFormatDesignation::LdpVc, "DataIntegrityProof")
+        ));
+        assert!(!evaluate_candidate(
+            &presentation_definition,
+            ldp,
+            &candidate(ClaimFormatDesignation::JwtVcJson, "EdDSA")
+        ));
+        // The disclosure of a `jwt_vc_json` Credential cannot be limited.
+        assert!(!evaluate_candidate(
+            &presentation_definition,
+            limited,
+            &candidate(ClaimFormatDesignation::JwtVcJson, "EdDSA")
+        ));
+        assert!(evaluate_candidate(
+            &presentation_definition,
+            limited,
+            &candidate(ClaimFormatDesignation::VcSdJwt, "ES256")
+        ));
     }
 }
@@ -1,9 +1,22 @@
+// `Option::is_none_or`, which this lint suggests, is only stable since Rust 1.82.
+#![allow(clippy::unnecessary_map_or)]
+
+pub mod candidate_matching;
 pub mod input_evaluation;
 pub mod presentation_definition;
 pub mod presentation_submission;
+pub mod relational_constraints;
+pub mod submission_evaluation;
+pub mod submission_requirement;
 
-pub use input_evaluation::evaluate_input;
+pub use candidate_matching::{match_candidates, CandidateMatch, InputDescriptorMatches};
+pub use input_evaluation::{
+    evaluate_candidate, evaluate_input, match_candidate, match_input, Candidate, FieldQueryResult, Rejection,
+};
 pub use presentation_definition::{
     ClaimFormatDesignation, ClaimFormatProperty, InputDescriptor, PresentationDefinition,
 };
 pub use presentation_submission::{InputDescriptorMappingObject, PathNested, PresentationSubmission};
+pub use relational_constraints::evaluate_relational_constraints;
+pub use submission_evaluation::{evaluate_submission, DescriptorEvaluation, SubmissionEvaluation};
+pub use submission_requirement::{Rule, SubmissionRequirement};
@@ -1,8 +1,9 @@
+use crate::submission_requirement::SubmissionRequirement;
 use getset::Getters;
 use jsonwebtoken::Algorithm;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// As specified in https://identity.foundation/presentation-exchange/#presentation-definition.
 #[allow(dead_code)]
@@ -18,6 +19,88 @@ pub struct PresentationDefinition {
     pub(crate) name: Option<String>,
     pub(crate) purpose: Option<String>,
     pub(crate) format: Option<HashMap<ClaimFormatDesignation, ClaimFormatProperty>>,
+    // When present, only the Input Descriptors that are required by these Submission Requirements must be satisfied.
+    #[getset(get = "pub")]
+    pub(crate) submission_requirements: Option<Vec<SubmissionRequirement>>,
+}
+
+impl PresentationDefinition {
+    /// Validates the constraints on the Presentation Definition that cannot be expressed by its type, i.e. that the
+    /// `id`s of its Input Descriptors are unique.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        match self
+            .input_descriptors
+            .iter()
+            .find(|input_descriptor| !ids.insert(input_descriptor.id.as_str()))
+        {
+            Some(input_descriptor) => Err(format!(
+                "The `id` of the Input Descriptor `{}` is not unique.",
+                input_descriptor.id
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether a submission that satisfies the Input Descriptors with the given `id`s is sufficient.
+    pub fn is_satisfied_by(&self, satisfied: &HashSet<&str>) -> bool {
+        match &self.submission_requirements {
+            Some(submission_requirements) => submission_requirements
+                .iter()
+                .all(|submission_requirement| submission_requirement.is_met(&self.input_descriptors, satisfied)),
+            None => self
+                .input_descriptors
+                .iter()
+                .all(|input_descriptor| satisfied.contains(input_descriptor.id.as_str())),
+        }
+    }
+
+    /// Selects the Input Descriptors that should be submitted, given the `id`s of the Input Descriptors that can be
+    /// satisfied. Returns `None` when the Presentation Definition cannot be satisfied.
+    pub fn select_input_descriptors(&self, satisfiable: &HashSet<&str>) -> Option<HashSet<&str>> {
+        match &self.submission_requirements {
+            Some(submission_requirements) => submission_requirements
+                .iter()
+                .map(|submission_requirement| submission_requirement.select(&self.input_descriptors, satisfiable))
+                .try_fold(HashSet::new(), |mut selected, selection| {
+                    selected.extend(selection?);
+                    Some(selected)
+                }),
+            None => self.is_satisfied_by(satisfiable).then(|| {
+                self.input_descriptors
+                    .iter()
+                    .map(|input_descriptor| input_descriptor.id.as_str())
+                    .collect()
+            }),
+        }
+    }
+
+    /// Returns the formats that are accepted for the Input Descriptor: those of the Input Descriptor itself, or else
+    /// those of the Presentation Definition. `None` means that any format is accepted.
+    pub fn formats<'a>(
+        &'a self,
+        input_descriptor: &'a InputDescriptor,
+    ) -> Option<&'a HashMap<ClaimFormatDesignation, ClaimFormatProperty>> {
+        input_descriptor.format.as_ref().or(self.format.as_ref())
+    }
+
+    /// Returns whether a Claim of the given `format`, secured with `alg`, may be submitted for the Input Descriptor.
+    /// A Claim is also accepted when only the format of the presentation in which it is presented is listed, in which
+    /// case its `alg` is not checked. When the `alg` is unknown, only the format is checked.
+    pub fn accepts_format(
+        &self,
+        input_descriptor: &InputDescriptor,
+        format: &ClaimFormatDesignation,
+        alg: Option<&str>,
+    ) -> bool {
+        match self.formats(input_descriptor) {
+            None => true,
+            Some(formats) => match formats.get(format) {
+                Some(property) => alg.map_or(true, |alg| property.accepts(alg)),
+                None => formats.contains_key(&format.presentation_format()),
+            },
+        }
+    }
 }
 
 /// As specified in https://identity.foundation/presentation-exchange/#input-descriptor-object.
@@ -29,12 +112,24 @@ pub struct InputDescriptor {
     // Must not conflict with other input descriptors.
     #[getset(get = "pub")]
     pub(crate) id: String,
+    #[getset(get = "pub")]
     pub(crate) name: Option<String>,
+    #[getset(get = "pub")]
     pub(crate) purpose: Option<String>,
     pub(crate) format: Option<HashMap<ClaimFormatDesignation, ClaimFormatProperty>>,
     #[getset(get = "pub")]
     pub(crate) constraints: Constraints,
     pub(crate) schema: Option<String>,
+    // The groups to which the Input Descriptor belongs, which are referred to by Submission Requirements.
+    #[getset(get = "pub")]
+    pub(crate) group: Option<Vec<String>>,
+}
+
+impl InputDescriptor {
+    /// Returns whether only the claims that are named in the `fields` may be disclosed.
+    pub fn requires_limited_disclosure(&self) -> bool {
+        self.constraints.limit_disclosure == Some(LimitDisclosure::Required)
+    }
 }
 
 // Its value MUST be an array of one or more format-specific algorithmic identifier references
@@ -54,6 +149,33 @@ pub enum ClaimFormatDesignation {
     AcVc,
     AcVp,
     MsoMdoc,
+    #[serde(rename = "vc+sd-jwt")]
+    VcSdJwt,
+}
+
+impl ClaimFormatDesignation {
+    /// Returns whether the claims of a Credential of this format can be disclosed selectively.
+    pub fn supports_selective_disclosure(&self) -> bool {
+        matches!(
+            self,
+            ClaimFormatDesignation::VcSdJwt
+                | ClaimFormatDesignation::MsoMdoc
+                | ClaimFormatDesignation::AcVc
+                | ClaimFormatDesignation::AcVp
+        )
+    }
+
+    /// Returns the format of the presentation in which a Claim of this format is presented. SD-JWT VCs and mdocs are
+    /// presented as is.
+    pub fn presentation_format(&self) -> ClaimFormatDesignation {
+        match self {
+            ClaimFormatDesignation::JwtVc => ClaimFormatDesignation::JwtVp,
+            ClaimFormatDesignation::JwtVcJson => ClaimFormatDesignation::JwtVpJson,
+            ClaimFormatDesignation::LdpVc => ClaimFormatDesignation::LdpVp,
+            ClaimFormatDesignation::AcVc => ClaimFormatDesignation::AcVp,
+            format => format.clone(),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -64,6 +186,17 @@ pub enum ClaimFormatProperty {
     ProofType(Vec<String>),
 }
 
+impl ClaimFormatProperty {
+    /// Returns whether a Claim that is secured with `alg` is accepted. For Data Integrity proofs, `alg` is the proof
+    /// type.
+    pub fn accepts(&self, alg: &str) -> bool {
+        match self {
+            ClaimFormatProperty::Alg(algs) => alg.parse().is_ok_and(|alg| algs.contains(&alg)),
+            ClaimFormatProperty::ProofType(proof_types) => proof_types.iter().any(|proof_type| proof_type == alg),
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[skip_serializing_none]
 #[derive(Deserialize, Debug, Getters, Default, PartialEq, Clone, Serialize)]
@@ -74,6 +207,15 @@ pub struct Constraints {
     // more than the data described in the `fields` array.
     #[getset(get = "pub")]
     pub(crate) limit_disclosure: Option<LimitDisclosure>,
+    // Whether the subject of the Claim must be its issuer, i.e. the Claim must be self-attested.
+    #[getset(get = "pub")]
+    pub(crate) subject_is_issuer: Option<Directive>,
+    // The fields of which the subject must be the Holder that submits the Claim.
+    #[getset(get = "pub")]
+    pub(crate) is_holder: Option<Vec<SubjectConstraint>>,
+    // The fields, possibly of other Input Descriptors, of which the subject must be the same.
+    #[getset(get = "pub")]
+    pub(crate) same_subject: Option<Vec<SubjectConstraint>>,
 }
 
 #[allow(dead_code)]
@@ -84,6 +226,26 @@ pub enum LimitDisclosure {
     Preferred,
 }
 
+/// Whether a Feature is required or merely preferred by the Verifier.
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Directive {
+    Required,
+    Preferred,
+}
+
+/// As specified in https://identity.foundation/presentation-exchange/spec/v2.0.0/#relational-constraint-feature.
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Getters, PartialEq, Clone, Serialize)]
+pub struct SubjectConstraint {
+    // The `id`s of the fields to which the constraint applies.
+    #[getset(get = "pub")]
+    pub(crate) field_id: Vec<String>,
+    #[getset(get = "pub")]
+    pub(crate) directive: Directive,
+}
+
 #[allow(dead_code)]
 #[skip_serializing_none]
 #[derive(Deserialize, Debug, Getters, Default, PartialEq, Clone, Serialize)]
@@ -92,6 +254,7 @@ pub struct Field {
     // The ability to declare multiple expressions in this way allows the Verifier to account for format differences.
     #[getset(get = "pub")]
     pub(crate) path: Vec<String>,
+    #[getset(get = "pub")]
     pub(crate) id: Option<String>,
     pub(crate) purpose: Option<String>,
     pub(crate) name: Option<String>,
@@ -100,6 +263,10 @@ pub struct Field {
     // TODO: check default behaviour
     #[getset(get = "pub")]
     pub(crate) optional: Option<bool>,
+    // Whether the Verifier wants to learn only whether the value matches the `filter`, rather than the value itself.
+    // Requires a `filter`. Predicate proofs are not supported, so the value itself is evaluated against the `filter`.
+    #[getset(get = "pub")]
+    pub(crate) predicate: Option<Directive>,
 }
 
 #[cfg(test)]
@@ -117,6 +284,24 @@ mod tests {
         serde_json::from_reader::<_, T>(file).expect("could not parse json")
     }
 
+    #[test]
+    fn test_validate() {
+        let mut presentation_definition: PresentationDefinition =
+            json_example("../oid4vp/tests/examples/request/pd_jwt_vc.json");
+        assert!(presentation_definition.validate().is_ok());
+
+        presentation_definition
+            .input_descriptors
+            .push(presentation_definition.input_descriptors[0].clone());
+        assert_eq!(
+            presentation_definition.validate(),
+            Err(format!(
+                "The `id` of the Input Descriptor `{}` is not unique.",
+                presentation_definition.input_descriptors[0].id
+            ))
+        );
+    }
+
     #[test]
     fn test_deserialize_presentation_definition() {
         assert_eq!(
@@ -152,10 +337,13 @@ mod tests {
                                 ..Default::default()
                             }
                         ]),
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/pd_ac_vc_sd.json")
         );
@@ -183,10 +371,13 @@ mod tests {
                             ..Default::default()
                         }]),
                         limit_disclosure: None,
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/pd_ac_vc.json")
         );
@@ -216,10 +407,13 @@ mod tests {
                             ..Default::default()
                         }]),
                         limit_disclosure: None,
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/pd_jwt_vc.json")
         );
@@ -249,10 +443,13 @@ mod tests {
                             ..Default::default()
                         }]),
                         limit_disclosure: None,
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/pd_ldp_vc.json")
         );
@@ -303,10 +500,13 @@ mod tests {
                                 ..Default::default()
                             },
                         ]),
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/pd_mdl_iso_cbor.json")
         );
@@ -348,10 +548,13 @@ mod tests {
                             }
                         ]),
                         limit_disclosure: Some(LimitDisclosure::Required),
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/vp_token_type_and_claims.json")
         );
@@ -379,10 +582,13 @@ mod tests {
                             ..Default::default()
                         }]),
                         limit_disclosure: None,
+                        ..Default::default()
                     },
                     schema: None,
+                    group: None,
                 }],
                 purpose: None,
+                submission_requirements: None,
             },
             json_example::<PresentationDefinition>("../oid4vp/tests/examples/request/vp_token_type_only.json")
         );
@@ -6,9 +6,9 @@ use serde_with::skip_serializing_none;
 #[allow(dead_code)]
 #[derive(Deserialize, Debug, Serialize, PartialEq, Clone)]
 pub struct PresentationSubmission {
-    // TODO: Must be unique.
+    // Must be a unique identifier, such as a UUID.
     pub id: String,
-    // TODO: Value must be the id value of a valid presentation definition.
+    // Must be the `id` value of a valid Presentation Definition, which is checked by `evaluate_submission`.
     pub definition_id: String,
     pub descriptor_map: Vec<InputDescriptorMappingObject>,
 }
@@ -22,7 +22,7 @@ pub struct InputDescriptorMappingObject {
     pub id: String,
     // Matches one of the Claim Format Designation. This denotes the data format of the Claim.
     pub format: ClaimFormatDesignation,
-    // TODO Must be a JSONPath string expression
+    // Must be a JSONPath string expression, which is checked by `evaluate_submission`.
     // Indicates the Claim submitted in relation to the identified Input Descriptor, When executed against the
     // top-level of the object the Presentation Submission is embedded within.
     pub path: String,
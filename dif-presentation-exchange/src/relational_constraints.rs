@@ -0,0 +1,270 @@
+use crate::{
+    presentation_definition::{Directive, SubjectConstraint},
+    InputDescriptor, PresentationDefinition,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Returns the identifier of the subject of a Claim, which is either the `sub` claim of a JWT or SD-JWT based
+/// Credential, or the `id` of the `credentialSubject`.
+pub fn subject_of(claim: &Value) -> Option<&str> {
+    claim
+        .get("sub")
+        .or_else(|| claim.pointer("/vc/credentialSubject/id"))
+        .or_else(|| claim.pointer("/credentialSubject/id"))
+        .and_then(Value::as_str)
+}
+
+/// Returns the identifier of the issuer of a Claim, which is either the `iss` claim of a JWT or SD-JWT based
+/// Credential, or the `issuer` of the Credential, which is either a string or an object with an `id`.
+pub fn issuer_of(claim: &Value) -> Option<&str> {
+    claim
+        .get("iss")
+        .or_else(|| claim.pointer("/vc/issuer"))
+        .or_else(|| claim.get("issuer"))
+        .and_then(|issuer| issuer.as_str().or_else(|| issuer.get("id").and_then(Value::as_str)))
+}
+
+/// Compares two identifiers as DIDs, so that a DID URL that refers to a key of a DID matches the DID itself.
+pub fn same_identifier(a: &str, b: &str) -> bool {
+    let did = |identifier: &str| identifier.split('#').next().unwrap_or_default().to_owned();
+    did(a) == did(b)
+}
+
+fn required(constraints: &[SubjectConstraint]) -> impl Iterator<Item = &SubjectConstraint> {
+    constraints
+        .iter()
+        .filter(|constraint| constraint.directive == Directive::Required)
+}
+
+/// Returns the `id`s of the Input Descriptors that contain a field with one of the given `field_id`s.
+fn input_descriptors_with_fields<'a>(
+    input_descriptors: &'a [InputDescriptor],
+    field_ids: &'a [String],
+) -> impl Iterator<Item = &'a str> {
+    input_descriptors
+        .iter()
+        .filter(|input_descriptor| {
+            input_descriptor
+                .constraints
+                .fields
+                .iter()
+                .flatten()
+                .any(|field| field.id.as_ref().is_some_and(|field_id| field_ids.contains(field_id)))
+        })
+        .map(|input_descriptor| input_descriptor.id.as_str())
+}
+
+/// Evaluates the `is_holder` and `same_subject` constraints of the Input Descriptors as described here:
+/// https://identity.foundation/presentation-exchange/spec/v2.0.0/#relational-constraint-feature. The `claims` are the
+/// submitted Claims by the `id` of the Input Descriptor they are submitted for. The `is_holder` constraints are only
+/// evaluated when the `holder` of the presentation is known. Only the `required` constraints are enforced. Returns
+/// the constraints that are not met.
+pub fn evaluate_relational_constraints(
+    definition: &PresentationDefinition,
+    claims: &HashMap<&str, &Value>,
+    holder: Option<&str>,
+) -> Vec<String> {
+    let mut errors = vec![];
+
+    for input_descriptor in &definition.input_descriptors {
+        let Some(claim) = claims.get(input_descriptor.id.as_str()) else {
+            continue;
+        };
+
+        if let Some(holder) = holder {
+            for constraint in required(input_descriptor.constraints.is_holder.as_deref().unwrap_or_default()) {
+                match subject_of(claim) {
+                    Some(subject) if same_identifier(subject, holder) => {}
+                    _ => errors.push(format!(
+                        "The subject of the fields `{}` of Input Descriptor `{}` is not the Holder.",
+                        constraint.field_id.join("`, `"),
+                        input_descriptor.id
+                    )),
+                }
+            }
+        }
+
+        for constraint in required(input_descriptor.constraints.same_subject.as_deref().unwrap_or_default()) {
+            let subjects: Vec<Option<&str>> =
+                input_descriptors_with_fields(&definition.input_descriptors, &constraint.field_id)
+                    .filter_map(|id| claims.get(id))
+                    .map(|claim| subject_of(claim))
+                    .collect();
+            let same_subject = subjects.first().is_some_and(|first| {
+                first.is_some_and(|first| {
+                    subjects
+                        .iter()
+                        .all(|subject| subject.is_some_and(|subject| same_identifier(subject, first)))
+                })
+            });
+            if !same_subject {
+                errors.push(format!(
+                    "The subjects of the fields `{}` are not the same.",
+                    constraint.field_id.join("`, `")
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn presentation_definition() -> PresentationDefinition {
+        serde_json::from_value(json!({
+            "id": "relational_constraints",
+            "input_descriptors": [
+                {
+                    "id": "passport",
+                    "constraints": {
+                        "fields": [{ "id": "passport_number", "path": ["$.credentialSubject.passport_number"] }],
+                        "is_holder": [{ "field_id": ["passport_number"], "directive": "required" }],
+                        "same_subject": [
+                            { "field_id": ["passport_number", "address"], "directive": "required" }
+                        ]
+                    }
+                },
+                {
+                    "id": "utility_bill",
+                    "constraints": {
+                        "fields": [{ "id": "address", "path": ["$.credentialSubject.address"] }]
+                    }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_relational_constraints() {
+        let definition = presentation_definition();
+        let passport = json!({ "credentialSubject": { "id": "did:example:holder", "passport_number": "123" } });
+        let utility_bill =
+            json!({ "vc": { "credentialSubject": { "id": "did:example:holder", "address": "Main St" } } });
+        let other_bill = json!({ "credentialSubject": { "id": "did:example:other", "address": "Main St" } });
+
+        assert!(evaluate_relational_constraints(
+            &definition,
+            &HashMap::from([("passport", &passport), ("utility_bill", &utility_bill)]),
+            Some("did:example:holder#key-1"),
+        )
+        .is_empty());
+
+        // Without a Holder, only the `same_subject` constraint is evaluated.
+        assert!(evaluate_relational_constraints(
+            &definition,
+            &HashMap::from([("passport", &passport), ("utility_bill", &utility_bill)]),
+            None,
+        )
+        .is_empty());
+
+        assert_eq!(
+            evaluate_relational_constraints(
+                &definition,
+                &HashMap::from([("passport", &passport), ("utility_bill", &other_bill)]),
+                Some("did:example:other"),
+            ),
+            vec![
+                "The subject of the fields `passport_number` of Input Descriptor `passport` is not the Holder."
+                    .to_string(),
+                "The subjects of the fields `passport_number`, `address` are not the same.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_relational_constraints_without_subject() {
+        let definition = presentation_definition();
+        let passport = json!({ "credentialSubject": { "passport_number": "123" } });
+        let utility_bill = json!({ "credentialSubject": { "id": "did:example:holder", "address": "Main St" } });
+
+        // A Claim without a subject can neither be bound to the Holder nor to the subject of another Claim.
+        assert_eq!(
+            evaluate_relational_constraints(
+                &definition,
+                &HashMap::from([("passport", &passport), ("utility_bill", &utility_bill)]),
+                Some("did:example:holder"),
+            ),
+            vec![
+                "The subject of the fields `passport_number` of Input Descriptor `passport` is not the Holder."
+                    .to_string(),
+                "The subjects of the fields `passport_number`, `address` are not the same.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_relational_constraints_not_enforced() {
+        let passport = json!({ "credentialSubject": { "id": "did:example:holder", "passport_number": "123" } });
+        let other_bill = json!({ "credentialSubject": { "id": "did:example:other", "address": "Main St" } });
+
+        // Constraints of Input Descriptors for which no Claim is submitted are not evaluated.
+        assert!(evaluate_relational_constraints(
+            &presentation_definition(),
+            &HashMap::from([("utility_bill", &other_bill)]),
+            Some("did:example:holder"),
+        )
+        .is_empty());
+
+        // Only `required` constraints are enforced.
+        let definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "preferred_relational_constraints",
+            "input_descriptors": [
+                {
+                    "id": "passport",
+                    "constraints": {
+                        "fields": [{ "id": "passport_number", "path": ["$.credentialSubject.passport_number"] }],
+                        "is_holder": [{ "field_id": ["passport_number"], "directive": "preferred" }],
+                        "same_subject": [
+                            { "field_id": ["passport_number", "address"], "directive": "preferred" }
+                        ]
+                    }
+                },
+                {
+                    "id": "utility_bill",
+                    "constraints": {
+                        "fields": [{ "id": "address", "path": ["$.credentialSubject.address"] }]
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        assert!(evaluate_relational_constraints(
+            &definition,
+            &HashMap::from([("passport", &passport), ("utility_bill", &other_bill)]),
+            Some("did:example:other"),
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_same_identifier() {
+        assert!(same_identifier("did:example:holder", "did:example:holder"));
+        assert!(same_identifier("did:example:holder#key-1", "did:example:holder"));
+        assert!(same_identifier("did:example:holder#key-1", "did:example:holder#key-2"));
+        assert!(!same_identifier("did:example:holder", "did:example:other"));
+        assert!(!same_identifier("did:example:holder", "did:example:holder2#key-1"));
+    }
+
+    #[test]
+    fn test_subject_and_issuer() {
+        assert_eq!(
+            subject_of(&json!({ "sub": "did:example:holder" })),
+            Some("did:example:holder")
+        );
+        assert_eq!(
+            issuer_of(&json!({ "issuer": { "id": "did:example:issuer" } })),
+            Some("did:example:issuer")
+        );
+        assert_eq!(
+            issuer_of(&json!({ "vc": { "issuer": "did:example:issuer" } })),
+            Some("did:example:issuer")
+        );
+        assert_eq!(issuer_of(&json!({})), None);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::{
+    evaluate_input, evaluate_relational_constraints, ClaimFormatDesignation, InputDescriptor,
+    InputDescriptorMappingObject, PresentationDefinition, PresentationSubmission,
+};
+use jsonpath_lib as jsonpath;
+use jsonwebtoken::{DecodingKey, Validation};
+use std::collections::{HashMap, HashSet};
+
+/// The result of resolving and evaluating a single entry of the `descriptor_map`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorEvaluation {
+    /// The `id` of the Input Descriptor that the entry refers to.
+    pub id: String,
+    /// The Claim that the `path` (and `path_nested`) of the entry resolves to.
+    pub claim: Option<serde_json::Value>,
+    /// The reason why the entry does not satisfy the Input Descriptor, or `None` when it does.
+    pub error: Option<String>,
+}
+
+impl DescriptorEvaluation {
+    pub fn is_satisfied(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The result of [`evaluate_submission`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubmissionEvaluation {
+    /// Errors that concern the Presentation Submission as a whole.
+    pub errors: Vec<String>,
+    pub descriptors: Vec<DescriptorEvaluation>,
+    /// The `id`s of the Input Descriptors of the Presentation Definition that no entry satisfies. These need not be
+    /// satisfied when the Submission Requirements of the Presentation Definition are met without them.
+    pub unsatisfied: Vec<String>,
+}
+
+impl SubmissionEvaluation {
+    /// Returns `true` when every entry of the `descriptor_map` satisfies its Input Descriptor, and the satisfied Input
+    /// Descriptors answer the Presentation Definition.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.descriptors.iter().all(DescriptorEvaluation::is_satisfied)
+    }
+
+    /// Returns the `id`s of the Input Descriptors that are satisfied.
+    pub fn satisfied(&self) -> impl Iterator<Item = &str> {
+        self.descriptors
+            .iter()
+            .filter(|descriptor| descriptor.is_satisfied())
+            .map(|descriptor| descriptor.id.as_str())
+    }
+
+    /// Returns all errors in a single message, or `None` when the submission is valid.
+    pub fn error_message(&self) -> Option<String> {
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .cloned()
+            .chain(self.descriptors.iter().filter_map(|descriptor| {
+                descriptor
+                    .error
+                    .as_ref()
+                    .map(|error| format!("Input Descriptor `{}`: {error}", descriptor.id))
+            }))
+            .collect();
+        (!errors.is_empty()).then(|| errors.join(" "))
+    }
+}
+
+/// Validates a Presentation Submission against the Presentation Definition it answers, as described in section [6.
+/// Presentation Submission](https://identity.foundation/presentation-exchange/spec/v2.0.0/#presentation-submission).
+/// The `presentation` is the object in which the Presentation Submission is embedded, against which the `path` of
+/// every entry of the `descriptor_map` is evaluated. Each `path_nested` is evaluated against the Claim that its parent
+/// resolves to, after decoding it according to the parent's format. Signatures are NOT verified, so the
+/// `presentation` must be verified separately. Finally [`evaluate_input`] is run on every resolved Claim, and the
+/// relational constraints are evaluated across the Claims, for which the `holder` is the verified identifier of the
+/// Holder that created the `presentation`, if known.
+pub fn evaluate_submission(
+    presentation_definition: &PresentationDefinition,
+    presentation_submission: &PresentationSubmission,
+    presentation: &serde_json::Value,
+    holder: Option<&str>,
+) -> SubmissionEvaluation {
+    let mut evaluation = SubmissionEvaluation::default();
+
+    if presentation_submission.id.is_empty() {
+        evaluation
+            .errors
+            .push("The Presentation Submission must have an `id`.".to_string());
+    }
+    if &presentation_submission.definition_id != presentation_definition.id() {
+        evaluation.errors.push(format!(
+            "The Presentation Submission refers to the Presentation Definition `{}` instead of `{}`.",
+            presentation_submission.definition_id,
+            presentation_definition.id()
+        ));
+    }
+
+    evaluation.descriptors = presentation_submission
+        .descriptor_map
+        .iter()
+        .map(|descriptor| {
+            let (claim, error) = match presentation_definition
+                .input_descriptors()
+                .iter()
+                .find(|input_descriptor| input_descriptor.id() == &descriptor.id)
+            {
+                Some(input_descriptor) => match resolve(descriptor, presentation) {
+                    Ok((format, alg, claim)) => {
+                        let error = satisfies(
+                            presentation_definition,
+                            input_descriptor,
+                            &format,
+                            alg.as_deref(),
+                            &claim,
+                        )
+                        .err();
+                        (Some(claim), error)
+                    }
+                    Err(error) => (None, Some(error)),
+                },
+                None => (
+                    None,
+                    Some("The Presentation Definition has no such Input Descriptor.".to_string()),
+                ),
+            };
+            DescriptorEvaluation {
+                id: descriptor.id.clone(),
+                claim,
+                error,
+            }
+        })
+        .collect();
+
+    let satisfied: HashSet<&str> = evaluation.satisfied().collect();
+    let unsatisfied: Vec<String> = presentation_definition
+        .input_descriptors()
+        .iter()
+        .map(|input_descriptor| input_descriptor.id())
+        .filter(|id| !satisfied.contains(id.as_str()))
+        .cloned()
+        .collect();
+    if !presentation_definition.is_satisfied_by(&satisfied) {
+        let error = match presentation_definition.submission_requirements() {
+            Some(_) => "The Submission Requirements are not met.".to_string(),
+            None => format!(
+                "The Input Descriptors `{}` are not satisfied.",
+                unsatisfied.join("`, `")
+            ),
+        };
+        evaluation.errors.push(error);
+    }
+    evaluation.unsatisfied = unsatisfied;
+
+    let claims: HashMap<&str, &serde_json::Value> = evaluation
+        .descriptors
+        .iter()
+        .filter(|descriptor| descriptor.is_satisfied())
+        .filter_map(|descriptor| Some((descriptor.id.as_str(), descriptor.claim.as_ref()?)))
+        .collect();
+    let errors = evaluate_relational_constraints(presentation_definition, &claims, holder);
+    evaluation.errors.extend(errors);
+
+    evaluation
+}
+
+/// Resolves the `path` and any `path_nested` of the entry, returning the format, the algorithm with which it is
+/// secured, if known, and the value of the innermost Claim.
+fn resolve(
+    descriptor: &InputDescriptorMappingObject,
+    presentation: &serde_json::Value,
+) -> Result<(ClaimFormatDesignation, Option<String>, serde_json::Value), String> {
+    let mut claim = select(presentation, &descriptor.path)?;
+    let mut format = descriptor.format.clone();
+    let mut path_nested = descriptor.path_nested.as_ref();
+    while let Some(nested) = path_nested {
+        if nested.id.as_ref().is_some_and(|id| id != &descriptor.id) {
+            return Err(format!(
+                "The `id` of the `path_nested` `{}` does not match.",
+                nested.id.as_deref().unwrap_or_default()
+            ));
+        }
+        claim = select(&decode(&format, claim)?, &nested.path)?;
+        format = nested.format.clone();
+        path_nested = nested.path_nested.as_deref();
+    }
+    let alg = alg(&format, &claim);
+    Ok((format.clone(), alg, decode(&format, claim)?))
+}
+
+/// Returns the algorithm of a JWT based Claim, or the proof type of a Claim that is secured with a Data Integrity
+/// proof.
+fn alg(format: &ClaimFormatDesignation, claim: &serde_json::Value) -> Option<String> {
+    use ClaimFormatDesignation::*;
+
+    match format {
+        Jwt | JwtVc | JwtVcJson | JwtVp | JwtVpJson | VcSdJwt => {
+            let header = jsonwebtoken::decode_header(issuer_signed_jwt(encoded(claim)?)).ok()?;
+            serde_json::to_value(header.alg)
+                .ok()
+                .and_then(|alg| alg.as_str().map(ToOwned::to_owned))
+        }
+        Ldp | LdpVc | LdpVp => claim
+            .pointer("/proof/type")
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned),
+        _ => None,
+    }
+}
+
+/// Evaluates the JSONPath expression, which must match exactly one value.
+fn select(value: &serde_json::Value, path: &str) -> Result<serde_json::Value, String> {
+    let values = jsonpath::select(value, path).map_err(|_| format!("`{path}` is not a valid JSONPath expression."))?;
+    match values.as_slice() {
+        [value] => Ok((*value).clone()),
+        [] => Err(format!("`{path}` does not match any Claim.")),
+        _ => Err(format!("`{path}` matches more than one Claim.")),
+    }
+}
+
+/// Returns the encoded Claim, which is either a string or enveloped in a `data:` URL.
+fn encoded(claim: &serde_json::Value) -> Option<&str> {
+    match claim {
+        serde_json::Value::String(encoded) => Some(encoded),
+        serde_json::Value::Object(object) => object
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .filter(|id| id.starts_with("data:"))
+            .map(|id| id.split_once(',').map(|(_, encoded)| encoded).unwrap_or_default()),
+        _ => None,
+    }
+}
+
+/// The Issuer-signed JWT of an SD-JWT is followed by its Disclosures.
+fn issuer_signed_jwt(encoded: &str) -> &str {
+    encoded.split('~').next().unwrap_or_default()
+}
+
+/// Decodes a Claim that is encoded as a string according to its `format`. Credentials that are enveloped in a `data:`
+/// URL, as in VCDM 2.0 Verifiable Presentations, are unwrapped first.
+fn decode(format: &ClaimFormatDesignation, claim: serde_json::Value) -> Result<serde_json::Value, String> {
+    use ClaimFormatDesignation::*;
+
+    let Some(encoded) = encoded(&claim) else {
+        return Ok(claim);
+    };
+    match format {
+        Jwt | JwtVc | JwtVcJson | JwtVp | JwtVpJson | VcSdJwt => {
+            let jwt = issuer_signed_jwt(encoded);
+            let mut validation = Validation::default();
+            validation.insecure_disable_signature_validation();
+            validation.validate_exp = false;
+            validation.validate_aud = false;
+            validation.required_spec_claims.clear();
+            jsonwebtoken::decode::<serde_json::Value>(jwt, &DecodingKey::from_secret(&[]), &validation)
+                .map(|token| token.claims)
+                .map_err(|error| format!("The Claim cannot be decoded as a JWT: {error}."))
+        }
+        _ => Ok(claim),
+    }
+}
+
+fn satisfies(
+    presentation_definition: &PresentationDefinition,
+    input_descriptor: &InputDescriptor,
+    format: &ClaimFormatDesignation,
+    alg: Option<&str>,
+    claim: &serde_json::Value,
+) -> Result<(), String> {
+    if !presentation_definition.accepts_format(input_descriptor, format, alg) {
+        return Err("The format of the Claim is not accepted by the Input Descriptor.".to_string());
+    }
+    if input_descriptor.requires_limited_disclosure() && !format.supports_selective_disclosure() {
+        return Err("The disclosure of the Claim cannot be limited as required by the Input Descriptor.".to_string());
+    }
+    evaluate_input(input_descriptor, claim)
+        .then_some(())
+        .ok_or("The Claim does not meet the constraints of the Input Descriptor.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presentation_definition::LimitDisclosure;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    fn presentation_definition() -> PresentationDefinition {
+        serde_json::from_value(json!({
+            "id": "definition",
+            "input_descriptors": [
+                {
+                    "id": "degree",
+                    "constraints": {
+                        "fields": [{ "path": ["$.vc.type"], "filter": { "type": "array", "contains": { "const": "UniversityDegreeCredential" } } }]
+                    }
+                },
+                {
+                    "id": "email",
+                    "constraints": {
+                        "fields": [{ "path": ["$.vc.credentialSubject.email"] }]
+                    }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn jwt(claims: serde_json::Value) -> String {
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(b"secret")).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_submission() {
+        let degree = jwt(json!({ "vc": { "type": ["VerifiableCredential", "UniversityDegreeCredential"] } }));
+        let email = jwt(
+            json!({ "vc": { "type": ["VerifiableCredential"], "credentialSubject": { "email": "ferris@example.com" } } }),
+        );
+        let vp_token = json!(jwt(json!({ "vp": { "verifiableCredential": [degree, email] } })));
+        let mut presentation_submission: PresentationSubmission = serde_json::from_value(json!({
+            "id": "submission",
+            "definition_id": "definition",
+            "descriptor_map": [
+                {
+                    "id": "degree",
+                    "format": "jwt_vp_json",
+                    "path": "$",
+                    "path_nested": { "format": "jwt_vc_json", "path": "$.vp.verifiableCredential[0]" }
+                },
+                {
+                    "id": "email",
+                    "format": "jwt_vp_json",
+                    "path": "$",
+                    "path_nested": { "format": "jwt_vc_json", "path": "$.vp.verifiableCredential[1]" }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let evaluation = evaluate_submission(&presentation_definition(), &presentation_submission, &vp_token, None);
+        assert!(evaluation.is_valid(), "{:?}", evaluation.error_message());
+        assert_eq!(evaluation.satisfied().collect::<Vec<_>>(), vec!["degree", "email"]);
+        assert_eq!(
+            evaluation.descriptors[1].claim.as_ref().unwrap()["vc"]["credentialSubject"]["email"],
+            "ferris@example.com"
+        );
+
+        // The Credentials are secured with `HS256`, which the Presentation Definition does not accept.
+        let mut restricted_definition = json!(presentation_definition());
+        restricted_definition["format"] = json!({ "jwt_vc_json": { "alg": ["EdDSA", "ES256"] } });
+        let restricted_definition = serde_json::from_value(restricted_definition).unwrap();
+        let evaluation = evaluate_submission(&restricted_definition, &presentation_submission, &vp_token, None);
+        assert_eq!(evaluation.satisfied().count(), 0);
+
+        // A descriptor that points to the wrong Credential does not satisfy its Input Descriptor.
+        presentation_submission.descriptor_map[1]
+            .path_nested
+            .as_mut()
+            .unwrap()
+            .path = "$.vp.verifiableCredential[0]".to_string();
+        let evaluation = evaluate_submission(&presentation_definition(), &presentation_submission, &vp_token, None);
+        assert!(!evaluation.is_valid());
+        assert_eq!(evaluation.satisfied().collect::<Vec<_>>(), vec!["degree"]);
+        assert_eq!(evaluation.unsatisfied, vec!["email".to_string()]);
+
+        // Paths that do not resolve and foreign definitions are reported.
+        presentation_submission.descriptor_map[1]
+            .path_nested
+            .as_mut()
+            .unwrap()
+            .path = "$.vp.verifiableCredential[2]".to_string();
+        presentation_submission.definition_id = "other".to_string();
+        let evaluation = evaluate_submission(&presentation_definition(), &presentation_submission, &vp_token, None);
+        assert_eq!(
+            evaluation.errors,
+            vec![
+                "The Presentation Submission refers to the Presentation Definition `other` instead of `definition`."
+                    .to_string(),
+                "The Input Descriptors `email` are not satisfied.".to_string()
+            ]
+        );
+        assert_eq!(
+            evaluation.descriptors[1].error,
+            Some("`$.vp.verifiableCredential[2]` does not match any Claim.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_evaluate_submission_requirements() {
+        let mut presentation_definition = presentation_definition();
+        presentation_definition.submission_requirements = Some(vec![serde_json::from_value(
+            json!({ "rule": "pick", "count": 1, "from": "A" }),
+        )
+        .unwrap()]);
+        for input_descriptor in presentation_definition.input_descriptors.iter_mut() {
+            input_descriptor.group = Some(vec!["A".to_string()]);
+        }
+
+        let email = jwt(json!({ "vc": { "credentialSubject": { "email": "ferris@example.com" } } }));
+        let vp_token = json!(jwt(json!({ "vp": { "verifiableCredential": [email] } })));
+        let mut presentation_submission: PresentationSubmission = serde_json::from_value(json!({
+            "id": "submission",
+            "definition_id": "definition",
+            "descriptor_map": [
+                {
+                    "id": "email",
+                    "format": "jwt_vp_json",
+                    "path": "$",
+                    "path_nested": { "format": "jwt_vc_json", "path": "$.vp.verifiableCredential[0]" }
+                }
+            ]
+        }))
+        .unwrap();
+
+        // One of the Input Descriptors of group `A` suffices.
+        let evaluation = evaluate_submission(&presentation_definition, &presentation_submission, &vp_token, None);
+        assert!(evaluation.is_valid(), "{:?}", evaluation.error_message());
+        assert_eq!(evaluation.unsatisfied, vec!["degree".to_string()]);
+
+        presentation_submission.descriptor_map.clear();
+        let evaluation = evaluate_submission(&presentation_definition, &presentation_submission, &vp_token, None);
+        assert_eq!(
+            evaluation.error_message(),
+            Some("The Submission Requirements are not met.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_evaluate_enveloped_submission() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "definition",
+            "input_descriptors": [
+                { "id": "degree", "constraints": { "fields": [{ "path": ["$.credentialSubject.degree"] }] } }
+            ]
+        }))
+        .unwrap();
+        let credential = jwt(json!({ "credentialSubject": { "degree": "Bachelor of Science and Arts" } }));
+        let vp_token = json!(jwt(json!({
+            "verifiableCredential": [
+                { "id": format!("data:application/vc+jwt,{credential}"), "type": "EnvelopedVerifiableCredential" }
+            ]
+        })));
+        let presentation_submission: PresentationSubmission = serde_json::from_value(json!({
+            "id": "submission",
+            "definition_id": "definition",
+            "descriptor_map": [
+                {
+                    "id": "degree",
+                    "format": "jwt_vp_json",
+                    "path": "$",
+                    "path_nested": { "id": "degree", "format": "jwt_vc_json", "path": "$.verifiableCredential[0]" }
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert!(evaluate_submission(&presentation_definition, &presentation_submission, &vp_token, None).is_valid());
+
+        // The disclosure of a `vc+jwt` Credential cannot be limited.
+        let mut presentation_definition = presentation_definition;
+        presentation_definition.input_descriptors[0]
+            .constraints
+            .limit_disclosure = Some(LimitDisclosure::Required);
+        let evaluation = evaluate_submission(&presentation_definition, &presentation_submission, &vp_token, None);
+        assert_eq!(
+            evaluation.descriptors[0].error,
+            Some("The disclosure of the Claim cannot be limited as required by the Input Descriptor.".to_string())
+        );
+    }
+}
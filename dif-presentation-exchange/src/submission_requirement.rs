@@ -0,0 +1,301 @@
+use crate::InputDescriptor;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::collections::HashSet;
+
+/// As specified in https://identity.foundation/presentation-exchange/spec/v2.0.0/#submission-requirement-feature.
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[derive(Deserialize, Debug, Getters, PartialEq, Clone, Serialize)]
+pub struct SubmissionRequirement {
+    pub(crate) name: Option<String>,
+    pub(crate) purpose: Option<String>,
+    #[getset(get = "pub")]
+    pub(crate) rule: Rule,
+    #[getset(get = "pub")]
+    pub(crate) count: Option<usize>,
+    #[getset(get = "pub")]
+    pub(crate) min: Option<usize>,
+    #[getset(get = "pub")]
+    pub(crate) max: Option<usize>,
+    // Either `from` or `from_nested` must be present, but not both.
+    #[getset(get = "pub")]
+    pub(crate) from: Option<String>,
+    #[getset(get = "pub")]
+    pub(crate) from_nested: Option<Vec<SubmissionRequirement>>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    All,
+    Pick,
+}
+
+/// The members of a Submission Requirement: the `id`s of the Input Descriptors of the `from` group, or the nested
+/// Submission Requirements.
+enum Members<'s, 'a> {
+    Group(Vec<&'a str>),
+    Nested(&'s [SubmissionRequirement]),
+}
+
+impl SubmissionRequirement {
+    fn members<'a>(&self, input_descriptors: &'a [InputDescriptor]) -> Option<Members<'_, 'a>> {
+        match (&self.from, &self.from_nested) {
+            (Some(group), None) => Some(Members::Group(
+                input_descriptors
+                    .iter()
+                    .filter(|input_descriptor| {
+                        input_descriptor
+                            .group
+                            .as_ref()
+                            .is_some_and(|groups| groups.contains(group))
+                    })
+                    .map(|input_descriptor| input_descriptor.id.as_str())
+                    .collect(),
+            )),
+            (None, Some(from_nested)) => Some(Members::Nested(from_nested)),
+            _ => None,
+        }
+    }
+
+    /// Returns whether `satisfied` out of `total` members meet the `rule`.
+    fn accepts(&self, satisfied: usize, total: usize) -> bool {
+        match self.rule {
+            Rule::All => satisfied == total,
+            Rule::Pick => {
+                self.count.map_or(true, |count| satisfied == count)
+                    && self.min.map_or(true, |min| satisfied >= min)
+                    && self.max.map_or(true, |max| satisfied <= max)
+            }
+        }
+    }
+
+    /// Returns whether the requirement is met when the Input Descriptors with the given `id`s are satisfied.
+    pub fn is_met(&self, input_descriptors: &[InputDescriptor], satisfied: &HashSet<&str>) -> bool {
+        match self.members(input_descriptors) {
+            Some(Members::Group(ids)) => {
+                self.accepts(ids.iter().filter(|id| satisfied.contains(*id)).count(), ids.len())
+            }
+            Some(Members::Nested(requirements)) => self.accepts(
+                requirements
+                    .iter()
+                    .filter(|requirement| requirement.is_met(input_descriptors, satisfied))
+                    .count(),
+                requirements.len(),
+            ),
+            None => false,
+        }
+    }
+
+    /// Selects the Input Descriptors out of the `satisfiable` ones that should be submitted to meet the requirement,
+    /// or returns `None` if the requirement cannot be met. No more members are picked than needed, except when the
+    /// rule only sets a `max`, in which case as many members as allowed are picked.
+    pub fn select<'a>(
+        &self,
+        input_descriptors: &'a [InputDescriptor],
+        satisfiable: &HashSet<&str>,
+    ) -> Option<HashSet<&'a str>> {
+        match self.members(input_descriptors)? {
+            Members::Group(ids) => {
+                let candidates = ids
+                    .iter()
+                    .filter(|id| satisfiable.contains(*id))
+                    .map(|id| HashSet::from([*id]))
+                    .collect();
+                self.pick(candidates, ids.len())
+            }
+            Members::Nested(requirements) => {
+                let candidates = requirements
+                    .iter()
+                    .filter_map(|requirement| requirement.select(input_descriptors, satisfiable))
+                    .collect();
+                self.pick(candidates, requirements.len())
+            }
+        }
+    }
+
+    fn pick<'a>(&self, candidates: Vec<HashSet<&'a str>>, total: usize) -> Option<HashSet<&'a str>> {
+        let needed = match self.rule {
+            Rule::All => total,
+            Rule::Pick => self
+                .count
+                .or(self.min)
+                .unwrap_or_else(|| self.max.unwrap_or(candidates.len()).min(candidates.len())),
+        };
+        (candidates.len() >= needed && self.accepts(needed, total))
+            .then(|| candidates.into_iter().take(needed).flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input_descriptors() -> Vec<InputDescriptor> {
+        serde_json::from_value(json!([
+            { "id": "passport", "group": ["A"], "constraints": {} },
+            { "id": "drivers_license", "group": ["A"], "constraints": {} },
+            { "id": "id_card", "group": ["A"], "constraints": {} },
+            { "id": "utility_bill", "group": ["B"], "constraints": {} }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_submission_requirement() {
+        let input_descriptors = input_descriptors();
+        let pick_one: SubmissionRequirement =
+            serde_json::from_value(json!({ "rule": "pick", "count": 1, "from": "A" })).unwrap();
+        let all_b: SubmissionRequirement = serde_json::from_value(json!({ "rule": "all", "from": "B" })).unwrap();
+        let nested: SubmissionRequirement = serde_json::from_value(json!({
+            "rule": "pick",
+            "min": 1,
+            "from_nested": [
+                { "rule": "pick", "count": 2, "from": "A" },
+                { "rule": "all", "from": "B" }
+            ]
+        }))
+        .unwrap();
+
+        assert!(pick_one.is_met(&input_descriptors, &HashSet::from(["drivers_license"])));
+        assert!(!pick_one.is_met(&input_descriptors, &HashSet::from(["passport", "id_card"])));
+        assert!(!pick_one.is_met(&input_descriptors, &HashSet::new()));
+        assert!(all_b.is_met(&input_descriptors, &HashSet::from(["utility_bill"])));
+        assert!(nested.is_met(&input_descriptors, &HashSet::from(["utility_bill"])));
+        assert!(nested.is_met(&input_descriptors, &HashSet::from(["passport", "id_card"])));
+        assert!(!nested.is_met(&input_descriptors, &HashSet::from(["passport"])));
+
+        assert_eq!(
+            pick_one.select(&input_descriptors, &HashSet::from(["id_card", "drivers_license"])),
+            Some(HashSet::from(["drivers_license"]))
+        );
+        assert_eq!(
+            pick_one.select(&input_descriptors, &HashSet::from(["utility_bill"])),
+            None
+        );
+        assert_eq!(
+            nested.select(&input_descriptors, &HashSet::from(["passport", "utility_bill"])),
+            Some(HashSet::from(["utility_bill"]))
+        );
+        assert_eq!(nested.select(&input_descriptors, &HashSet::from(["passport"])), None);
+
+        // Either `from` or `from_nested` must be present.
+        let invalid: SubmissionRequirement = serde_json::from_value(json!({ "rule": "all" })).unwrap();
+        assert!(!invalid.is_met(&input_descriptors, &HashSet::from(["passport"])));
+    }
+
+    #[test]
+    fn test_submission_requirement_pick_max() {
+        let input_descriptors = input_descriptors();
+        let pick_max: SubmissionRequirement =
+            serde_json::from_value(json!({ "rule": "pick", "max": 2, "from": "A" })).unwrap();
+
+        assert!(pick_max.is_met(&input_descriptors, &HashSet::new()));
+        assert!(pick_max.is_met(&input_descriptors, &HashSet::from(["passport", "id_card"])));
+        assert!(!pick_max.is_met(
+            &input_descriptors,
+            &HashSet::from(["passport", "drivers_license", "id_card"])
+        ));
+
+        // As many members as allowed are picked.
+        assert_eq!(
+            pick_max.select(
+                &input_descriptors,
+                &HashSet::from(["passport", "drivers_license", "id_card"])
+            ),
+            Some(HashSet::from(["passport", "drivers_license"]))
+        );
+        assert_eq!(
+            pick_max.select(&input_descriptors, &HashSet::from(["id_card", "utility_bill"])),
+            Some(HashSet::from(["id_card"]))
+        );
+        assert_eq!(
+            pick_max.select(&input_descriptors, &HashSet::new()),
+            Some(HashSet::new())
+        );
+    }
+
+    #[test]
+    fn test_submission_requirement_pick_min_max() {
+        let input_descriptors = input_descriptors();
+        let pick_min_max: SubmissionRequirement =
+            serde_json::from_value(json!({ "rule": "pick", "min": 2, "max": 3, "from": "A" })).unwrap();
+
+        assert!(!pick_min_max.is_met(&input_descriptors, &HashSet::from(["passport"])));
+        assert!(!pick_min_max.is_met(&input_descriptors, &HashSet::from(["passport", "utility_bill"])));
+        assert!(pick_min_max.is_met(&input_descriptors, &HashSet::from(["passport", "id_card"])));
+        assert!(pick_min_max.is_met(
+            &input_descriptors,
+            &HashSet::from(["passport", "drivers_license", "id_card"])
+        ));
+
+        // No more members are picked than the `min` requires.
+        assert_eq!(
+            pick_min_max.select(
+                &input_descriptors,
+                &HashSet::from(["passport", "drivers_license", "id_card"])
+            ),
+            Some(HashSet::from(["passport", "drivers_license"]))
+        );
+        assert_eq!(
+            pick_min_max.select(&input_descriptors, &HashSet::from(["id_card", "utility_bill"])),
+            None
+        );
+
+        // A `min` that exceeds the `max` can never be met.
+        let impossible: SubmissionRequirement =
+            serde_json::from_value(json!({ "rule": "pick", "min": 3, "max": 2, "from": "A" })).unwrap();
+        assert!(!impossible.is_met(
+            &input_descriptors,
+            &HashSet::from(["passport", "drivers_license", "id_card"])
+        ));
+        assert_eq!(
+            impossible.select(
+                &input_descriptors,
+                &HashSet::from(["passport", "drivers_license", "id_card"])
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_submission_requirement_all_from_nested() {
+        let input_descriptors = input_descriptors();
+        let all_nested: SubmissionRequirement = serde_json::from_value(json!({
+            "rule": "all",
+            "from_nested": [
+                { "rule": "pick", "count": 1, "from": "A" },
+                { "rule": "all", "from": "B" }
+            ]
+        }))
+        .unwrap();
+
+        assert!(all_nested.is_met(&input_descriptors, &HashSet::from(["passport", "utility_bill"])));
+        assert!(!all_nested.is_met(&input_descriptors, &HashSet::from(["passport"])));
+        assert!(!all_nested.is_met(&input_descriptors, &HashSet::from(["utility_bill"])));
+        assert!(!all_nested.is_met(
+            &input_descriptors,
+            &HashSet::from(["passport", "id_card", "utility_bill"])
+        ));
+
+        assert_eq!(
+            all_nested.select(
+                &input_descriptors,
+                &HashSet::from(["drivers_license", "id_card", "utility_bill"])
+            ),
+            Some(HashSet::from(["drivers_license", "utility_bill"]))
+        );
+        assert_eq!(
+            all_nested.select(&input_descriptors, &HashSet::from(["passport", "id_card"])),
+            None
+        );
+        assert_eq!(
+            all_nested.select(&input_descriptors, &HashSet::from(["utility_bill"])),
+            None
+        );
+    }
+}
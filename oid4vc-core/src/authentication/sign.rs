@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use jsonwebtoken::Algorithm;
 use std::sync::Arc;
@@ -9,6 +9,16 @@ pub trait Sign: Send + Sync {
     // fn jwt_alg_name() -> &'static str;
     async fn key_id(&self, subject_syntax_type: &str, algorithm: Algorithm) -> Option<String>;
     async fn sign(&self, message: &str, subject_syntax_type: &str, algorithm: Algorithm) -> Result<Vec<u8>>;
+    /// Signs a binary message, such as the `Sig_structure` of a COSE object. Unlike JWTs, these messages are not
+    /// guaranteed to be valid UTF-8, so signers that support COSE need to override this method.
+    async fn sign_bytes(&self, _message: &[u8], _subject_syntax_type: &str, _algorithm: Algorithm) -> Result<Vec<u8>> {
+        Err(anyhow!("Signing binary messages is not supported."))
+    }
+    /// Returns the base64 encoded DER certificates of the `x5c` certificate chain of the signing key, starting with the
+    /// certificate that contains the key. The chain is added to the header of the JWTs that are signed with the key.
+    async fn certificate_chain(&self, _subject_syntax_type: &str, _algorithm: Algorithm) -> Option<Vec<String>> {
+        None
+    }
     fn external_signer(&self) -> Option<Arc<dyn ExternalSign>>;
 }
 
@@ -1,5 +1,6 @@
 use crate::{jwt, Subject, Verify};
 use anyhow::Result;
+use jsonwebtoken::Header;
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
@@ -16,10 +17,17 @@ impl Validator {
         }
     }
 
+    pub async fn resolve_public_key(&self, header: &Header) -> Result<Vec<u8>> {
+        match self {
+            Validator::Subject(subject) => subject.resolve_public_key(header).await,
+            Validator::Verifier(verifier) => verifier.resolve_public_key(header).await,
+        }
+    }
+
     pub async fn decode<T: DeserializeOwned>(&self, jwt: String) -> Result<T> {
-        let (kid, algorithm) = jwt::extract_header(&jwt)?;
+        let header = jsonwebtoken::decode_header(&jwt)?;
 
-        let public_key = self.public_key(&kid).await?;
-        jwt::decode(&jwt, public_key, algorithm)
+        let public_key = self.resolve_public_key(&header).await?;
+        jwt::decode(&jwt, public_key, header.alg)
     }
 }
@@ -1,8 +1,18 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use jsonwebtoken::Header;
 
 /// This [`Verify`] trait is used to verify JWTs.
 #[async_trait]
 pub trait Verify: Send + Sync {
     async fn public_key(&self, kid: &str) -> Result<Vec<u8>>;
+    /// Resolves the public key with which a JWT is signed from its header. By default, the key is resolved by the
+    /// `kid` of the header.
+    async fn resolve_public_key(&self, header: &Header) -> Result<Vec<u8>> {
+        let kid = header
+            .kid
+            .as_ref()
+            .ok_or(anyhow!("No key identifier found in the header."))?;
+        self.public_key(kid).await
+    }
 }
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// The Client ID Scheme enables the use of different mechanisms to obtain and validate the Verifier's metadata. As
+/// described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-verifier-metadata-managemen
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientIdScheme {
+    #[serde(rename = "pre-registered")]
+    PreRegistered,
+    RedirectUri,
+    EntityId,
+    Did,
+    VerifierAttestation,
+    X509SanDns,
+    X509SanUri,
+}
+
+impl ClientIdScheme {
+    /// Infers the Client ID Scheme of a request that lacks the `client_id_scheme` parameter. A `client_id` that is a
+    /// DID is resolved with the `did` scheme and a `client_id` that equals the `redirect_uri` with the `redirect_uri`
+    /// scheme. Any other `client_id` is expected to be pre-registered, as described here:
+    /// https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#section-5.7-7
+    pub fn infer(client_id: &str, redirect_uri: &url::Url) -> Self {
+        if client_id.starts_with("did:") {
+            ClientIdScheme::Did
+        } else if client_id == redirect_uri.as_str() {
+            ClientIdScheme::RedirectUri
+        } else {
+            ClientIdScheme::PreRegistered
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClientIdScheme::PreRegistered => "pre-registered",
+            ClientIdScheme::RedirectUri => "redirect_uri",
+            ClientIdScheme::EntityId => "entity_id",
+            ClientIdScheme::Did => "did",
+            ClientIdScheme::VerifierAttestation => "verifier_attestation",
+            ClientIdScheme::X509SanDns => "x509_san_dns",
+            ClientIdScheme::X509SanUri => "x509_san_uri",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer() {
+        let redirect_uri: url::Url = "https://client.example.org/cb".parse().unwrap();
+        assert_eq!(
+            ClientIdScheme::infer("did:example:123", &redirect_uri),
+            ClientIdScheme::Did
+        );
+        assert_eq!(
+            ClientIdScheme::infer("https://client.example.org/cb", &redirect_uri),
+            ClientIdScheme::RedirectUri
+        );
+        assert_eq!(
+            ClientIdScheme::infer("client", &redirect_uri),
+            ClientIdScheme::PreRegistered
+        );
+    }
+}
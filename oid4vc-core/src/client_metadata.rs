@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use crate::resolver::ReferenceResolver;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::collections::HashMap;
 use url::Url;
@@ -22,3 +23,19 @@ pub enum ClientMetadataResource<T = ()> {
     },
     ClientMetadataUri(String),
 }
+
+impl<T: DeserializeOwned + Clone> ClientMetadataResource<T> {
+    /// Returns the client metadata, which is fetched from the `client_metadata_uri` when it is passed by reference.
+    pub async fn resolve(&self, resolver: &ReferenceResolver) -> anyhow::Result<Self> {
+        match self {
+            ClientMetadataResource::ClientMetadataUri(client_metadata_uri) => {
+                let client_metadata: serde_json::Map<String, serde_json::Value> =
+                    resolver.resolve(&client_metadata_uri.parse()?).await?;
+                serde_json::from_value(serde_json::json!({ "client_metadata": client_metadata })).map_err(|error| {
+                    anyhow::anyhow!("The client metadata at `{client_metadata_uri}` is invalid: {error}")
+                })
+            }
+            client_metadata => Ok(client_metadata.clone()),
+        }
+    }
+}
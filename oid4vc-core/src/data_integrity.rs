@@ -0,0 +1,444 @@
+use crate::{authentication::subject::SigningSubject, JsonObject, Validator};
+use anyhow::{anyhow, Result};
+use chrono::{SecondsFormat, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use sha2::{Digest, Sha256};
+use sophia_api::source::QuadSource;
+use sophia_inmem::dataset::LightDataset;
+use sophia_iri::Iri;
+use sophia_jsonld::{
+    loader::{BoxFuture, ClosureLoader, FutureExt},
+    JsonLdOptions, JsonLdParser,
+};
+
+/// The JSON-LD contexts that are bundled, so that documents can be canonicalized without fetching their contexts.
+pub const BUNDLED_CONTEXTS: [(&str, &str); 7] = [
+    ("https://www.w3.org/2018/credentials/v1", ssi_contexts::CREDENTIALS_V1),
+    (
+        "https://www.w3.org/2018/credentials/examples/v1",
+        ssi_contexts::CREDENTIALS_EXAMPLES_V1,
+    ),
+    // Imported by the VCDM 1.1 examples context.
+    ("https://www.w3.org/ns/odrl.jsonld", ssi_contexts::ODRL),
+    ("https://www.w3.org/ns/credentials/v2", ssi_contexts::CREDENTIALS_V2),
+    (
+        "https://www.w3.org/ns/credentials/examples/v2",
+        ssi_contexts::CREDENTIALS_EXAMPLES_V2,
+    ),
+    (
+        "https://w3id.org/security/data-integrity/v2",
+        ssi_contexts::W3ID_DATA_INTEGRITY_V2,
+    ),
+    ("https://w3id.org/security/multikey/v1", ssi_contexts::W3ID_MULTIKEY_V1),
+];
+
+pub const CREDENTIALS_V2: &str = "https://www.w3.org/ns/credentials/v2";
+/// The context that defines the terms of a [`DataIntegrityProof`].
+pub const DATA_INTEGRITY_V2: &str = "https://w3id.org/security/data-integrity/v2";
+
+/// Cryptographic suites for Data Integrity proofs that use RDF Dataset Canonicalization.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Cryptosuite {
+    /// As described here: https://www.w3.org/TR/vc-di-eddsa/#eddsa-rdfc-2022
+    #[serde(rename = "eddsa-rdfc-2022")]
+    EddsaRdfc2022,
+    /// As described here: https://www.w3.org/TR/vc-di-ecdsa/#ecdsa-rdfc-2019 (only P-256 is supported).
+    #[serde(rename = "ecdsa-rdfc-2019")]
+    EcdsaRdfc2019,
+}
+
+impl Cryptosuite {
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Cryptosuite::EddsaRdfc2022 => Algorithm::EdDSA,
+            Cryptosuite::EcdsaRdfc2019 => Algorithm::ES256,
+        }
+    }
+}
+
+impl TryFrom<Algorithm> for Cryptosuite {
+    type Error = anyhow::Error;
+
+    fn try_from(algorithm: Algorithm) -> Result<Self> {
+        match algorithm {
+            Algorithm::EdDSA => Ok(Cryptosuite::EddsaRdfc2022),
+            Algorithm::ES256 => Ok(Cryptosuite::EcdsaRdfc2019),
+            _ => Err(anyhow!("No cryptosuite found for algorithm: {algorithm:?}")),
+        }
+    }
+}
+
+/// Data Integrity proof as described here: https://www.w3.org/TR/vc-data-integrity/#proofs
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub cryptosuite: Cryptosuite,
+    pub created: Option<String>,
+    pub verification_method: String,
+    pub proof_purpose: String,
+    pub challenge: Option<String>,
+    pub domain: Option<String>,
+    pub proof_value: Option<String>,
+}
+
+/// The options with which a [`DataIntegrityProof`] is created.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProofOptions {
+    pub cryptosuite: Cryptosuite,
+    pub proof_purpose: String,
+    pub challenge: Option<String>,
+    pub domain: Option<String>,
+}
+
+impl ProofOptions {
+    pub fn new(cryptosuite: Cryptosuite, proof_purpose: impl Into<String>) -> Self {
+        Self {
+            cryptosuite,
+            proof_purpose: proof_purpose.into(),
+            challenge: None,
+            domain: None,
+        }
+    }
+}
+
+impl DataIntegrityProof {
+    /// Returns the `document` secured with a [`DataIntegrityProof`] that is created by the `signer`, as described here:
+    /// https://www.w3.org/TR/vc-di-eddsa/#create-proof-eddsa-rdfc-2022
+    pub async fn sign(
+        mut document: JsonObject,
+        options: ProofOptions,
+        signer: SigningSubject,
+        subject_syntax_type: &str,
+    ) -> Result<JsonObject> {
+        if document.contains_key("proof") {
+            return Err(anyhow!("The document is already secured."));
+        }
+        // The terms of the proof are defined by the Data Integrity context, which is included in the VCDM 2.0 context.
+        match document.get_mut("@context") {
+            Some(serde_json::Value::Array(context))
+                if !context
+                    .iter()
+                    .any(|context| context == CREDENTIALS_V2 || context == DATA_INTEGRITY_V2) =>
+            {
+                context.push(serde_json::Value::String(DATA_INTEGRITY_V2.to_string()))
+            }
+            Some(serde_json::Value::Array(_)) => {}
+            _ => return Err(anyhow!("The `@context` of the document must be an array.")),
+        }
+        let algorithm = options.cryptosuite.algorithm();
+
+        let mut proof = DataIntegrityProof {
+            type_: "DataIntegrityProof".to_string(),
+            cryptosuite: options.cryptosuite,
+            created: Some(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+            verification_method: signer
+                .key_id(subject_syntax_type, algorithm)
+                .await
+                .ok_or(anyhow!("No key identifier found."))?,
+            proof_purpose: options.proof_purpose,
+            challenge: options.challenge,
+            domain: options.domain,
+            proof_value: None,
+        };
+
+        let signature = signer
+            .sign_bytes(&proof.hash_data(&document)?, subject_syntax_type, algorithm)
+            .await?;
+        proof.proof_value = Some(format!("z{}", bs58::encode(signature).into_string()));
+
+        document.insert("proof".to_string(), serde_json::to_value(proof)?);
+        Ok(document)
+    }
+
+    /// Verifies the [`DataIntegrityProof`] of the `secured_document`, using the public key that the `validator`
+    /// resolves for its `verificationMethod`, as described here:
+    /// https://www.w3.org/TR/vc-di-eddsa/#verify-proof-eddsa-rdfc-2022
+    /// The `proofPurpose`, `challenge` and `domain` of the returned proof must be matched by the caller.
+    pub async fn verify(secured_document: &JsonObject, validator: &Validator) -> Result<DataIntegrityProof> {
+        let mut document = secured_document.clone();
+        let proof: DataIntegrityProof = serde_json::from_value(
+            document
+                .remove("proof")
+                .ok_or(anyhow!("The document does not contain a proof."))?,
+        )?;
+        if proof.type_ != "DataIntegrityProof" {
+            return Err(anyhow!("Unsupported proof type: {}", proof.type_));
+        }
+
+        let signature = proof
+            .proof_value
+            .as_ref()
+            .and_then(|proof_value| proof_value.strip_prefix('z'))
+            .ok_or(anyhow!("The `proofValue` must be a base58btc-encoded multibase value."))?;
+        let signature = bs58::decode(signature).into_vec()?;
+
+        let algorithm = proof.cryptosuite.algorithm();
+        let public_key = validator.public_key(&proof.verification_method).await?;
+        let decoding_key = match algorithm {
+            Algorithm::EdDSA => DecodingKey::from_ed_der(&public_key),
+            _ => DecodingKey::from_ec_der(&public_key),
+        };
+
+        let hash_data = proof.hash_data(&document)?;
+        if !jsonwebtoken::crypto::verify(&base64_url::encode(&signature), &hash_data, &decoding_key, algorithm)? {
+            return Err(anyhow!("Invalid Data Integrity proof."));
+        }
+
+        Ok(proof)
+    }
+
+    /// Returns the concatenation of the hash of the canonical proof configuration and the hash of the canonical
+    /// unsecured `document`.
+    fn hash_data(&self, document: &JsonObject) -> Result<Vec<u8>> {
+        let mut proof_configuration = serde_json::to_value(DataIntegrityProof {
+            proof_value: None,
+            ..self.clone()
+        })?;
+        proof_configuration["@context"] = document
+            .get("@context")
+            .cloned()
+            .ok_or(anyhow!("The document does not contain a `@context`."))?;
+
+        Ok([
+            Sha256::digest(canonicalize(&proof_configuration)?),
+            Sha256::digest(canonicalize(&serde_json::Value::Object(document.clone()))?),
+        ]
+        .concat())
+    }
+}
+
+/// Returns the canonical N-Quads of a JSON-LD `document` as described here: https://www.w3.org/TR/rdf-canon/
+/// Only the [`BUNDLED_CONTEXTS`] can be used, so that no contexts are fetched while canonicalizing.
+pub fn canonicalize(document: &serde_json::Value) -> Result<String> {
+    let parser = JsonLdParser::new_with_options(
+        JsonLdOptions::new().with_document_loader_closure(|| ClosureLoader::new(load_bundled_context)),
+    );
+
+    // The document loader does not perform any I/O, so the parser can be polled to completion in place.
+    let dataset: LightDataset = futures::executor::block_on(parser.async_parse_str(&document.to_string()))
+        .collect_quads()
+        .map_err(|error| anyhow!("Failed to expand the JSON-LD document: {error}"))?;
+
+    let mut canonical_n_quads = vec![];
+    sophia_c14n::rdfc10::normalize(&dataset, &mut canonical_n_quads)
+        .map_err(|error| anyhow!("Failed to canonicalize the JSON-LD document: {error}"))?;
+    Ok(String::from_utf8(canonical_n_quads)?)
+}
+
+fn load_bundled_context(iri: Iri<String>) -> BoxFuture<'static, Result<String, String>> {
+    let context = BUNDLED_CONTEXTS
+        .iter()
+        .find(|(url, _)| *url == iri.as_str())
+        .map(|(_, context)| context.to_string())
+        .ok_or(format!("The context `{}` is not bundled.", iri.as_str()));
+    async move { context }.boxed()
+}
+
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        authentication::sign::ExternalSign,
+        test_utils::{MockVerifier, TestSubject},
+        Sign, Subject, Verify,
+    };
+    use async_trait::async_trait;
+    use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn credential() -> JsonObject {
+        json!({
+            "@context": [
+                "https://www.w3.org/ns/credentials/v2",
+                "https://www.w3.org/ns/credentials/examples/v2"
+            ],
+            "id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "name": "Alumni Credential",
+            "issuer": "did:test:123",
+            "validFrom": "2023-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:abcdefgh",
+                "alumniOf": "The School of Examples"
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonicalize(&json!({
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiableCredential"],
+                "issuer": "did:example:123",
+                "issuanceDate": "2024-01-01T00:00:00Z",
+                "credentialSubject": { "id": "did:example:456" }
+            }))
+            .unwrap(),
+            "_:c14n0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .\n\
+             _:c14n0 <https://www.w3.org/2018/credentials#credentialSubject> <did:example:456> .\n\
+             _:c14n0 <https://www.w3.org/2018/credentials#issuanceDate> \"2024-01-01T00:00:00Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime> .\n\
+             _:c14n0 <https://www.w3.org/2018/credentials#issuer> <did:example:123> .\n"
+        );
+
+        // Contexts are never fetched.
+        assert!(canonicalize(&json!({
+            "@context": ["https://example.com/context"],
+            "name": "Ferris"
+        }))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_eddsa_rdfc_2022() {
+        let subject = Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap());
+
+        let mut options = ProofOptions::new(Cryptosuite::EddsaRdfc2022, "assertionMethod");
+        options.domain = Some("https://example.com".to_string());
+        let secured_credential = DataIntegrityProof::sign(credential(), options, subject, "did:test")
+            .await
+            .unwrap();
+        assert!(secured_credential["proof"]["proofValue"]
+            .as_str()
+            .unwrap()
+            .starts_with('z'));
+
+        let validator = Validator::Verifier(Arc::new(MockVerifier::new()));
+        let proof = DataIntegrityProof::verify(&secured_credential, &validator)
+            .await
+            .unwrap();
+        assert_eq!(proof.cryptosuite, Cryptosuite::EddsaRdfc2022);
+        assert_eq!(proof.verification_method, "did:test:123#key-1");
+        assert_eq!(proof.proof_purpose, "assertionMethod");
+        assert_eq!(proof.domain, Some("https://example.com".to_string()));
+
+        // Changing a claim invalidates the proof.
+        let mut tampered_credential = secured_credential.clone();
+        tampered_credential["credentialSubject"]["alumniOf"] = json!("The School of Forgeries");
+        assert!(DataIntegrityProof::verify(&tampered_credential, &validator)
+            .await
+            .is_err());
+
+        // Changing the proof options invalidates the proof.
+        let mut tampered_credential = secured_credential.clone();
+        tampered_credential["proof"]["proofPurpose"] = json!("authentication");
+        assert!(DataIntegrityProof::verify(&tampered_credential, &validator)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_data_integrity_context() {
+        let subject = Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap());
+
+        // The Data Integrity context is added to VCDM 1.1 documents.
+        let secured_credential = DataIntegrityProof::sign(
+            json!({
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiableCredential"],
+                "issuer": "did:test:123",
+                "issuanceDate": "2024-01-01T00:00:00Z",
+                "credentialSubject": { "id": "did:example:456" }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+            ProofOptions::new(Cryptosuite::EddsaRdfc2022, "assertionMethod"),
+            subject,
+            "did:test",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            secured_credential["@context"],
+            json!([
+                "https://www.w3.org/2018/credentials/v1",
+                "https://w3id.org/security/data-integrity/v2"
+            ])
+        );
+        assert!(
+            DataIntegrityProof::verify(&secured_credential, &Validator::Verifier(Arc::new(MockVerifier::new())))
+                .await
+                .is_ok()
+        );
+    }
+
+    struct P256Subject(SigningKey);
+
+    #[async_trait]
+    impl Sign for P256Subject {
+        async fn key_id(&self, _subject_syntax_type: &str, _algorithm: Algorithm) -> Option<String> {
+            Some("did:test:456#key-1".to_string())
+        }
+
+        async fn sign(&self, message: &str, subject_syntax_type: &str, algorithm: Algorithm) -> Result<Vec<u8>> {
+            self.sign_bytes(message.as_bytes(), subject_syntax_type, algorithm)
+                .await
+        }
+
+        async fn sign_bytes(
+            &self,
+            message: &[u8],
+            _subject_syntax_type: &str,
+            _algorithm: Algorithm,
+        ) -> Result<Vec<u8>> {
+            let signature: Signature = self.0.sign(message);
+            Ok(signature.to_vec())
+        }
+
+        fn external_signer(&self) -> Option<Arc<dyn ExternalSign>> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl Verify for P256Subject {
+        async fn public_key(&self, _kid: &str) -> Result<Vec<u8>> {
+            Ok(self.0.verifying_key().to_encoded_point(false).as_bytes().to_vec())
+        }
+    }
+
+    #[async_trait]
+    impl Subject for P256Subject {
+        async fn identifier(&self, _subject_syntax_type: &str, _algorithm: Algorithm) -> Result<String> {
+            Ok("did:test:456".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ecdsa_rdfc_2019() {
+        let subject = Arc::new(P256Subject(SigningKey::random(&mut rand::rngs::OsRng)));
+
+        let secured_credential = DataIntegrityProof::sign(
+            credential(),
+            ProofOptions::new(Cryptosuite::EcdsaRdfc2019, "assertionMethod"),
+            subject.clone(),
+            "did:test",
+        )
+        .await
+        .unwrap();
+        assert_eq!(secured_credential["proof"]["cryptosuite"], "ecdsa-rdfc-2019");
+
+        let proof = DataIntegrityProof::verify(&secured_credential, &Validator::Subject(subject))
+            .await
+            .unwrap();
+        assert_eq!(proof.verification_method, "did:test:456#key-1");
+
+        // A proof cannot be verified with another key.
+        assert!(
+            DataIntegrityProof::verify(&secured_credential, &Validator::Verifier(Arc::new(MockVerifier::new())))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes128Gcm, Aes256Gcm, KeyInit, Nonce,
+};
+use anyhow::{anyhow, Result};
+use jsonwebtoken::jwk::Jwk;
+use p256::{ecdh::diffie_hellman, PublicKey, SecretKey};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use sha2::Sha256;
+use std::fmt::{Display, Formatter};
+
+/// Key Management Algorithms for JWE as described here: https://www.rfc-editor.org/rfc/rfc7518#section-4.1
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyManagementAlgorithm {
+    /// Elliptic Curve Diffie-Hellman Ephemeral Static key agreement using Concat KDF (direct key agreement).
+    #[serde(rename = "ECDH-ES")]
+    EcdhEs,
+}
+
+/// Content Encryption Algorithms for JWE as described here: https://www.rfc-editor.org/rfc/rfc7518#section-5.1
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncryptionAlgorithm {
+    #[serde(rename = "A128GCM")]
+    A128Gcm,
+    #[serde(rename = "A256GCM")]
+    A256Gcm,
+}
+
+impl ContentEncryptionAlgorithm {
+    fn key_length(&self) -> usize {
+        match self {
+            ContentEncryptionAlgorithm::A128Gcm => 16,
+            ContentEncryptionAlgorithm::A256Gcm => 32,
+        }
+    }
+}
+
+impl Display for KeyManagementAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyManagementAlgorithm::EcdhEs => write!(f, "ECDH-ES"),
+        }
+    }
+}
+
+impl Display for ContentEncryptionAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentEncryptionAlgorithm::A128Gcm => write!(f, "A128GCM"),
+            ContentEncryptionAlgorithm::A256Gcm => write!(f, "A256GCM"),
+        }
+    }
+}
+
+/// JOSE Header of a JWE as described here: https://www.rfc-editor.org/rfc/rfc7516#section-4
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct JweHeader {
+    alg: KeyManagementAlgorithm,
+    enc: ContentEncryptionAlgorithm,
+    epk: Jwk,
+    kid: Option<String>,
+    apu: Option<String>,
+    apv: Option<String>,
+}
+
+/// Agreement PartyUInfo and PartyVInfo, which are included in the key derivation as described here:
+/// https://www.rfc-editor.org/rfc/rfc7518#section-4.6.1
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartyInfo {
+    /// Information about the producer of the JWE, such as the `mdoc_generated_nonce` of ISO/IEC 18013-7.
+    pub apu: Option<Vec<u8>>,
+    /// Information about the recipient of the JWE, such as the `nonce` of the Authorization Request.
+    pub apv: Option<Vec<u8>>,
+}
+
+/// Ephemeral P-256 key pair that can be used by a recipient to decrypt a JWE.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    secret_key: SecretKey,
+}
+
+impl EncryptionKey {
+    /// Generates a new random P-256 key pair.
+    pub fn generate() -> Self {
+        Self {
+            secret_key: SecretKey::random(&mut OsRng),
+        }
+    }
+
+    /// Returns the public part of the key pair as a JWK.
+    pub fn public_jwk(&self) -> Result<Jwk> {
+        Ok(serde_json::from_str(&self.secret_key.public_key().to_jwk_string())?)
+    }
+
+    /// Decrypts a JWE in Compact Serialization and returns its plaintext.
+    pub fn decrypt(&self, jwe: &str) -> Result<Vec<u8>> {
+        self.decrypt_with_party_info(jwe).map(|(plaintext, _)| plaintext)
+    }
+
+    /// Decrypts a JWE in Compact Serialization and returns its plaintext together with the [`PartyInfo`] of its header.
+    pub fn decrypt_with_party_info(&self, jwe: &str) -> Result<(Vec<u8>, PartyInfo)> {
+        let [protected_header, encrypted_key, iv, ciphertext, tag]: [&str; 5] = jwe
+            .split('.')
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| anyhow!("Invalid JWE: expected five parts."))?;
+        if !encrypted_key.is_empty() {
+            return Err(anyhow!("Invalid JWE: `ECDH-ES` does not use an encrypted key."));
+        }
+
+        let header: JweHeader = serde_json::from_slice(&base64_url::decode(protected_header)?)?;
+        let party_info = PartyInfo {
+            apu: header.apu.as_deref().map(base64_url::decode).transpose()?,
+            apv: header.apv.as_deref().map(base64_url::decode).transpose()?,
+        };
+        let ephemeral_public_key = to_public_key(&header.epk)?;
+        let shared_secret = diffie_hellman(self.secret_key.to_nonzero_scalar(), ephemeral_public_key.as_affine());
+        let content_encryption_key = derive_key(shared_secret.raw_secret_bytes(), header.enc, &party_info)?;
+
+        let mut ciphertext_with_tag = base64_url::decode(ciphertext)?;
+        ciphertext_with_tag.extend(base64_url::decode(tag)?);
+        let iv = base64_url::decode(iv)?;
+        if iv.len() != 12 {
+            return Err(anyhow!("Invalid JWE: the initialization vector must be 96 bits."));
+        }
+        let payload = Payload {
+            msg: &ciphertext_with_tag,
+            aad: protected_header.as_bytes(),
+        };
+
+        let plaintext = match header.enc {
+            ContentEncryptionAlgorithm::A128Gcm => {
+                Aes128Gcm::new_from_slice(&content_encryption_key)?.decrypt(Nonce::from_slice(&iv), payload)
+            }
+            ContentEncryptionAlgorithm::A256Gcm => {
+                Aes256Gcm::new_from_slice(&content_encryption_key)?.decrypt(Nonce::from_slice(&iv), payload)
+            }
+        }
+        .map_err(|_| anyhow!("Failed to decrypt JWE."))?;
+        Ok((plaintext, party_info))
+    }
+}
+
+/// Encrypts `plaintext` for the holder of `jwk` and returns the resulting JWE in Compact Serialization. The
+/// `party_info` is added to the header as `apu` and `apv`.
+pub fn encrypt(
+    plaintext: &[u8],
+    jwk: &Jwk,
+    alg: KeyManagementAlgorithm,
+    enc: ContentEncryptionAlgorithm,
+    party_info: &PartyInfo,
+) -> Result<String> {
+    let KeyManagementAlgorithm::EcdhEs = alg;
+    let recipient_public_key = to_public_key(jwk)?;
+
+    let ephemeral_key = EncryptionKey::generate();
+    let shared_secret = diffie_hellman(
+        ephemeral_key.secret_key.to_nonzero_scalar(),
+        recipient_public_key.as_affine(),
+    );
+    let content_encryption_key = derive_key(shared_secret.raw_secret_bytes(), enc, party_info)?;
+
+    let header = JweHeader {
+        alg,
+        enc,
+        epk: ephemeral_key.public_jwk()?,
+        kid: jwk.common.key_id.clone(),
+        apu: party_info.apu.as_ref().map(base64_url::encode),
+        apv: party_info.apv.as_ref().map(base64_url::encode),
+    };
+    let protected_header = base64_url::encode(&serde_json::to_vec(&header)?);
+
+    let mut iv = [0u8; 12];
+    OsRng.fill_bytes(&mut iv);
+    let payload = Payload {
+        msg: plaintext,
+        aad: protected_header.as_bytes(),
+    };
+
+    let mut ciphertext = match enc {
+        ContentEncryptionAlgorithm::A128Gcm => {
+            Aes128Gcm::new_from_slice(&content_encryption_key)?.encrypt(Nonce::from_slice(&iv), payload)
+        }
+        ContentEncryptionAlgorithm::A256Gcm => {
+            Aes256Gcm::new_from_slice(&content_encryption_key)?.encrypt(Nonce::from_slice(&iv), payload)
+        }
+    }
+    .map_err(|_| anyhow!("Failed to encrypt JWE."))?;
+    // The authentication tag is appended to the ciphertext by the AEAD implementation.
+    let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+    Ok([
+        protected_header,
+        String::new(),
+        base64_url::encode(&iv),
+        base64_url::encode(&ciphertext),
+        base64_url::encode(&tag),
+    ]
+    .join("."))
+}
+
+fn to_public_key(jwk: &Jwk) -> Result<PublicKey> {
+    PublicKey::from_jwk_str(&serde_json::to_string(jwk)?).map_err(|_| anyhow!("Only P-256 JWKs are supported."))
+}
+
+/// Derives the Content Encryption Key using the Concat KDF as described here: https://www.rfc-editor.org/rfc/rfc7518#section-4.6.2
+fn derive_key(shared_secret: &[u8], enc: ContentEncryptionAlgorithm, party_info: &PartyInfo) -> Result<Vec<u8>> {
+    let key_length = enc.key_length();
+    let algorithm_id = enc.to_string();
+
+    let mut other_info = vec![];
+    // AlgorithmID
+    other_info.extend((algorithm_id.len() as u32).to_be_bytes());
+    other_info.extend(algorithm_id.as_bytes());
+    // PartyUInfo and PartyVInfo
+    for info in [&party_info.apu, &party_info.apv] {
+        let info = info.as_deref().unwrap_or_default();
+        other_info.extend((info.len() as u32).to_be_bytes());
+        other_info.extend(info);
+    }
+    // SuppPubInfo
+    other_info.extend(((key_length * 8) as u32).to_be_bytes());
+
+    let mut key = vec![0u8; key_length];
+    concat_kdf::derive_key_into::<Sha256>(shared_secret, &other_info, &mut key)
+        .map_err(|_| anyhow!("Failed to derive the content encryption key."))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(ContentEncryptionAlgorithm::A128Gcm)]
+    #[case(ContentEncryptionAlgorithm::A256Gcm)]
+    fn test_encrypt_decrypt(#[case] enc: ContentEncryptionAlgorithm) {
+        let encryption_key = EncryptionKey::generate();
+        let jwk = encryption_key.public_jwk().unwrap();
+
+        let jwe = encrypt(
+            b"{\"credential\":\"LUpixVCWJk0eOt4CXQe1NXK....WZwmhmn9OQp6YxX0a2L\"}",
+            &jwk,
+            KeyManagementAlgorithm::EcdhEs,
+            enc,
+            &PartyInfo::default(),
+        )
+        .unwrap();
+
+        let header: serde_json::Value =
+            serde_json::from_slice(&base64_url::decode(jwe.split('.').next().unwrap()).unwrap()).unwrap();
+        assert_eq!(header["alg"], "ECDH-ES");
+        assert_eq!(header["enc"], enc.to_string());
+        assert_eq!(header["epk"]["crv"], "P-256");
+
+        assert_eq!(
+            encryption_key.decrypt(&jwe).unwrap(),
+            b"{\"credential\":\"LUpixVCWJk0eOt4CXQe1NXK....WZwmhmn9OQp6YxX0a2L\"}"
+        );
+
+        // A different key cannot decrypt the JWE.
+        assert!(EncryptionKey::generate().decrypt(&jwe).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_tampered_header() {
+        let encryption_key = EncryptionKey::generate();
+        let jwe = encrypt(
+            b"plaintext",
+            &encryption_key.public_jwk().unwrap(),
+            KeyManagementAlgorithm::EcdhEs,
+            ContentEncryptionAlgorithm::A128Gcm,
+            &PartyInfo::default(),
+        )
+        .unwrap();
+
+        let mut parts: Vec<String> = jwe.split('.').map(ToString::to_string).collect();
+        let mut header: serde_json::Value = serde_json::from_slice(&base64_url::decode(&parts[0]).unwrap()).unwrap();
+        header["kid"] = serde_json::json!("tampered");
+        parts[0] = base64_url::encode(&serde_json::to_vec(&header).unwrap());
+
+        assert!(encryption_key.decrypt(&parts.join(".")).is_err());
+    }
+
+    #[test]
+    fn test_party_info() {
+        let encryption_key = EncryptionKey::generate();
+        let party_info = PartyInfo {
+            apu: Some(b"mdoc_generated_nonce".to_vec()),
+            apv: Some(b"nonce".to_vec()),
+        };
+        let jwe = encrypt(
+            b"plaintext",
+            &encryption_key.public_jwk().unwrap(),
+            KeyManagementAlgorithm::EcdhEs,
+            ContentEncryptionAlgorithm::A128Gcm,
+            &party_info,
+        )
+        .unwrap();
+
+        let mut parts: Vec<String> = jwe.split('.').map(ToString::to_string).collect();
+        let mut header: serde_json::Value = serde_json::from_slice(&base64_url::decode(&parts[0]).unwrap()).unwrap();
+        assert_eq!(header["apu"], base64_url::encode("mdoc_generated_nonce"));
+        assert_eq!(header["apv"], base64_url::encode("nonce"));
+
+        assert_eq!(
+            encryption_key.decrypt_with_party_info(&jwe).unwrap(),
+            (b"plaintext".to_vec(), party_info)
+        );
+
+        // The party info is part of the key derivation, so it cannot be replaced.
+        header["apu"] = serde_json::json!(base64_url::encode("other"));
+        parts[0] = base64_url::encode(&serde_json::to_vec(&header).unwrap());
+        assert!(encryption_key.decrypt(&parts.join(".")).is_err());
+    }
+}
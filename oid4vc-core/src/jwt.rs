@@ -1,7 +1,10 @@
-use crate::Sign;
+use crate::{JsonObject, Sign};
 use anyhow::{anyhow, Result};
 use getset::Getters;
-use jsonwebtoken::{Algorithm, DecodingKey, Header, Validation};
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk},
+    Algorithm, DecodingKey, Header, Validation,
+};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::sync::Arc;
@@ -39,6 +42,39 @@ pub fn extract_header(jwt: &str) -> Result<(String, Algorithm)> {
     }
 }
 
+/// Decodes the header and the payload of a JWT without verifying its signature. The header is returned as a
+/// [`JsonObject`] so that unsigned JWTs (`"alg": "none"`) and header parameters unknown to [`Header`] can be inspected
+/// before the key to verify the JWT with is determined.
+pub fn decode_unverified<T>(jwt: &str) -> Result<(JsonObject, T)>
+where
+    T: DeserializeOwned,
+{
+    let mut parts = jwt.split('.');
+    let (Some(header), Some(payload)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("Invalid JWT."));
+    };
+    Ok((
+        serde_json::from_slice(&base64_url::decode(header)?)?,
+        serde_json::from_slice(&base64_url::decode(payload)?)?,
+    ))
+}
+
+/// Returns the public key of a JWK in the same raw form as [`crate::Verify::public_key`].
+pub fn public_key_from_jwk(jwk: &Jwk) -> Result<Vec<u8>> {
+    match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(parameters) if parameters.curve == EllipticCurve::P256 => Ok([
+            vec![0x04],
+            base64_url::decode(&parameters.x)?,
+            base64_url::decode(&parameters.y)?,
+        ]
+        .concat()),
+        AlgorithmParameters::OctetKeyPair(parameters) if parameters.curve == EllipticCurve::Ed25519 => {
+            Ok(base64_url::decode(&parameters.x)?)
+        }
+        _ => Err(anyhow!("Only P-256 and Ed25519 keys are supported.")),
+    }
+}
+
 pub fn decode<T>(jwt: &str, public_key: Vec<u8>, algorithm: Algorithm) -> Result<T>
 where
     T: DeserializeOwned,
@@ -57,19 +93,46 @@ where
 }
 
 pub async fn encode<C, S>(signer: Arc<S>, header: Header, claims: C, subject_syntax_type: &str) -> Result<String>
+where
+    C: Serialize,
+    S: Sign + ?Sized,
+{
+    encode_with_parameters(signer, header, JsonObject::new(), claims, subject_syntax_type).await
+}
+
+/// Encodes a JWT like [`encode`], but adds `parameters` to the header that [`Header`] does not define, such as the `jwt`
+/// parameter that holds a Verifier Attestation.
+pub async fn encode_with_parameters<C, S>(
+    signer: Arc<S>,
+    header: Header,
+    parameters: JsonObject,
+    claims: C,
+    subject_syntax_type: &str,
+) -> Result<String>
 where
     C: Serialize,
     S: Sign + ?Sized,
 {
     let algorithm = header.alg;
-    let kid = signer
-        .key_id(subject_syntax_type, algorithm)
-        .await
-        .ok_or(anyhow!("No key identifier found."))?;
+    let mut header = header;
+    if header.x5c.is_none() {
+        header.x5c = signer.certificate_chain(subject_syntax_type, algorithm).await;
+    }
+    let kid = signer.key_id(subject_syntax_type, algorithm).await;
+    // A JWT with an `x5c` certificate chain does not need a `kid`, since the key is identified by the chain.
+    if kid.is_none() && header.x5c.is_none() {
+        return Err(anyhow!("No key identifier found."));
+    }
+    header.kid = kid;
 
-    let jwt = JsonWebToken::new(header, claims).kid(kid);
+    let jwt = JsonWebToken::new(header, claims);
+
+    let serde_json::Value::Object(mut header) = serde_json::to_value(&jwt.header)? else {
+        return Err(anyhow!("Invalid JWT header."));
+    };
+    header.extend(parameters);
 
-    let message = [base64_url_encode(&jwt.header)?, base64_url_encode(&jwt.payload)?].join(".");
+    let message = [base64_url_encode(&header)?, base64_url_encode(&jwt.payload)?].join(".");
 
     let proof_value = signer.sign(&message, subject_syntax_type, algorithm).await?;
     let signature = base64_url::encode(proof_value.as_slice());
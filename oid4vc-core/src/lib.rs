@@ -1,12 +1,20 @@
 pub mod authentication;
 pub mod authorization_request;
 pub mod authorization_response;
+pub mod client_id_scheme;
 pub mod client_metadata;
+pub mod data_integrity;
+pub mod jwe;
 pub mod jwt;
 pub mod openid4vc_extension;
+pub mod resolver;
 pub mod rfc7519_claims;
 pub mod scope;
+pub mod sd_jwt;
+pub mod status_list;
 pub mod subject_syntax_type;
+pub mod verifier_attestation;
+pub mod x509;
 
 pub use authentication::{sign::Sign, subject::Subject, validator::Validator, verify::Verify};
 use rand::{distributions::Alphanumeric, Rng};
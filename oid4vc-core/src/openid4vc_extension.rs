@@ -1,19 +1,26 @@
-use crate::{authorization_response::AuthorizationResponse, Subject, SubjectSyntaxType, Validator};
+use crate::{
+    authorization_request::Object, authorization_response::AuthorizationResponse, jwe::EncryptionKey,
+    resolver::ReferenceResolver, status_list::StatusListTransport, Subject, SubjectSyntaxType, Validator,
+};
 use jsonwebtoken::Algorithm;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{future::Future, sync::Arc};
 
 /// A [`RequestHandle`] is used to declare what functionality a request should have. Most notable, it declares the
 /// `response_type``, the extension-specific parameters, and the builder for the extension-specific `AuthorizationRequest`.
-pub trait RequestHandle: std::fmt::Debug + PartialEq {
+pub trait RequestHandle: std::fmt::Debug + PartialEq + Clone {
     type Parameters: Serialize + DeserializeOwned + std::fmt::Debug + PartialEq + Clone;
     type Builder: Default + std::fmt::Debug;
 }
 
 /// A [`ResponseHandle`] is used to declare what functionality a response should have. Most notable, it declares the
-/// input that is needed to generate a token, the extension-specific parameters, and the response item.
+/// input that is needed to generate a token, the generated tokens, the extension-specific parameters, and the response
+/// item.
 pub trait ResponseHandle: std::fmt::Debug + PartialEq + Clone {
     type Input;
+    /// The tokens that are generated from the [`ResponseHandle::Input`] and from which the Authorization Response is
+    /// built.
+    type Tokens;
     type Parameters: Serialize + DeserializeOwned + std::fmt::Debug + PartialEq + Clone;
     type ResponseItem: Serialize + std::fmt::Debug + PartialEq;
 }
@@ -24,6 +31,16 @@ pub trait Extension: Serialize + PartialEq + Sized + std::fmt::Debug + Clone + S
     type RequestHandle: RequestHandle;
     type ResponseHandle: ResponseHandle;
 
+    /// Replaces the extension-specific parameters that are passed by reference, such as the `client_metadata_uri`, with
+    /// the resources they refer to.
+    fn resolve_references(
+        _extension_parameters: &mut <Self::RequestHandle as RequestHandle>::Parameters,
+        _resolver: &ReferenceResolver,
+    ) -> impl Future<Output = anyhow::Result<()>> {
+        // Will be overwritten by the extension.
+        async { Ok(()) }
+    }
+
     fn generate_token(
         _subject: Arc<dyn Subject>,
         _client_id: &str,
@@ -31,7 +48,7 @@ pub trait Extension: Serialize + PartialEq + Sized + std::fmt::Debug + Clone + S
         _user_input: &<Self::ResponseHandle as ResponseHandle>::Input,
         _subject_syntax_type: impl TryInto<SubjectSyntaxType>,
         _signing_algorithm: impl TryInto<Algorithm>,
-    ) -> impl Future<Output = anyhow::Result<Vec<String>>> {
+    ) -> impl Future<Output = anyhow::Result<<Self::ResponseHandle as ResponseHandle>::Tokens>> {
         // Will be overwritten by the extension.
         async { Err(anyhow::anyhow!("Not implemented.")) }
     }
@@ -51,8 +68,9 @@ pub trait Extension: Serialize + PartialEq + Sized + std::fmt::Debug + Clone + S
     }
 
     fn build_authorization_response(
-        _jwts: Vec<String>,
+        _tokens: <Self::ResponseHandle as ResponseHandle>::Tokens,
         _user_input: <Self::ResponseHandle as ResponseHandle>::Input,
+        _extension_parameters: &<Self::RequestHandle as RequestHandle>::Parameters,
         _redirect_uri: String,
         _state: Option<String>,
     ) -> anyhow::Result<AuthorizationResponse<Self>> {
@@ -63,12 +81,35 @@ pub trait Extension: Serialize + PartialEq + Sized + std::fmt::Debug + Clone + S
     fn decode_authorization_response(
         _validator: Validator,
         _authorization_response: &AuthorizationResponse<Self>,
+        _verification_context: &VerificationContext<Self>,
     ) -> impl Future<Output = anyhow::Result<<Self::ResponseHandle as ResponseHandle>::ResponseItem>> + Send {
         // Will be overwritten by the extension.
         async { Err(anyhow::anyhow!("Not implemented.")) }
     }
 }
 
+/// Additional input for the verification of an Authorization Response by the Relying Party.
+pub struct VerificationContext<E: Extension = Generic> {
+    /// The Authorization Request to which the Authorization Response is a response. When `None`, the checks that
+    /// compare the response against the request are skipped.
+    pub authorization_request: Option<Object<E>>,
+    /// The transport that is used to fetch the status lists that are referenced by the presented Credentials. When
+    /// `None`, the status lists are fetched over HTTP.
+    pub status_list_transport: Option<Arc<dyn StatusListTransport>>,
+    /// The key with which encrypted Authorization Responses are decrypted.
+    pub encryption_key: Option<EncryptionKey>,
+}
+
+impl<E: Extension> Default for VerificationContext<E> {
+    fn default() -> Self {
+        Self {
+            authorization_request: None,
+            status_list_transport: None,
+            encryption_key: None,
+        }
+    }
+}
+
 impl RequestHandle for () {
     type Parameters = serde_json::Value;
     type Builder = ();
@@ -76,6 +117,7 @@ impl RequestHandle for () {
 
 impl ResponseHandle for () {
     type Input = ();
+    type Tokens = ();
     type Parameters = ();
     type ResponseItem = ();
 }
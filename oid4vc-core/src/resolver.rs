@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Result};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// The default maximum size in bytes of a resource that is passed by reference.
+pub const DEFAULT_MAX_SIZE: usize = 64 * 1024;
+/// The default duration during which a resolved resource is served from the cache.
+pub const DEFAULT_TIME_TO_LIVE: Duration = Duration::from_secs(300);
+/// The maximum number of resolved resources in the cache.
+pub const MAX_CACHE_ENTRIES: usize = 64;
+
+/// Resolves the parameters of an Authorization Request that are passed by reference, such as the `request_uri`, the
+/// `client_metadata_uri` and the `presentation_definition_uri`. Resources that exceed the `max_size` are rejected.
+/// Resolved resources are cached for the `time_to_live`, except for Request Objects, which carry a fresh `nonce`. The
+/// cache holds at most [`MAX_CACHE_ENTRIES`] resources, as the URIs are chosen by the Verifier.
+pub struct ReferenceResolver {
+    client: ClientWithMiddleware,
+    max_size: usize,
+    time_to_live: Duration,
+    cache: Mutex<HashMap<Url, (Instant, String)>>,
+}
+
+impl Default for ReferenceResolver {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SIZE, DEFAULT_TIME_TO_LIVE)
+    }
+}
+
+impl ReferenceResolver {
+    pub fn new(max_size: usize, time_to_live: Duration) -> Self {
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+
+        Self {
+            client,
+            max_size,
+            time_to_live,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fetches the Request Object behind a `request_uri`. Request Objects are never cached.
+    pub async fn request_object(&self, request_uri: &Url) -> Result<String> {
+        self.fetch(request_uri).await
+    }
+
+    /// Fetches the JSON document behind the `uri` and deserializes it into a `T`. Only documents that can be deserialized
+    /// are cached. Any further validation of the resource is left to the caller.
+    pub async fn resolve<T: DeserializeOwned>(&self, uri: &Url) -> Result<T> {
+        let cached = self
+            .cache
+            .lock()
+            .map_err(|_| anyhow!("The cache is poisoned."))?
+            .get(uri)
+            .filter(|(resolved_at, _)| resolved_at.elapsed() < self.time_to_live)
+            .map(|(_, document)| document.clone());
+
+        match cached {
+            Some(document) => parse(uri, &document),
+            None => {
+                let document = self.fetch(uri).await?;
+                let resource = parse(uri, &document)?;
+                let mut cache = self.cache.lock().map_err(|_| anyhow!("The cache is poisoned."))?;
+                // Expired resources are pruned, and when the cache is still full the least recently resolved resource
+                // is evicted.
+                cache.retain(|_, (resolved_at, _)| resolved_at.elapsed() < self.time_to_live);
+                if cache.len() >= MAX_CACHE_ENTRIES {
+                    if let Some(oldest) = cache
+                        .iter()
+                        .min_by_key(|(_, (resolved_at, _))| *resolved_at)
+                        .map(|(uri, _)| uri.clone())
+                    {
+                        cache.remove(&oldest);
+                    }
+                }
+                cache.insert(uri.clone(), (Instant::now(), document));
+                Ok(resource)
+            }
+        }
+    }
+
+    /// Fetches the resource behind the `uri`, without reading more than `max_size` bytes.
+    async fn fetch(&self, uri: &Url) -> Result<String> {
+        let mut response = self.client.get(uri.clone()).send().await?.error_for_status()?;
+        let too_large = || {
+            anyhow!(
+                "The resource at `{uri}` exceeds the maximum size of {} bytes.",
+                self.max_size
+            )
+        };
+
+        if response
+            .content_length()
+            .is_some_and(|content_length| content_length > self.max_size as u64)
+        {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > self.max_size {
+                return Err(too_large());
+            }
+        }
+        Ok(String::from_utf8(body)?)
+    }
+}
+
+fn parse<T: DeserializeOwned>(uri: &Url, document: &str) -> Result<T> {
+    serde_json::from_str(document).map_err(|error| anyhow!("The resource at `{uri}` is invalid: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Resource {
+        id: String,
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/resource"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "resource"}"#))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/invalid"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"name": "resource"}"#))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(r#"{{"id": "{}"}}"#, "a".repeat(64))))
+            .mount(&mock_server)
+            .await;
+
+        let resolver = ReferenceResolver::new(32, DEFAULT_TIME_TO_LIVE);
+        let uri = |resource: &str| format!("{}/{resource}", mock_server.uri()).parse::<Url>().unwrap();
+
+        // The second resolution is served from the cache.
+        for _ in 0..2 {
+            assert_eq!(
+                resolver.resolve::<Resource>(&uri("resource")).await.unwrap(),
+                Resource {
+                    id: "resource".to_string()
+                }
+            );
+        }
+
+        assert!(resolver
+            .resolve::<Resource>(&uri("invalid"))
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("is invalid"));
+        assert!(resolver
+            .resolve::<Resource>(&uri("large"))
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("exceeds the maximum size of 32 bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/resource"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": "resource"}"#))
+            .mount(&mock_server)
+            .await;
+
+        let uri = |n: usize| format!("{}/resource?n={n}", mock_server.uri()).parse::<Url>().unwrap();
+
+        // The cache does not grow beyond its maximum number of entries.
+        let resolver = ReferenceResolver::default();
+        for n in 0..MAX_CACHE_ENTRIES + 8 {
+            resolver.resolve::<Resource>(&uri(n)).await.unwrap();
+        }
+        {
+            let cache = resolver.cache.lock().unwrap();
+            assert_eq!(cache.len(), MAX_CACHE_ENTRIES);
+            assert!(!cache.contains_key(&uri(0)));
+            assert!(cache.contains_key(&uri(MAX_CACHE_ENTRIES + 7)));
+        }
+
+        // Expired resources are pruned when a new resource is cached.
+        let resolver = ReferenceResolver::new(DEFAULT_MAX_SIZE, Duration::ZERO);
+        for n in 0..8 {
+            resolver.resolve::<Resource>(&uri(n)).await.unwrap();
+        }
+        assert_eq!(resolver.cache.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,775 @@
+use crate::{jwt, JsonObject, Sign, Validator};
+use anyhow::{anyhow, Result};
+use jsonwebtoken::{Algorithm, DecodingKey, Header, Validation};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The hash algorithm used to calculate the digests of the Disclosures as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-hash-function-claim
+pub const SD_ALG: &str = "sha-256";
+
+/// The `typ` of a Key Binding JWT.
+pub const KB_JWT_TYP: &str = "kb+jwt";
+
+/// The number of seconds after its `iat` during which a Key Binding JWT is accepted.
+const KB_JWT_MAX_AGE: i64 = 300;
+/// The number of seconds that the `iat` of a Key Binding JWT may lie in the future, to allow for clock skew.
+const KB_JWT_LEEWAY: i64 = 60;
+
+const SD: &str = "_sd";
+const SD_ALG_CLAIM: &str = "_sd_alg";
+const ARRAY_ELEMENT: &str = "...";
+
+/// Disclosure of a selectively disclosable claim or array element as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-disclosures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disclosure {
+    pub salt: String,
+    /// The name of the claim, which is `None` for array elements.
+    pub claim_name: Option<String>,
+    pub claim_value: Value,
+    encoded: String,
+}
+
+impl Disclosure {
+    /// Creates a new Disclosure with a random salt.
+    pub fn new(claim_name: Option<String>, claim_value: Value) -> Self {
+        let salt = random_salt();
+        let array = match &claim_name {
+            Some(claim_name) => json!([salt, claim_name, claim_value]),
+            None => json!([salt, claim_value]),
+        };
+        Self {
+            encoded: base64_url::encode(&array.to_string()),
+            salt,
+            claim_name,
+            claim_value,
+        }
+    }
+
+    /// Returns the base64url-encoded digest of the Disclosure.
+    pub fn digest(&self) -> String {
+        digest(&self.encoded)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl FromStr for Disclosure {
+    type Err = anyhow::Error;
+
+    fn from_str(encoded: &str) -> Result<Self> {
+        let array: Vec<Value> = serde_json::from_slice(&base64_url::decode(encoded)?)?;
+        let (salt, claim_name, claim_value) = match array.as_slice() {
+            [Value::String(salt), Value::String(claim_name), claim_value] => {
+                (salt, Some(claim_name.clone()), claim_value)
+            }
+            [Value::String(salt), claim_value] => (salt, None, claim_value),
+            _ => return Err(anyhow!("Invalid disclosure: {encoded}")),
+        };
+        if matches!(claim_name.as_deref(), Some(SD) | Some(ARRAY_ELEMENT)) {
+            return Err(anyhow!(
+                "Invalid disclosure: the claim name must not be `_sd` or `...`."
+            ));
+        }
+
+        Ok(Self {
+            salt: salt.clone(),
+            claim_name,
+            claim_value: claim_value.clone(),
+            encoded: encoded.to_string(),
+        })
+    }
+}
+
+impl Display for Disclosure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encoded)
+    }
+}
+
+/// Turns claims of a JSON object into selectively disclosable claims. Claims are addressed by JSON Pointers as
+/// described in [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901), e.g. `/address/street_address` or
+/// `/nationalities/1`. Nested claims must be concealed before their parent claim.
+#[derive(Debug, Clone)]
+pub struct SdObjectEncoder {
+    object: Value,
+    disclosures: Vec<Disclosure>,
+}
+
+impl SdObjectEncoder {
+    pub fn new(object: JsonObject) -> Self {
+        Self {
+            object: Value::Object(object),
+            disclosures: vec![],
+        }
+    }
+
+    /// Replaces the claim or array element at `pointer` by the digest of its Disclosure.
+    pub fn conceal(&mut self, pointer: &str) -> Result<&Disclosure> {
+        let (parent_pointer, key) = pointer
+            .rsplit_once('/')
+            .ok_or(anyhow!("Invalid JSON Pointer: {pointer}"))?;
+        let key = key.replace("~1", "/").replace("~0", "~");
+
+        let disclosure = match self.object.pointer_mut(parent_pointer) {
+            Some(Value::Object(parent)) => {
+                if key == SD || key == ARRAY_ELEMENT {
+                    return Err(anyhow!("The claim `{key}` cannot be concealed."));
+                }
+                let claim_value = parent.remove(&key).ok_or(anyhow!("No claim found at {pointer}"))?;
+                let disclosure = Disclosure::new(Some(key), claim_value);
+                add_digest(parent, disclosure.digest())?;
+                disclosure
+            }
+            Some(Value::Array(parent)) => {
+                let element = key
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| parent.get_mut(index))
+                    .ok_or(anyhow!("No array element found at {pointer}"))?;
+                let disclosure = Disclosure::new(None, element.take());
+                *element = json!({ ARRAY_ELEMENT: disclosure.digest() });
+                disclosure
+            }
+            _ => return Err(anyhow!("No object or array found at {parent_pointer}")),
+        };
+
+        self.disclosures.push(disclosure);
+        Ok(self.disclosures.last().expect("a disclosure was just added"))
+    }
+
+    /// Adds `number_of_decoys` decoy digests to the object at `pointer`, which hide the actual number of selectively
+    /// disclosable claims in that object.
+    pub fn add_decoys(&mut self, pointer: &str, number_of_decoys: usize) -> Result<()> {
+        let object = self
+            .object
+            .pointer_mut(pointer)
+            .and_then(Value::as_object_mut)
+            .ok_or(anyhow!("No object found at {pointer}"))?;
+        for _ in 0..number_of_decoys {
+            add_digest(object, digest(&random_salt()))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the resulting SD-JWT payload, including the `_sd_alg` claim, together with all its Disclosures.
+    pub fn finish(self) -> (JsonObject, Vec<Disclosure>) {
+        let mut object = match self.object {
+            Value::Object(object) => object,
+            _ => unreachable!("The encoder always holds a JSON object."),
+        };
+        object.insert(SD_ALG_CLAIM.to_string(), json!(SD_ALG));
+        (object, self.disclosures)
+    }
+}
+
+/// Selective Disclosure JWT as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-sd-jwt-structure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdJwt {
+    /// The Issuer-signed JWT.
+    pub jwt: String,
+    pub disclosures: Vec<Disclosure>,
+    pub key_binding_jwt: Option<String>,
+}
+
+impl SdJwt {
+    /// Signs the payload of the `encoder` and returns the resulting SD-JWT including all Disclosures.
+    pub async fn encode<S: Sign + ?Sized>(
+        signer: Arc<S>,
+        header: Header,
+        encoder: SdObjectEncoder,
+        subject_syntax_type: &str,
+    ) -> Result<Self> {
+        let (payload, disclosures) = encoder.finish();
+        Ok(Self {
+            jwt: jwt::encode(signer, header, payload, subject_syntax_type).await?,
+            disclosures,
+            key_binding_jwt: None,
+        })
+    }
+
+    /// Verifies the signature of the Issuer-signed JWT and returns its payload with all disclosed claims.
+    pub async fn decode(&self, validator: &Validator) -> Result<JsonObject> {
+        let payload: JsonObject = validator.decode(self.jwt.clone()).await?;
+        self.disclose(payload)
+    }
+
+    /// Replaces the digests in the `payload` by the claims of the matching Disclosures as described here:
+    /// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-processing-of-disclosures
+    /// Digests without a matching Disclosure are removed. Disclosures that are not referenced by a digest, or that
+    /// are referenced more than once, are rejected.
+    pub fn disclose(&self, payload: JsonObject) -> Result<JsonObject> {
+        self.process_disclosures(payload).map(|(payload, _)| payload)
+    }
+
+    /// Returns the Disclosures together with the location of their claim in the disclosed payload, as a list of claim
+    /// names and array indices. The payload of the Issuer-signed JWT is NOT verified, so this should only be used by
+    /// the Holder to select the Disclosures to present.
+    pub fn disclosure_paths(&self) -> Result<DisclosurePaths<'_>> {
+        let payload = self
+            .jwt
+            .split('.')
+            .nth(1)
+            .ok_or(anyhow!("Invalid JWT: missing payload."))?;
+        let payload: JsonObject = serde_json::from_slice(&base64_url::decode(payload)?)?;
+        self.process_disclosures(payload).map(|(_, paths)| paths)
+    }
+
+    /// Returns the base64url-encoded digest over the SD-JWT without its Key Binding JWT, which is included as the
+    /// `sd_hash` claim of the Key Binding JWT.
+    pub fn sd_hash(&self) -> String {
+        digest(
+            &Self {
+                key_binding_jwt: None,
+                ..self.clone()
+            }
+            .to_string(),
+        )
+    }
+
+    /// Appends a Key Binding JWT, signed by the Holder's `signer`, for the given audience and nonce as described here:
+    /// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-key-binding-jwt
+    pub async fn bind<S: Sign + ?Sized>(
+        mut self,
+        signer: Arc<S>,
+        algorithm: Algorithm,
+        subject_syntax_type: &str,
+        aud: impl Into<String>,
+        nonce: impl Into<String>,
+    ) -> Result<Self> {
+        let claims = KeyBindingJwtClaims {
+            iat: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+            aud: aud.into(),
+            nonce: nonce.into(),
+            sd_hash: self.sd_hash(),
+        };
+        let header = Header {
+            typ: Some(KB_JWT_TYP.to_string()),
+            ..Header::new(algorithm)
+        };
+        self.key_binding_jwt
+            .replace(jwt::encode(signer, header, claims, subject_syntax_type).await?);
+        Ok(self)
+    }
+
+    /// Verifies the Key Binding JWT against the (verified) `payload` of the Issuer-signed JWT. The Holder's public key
+    /// is taken from the `cnf` claim of the payload if present. Otherwise it is resolved by the `validator` from the
+    /// `kid` of the Key Binding JWT, which must then refer to the `sub` claim of the payload. The Key Binding JWT must
+    /// have been issued within the last [`KB_JWT_MAX_AGE`] seconds.
+    pub async fn verify_key_binding(&self, payload: &JsonObject, validator: &Validator) -> Result<KeyBindingJwtClaims> {
+        let key_binding_jwt = self
+            .key_binding_jwt
+            .as_ref()
+            .ok_or(anyhow!("The SD-JWT does not contain a Key Binding JWT."))?;
+
+        let header = jsonwebtoken::decode_header(key_binding_jwt)?;
+        if header.typ.as_deref() != Some(KB_JWT_TYP) {
+            return Err(anyhow!("The Key Binding JWT must be of type `{KB_JWT_TYP}`."));
+        }
+
+        let claims: KeyBindingJwtClaims = match payload.get("cnf").and_then(|cnf| cnf.get("jwk")) {
+            Some(jwk) => {
+                let decoding_key = DecodingKey::from_jwk(&serde_json::from_value(jwk.clone())?)?;
+                let mut validation = Validation::new(header.alg);
+                validation.validate_exp = false;
+                validation.validate_aud = false;
+                validation.required_spec_claims.clear();
+                jsonwebtoken::decode(key_binding_jwt, &decoding_key, &validation)?.claims
+            }
+            None => {
+                let (kid, _) = jwt::extract_header(key_binding_jwt)?;
+                let holder = payload
+                    .get("sub")
+                    .and_then(Value::as_str)
+                    .ok_or(anyhow!("The SD-JWT does not contain a `cnf` or `sub` claim."))?;
+                if kid.split('#').next() != Some(holder) {
+                    return Err(anyhow!("The Key Binding JWT is not signed by the Holder."));
+                }
+                validator.decode(key_binding_jwt.clone()).await?
+            }
+        };
+
+        if claims.sd_hash != self.sd_hash() {
+            return Err(anyhow!(
+                "The `sd_hash` of the Key Binding JWT does not match the SD-JWT."
+            ));
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        if claims.iat > now + KB_JWT_LEEWAY || claims.iat < now - KB_JWT_MAX_AGE {
+            return Err(anyhow!(
+                "The `iat` of the Key Binding JWT is outside of the acceptable time window."
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    fn process_disclosures(&self, mut payload: JsonObject) -> Result<(JsonObject, DisclosurePaths<'_>)> {
+        match payload.remove(SD_ALG_CLAIM) {
+            None => {}
+            Some(Value::String(sd_alg)) if sd_alg == SD_ALG => {}
+            Some(sd_alg) => return Err(anyhow!("Unsupported hash algorithm: {sd_alg}")),
+        }
+
+        let mut disclosures = HashMap::new();
+        for disclosure in &self.disclosures {
+            if disclosures.insert(disclosure.digest(), disclosure).is_some() {
+                return Err(anyhow!("The SD-JWT contains duplicate disclosures."));
+            }
+        }
+
+        let mut processed = HashSet::new();
+        let mut paths = vec![];
+        let mut payload = Value::Object(payload);
+        process(&mut payload, &[], &disclosures, &mut processed, &mut paths)?;
+
+        if processed.len() != disclosures.len() {
+            return Err(anyhow!("The SD-JWT contains disclosures that are not referenced."));
+        }
+
+        match payload {
+            Value::Object(payload) => Ok((payload, paths)),
+            _ => unreachable!("The payload is always a JSON object."),
+        }
+    }
+}
+
+/// Disclosures together with the location of their claim, as a list of claim names and array indices.
+pub type DisclosurePaths<'a> = Vec<(Vec<String>, &'a Disclosure)>;
+
+/// Claims of a Key Binding JWT as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#name-key-binding-jwt
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindingJwtClaims {
+    pub iat: i64,
+    pub aud: String,
+    pub nonce: String,
+    pub sd_hash: String,
+}
+
+impl FromStr for SdJwt {
+    type Err = anyhow::Error;
+
+    fn from_str(sd_jwt: &str) -> Result<Self> {
+        let mut parts: Vec<&str> = sd_jwt.split('~').collect();
+        if parts.len() < 2 {
+            return Err(anyhow!("Invalid SD-JWT: missing `~` separator."));
+        }
+        let key_binding_jwt = parts.pop().filter(|part| !part.is_empty()).map(ToString::to_string);
+        let jwt = parts.remove(0).to_string();
+        let disclosures = parts.into_iter().map(Disclosure::from_str).collect::<Result<_>>()?;
+
+        Ok(Self {
+            jwt,
+            disclosures,
+            key_binding_jwt,
+        })
+    }
+}
+
+impl Display for SdJwt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}~", self.jwt)?;
+        for disclosure in &self.disclosures {
+            write!(f, "{disclosure}~")?;
+        }
+        if let Some(key_binding_jwt) = &self.key_binding_jwt {
+            write!(f, "{key_binding_jwt}")?;
+        }
+        Ok(())
+    }
+}
+
+fn process<'a>(
+    value: &mut Value,
+    path: &[String],
+    disclosures: &HashMap<String, &'a Disclosure>,
+    processed: &mut HashSet<&'a str>,
+    paths: &mut DisclosurePaths<'a>,
+) -> Result<()> {
+    let mut find = |digest: &Value| -> Result<Option<&'a Disclosure>> {
+        let digest = digest.as_str().ok_or(anyhow!("Digests must be strings."))?;
+        match disclosures.get(digest) {
+            Some(disclosure) => {
+                if !processed.insert(disclosure.as_str()) {
+                    return Err(anyhow!("The digest {digest} is referenced more than once."));
+                }
+                Ok(Some(*disclosure))
+            }
+            None => Ok(None),
+        }
+    };
+    let child_path = |segment: String| [path, &[segment]].concat();
+
+    match value {
+        Value::Object(object) => {
+            let digests = match object.remove(SD) {
+                Some(Value::Array(digests)) => digests,
+                Some(_) => return Err(anyhow!("The `_sd` claim must be an array.")),
+                None => vec![],
+            };
+            for digest in &digests {
+                let Some(disclosure) = find(digest)? else {
+                    continue;
+                };
+                let claim_name = disclosure
+                    .claim_name
+                    .clone()
+                    .ok_or(anyhow!("Array element disclosures cannot be referenced from `_sd`."))?;
+                if object.contains_key(&claim_name) {
+                    return Err(anyhow!("The disclosed claim `{claim_name}` already exists."));
+                }
+                paths.push((child_path(claim_name.clone()), disclosure));
+                object.insert(claim_name, disclosure.claim_value.clone());
+            }
+            for (claim_name, value) in object.iter_mut() {
+                process(value, &child_path(claim_name.clone()), disclosures, processed, paths)?;
+            }
+        }
+        Value::Array(array) => {
+            let mut elements = vec![];
+            for mut element in std::mem::take(array) {
+                match element
+                    .as_object()
+                    .filter(|element| element.len() == 1)
+                    .and_then(|element| element.get(ARRAY_ELEMENT))
+                {
+                    Some(digest) => match find(digest)? {
+                        Some(
+                            disclosure @ Disclosure {
+                                claim_name: None,
+                                claim_value,
+                                ..
+                            },
+                        ) => {
+                            paths.push((child_path(elements.len().to_string()), disclosure));
+                            elements.push(claim_value.clone());
+                        }
+                        Some(_) => return Err(anyhow!("Object property disclosures cannot be referenced from `...`.")),
+                        None => {}
+                    },
+                    None => elements.push(element.take()),
+                }
+            }
+            for (index, element) in elements.iter_mut().enumerate() {
+                process(element, &child_path(index.to_string()), disclosures, processed, paths)?;
+            }
+            *array = elements;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Adds a digest to the `_sd` array of the object. The digests are sorted so that their order does not reveal the
+/// original order of the claims.
+fn add_digest(object: &mut JsonObject, digest: String) -> Result<()> {
+    let digests = object
+        .entry(SD)
+        .or_insert_with(|| json!([]))
+        .as_array_mut()
+        .ok_or(anyhow!("The `_sd` claim must be an array."))?;
+    digests.push(json!(digest));
+    digests.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    Ok(())
+}
+
+fn digest(input: &str) -> String {
+    base64_url::encode(&Sha256::digest(input.as_bytes()))
+}
+
+fn random_salt() -> String {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    base64_url::encode(&salt)
+}
+
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockVerifier, TestSubject, TEST_KEYPAIR};
+    use jsonwebtoken::Algorithm;
+
+    #[test]
+    fn test_disclosure() {
+        // Example Disclosure from the specification:
+        // https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html#section-5.2.1
+        let disclosure: Disclosure = "WyJfMjZiYzRMVC1hYzZxMktJNmNCVzVlcyIsICJmYW1pbHlfbmFtZSIsICJNw7ZiaXVzIl0"
+            .parse()
+            .unwrap();
+        assert_eq!(disclosure.salt, "_26bc4LT-ac6q2KI6cBW5es");
+        assert_eq!(disclosure.claim_name, Some("family_name".to_string()));
+        assert_eq!(disclosure.claim_value, json!("Möbius"));
+
+        let disclosure: Disclosure = "WyJsa2x4RjVqTVlsR1RQVW92TU5JdkNBIiwgIkZSIl0".parse().unwrap();
+        assert_eq!(disclosure.claim_name, None);
+        assert_eq!(disclosure.claim_value, json!("FR"));
+        assert_eq!(disclosure.digest(), "w0I8EKcdCtUPkGCNUrfwVp2xEgNjtoIDlOxc9-PlOhs");
+
+        // Reserved claim names are rejected.
+        assert!(Disclosure::new(Some("_sd".to_string()), json!([]))
+            .to_string()
+            .parse::<Disclosure>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_sd_object_encoder() {
+        let mut encoder = SdObjectEncoder::new(
+            json!({
+                "given_name": "John",
+                "address": {
+                    "street_address": "123 Main St",
+                    "country": "US"
+                },
+                "nationalities": ["US", "DE"]
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        encoder.conceal("/address/street_address").unwrap();
+        encoder.conceal("/address").unwrap();
+        encoder.conceal("/nationalities/1").unwrap();
+        encoder.add_decoys("", 3).unwrap();
+        assert!(encoder.conceal("/unknown").is_err());
+
+        let (payload, disclosures) = encoder.finish();
+        assert_eq!(disclosures.len(), 3);
+        assert_eq!(payload["_sd_alg"], "sha-256");
+        assert_eq!(payload["given_name"], "John");
+        assert!(payload.get("address").is_none());
+        // One digest for the `address` claim and three decoys, in sorted order.
+        let digests = payload["_sd"].as_array().unwrap();
+        assert_eq!(digests.len(), 4);
+        assert!(digests.windows(2).all(|pair| pair[0].as_str() <= pair[1].as_str()));
+        assert!(digests.contains(&json!(disclosures[1].digest())));
+        assert_eq!(
+            payload["nationalities"],
+            json!(["US", { "...": disclosures[2].digest() }])
+        );
+        assert_eq!(
+            disclosures[1].claim_value,
+            json!({
+                "_sd": [disclosures[0].digest()],
+                "country": "US"
+            })
+        );
+
+        let sd_jwt = SdJwt {
+            jwt: String::new(),
+            disclosures: disclosures.clone(),
+            key_binding_jwt: None,
+        };
+        assert_eq!(
+            Value::Object(sd_jwt.disclose(payload.clone()).unwrap()),
+            json!({
+                "given_name": "John",
+                "address": {
+                    "street_address": "123 Main St",
+                    "country": "US"
+                },
+                "nationalities": ["US", "DE"]
+            })
+        );
+
+        // Claims of which the Disclosure is withheld are not disclosed.
+        let sd_jwt = SdJwt {
+            disclosures: vec![disclosures[1].clone()],
+            ..sd_jwt
+        };
+        assert_eq!(
+            Value::Object(sd_jwt.disclose(payload.clone()).unwrap()),
+            json!({
+                "given_name": "John",
+                "address": {
+                    "country": "US"
+                },
+                "nationalities": ["US"]
+            })
+        );
+
+        // Disclosures that are not referenced or that are duplicated are rejected.
+        let unreferenced = SdJwt {
+            disclosures: vec![Disclosure::new(Some("age".to_string()), json!(42))],
+            ..sd_jwt.clone()
+        };
+        assert!(unreferenced.disclose(payload.clone()).is_err());
+        let duplicated = SdJwt {
+            disclosures: vec![disclosures[1].clone(), disclosures[1].clone()],
+            ..sd_jwt
+        };
+        assert!(duplicated.disclose(payload).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sd_jwt() {
+        let mut encoder = SdObjectEncoder::new(
+            json!({
+                "iss": "https://example.com/issuer",
+                "given_name": "John",
+                "family_name": "Doe"
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        encoder.conceal("/given_name").unwrap();
+        encoder.conceal("/family_name").unwrap();
+
+        let sd_jwt = SdJwt::encode(
+            Arc::new(TestSubject::default()),
+            Header::new(Algorithm::EdDSA),
+            encoder,
+            "did:test",
+        )
+        .await
+        .unwrap();
+
+        let serialized = sd_jwt.to_string();
+        assert_eq!(serialized.matches('~').count(), 3);
+        assert!(serialized.ends_with('~'));
+
+        let parsed: SdJwt = serialized.parse().unwrap();
+        assert_eq!(parsed, sd_jwt);
+        assert_eq!(
+            Value::Object(
+                parsed
+                    .decode(&Validator::Verifier(Arc::new(MockVerifier::new())))
+                    .await
+                    .unwrap()
+            ),
+            json!({
+                "iss": "https://example.com/issuer",
+                "given_name": "John",
+                "family_name": "Doe"
+            })
+        );
+
+        assert!("eyJhbGciOiJFZERTQSJ9".parse::<SdJwt>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_binding() {
+        let holder = Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap());
+        let holder_jwk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": base64_url::encode(TEST_KEYPAIR.verifying_key().as_bytes())
+        });
+        let validator = Validator::Verifier(Arc::new(MockVerifier::new()));
+
+        for holder_binding in [
+            json!({ "sub": "did:test:123" }),
+            json!({ "cnf": { "jwk": holder_jwk } }),
+        ] {
+            let mut claims = holder_binding.as_object().unwrap().clone();
+            claims.insert("iss".to_string(), json!("https://example.com/issuer"));
+            claims.insert("address".to_string(), json!({ "locality": "Anytown", "country": "US" }));
+            claims.insert("nationalities".to_string(), json!(["US", "DE"]));
+            let mut encoder = SdObjectEncoder::new(claims);
+            encoder.conceal("/address/locality").unwrap();
+            encoder.conceal("/address").unwrap();
+            encoder.conceal("/nationalities/1").unwrap();
+
+            let sd_jwt = SdJwt::encode(holder.clone(), Header::new(Algorithm::EdDSA), encoder, "did:test")
+                .await
+                .unwrap();
+            assert_eq!(
+                sd_jwt
+                    .disclosure_paths()
+                    .unwrap()
+                    .into_iter()
+                    .map(|(path, _)| path.join("/"))
+                    .collect::<HashSet<_>>(),
+                HashSet::from_iter(["address", "address/locality", "nationalities/1"].map(String::from))
+            );
+
+            let sd_jwt = sd_jwt
+                .bind(
+                    holder.clone(),
+                    Algorithm::EdDSA,
+                    "did:test",
+                    "https://verifier.example.com",
+                    "n-0S6_WzA2Mj",
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                jsonwebtoken::decode_header(sd_jwt.key_binding_jwt.as_ref().unwrap())
+                    .unwrap()
+                    .typ,
+                Some("kb+jwt".to_string())
+            );
+
+            let sd_jwt: SdJwt = sd_jwt.to_string().parse().unwrap();
+            let payload = sd_jwt.decode(&validator).await.unwrap();
+            let key_binding = sd_jwt.verify_key_binding(&payload, &validator).await.unwrap();
+            assert_eq!(key_binding.aud, "https://verifier.example.com");
+            assert_eq!(key_binding.nonce, "n-0S6_WzA2Mj");
+            assert_eq!(key_binding.sd_hash, sd_jwt.sd_hash());
+
+            // Withholding a Disclosure after the Key Binding JWT was created invalidates the `sd_hash`.
+            let tampered = SdJwt {
+                disclosures: sd_jwt.disclosures[..2].to_vec(),
+                ..sd_jwt.clone()
+            };
+            assert!(tampered.verify_key_binding(&payload, &validator).await.is_err());
+
+            // A Key Binding JWT that is issued too long ago, issued in the future or lacks an `iat` is rejected.
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+            let header = Header {
+                typ: Some(KB_JWT_TYP.to_string()),
+                ..Header::new(Algorithm::EdDSA)
+            };
+            for claims in [
+                json!({ "iat": now - 3600, "aud": "https://verifier.example.com", "nonce": "n-0S6_WzA2Mj", "sd_hash": sd_jwt.sd_hash() }),
+                json!({ "iat": now + 3600, "aud": "https://verifier.example.com", "nonce": "n-0S6_WzA2Mj", "sd_hash": sd_jwt.sd_hash() }),
+                json!({ "aud": "https://verifier.example.com", "nonce": "n-0S6_WzA2Mj", "sd_hash": sd_jwt.sd_hash() }),
+            ] {
+                let stale = SdJwt {
+                    key_binding_jwt: Some(
+                        jwt::encode(holder.clone(), header.clone(), claims, "did:test")
+                            .await
+                            .unwrap(),
+                    ),
+                    ..sd_jwt.clone()
+                };
+                assert!(stale.verify_key_binding(&payload, &validator).await.is_err());
+            }
+        }
+
+        // A Key Binding JWT signed by another key than the Holder's is rejected.
+        let mut encoder = SdObjectEncoder::new(json!({ "sub": "did:test:456" }).as_object().unwrap().clone());
+        encoder.add_decoys("", 1).unwrap();
+        let sd_jwt = SdJwt::encode(holder.clone(), Header::new(Algorithm::EdDSA), encoder, "did:test")
+            .await
+            .unwrap()
+            .bind(
+                holder,
+                Algorithm::EdDSA,
+                "did:test",
+                "https://verifier.example.com",
+                "n-0S6_WzA2Mj",
+            )
+            .await
+            .unwrap();
+        let payload = sd_jwt.decode(&validator).await.unwrap();
+        assert!(sd_jwt.verify_key_binding(&payload, &validator).await.is_err());
+    }
+}
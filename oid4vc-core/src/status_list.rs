@@ -0,0 +1,431 @@
+use crate::RFC7519Claims;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use jsonwebtoken::{Algorithm, Header};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::io::{Read, Write};
+use url::Url;
+
+/// The `typ` of a Status List Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list-token-in-jwt-fo
+pub const STATUS_LIST_JWT_TYP: &str = "statuslist+jwt";
+
+/// The minimum length of a Bitstring Status List, which provides group privacy to the Holders, as described here:
+/// https://www.w3.org/TR/vc-bitstring-status-list/#bitstring-generation-algorithm
+pub const MIN_BITSTRING_LENGTH: usize = 131_072;
+
+/// The maximum size of a decompressed status list, which protects against decompression bombs. It is sufficient for a
+/// Status List of 16 million Referenced Tokens with 8 bits each, or a Bitstring of over 130 million entries.
+pub const MAX_STATUS_LIST_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The status of a Referenced Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-types-values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusType {
+    Valid,
+    Invalid,
+    Suspended,
+    ApplicationSpecific(u8),
+}
+
+impl From<u8> for StatusType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => StatusType::Valid,
+            0x01 => StatusType::Invalid,
+            0x02 => StatusType::Suspended,
+            value => StatusType::ApplicationSpecific(value),
+        }
+    }
+}
+
+impl From<StatusType> for u8 {
+    fn from(status_type: StatusType) -> Self {
+        match status_type {
+            StatusType::Valid => 0x00,
+            StatusType::Invalid => 0x01,
+            StatusType::Suspended => 0x02,
+            StatusType::ApplicationSpecific(value) => value,
+        }
+    }
+}
+
+/// Token Status List in which the status of each Referenced Token is represented by `bits` bits, as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list
+/// The status of the Referenced Token with index 0 is stored in the least significant bits of the first byte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusList {
+    bits: u8,
+    lst: Vec<u8>,
+}
+
+impl StatusList {
+    /// Returns a Status List for `size` Referenced Tokens of which all statuses are `VALID`.
+    pub fn new(bits: u8, size: usize) -> Result<Self> {
+        if ![1, 2, 4, 8].contains(&bits) {
+            return Err(anyhow!("The number of bits per status must be 1, 2, 4 or 8."));
+        }
+        Ok(Self {
+            bits,
+            lst: vec![0; (size * bits as usize).div_ceil(8)],
+        })
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Returns the number of statuses in the Status List.
+    pub fn len(&self) -> usize {
+        self.lst.len() * 8 / self.bits as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lst.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Result<StatusType> {
+        let (byte, shift) = self.position(idx)?;
+        Ok(((self.lst[byte] >> shift) & self.mask()).into())
+    }
+
+    pub fn set(&mut self, idx: usize, status_type: StatusType) -> Result<()> {
+        let value: u8 = status_type.into();
+        if value > self.mask() {
+            return Err(anyhow!("The status does not fit in {} bit(s).", self.bits));
+        }
+        let (byte, shift) = self.position(idx)?;
+        self.lst[byte] = (self.lst[byte] & !(self.mask() << shift)) | (value << shift);
+        Ok(())
+    }
+
+    fn mask(&self) -> u8 {
+        (((1u16) << self.bits) - 1) as u8
+    }
+
+    fn position(&self, idx: usize) -> Result<(usize, usize)> {
+        if idx >= self.len() {
+            return Err(anyhow!("The index {idx} is out of bounds of the Status List."));
+        }
+        let offset = idx * self.bits as usize;
+        Ok((offset / 8, offset % 8))
+    }
+}
+
+/// The Status List as it is encoded in a Status List Token: `lst` contains the base64url-encoded, ZLIB-compressed
+/// statuses.
+#[derive(Serialize, Deserialize)]
+struct EncodedStatusList {
+    bits: u8,
+    lst: String,
+}
+
+impl Serialize for StatusList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&self.lst)
+            .and_then(|_| encoder.finish())
+            .map_err(serde::ser::Error::custom)
+            .and_then(|lst| {
+                EncodedStatusList {
+                    bits: self.bits,
+                    lst: base64_url::encode(&lst),
+                }
+                .serialize(serializer)
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusList {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let EncodedStatusList { bits, lst } = EncodedStatusList::deserialize(deserializer)?;
+        let compressed = base64_url::decode(&lst).map_err(serde::de::Error::custom)?;
+        let lst = decompress(ZlibDecoder::new(compressed.as_slice())).map_err(serde::de::Error::custom)?;
+        if ![1, 2, 4, 8].contains(&bits) {
+            return Err(serde::de::Error::custom("`bits` must be 1, 2, 4 or 8."));
+        }
+        Ok(StatusList { bits, lst })
+    }
+}
+
+/// The payload of a Status List Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list-token-in-jwt-fo
+/// The `sub` claim contains the URI at which the Status List Token is published.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusListToken {
+    #[serde(flatten)]
+    pub rfc7519_claims: RFC7519Claims,
+    /// The number of seconds for which the Status List Token may be cached.
+    pub ttl: Option<u64>,
+    pub status_list: StatusList,
+}
+
+impl StatusListToken {
+    pub fn header(algorithm: Algorithm) -> Header {
+        Header {
+            typ: Some(STATUS_LIST_JWT_TYP.to_string()),
+            ..Header::new(algorithm)
+        }
+    }
+}
+
+/// The `status` claim of a Referenced Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-referenced-token-in-jose
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Status {
+    pub status_list: StatusListReference,
+}
+
+/// The position of a Referenced Token in the Status List Token that is published at `uri`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusListReference {
+    pub idx: usize,
+    pub uri: Url,
+}
+
+/// The purpose of a Bitstring Status List as described here:
+/// https://www.w3.org/TR/vc-bitstring-status-list/#bitstringstatuslistentry
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPurpose {
+    Revocation,
+    Suspension,
+}
+
+impl std::fmt::Display for StatusPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusPurpose::Revocation => write!(f, "revocation"),
+            StatusPurpose::Suspension => write!(f, "suspension"),
+        }
+    }
+}
+
+/// The `credentialStatus` of a W3C Verifiable Credential that is included in a Bitstring Status List, as described
+/// here: https://www.w3.org/TR/vc-bitstring-status-list/#bitstringstatuslistentry
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BitstringStatusListEntry {
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub status_purpose: StatusPurpose,
+    /// The index of the Verifiable Credential in the Bitstring Status List, as a string.
+    pub status_list_index: String,
+    pub status_list_credential: Url,
+}
+
+impl BitstringStatusListEntry {
+    pub fn new(status_list_credential: Url, status_purpose: StatusPurpose, status_list_index: usize) -> Self {
+        Self {
+            id: Some(format!("{status_list_credential}#{status_list_index}")),
+            type_: "BitstringStatusListEntry".to_string(),
+            status_purpose,
+            status_list_index: status_list_index.to_string(),
+            status_list_credential,
+        }
+    }
+
+    pub fn index(&self) -> Result<usize> {
+        self.status_list_index
+            .parse()
+            .map_err(|_| anyhow!("The `statusListIndex` must be a non-negative integer."))
+    }
+}
+
+/// Bitstring of a Bitstring Status List as described here:
+/// https://www.w3.org/TR/vc-bitstring-status-list/#bitstring-encoding
+/// The status of the Verifiable Credential with index 0 is stored in the most significant bit of the first byte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitstring(Vec<u8>);
+
+impl Bitstring {
+    /// Returns a Bitstring of `length` bits that are all unset.
+    pub fn new(length: usize) -> Self {
+        Self(vec![0; length.div_ceil(8)])
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len() * 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Result<bool> {
+        let (byte, mask) = self.position(idx)?;
+        Ok(self.0[byte] & mask != 0)
+    }
+
+    pub fn set(&mut self, idx: usize, value: bool) -> Result<()> {
+        let (byte, mask) = self.position(idx)?;
+        if value {
+            self.0[byte] |= mask;
+        } else {
+            self.0[byte] &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Returns the `encodedList`: the multibase base64url-encoded, GZIP-compressed Bitstring.
+    pub fn encode(&self) -> Result<String> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&self.0)?;
+        Ok(format!("u{}", base64_url::encode(&encoder.finish()?)))
+    }
+
+    pub fn decode(encoded_list: &str) -> Result<Self> {
+        let compressed = base64_url::decode(
+            encoded_list
+                .strip_prefix('u')
+                .ok_or(anyhow!("The `encodedList` must be multibase base64url-encoded."))?,
+        )?;
+        Ok(Self(decompress(GzDecoder::new(compressed.as_slice()))?))
+    }
+
+    fn position(&self, idx: usize) -> Result<(usize, u8)> {
+        if idx >= self.len() {
+            return Err(anyhow!("The index {idx} is out of bounds of the Bitstring."));
+        }
+        Ok((idx / 8, 0x80 >> (idx % 8)))
+    }
+}
+
+/// Reads a compressed status list, of which the decompressed size must not exceed [`MAX_STATUS_LIST_SIZE`].
+fn decompress(decoder: impl Read) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    decoder.take(MAX_STATUS_LIST_SIZE + 1).read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_STATUS_LIST_SIZE {
+        return Err(anyhow!(
+            "The status list exceeds the maximum size of {MAX_STATUS_LIST_SIZE} bytes."
+        ));
+    }
+    Ok(decompressed)
+}
+
+/// The references to the position of a Credential in the status lists of its Issuer. SD-JWT VCs contain the `status`
+/// claim, whereas W3C Verifiable Credentials contain the `credentialStatus` entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialStatus {
+    pub status: Status,
+    pub credential_status: Vec<BitstringStatusListEntry>,
+}
+
+/// Retrieves the status lists that are referenced by Credentials, i.e. Status List Tokens and Bitstring Status List
+/// Credentials.
+#[async_trait]
+pub trait StatusListTransport: Send + Sync {
+    /// Returns the status list that is published at `uri`.
+    async fn fetch(&self, uri: &Url) -> Result<String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_status_list() {
+        // Example from https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list
+        let mut status_list = StatusList::new(1, 16).unwrap();
+        for idx in [0, 3, 4, 5, 7, 8, 9, 13, 15] {
+            status_list.set(idx, StatusType::Invalid).unwrap();
+        }
+        assert_eq!(status_list.lst, vec![0xB9, 0xA3]);
+
+        let encoded = serde_json::to_value(&status_list).unwrap();
+        assert_eq!(encoded["bits"], json!(1));
+        assert_eq!(serde_json::from_value::<StatusList>(encoded).unwrap(), status_list);
+        assert_eq!(
+            serde_json::from_value::<StatusList>(json!({ "bits": 1, "lst": "eNrbuRgAAhcBXQ" })).unwrap(),
+            status_list
+        );
+
+        let mut status_list = StatusList::new(2, 12).unwrap();
+        for (idx, status_type) in [
+            (0, StatusType::Invalid),
+            (1, StatusType::Suspended),
+            (2, StatusType::Valid),
+            (3, StatusType::ApplicationSpecific(3)),
+            (4, StatusType::Valid),
+            (5, StatusType::Invalid),
+            (6, StatusType::Valid),
+            (7, StatusType::Invalid),
+            (8, StatusType::Invalid),
+            (9, StatusType::Suspended),
+            (10, StatusType::ApplicationSpecific(3)),
+            (11, StatusType::ApplicationSpecific(3)),
+        ] {
+            status_list.set(idx, status_type).unwrap();
+        }
+        assert_eq!(status_list.lst, vec![0xC9, 0x44, 0xF9]);
+        assert_eq!(status_list.get(1).unwrap(), StatusType::Suspended);
+        assert_eq!(status_list.get(2).unwrap(), StatusType::Valid);
+
+        assert!(status_list.get(12).is_err());
+        assert!(status_list.set(0, StatusType::ApplicationSpecific(4)).is_err());
+        assert!(StatusList::new(3, 8).is_err());
+    }
+
+    #[test]
+    fn test_bitstring() {
+        let mut bitstring = Bitstring::new(MIN_BITSTRING_LENGTH);
+        assert_eq!(bitstring.len(), MIN_BITSTRING_LENGTH);
+
+        bitstring.set(0, true).unwrap();
+        bitstring.set(94567, true).unwrap();
+        assert_eq!(bitstring.0[0], 0x80);
+        assert!(bitstring.get(94567).unwrap());
+        assert!(!bitstring.get(94566).unwrap());
+
+        let encoded_list = bitstring.encode().unwrap();
+        assert!(encoded_list.starts_with('u'));
+        assert_eq!(Bitstring::decode(&encoded_list).unwrap(), bitstring);
+
+        bitstring.set(0, false).unwrap();
+        assert!(!bitstring.get(0).unwrap());
+        assert!(bitstring.get(MIN_BITSTRING_LENGTH).is_err());
+    }
+
+    #[test]
+    fn test_max_status_list_size() {
+        // Status lists that decompress beyond the maximum size are rejected.
+        let bitstring = Bitstring::new(MAX_STATUS_LIST_SIZE as usize * 8 + 8);
+        assert!(Bitstring::decode(&bitstring.encode().unwrap()).is_err());
+        let bitstring = Bitstring::new(MAX_STATUS_LIST_SIZE as usize * 8);
+        assert_eq!(Bitstring::decode(&bitstring.encode().unwrap()).unwrap(), bitstring);
+
+        let status_list = StatusList::new(8, MAX_STATUS_LIST_SIZE as usize + 1).unwrap();
+        assert!(serde_json::from_value::<StatusList>(json!(status_list)).is_err());
+    }
+
+    #[test]
+    fn test_bitstring_status_list_entry() {
+        let entry = BitstringStatusListEntry::new(
+            "https://example.com/credentials/status/3".parse().unwrap(),
+            StatusPurpose::Revocation,
+            94567,
+        );
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap(),
+            json!({
+                "id": "https://example.com/credentials/status/3#94567",
+                "type": "BitstringStatusListEntry",
+                "statusPurpose": "revocation",
+                "statusListIndex": "94567",
+                "statusListCredential": "https://example.com/credentials/status/3"
+            })
+        );
+        assert_eq!(entry.index().unwrap(), 94567);
+    }
+}
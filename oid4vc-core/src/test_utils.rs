@@ -42,6 +42,11 @@ impl Sign for TestSubject {
         Ok(signature.to_bytes().to_vec())
     }
 
+    async fn sign_bytes(&self, message: &[u8], _subject_syntax_type: &str, _algorithm: Algorithm) -> Result<Vec<u8>> {
+        let signature: Signature = TEST_KEYPAIR.sign(message);
+        Ok(signature.to_bytes().to_vec())
+    }
+
     fn external_signer(&self) -> Option<Arc<dyn ExternalSign>> {
         None
     }
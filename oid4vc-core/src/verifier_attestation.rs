@@ -0,0 +1,113 @@
+use crate::{jwt, JsonObject, RFC7519Claims, Validator, Verify};
+use anyhow::{anyhow, ensure, Result};
+use async_trait::async_trait;
+use jsonwebtoken::{jwk::Jwk, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use std::{collections::HashMap, sync::Arc};
+
+/// The `typ` of a Verifier Attestation JWT.
+pub const VERIFIER_ATTESTATION_TYPE: &str = "verifier-attestation+jwt";
+
+/// The key to which a Verifier Attestation is bound, as described here:
+/// https://www.rfc-editor.org/rfc/rfc7800.html#section-3.2
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Confirmation {
+    pub jwk: Jwk,
+}
+
+/// The claims of a Verifier Attestation JWT, with which a trusted third party attests the Verifier's key and its
+/// `redirect_uri`s. As described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-verifier-attestation-jwt
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct VerifierAttestation {
+    #[serde(flatten)]
+    pub rfc7519_claims: RFC7519Claims,
+    pub cnf: Confirmation,
+    pub redirect_uris: Option<Vec<url::Url>>,
+}
+
+impl VerifierAttestation {
+    /// Decodes a Verifier Attestation JWT and verifies that it is issued by one of the trusted `issuers`, which resolve
+    /// the keys of the issuers by their identifier, and that it is not expired.
+    pub async fn verify(jwt: &str, issuers: &HashMap<String, Arc<dyn Verify>>) -> Result<Self> {
+        let (header, attestation): (JsonObject, VerifierAttestation) = jwt::decode_unverified(jwt)?;
+        ensure!(
+            header.get("typ").and_then(Value::as_str) == Some(VERIFIER_ATTESTATION_TYPE),
+            "The `typ` of a Verifier Attestation must be `{VERIFIER_ATTESTATION_TYPE}`."
+        );
+        let iss = attestation
+            .rfc7519_claims
+            .iss
+            .as_ref()
+            .ok_or(anyhow!("The Verifier Attestation has no `iss` claim."))?;
+        let issuer = issuers
+            .get(iss)
+            .ok_or(anyhow!("The Verifier Attestation issuer `{iss}` is not trusted."))?;
+
+        let attestation: VerifierAttestation = Validator::Verifier(issuer.clone()).decode(jwt.to_owned()).await?;
+        let exp = attestation
+            .rfc7519_claims
+            .exp
+            .ok_or(anyhow!("The Verifier Attestation has no `exp` claim."))?;
+        ensure!(
+            chrono::Utc::now().timestamp() < exp,
+            "The Verifier Attestation is expired."
+        );
+
+        Ok(attestation)
+    }
+
+    /// Returns the attested key in the same raw form as [`Verify::public_key`].
+    pub fn public_key(&self) -> Result<Vec<u8>> {
+        jwt::public_key_from_jwk(&self.cnf.jwk)
+    }
+}
+
+/// Request objects of a Verifier with a [`VerifierAttestation`] are verified with the attested `cnf` key, regardless
+/// of the `kid` in their header.
+#[async_trait]
+impl Verify for VerifierAttestation {
+    async fn public_key(&self, _kid: &str) -> Result<Vec<u8>> {
+        VerifierAttestation::public_key(self)
+    }
+
+    async fn resolve_public_key(&self, _header: &Header) -> Result<Vec<u8>> {
+        VerifierAttestation::public_key(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_public_key() {
+        let attestation: VerifierAttestation = serde_json::from_value(json!({
+            "iss": "https://attestation.example.org",
+            "sub": "client",
+            "exp": 9999999999i64,
+            "cnf": {
+                "jwk": {
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+                }
+            },
+            "redirect_uris": ["https://client.example.org/cb"]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            attestation.public_key().unwrap(),
+            base64_url::decode("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo").unwrap()
+        );
+        assert_eq!(
+            attestation.redirect_uris,
+            Some(vec!["https://client.example.org/cb".parse().unwrap()])
+        );
+    }
+}
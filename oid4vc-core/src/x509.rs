@@ -0,0 +1,369 @@
+use crate::Verify;
+use anyhow::{anyhow, ensure, Result};
+use async_trait::async_trait;
+use base64_url::base64::{engine::general_purpose::STANDARD, Engine};
+use jsonwebtoken::Header;
+use x509_cert::{
+    der::{oid::ObjectIdentifier, Decode, Encode},
+    ext::pkix::{name::GeneralName, BasicConstraints, KeyUsage, SubjectAltName},
+    Certificate,
+};
+
+const ECDSA_WITH_SHA_256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// A set of trusted root certificates, against which the certificate chains in `x5c` JOSE headers are validated, as
+/// described here: https://www.rfc-editor.org/rfc/rfc7515.html#section-4.1.6.
+#[derive(Debug, Clone, Default)]
+pub struct TrustAnchors {
+    certificates: Vec<Certificate>,
+}
+
+impl TrustAnchors {
+    /// Adds a DER encoded certificate as a trust anchor.
+    pub fn add(&mut self, der: &[u8]) -> Result<()> {
+        self.certificates.push(Certificate::from_der(der)?);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// Validates the certification path of an `x5c` certificate chain, which starts with the end-entity certificate.
+    /// The last certificate must either be a trust anchor or be issued by one. Along the path, including the trust
+    /// anchor, every certificate must be within its validity period and must be issued, by name and by signature, by
+    /// the next certificate, which must be a CA that may sign certificates and whose path length constraint holds.
+    pub fn verify(&self, x5c: &[String]) -> Result<VerifiedChain> {
+        let chain = x5c
+            .iter()
+            .map(|certificate| Ok(Certificate::from_der(&STANDARD.decode(certificate)?)?))
+            .collect::<Result<Vec<_>>>()?;
+        let leaf = chain.first().ok_or(anyhow!("The certificate chain is empty."))?;
+        let last = chain.last().ok_or(anyhow!("The certificate chain is empty."))?;
+
+        let anchor = self
+            .certificates
+            .iter()
+            .find(|anchor| {
+                *anchor == last
+                    || (anchor.tbs_certificate.subject == last.tbs_certificate.issuer
+                        && verify_signature(last, anchor).is_ok())
+            })
+            .ok_or(anyhow!(
+                "The certificate chain of `{}` is not issued by a trust anchor.",
+                leaf.tbs_certificate.subject
+            ))?;
+        let mut path: Vec<&Certificate> = chain.iter().collect();
+        if anchor != last {
+            path.push(anchor);
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        for certificate in &path {
+            let validity = &certificate.tbs_certificate.validity;
+            ensure!(
+                validity.not_before.to_unix_duration().as_secs() <= now
+                    && now <= validity.not_after.to_unix_duration().as_secs(),
+                "The certificate of `{}` is not valid at this time.",
+                certificate.tbs_certificate.subject
+            );
+        }
+
+        // The number of intermediate CA certificates below the issuer equals the position of the certificate it issued.
+        for (intermediates, pair) in path.windows(2).enumerate() {
+            let (certificate, issuer) = (pair[0], pair[1]);
+            ensure!(
+                certificate.tbs_certificate.issuer == issuer.tbs_certificate.subject,
+                "The certificate of `{}` is not issued by `{}`.",
+                certificate.tbs_certificate.subject,
+                issuer.tbs_certificate.subject
+            );
+            let basic_constraints = issuer
+                .tbs_certificate
+                .get::<BasicConstraints>()?
+                .map(|(_, basic_constraints)| basic_constraints)
+                .filter(|basic_constraints| basic_constraints.ca)
+                .ok_or(anyhow!(
+                    "The certificate of `{}` is not a CA.",
+                    issuer.tbs_certificate.subject
+                ))?;
+            if let Some(path_len_constraint) = basic_constraints.path_len_constraint {
+                ensure!(
+                    intermediates <= path_len_constraint as usize,
+                    "The certificate chain exceeds the path length constraint of `{}`.",
+                    issuer.tbs_certificate.subject
+                );
+            }
+            if let Some((_, key_usage)) = issuer.tbs_certificate.get::<KeyUsage>()? {
+                ensure!(
+                    key_usage.key_cert_sign(),
+                    "The key of `{}` may not be used to sign certificates.",
+                    issuer.tbs_certificate.subject
+                );
+            }
+            verify_signature(certificate, issuer)?;
+        }
+
+        Ok(VerifiedChain { leaf: leaf.clone() })
+    }
+}
+
+/// A certificate chain of which the certification path is validated against the [`TrustAnchors`].
+#[derive(Debug, Clone)]
+pub struct VerifiedChain {
+    leaf: Certificate,
+}
+
+impl VerifiedChain {
+    /// Returns the public key of the end-entity certificate, in the same raw form as [`Verify::public_key`].
+    pub fn public_key(&self) -> Vec<u8> {
+        self.leaf
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes()
+            .to_vec()
+    }
+
+    /// Returns the `dNSName` entries of the Subject Alternative Name of the end-entity certificate.
+    pub fn dns_names(&self) -> Result<Vec<String>> {
+        self.subject_alt_names(|name| match name {
+            GeneralName::DnsName(dns_name) => Some(dns_name.to_string()),
+            _ => None,
+        })
+    }
+
+    /// Returns the `uniformResourceIdentifier` entries of the Subject Alternative Name of the end-entity certificate.
+    pub fn uris(&self) -> Result<Vec<String>> {
+        self.subject_alt_names(|name| match name {
+            GeneralName::UniformResourceIdentifier(uri) => Some(uri.to_string()),
+            _ => None,
+        })
+    }
+
+    fn subject_alt_names(&self, f: impl Fn(&GeneralName) -> Option<String>) -> Result<Vec<String>> {
+        Ok(self
+            .leaf
+            .tbs_certificate
+            .get::<SubjectAltName>()?
+            .map(|(_, subject_alt_name)| subject_alt_name.0.iter().filter_map(f).collect())
+            .unwrap_or_default())
+    }
+}
+
+/// A [`Verify`] implementation that resolves the public key with which a JWT is signed from the `x5c` certificate
+/// chain in its header instead of from its `kid`.
+#[derive(Debug, Clone, Default)]
+pub struct X509Verifier {
+    pub trust_anchors: TrustAnchors,
+}
+
+impl X509Verifier {
+    pub fn new(trust_anchors: TrustAnchors) -> Self {
+        X509Verifier { trust_anchors }
+    }
+}
+
+#[async_trait]
+impl Verify for X509Verifier {
+    async fn public_key(&self, kid: &str) -> Result<Vec<u8>> {
+        Err(anyhow!(
+            "The key `{kid}` cannot be resolved without an `x5c` certificate chain."
+        ))
+    }
+
+    async fn resolve_public_key(&self, header: &Header) -> Result<Vec<u8>> {
+        let x5c = header
+            .x5c
+            .as_ref()
+            .ok_or(anyhow!("No `x5c` certificate chain found in the header."))?;
+        Ok(self.trust_anchors.verify(x5c)?.public_key())
+    }
+}
+
+/// Verifies that the `certificate` is signed with the public key of the `issuer`.
+fn verify_signature(certificate: &Certificate, issuer: &Certificate) -> Result<()> {
+    let message = certificate.tbs_certificate.to_der()?;
+    let signature = certificate
+        .signature
+        .as_bytes()
+        .ok_or(anyhow!("Invalid certificate signature."))?;
+    let public_key = issuer
+        .tbs_certificate
+        .subject_public_key_info
+        .subject_public_key
+        .raw_bytes();
+
+    match certificate.signature_algorithm.oid {
+        ECDSA_WITH_SHA_256 => {
+            use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+            VerifyingKey::from_sec1_bytes(public_key)?.verify(&message, &Signature::from_der(signature)?)?;
+        }
+        ED25519 => {
+            use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+            VerifyingKey::from_bytes(public_key.try_into()?)?.verify(&message, &Signature::from_slice(signature)?)?;
+        }
+        oid => return Err(anyhow!("Unsupported certificate signature algorithm `{oid}`.")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, KeyUsagePurpose, SanType, PKCS_ECDSA_P256_SHA256};
+
+    fn ca_params(name: &str) -> CertificateParams {
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.distinguished_name.push(rcgen::DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+    }
+
+    fn ca(name: &str) -> (rcgen::Certificate, KeyPair) {
+        let key_pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        (ca_params(name).self_signed(&key_pair).unwrap(), key_pair)
+    }
+
+    fn leaf(issuer: &rcgen::Certificate, issuer_key: &KeyPair, expired: bool) -> (rcgen::Certificate, KeyPair) {
+        let key_pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = CertificateParams::new(vec!["verifier.example.org".to_string()]).unwrap();
+        params
+            .subject_alt_names
+            .push(SanType::URI("https://verifier.example.org/cb".try_into().unwrap()));
+        if expired {
+            params.not_after = rcgen::date_time_ymd(2000, 1, 1);
+        }
+        (params.signed_by(&key_pair, issuer, issuer_key).unwrap(), key_pair)
+    }
+
+    fn x5c(certificates: &[&rcgen::Certificate]) -> Vec<String> {
+        certificates
+            .iter()
+            .map(|certificate| STANDARD.encode(certificate.der()))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let (root, root_key) = ca("Root");
+        let (leaf, leaf_key) = leaf(&root, &root_key, false);
+
+        let mut trust_anchors = TrustAnchors::default();
+        trust_anchors.add(root.der()).unwrap();
+
+        let chain = trust_anchors.verify(&x5c(&[&leaf])).unwrap();
+        assert_eq!(chain.public_key(), leaf_key.public_key_raw());
+        assert_eq!(chain.dns_names().unwrap(), vec!["verifier.example.org"]);
+        assert_eq!(chain.uris().unwrap(), vec!["https://verifier.example.org/cb"]);
+
+        // The chain may also include the trust anchor itself.
+        assert!(trust_anchors.verify(&x5c(&[&leaf, &root])).is_ok());
+
+        // A chain that is not issued by a trust anchor is rejected.
+        let (other_root, other_root_key) = ca("Other Root");
+        let (other_leaf, _) = self::leaf(&other_root, &other_root_key, false);
+        assert!(trust_anchors.verify(&x5c(&[&other_leaf, &other_root])).is_err());
+
+        // An expired certificate is rejected.
+        let (expired, _) = self::leaf(&root, &root_key, true);
+        assert!(trust_anchors.verify(&x5c(&[&expired])).is_err());
+
+        // An end-entity certificate cannot issue other certificates.
+        let (intermediate, intermediate_key) = self::leaf(&root, &root_key, false);
+        let (leaf, _) = self::leaf(&intermediate, &intermediate_key, false);
+        assert!(trust_anchors.verify(&x5c(&[&leaf, &intermediate])).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_constraints() {
+        let trust_anchors = |root: &rcgen::Certificate| {
+            let mut trust_anchors = TrustAnchors::default();
+            trust_anchors.add(root.der()).unwrap();
+            trust_anchors
+        };
+
+        // A certificate that is signed with the key of the CA, but names another issuer, is rejected.
+        let (root, root_key) = ca("Root");
+        let other_root = ca_params("Other Root").self_signed(&root_key).unwrap();
+        let (leaf, _) = self::leaf(&other_root, &root_key, false);
+        assert_eq!(
+            trust_anchors(&root)
+                .verify(&x5c(&[&leaf, &root]))
+                .unwrap_err()
+                .to_string(),
+            "The certificate of `CN=rcgen self signed cert` is not issued by `CN=Root`."
+        );
+
+        // An expired trust anchor is rejected.
+        let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = ca_params("Root");
+        params.not_after = rcgen::date_time_ymd(2000, 1, 1);
+        let root = params.self_signed(&root_key).unwrap();
+        let (leaf, _) = self::leaf(&root, &root_key, false);
+        assert_eq!(
+            trust_anchors(&root).verify(&x5c(&[&leaf])).unwrap_err().to_string(),
+            "The certificate of `CN=Root` is not valid at this time."
+        );
+
+        // The path length constraint limits the number of intermediate CAs below a CA.
+        for (path_len_constraint, error) in [
+            (
+                0,
+                Some("The certificate chain exceeds the path length constraint of `CN=Root`."),
+            ),
+            (1, None),
+        ] {
+            let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+            let mut params = ca_params("Root");
+            params.is_ca = IsCa::Ca(BasicConstraints::Constrained(path_len_constraint));
+            let root = params.self_signed(&root_key).unwrap();
+            let intermediate_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+            let intermediate = ca_params("Intermediate")
+                .signed_by(&intermediate_key, &root, &root_key)
+                .unwrap();
+            let (leaf, _) = self::leaf(&intermediate, &intermediate_key, false);
+            let result = trust_anchors(&root).verify(&x5c(&[&leaf, &intermediate]));
+            assert_eq!(result.err().map(|error| error.to_string()).as_deref(), error);
+        }
+
+        // A CA of which the key usage does not include `keyCertSign` cannot issue certificates.
+        for (key_usages, error) in [
+            (
+                vec![KeyUsagePurpose::DigitalSignature],
+                Some("The key of `CN=Root` may not be used to sign certificates."),
+            ),
+            (vec![KeyUsagePurpose::KeyCertSign], None),
+        ] {
+            let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+            let mut params = ca_params("Root");
+            params.key_usages = key_usages;
+            let root = params.self_signed(&root_key).unwrap();
+            let (leaf, _) = self::leaf(&root, &root_key, false);
+            let result = trust_anchors(&root).verify(&x5c(&[&leaf]));
+            assert_eq!(result.err().map(|error| error.to_string()).as_deref(), error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_x509_verifier() {
+        let (root, root_key) = ca("Root");
+        let (leaf, leaf_key) = leaf(&root, &root_key, false);
+
+        let mut header = Header::new(jsonwebtoken::Algorithm::ES256);
+        header.x5c = Some(x5c(&[&leaf]));
+        let encoding_key = jsonwebtoken::EncodingKey::from_ec_pem(leaf_key.serialize_pem().as_bytes()).unwrap();
+        let jwt = jsonwebtoken::encode(&header, &serde_json::json!({ "iss": "verifier" }), &encoding_key).unwrap();
+
+        let mut trust_anchors = TrustAnchors::default();
+        trust_anchors.add(root.der()).unwrap();
+        let validator = crate::Validator::Verifier(std::sync::Arc::new(X509Verifier::new(trust_anchors)));
+        let claims: serde_json::Value = validator.decode(jwt.clone()).await.unwrap();
+        assert_eq!(claims, serde_json::json!({ "iss": "verifier" }));
+
+        // Without the trust anchor, the key cannot be resolved.
+        let validator = crate::Validator::Verifier(std::sync::Arc::new(X509Verifier::default()));
+        assert!(validator.decode::<serde_json::Value>(jwt).await.is_err());
+    }
+}
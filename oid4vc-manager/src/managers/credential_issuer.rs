@@ -1,16 +1,22 @@
 use crate::storage::Storage;
 use anyhow::Result;
-use oid4vc_core::Subject;
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    status_list::{StatusPurpose, StatusType},
+    Subject,
+};
 use oid4vci::{
     credential_format_profiles::CredentialFormatCollection,
     credential_issuer::{
         authorization_server_metadata::AuthorizationServerMetadata,
-        credential_issuer_metadata::CredentialIssuerMetadata, CredentialIssuer,
+        credential_issuer_metadata::CredentialIssuerMetadata, credential_signer::CredentialSigner,
+        status_list_manager::StatusListManager, CredentialIssuer,
     },
     credential_offer::{CredentialOffer, CredentialOfferParameters, Grants},
 };
 use reqwest::Url;
 use std::{net::TcpListener, sync::Arc};
+use tokio::sync::Mutex;
 
 #[derive(Clone)]
 pub struct CredentialIssuerManager<S: Storage<CFC>, CFC: CredentialFormatCollection> {
@@ -18,6 +24,11 @@ pub struct CredentialIssuerManager<S: Storage<CFC>, CFC: CredentialFormatCollect
     pub subject: Arc<dyn Subject>,
     pub storage: S,
     pub listener: Arc<TcpListener>,
+    pub credential_signer: CredentialSigner,
+    /// Keeps track of the status of the issued Credentials. When `None`, the Credentials do not contain any status.
+    pub status_list_manager: Option<Arc<Mutex<StatusListManager>>>,
+    /// The algorithm with which the status lists are signed when they are published.
+    pub status_list_signing_algorithm: Algorithm,
 }
 
 impl<S: Storage<CFC>, CFC: CredentialFormatCollection> CredentialIssuerManager<S, CFC> {
@@ -33,8 +44,11 @@ impl<S: Storage<CFC>, CFC: CredentialFormatCollection> CredentialIssuerManager<S
                     authorization_servers: vec![],
                     credential_endpoint: issuer_url.join("/credential")?,
                     batch_credential_endpoint: Some(issuer_url.join("/batch_credential")?),
-                    deferred_credential_endpoint: None,
-                    notification_endpoint: None,
+                    deferred_credential_endpoint: Some(issuer_url.join("/deferred_credential")?),
+                    notification_endpoint: storage
+                        .supports_notifications()
+                        .then(|| issuer_url.join("/notification"))
+                        .transpose()?,
                     credential_response_encryption: None,
                     credential_identifiers_supported: None,
                     signed_metadata: None,
@@ -49,12 +63,87 @@ impl<S: Storage<CFC>, CFC: CredentialFormatCollection> CredentialIssuerManager<S
                     ..Default::default()
                 },
             },
+            // The `KeySubject` is used by default, which uses the 'key' DID method.
+            credential_signer: CredentialSigner::new(subject.clone(), "did:key"),
             subject,
             storage,
             listener: Arc::new(listener),
+            status_list_manager: None,
+            status_list_signing_algorithm: Algorithm::EdDSA,
         })
     }
 
+    /// Includes the status of the issued Credentials in status lists with room for `size` Credentials, which are
+    /// published at the `/status_list` endpoint. Like the Credentials, the status lists are issued by the DID of the
+    /// signing subject.
+    pub async fn enable_status_list(&mut self, size: usize) -> Result<()> {
+        let issuer = self
+            .subject
+            .identifier(
+                &self.credential_signer.subject_syntax_type,
+                self.status_list_signing_algorithm,
+            )
+            .await?;
+        let credential_issuer = &self.credential_issuer.metadata.credential_issuer;
+        self.status_list_manager
+            .replace(Arc::new(Mutex::new(StatusListManager::new(
+                issuer,
+                credential_issuer.join("/status_list")?,
+                size,
+            )?)));
+        Ok(())
+    }
+
+    /// Revokes, suspends or reinstates the Credential with the status list index `idx`.
+    pub async fn set_credential_status(&self, idx: usize, status_type: StatusType) -> Result<()> {
+        self.status_list_manager()?.lock().await.set_status(idx, status_type)
+    }
+
+    /// Returns the signed Status List Token.
+    pub async fn status_list_token(&self) -> Result<String> {
+        self.status_list_manager()?
+            .lock()
+            .await
+            .status_list_token(
+                self.subject.clone(),
+                &self.credential_signer.subject_syntax_type,
+                self.status_list_signing_algorithm,
+            )
+            .await
+    }
+
+    /// Returns the signed Bitstring Status List Credential for the `status_purpose`.
+    pub async fn bitstring_status_list_credential(&self, status_purpose: StatusPurpose) -> Result<String> {
+        self.status_list_manager()?
+            .lock()
+            .await
+            .bitstring_status_list_credential(
+                status_purpose,
+                self.subject.clone(),
+                &self.credential_signer.subject_syntax_type,
+                self.status_list_signing_algorithm,
+            )
+            .await
+    }
+
+    fn status_list_manager(&self) -> Result<&Arc<Mutex<StatusListManager>>> {
+        self.status_list_manager
+            .as_ref()
+            .ok_or(anyhow::anyhow!("The status list is not enabled."))
+    }
+
+    /// Signs the Credential Issuer Metadata with the Credential Issuer's `subject` and includes the result as the
+    /// `signed_metadata` parameter.
+    pub async fn sign_metadata(&mut self, subject_syntax_type: &str, algorithm: Algorithm) -> Result<()> {
+        let signed_metadata = self
+            .credential_issuer
+            .metadata
+            .sign(self.subject.clone(), subject_syntax_type, algorithm)
+            .await?;
+        self.credential_issuer.metadata.signed_metadata.replace(signed_metadata);
+        Ok(())
+    }
+
     pub fn credential_issuer_url(&self) -> Result<Url> {
         Ok(self.credential_issuer.metadata.credential_issuer.clone())
     }
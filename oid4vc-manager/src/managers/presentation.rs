@@ -1,44 +1,399 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use oid4vp::{
-    evaluate_input, ClaimFormatDesignation, InputDescriptorMappingObject, PathNested, PresentationDefinition,
-    PresentationSubmission,
+    evaluate_relational_constraints, match_candidate, match_candidates, Candidate, ClaimFormatDesignation,
+    InputDescriptorMappingObject, PathNested, PresentationDefinition, PresentationSubmission,
 };
+use std::collections::{HashMap, HashSet};
 
-/// Takes a [`PresentationDefinition`] and a credential and creates a [`PresentationSubmission`] from it if the
-/// credential meets the requirements.
-// TODO: make VP/VC fromat agnostic. In current form only jwt_vp_json + jwt_vc_json are supported.
+/// Takes a [`PresentationDefinition`] and the candidate credentials that can be presented, and creates a
+/// [`PresentationSubmission`] from it if the credentials meet the requirements, together with the indices of the
+/// candidates to present, in the order in which they must be embedded. Each Input Descriptor is answered with
+/// the first candidate that satisfies it. When the [`PresentationDefinition`] has Submission Requirements, only the
+/// Input Descriptors that are needed to meet them are answered. To let the Holder choose the credentials instead, use
+/// [`match_candidates`] and [`create_presentation_submission_from_selection`].
 pub fn create_presentation_submission(
     presentation_definition: &PresentationDefinition,
-    credentials: &[serde_json::Value],
-) -> Result<PresentationSubmission> {
+    candidates: &[Candidate],
+) -> Result<(PresentationSubmission, Vec<usize>)> {
+    let matches = match_candidates(presentation_definition, candidates);
+    let first_matches: HashMap<&str, usize> = matches
+        .iter()
+        .filter_map(|input_descriptor_matches| {
+            input_descriptor_matches
+                .accepted()
+                .next()
+                .map(|index| (input_descriptor_matches.id.as_str(), index))
+        })
+        .collect();
+    let selection = presentation_definition
+        .select_input_descriptors(&first_matches.keys().copied().collect())
+        .ok_or(anyhow!("The credentials do not satisfy the presentation definition."))?
+        .into_iter()
+        .map(|id| (id.to_string(), first_matches[id]))
+        .collect();
+    create_presentation_submission_from_selection(presentation_definition, candidates, &selection)
+}
+
+/// Creates a [`PresentationSubmission`] from the choice of the Holder, which maps the `id`s of the Input Descriptors to
+/// answer to the index of the candidate credential to answer it with. The choice must satisfy the
+/// [`PresentationDefinition`], and the `same_subject` constraints must hold between the chosen credentials, whereas
+/// the `is_holder` constraints are left to the Verifier, since the Holder is not known here.
+///
+/// The chosen credentials are presented in a single presentation: `jwt_vc_json` credentials in the `vp` claim of a
+/// `jwt_vp_json` Verifiable Presentation and `ldp_vc` credentials in an `ldp_vp` Verifiable Presentation, whereas a
+/// single SD-JWT VC or mdoc is presented as is. Besides the [`PresentationSubmission`], the indices of the chosen
+/// candidates are returned in the order in which they must be embedded in the presentation, which is the order of the
+/// Input Descriptors that they answer.
+pub fn create_presentation_submission_from_selection(
+    presentation_definition: &PresentationDefinition,
+    candidates: &[Candidate],
+    selection: &HashMap<String, usize>,
+) -> Result<(PresentationSubmission, Vec<usize>)> {
     let id = "Submission ID".to_string();
     let definition_id = presentation_definition.id().clone();
-    let descriptor_map = presentation_definition
+
+    for (input_descriptor_id, index) in selection {
+        let input_descriptor = presentation_definition
+            .input_descriptors()
+            .iter()
+            .find(|input_descriptor| input_descriptor.id() == input_descriptor_id)
+            .ok_or(anyhow!("There is no Input Descriptor `{input_descriptor_id}`."))?;
+        let candidate = candidates
+            .get(*index)
+            .ok_or(anyhow!("There is no credential at index {index}."))?;
+        match_candidate(presentation_definition, input_descriptor, candidate).map_err(|rejection| {
+            anyhow!("The credential does not satisfy Input Descriptor `{input_descriptor_id}`: {rejection}")
+        })?;
+    }
+    let selected: HashSet<&str> = selection.keys().map(String::as_str).collect();
+    if !presentation_definition.is_satisfied_by(&selected) {
+        return Err(anyhow!("The credentials do not satisfy the presentation definition."));
+    }
+    let matches: HashMap<&str, usize> = selection.iter().map(|(id, index)| (id.as_str(), *index)).collect();
+
+    let selected_credentials: HashMap<&str, &serde_json::Value> = selected
+        .iter()
+        .map(|id| (*id, &candidates[matches[id]].claim))
+        .collect();
+    let errors = evaluate_relational_constraints(presentation_definition, &selected_credentials, None);
+    if !errors.is_empty() {
+        return Err(anyhow!(errors.join(" ")));
+    }
+
+    let answered: Vec<&String> = presentation_definition
         .input_descriptors()
         .iter()
-        .enumerate()
-        .map(|(index, input_descriptor)| {
-            credentials
+        .map(|input_descriptor| input_descriptor.id())
+        .filter(|id| selected.contains(id.as_str()))
+        .collect();
+    let mut presented: Vec<usize> = vec![];
+    for id in &answered {
+        let index = matches[id.as_str()];
+        if !presented.contains(&index) {
+            presented.push(index);
+        }
+    }
+    let presentation_formats: HashSet<ClaimFormatDesignation> = presented
+        .iter()
+        .map(|index| candidates[*index].format.presentation_format())
+        .collect();
+    if presentation_formats.len() > 1 {
+        return Err(anyhow!(
+            "The selected credentials cannot be presented in a single presentation."
+        ));
+    }
+
+    let descriptor_map = answered
+        .into_iter()
+        .map(|id| {
+            let index = matches[id.as_str()];
+            let format = candidates[index].format.clone();
+            // The position of the credential within the presentation, rather than within the candidates.
+            let position = presented
                 .iter()
-                .find_map(|credential| {
-                    evaluate_input(input_descriptor, credential).then_some(InputDescriptorMappingObject {
-                        id: input_descriptor.id().clone(),
-                        format: ClaimFormatDesignation::JwtVpJson,
-                        path: "$".to_string(),
-                        path_nested: Some(PathNested {
-                            id: None,
-                            path: format!("$.vp.verifiableCredential[{}]", index),
-                            format: ClaimFormatDesignation::JwtVcJson,
-                            path_nested: None,
-                        }),
-                    })
+                .position(|presented| *presented == index)
+                .unwrap_or_default();
+            let path_nested = |path: String| {
+                Some(PathNested {
+                    id: None,
+                    path,
+                    format: format.clone(),
+                    path_nested: None,
                 })
-                .unwrap()
+            };
+            let path_nested = match format {
+                ClaimFormatDesignation::JwtVc | ClaimFormatDesignation::JwtVcJson => {
+                    path_nested(format!("$.vp.verifiableCredential[{position}]"))
+                }
+                ClaimFormatDesignation::LdpVc => path_nested(format!("$.verifiableCredential[{position}]")),
+                ClaimFormatDesignation::VcSdJwt | ClaimFormatDesignation::MsoMdoc if presented.len() == 1 => None,
+                ClaimFormatDesignation::VcSdJwt | ClaimFormatDesignation::MsoMdoc => {
+                    return Err(anyhow!("Only a single SD-JWT VC or mdoc can be presented."))
+                }
+                _ => return Err(anyhow!("Credentials of the format {format:?} cannot be presented.")),
+            };
+            Ok(InputDescriptorMappingObject {
+                id: id.clone(),
+                format: format.presentation_format(),
+                path: "$".to_string(),
+                path_nested,
+            })
         })
-        .collect::<Vec<_>>();
-    Ok(PresentationSubmission {
-        id,
-        definition_id,
-        descriptor_map,
-    })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((
+        PresentationSubmission {
+            id,
+            definition_id,
+            descriptor_map,
+        },
+        presented,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn candidate(format: ClaimFormatDesignation, claim: serde_json::Value) -> Candidate {
+        Candidate {
+            format,
+            alg: None,
+            claim,
+        }
+    }
+
+    fn jwt_vc_json(claim: serde_json::Value) -> Candidate {
+        candidate(ClaimFormatDesignation::JwtVcJson, claim)
+    }
+
+    #[test]
+    fn test_create_presentation_submission() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "Identity",
+            "submission_requirements": [{ "rule": "pick", "count": 1, "from": "identity" }],
+            "input_descriptors": [
+                {
+                    "id": "Passport",
+                    "group": ["identity"],
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.passportNumber"] }] }
+                },
+                {
+                    "id": "DriversLicense",
+                    "group": ["identity"],
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.licenseNumber"] }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let credentials = vec![
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "email": "ferris@example.com" } } })),
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "licenseNumber": "123" } } })),
+        ];
+
+        // Only one of the Input Descriptors of the group is answered, with the credential that satisfies it, which is
+        // the only credential in the presentation.
+        let (presentation_submission, presented) =
+            create_presentation_submission(&presentation_definition, &credentials).unwrap();
+        assert_eq!(presented, vec![1]);
+        assert_eq!(presentation_submission.descriptor_map.len(), 1);
+        assert_eq!(presentation_submission.descriptor_map[0].id, "DriversLicense");
+        assert_eq!(
+            presentation_submission.descriptor_map[0]
+                .path_nested
+                .as_ref()
+                .unwrap()
+                .path,
+            "$.vp.verifiableCredential[0]"
+        );
+
+        assert!(create_presentation_submission(&presentation_definition, &credentials[..1]).is_err());
+
+        let mut presentation_definition = json!(presentation_definition);
+        presentation_definition["input_descriptors"][1]["constraints"]["limit_disclosure"] = json!("required");
+        let presentation_definition = serde_json::from_value(presentation_definition).unwrap();
+        assert!(create_presentation_submission(&presentation_definition, &credentials).is_err());
+    }
+
+    #[test]
+    fn test_create_presentation_submission_from_selection() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "Email",
+            "input_descriptors": [
+                {
+                    "id": "Email",
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.email"] }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let credentials = vec![
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "email": "ferris@example.com" } } })),
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "email": "ferris@crabmail.com" } } })),
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "licenseNumber": "123" } } })),
+        ];
+
+        // Both email credentials are offered to the Holder, who chooses the second one.
+        let [matches] = match_candidates(&presentation_definition, &credentials)
+            .try_into()
+            .unwrap();
+        assert_eq!(matches.accepted().collect::<Vec<_>>(), vec![0, 1]);
+        let (presentation_submission, presented) = create_presentation_submission_from_selection(
+            &presentation_definition,
+            &credentials,
+            &HashMap::from([("Email".to_string(), 1)]),
+        )
+        .unwrap();
+        assert_eq!(presented, vec![1]);
+        assert_eq!(
+            presentation_submission.descriptor_map[0]
+                .path_nested
+                .as_ref()
+                .unwrap()
+                .path,
+            "$.vp.verifiableCredential[0]"
+        );
+
+        // Choosing a credential that does not satisfy the Input Descriptor explains why.
+        let error = create_presentation_submission_from_selection(
+            &presentation_definition,
+            &credentials,
+            &HashMap::from([("Email".to_string(), 2)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The credential does not satisfy Input Descriptor `Email`: The field `$.vc.credentialSubject.email` is missing."
+        );
+
+        // An empty choice does not satisfy the Presentation Definition.
+        assert!(
+            create_presentation_submission_from_selection(&presentation_definition, &credentials, &HashMap::new())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_create_presentation_submission_order() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "Identity",
+            "input_descriptors": [
+                {
+                    "id": "Name",
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.name"] }] }
+                },
+                {
+                    "id": "Email",
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.email"] }] }
+                },
+                {
+                    "id": "Address",
+                    "constraints": { "fields": [{ "path": ["$.vc.credentialSubject.address"] }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let credentials = vec![
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "licenseNumber": "123" } } })),
+            jwt_vc_json(
+                json!({ "vc": { "credentialSubject": { "email": "ferris@crabmail.com", "address": "Main St" } } }),
+            ),
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "name": "Ferris" } } })),
+        ];
+
+        // The credentials are embedded in the order of the Input Descriptors, and a credential that answers multiple
+        // Input Descriptors is embedded once.
+        let (presentation_submission, presented) =
+            create_presentation_submission(&presentation_definition, &credentials).unwrap();
+        assert_eq!(presented, vec![2, 1]);
+        let paths: Vec<_> = presentation_submission
+            .descriptor_map
+            .iter()
+            .map(|descriptor| descriptor.path_nested.as_ref().unwrap().path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$.vp.verifiableCredential[0]",
+                "$.vp.verifiableCredential[1]",
+                "$.vp.verifiableCredential[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_presentation_submission_same_subject() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "Residence",
+            "input_descriptors": [
+                {
+                    "id": "Passport",
+                    "constraints": {
+                        "fields": [{ "id": "name", "path": ["$.vc.credentialSubject.name"] }],
+                        "same_subject": [{ "field_id": ["name", "address"], "directive": "required" }]
+                    }
+                },
+                {
+                    "id": "UtilityBill",
+                    "constraints": { "fields": [{ "id": "address", "path": ["$.vc.credentialSubject.address"] }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let passport =
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "id": "did:example:ferris", "name": "Ferris" } } }));
+
+        let utility_bill =
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "id": "did:example:ferris", "address": "Main St" } } }));
+        assert!(create_presentation_submission(&presentation_definition, &[passport.clone(), utility_bill]).is_ok());
+
+        let utility_bill =
+            jwt_vc_json(json!({ "vc": { "credentialSubject": { "id": "did:example:crab", "address": "Main St" } } }));
+        assert!(create_presentation_submission(&presentation_definition, &[passport, utility_bill]).is_err());
+    }
+
+    #[test]
+    fn test_create_presentation_submission_formats() {
+        let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+            "id": "Identity",
+            "input_descriptors": [
+                {
+                    "id": "IdentityCredential",
+                    "format": { "vc+sd-jwt": { "alg": ["ES256"] }, "ldp_vp": { "proof_type": ["DataIntegrityProof"] } },
+                    "constraints": { "fields": [{ "path": ["$.given_name", "$.credentialSubject.given_name"] }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let claim = json!({ "given_name": "Ferris" });
+
+        // The `jwt_vc_json` credential is not accepted, so the SD-JWT VC is presented as is.
+        let (presentation_submission, _) = create_presentation_submission(
+            &presentation_definition,
+            &[
+                jwt_vc_json(claim.clone()),
+                candidate(ClaimFormatDesignation::VcSdJwt, claim),
+            ],
+        )
+        .unwrap();
+        let descriptor = &presentation_submission.descriptor_map[0];
+        assert_eq!(descriptor.format, ClaimFormatDesignation::VcSdJwt);
+        assert_eq!(descriptor.path, "$");
+        assert_eq!(descriptor.path_nested, None);
+
+        // An `ldp_vc` credential is accepted, since its presentation format is listed.
+        let (presentation_submission, _) = create_presentation_submission(
+            &presentation_definition,
+            &[candidate(
+                ClaimFormatDesignation::LdpVc,
+                json!({ "credentialSubject": { "given_name": "Ferris" } }),
+            )],
+        )
+        .unwrap();
+        let descriptor = &presentation_submission.descriptor_map[0];
+        assert_eq!(descriptor.format, ClaimFormatDesignation::LdpVp);
+        let path_nested = descriptor.path_nested.as_ref().unwrap();
+        assert_eq!(path_nested.format, ClaimFormatDesignation::LdpVc);
+        assert_eq!(path_nested.path, "$.verifiableCredential[0]");
+    }
 }
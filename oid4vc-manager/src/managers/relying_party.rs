@@ -50,6 +50,16 @@ impl RelyingPartyManager {
         self.relying_party.validate_response(authorization_response).await
     }
 
+    pub async fn validate_response_to_request<E: Extension>(
+        &self,
+        authorization_request: &AuthorizationRequest<Object<E>>,
+        authorization_response: &AuthorizationResponse<E>,
+    ) -> Result<<E::ResponseHandle as ResponseHandle>::ResponseItem> {
+        self.relying_party
+            .validate_response_to_request(authorization_request, authorization_response)
+            .await
+    }
+
     pub fn default_subject_syntax_type(&self) -> &SubjectSyntaxType {
         &self.relying_party.default_subject_syntax_type
     }
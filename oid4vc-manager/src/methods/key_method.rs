@@ -58,6 +58,13 @@ impl Sign for KeySubject {
         }
     }
 
+    async fn sign_bytes(&self, message: &[u8], _subject_syntax_type: &str, _algorithm: Algorithm) -> Result<Vec<u8>> {
+        match self.external_signer() {
+            Some(_) => Err(anyhow!("The external signer does not support signing binary messages.")),
+            None => Ok(self.keypair.sign(message)),
+        }
+    }
+
     fn external_signer(&self) -> Option<Arc<dyn ExternalSign>> {
         self.external_signer.clone()
     }
@@ -126,12 +133,13 @@ mod tests {
         let provider_manager =
             ProviderManager::new(Arc::new(subject), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
 
-        // Get a new SIOP authorization_request with response mode `direct_post` for cross-device communication.
+        // Get a new SIOP authorization_request with response mode `direct_post` for cross-device communication. Since it
+        // is not signed, its `client_id` is its `redirect_uri`, according to the `redirect_uri` Client ID Scheme.
         let request_url = "\
             siopv2://idtoken?\
                 scope=openid\
                 &response_type=id_token\
-                &client_id=did:key:z6MkiTcXZ1JxooACo99YcfkugH6Kifzj7ZupSDCmLEABpjpF\
+                &client_id=https%3A%2F%2Fclient.example.org%2Fcb\
                 &redirect_uri=https%3A%2F%2Fclient.example.org%2Fcb\
                 &response_mode=direct_post\
                 &client_metadata=%7B%22subject_syntax_types_supported%22%3A\
@@ -163,4 +171,34 @@ mod tests {
             .await
             .is_ok());
     }
+
+    #[tokio::test]
+    async fn test_key_subject_unsigned_did_request() {
+        let provider_manager =
+            ProviderManager::new(Arc::new(KeySubject::new()), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+        // An unsigned authorization_request whose `client_id` is a DID, and therefore uses the `did` Client ID Scheme,
+        // is rejected, since the Client cannot be authenticated.
+        let request_url = "\
+            siopv2://idtoken?\
+                scope=openid\
+                &response_type=id_token\
+                &client_id=did:key:z6MkiTcXZ1JxooACo99YcfkugH6Kifzj7ZupSDCmLEABpjpF\
+                &redirect_uri=https%3A%2F%2Fclient.example.org%2Fcb\
+                &response_mode=direct_post\
+                &client_metadata=%7B%22subject_syntax_types_supported%22%3A\
+                %5B%22did%3Akey%22%5D%2C%0A%20%20%20%20\
+                %22id_token_signing_alg_values_supported%22%3A%5B%22EdDSA%22%5D%7D\
+                &nonce=n-0S6_WzA2Mj\
+            ";
+
+        assert_eq!(
+            provider_manager
+                .validate_request(request_url.to_string())
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Requests with the `did` Client ID Scheme must be signed."
+        );
+    }
 }
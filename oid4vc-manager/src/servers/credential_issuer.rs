@@ -3,25 +3,42 @@ use std::time::Duration;
 use crate::{managers::credential_issuer::CredentialIssuerManager, storage::Storage};
 use anyhow::Result;
 use axum::{
-    extract::State,
-    http::{header::CONTENT_TYPE, Method, StatusCode},
-    response::{AppendHeaders, IntoResponse},
+    extract::{Path, State},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE, WWW_AUTHENTICATE},
+        Method, StatusCode,
+    },
+    response::{AppendHeaders, IntoResponse, Response},
     routing::{get, post},
     Form, Json, Router,
 };
 use axum_auth::AuthBearer;
-use oid4vc_core::Validator;
+use oid4vc_core::{
+    generate_nonce, jwe,
+    status_list::{StatusPurpose, STATUS_LIST_JWT_TYP},
+    Validator,
+};
 use oid4vci::{
     authorization_request::AuthorizationRequest,
     credential_format_profiles::CredentialFormatCollection,
-    credential_request::{BatchCredentialRequest, CredentialRequest},
-    credential_response::BatchCredentialResponse,
+    credential_issuer::credential_signer::HolderBinding,
+    credential_request::{
+        BatchCredentialRequest, CredentialRequest, CredentialResponseEncryptionSpecification, DeferredCredentialRequest,
+    },
+    credential_response::{
+        BatchCredentialResponse, CredentialErrorCode, CredentialErrorResponse, CredentialResponse,
+        CredentialResponseType,
+    },
+    notification_request::{NotificationErrorCode, NotificationErrorResponse, NotificationRequest},
     token_request::TokenRequest,
 };
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::task::JoinHandle;
 use tower_http::cors::AllowOrigin;
 
+/// The number of seconds after which the fresh `c_nonce` of a (Batch) Credential Response expires.
+const C_NONCE_EXPIRES_IN: u64 = 86400;
+
 pub struct Server<S, CFC>
 where
     S: Storage<CFC>,
@@ -70,6 +87,10 @@ impl<S: Storage<CFC> + Clone, CFC: CredentialFormatCollection + Clone + Deserial
                     .route("/token", post(token))
                     .route("/credential", post(credential))
                     .route("/batch_credential", post(batch_credential))
+                    .route("/deferred_credential", post(deferred_credential))
+                    .route("/notification", post(notification))
+                    .route("/status_list", get(status_list))
+                    .route("/status_list/:status_purpose", get(bitstring_status_list))
                     .merge(extension.unwrap_or_default())
                     .layer(
                         tower_http::cors::CorsLayer::new()
@@ -173,35 +194,25 @@ async fn credential<S: Storage<CFC>, CFC: CredentialFormatCollection>(
     AuthBearer(access_token): AuthBearer,
     Json(credential_request): Json<CredentialRequest<CFC>>,
 ) -> impl IntoResponse {
-    // TODO: The bunch of unwrap's here should be replaced with error responses as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-credential-error-response
-    let proof = credential_issuer_manager
+    let credential_response_encryption = credential_request.credential_response_encryption.as_ref();
+    if let Err(error) = credential_issuer_manager
         .credential_issuer
-        .validate_proof(
-            credential_request.proof.unwrap(),
-            Validator::Subject(credential_issuer_manager.credential_issuer.subject.clone()),
-        )
-        .await
-        .unwrap();
-    (
-        StatusCode::OK,
-        AppendHeaders([("Cache-Control", "no-store")]),
-        Json(
-            credential_issuer_manager
-                .storage
-                .get_credential_response(
-                    access_token,
-                    proof.rfc7519_claims.iss().as_ref().unwrap().parse().unwrap(),
-                    credential_issuer_manager
-                        .credential_issuer
-                        .metadata
-                        .credential_issuer
-                        .clone(),
-                    credential_request.credential_format.clone(),
-                    credential_issuer_manager.credential_issuer.subject.clone(),
-                )
-                .unwrap(),
+        .validate_credential_response_encryption(credential_response_encryption)
+    {
+        return credential_error_response(CredentialErrorCode::InvalidEncryptionParameters, error);
+    }
+
+    match issue_credential(&credential_issuer_manager, access_token, credential_request.clone()).await {
+        Ok(credential) => credential_response(
+            credential_response_encryption,
+            CredentialResponse {
+                credential,
+                c_nonce: Some(generate_nonce(16)),
+                c_nonce_expires_in: Some(C_NONCE_EXPIRES_IN),
+            },
         ),
-    )
+        Err(response) => response,
+    }
 }
 
 async fn batch_credential<S: Storage<CFC>, CFC: CredentialFormatCollection>(
@@ -209,44 +220,305 @@ async fn batch_credential<S: Storage<CFC>, CFC: CredentialFormatCollection>(
     AuthBearer(access_token): AuthBearer,
     Json(batch_credential_request): Json<BatchCredentialRequest<CFC>>,
 ) -> impl IntoResponse {
+    // The Batch Credential Response is encrypted as a whole, so all Credential Requests must contain the same
+    // encryption parameters.
+    let credential_response_encryption = batch_credential_request
+        .credential_requests
+        .first()
+        .and_then(|credential_request| credential_request.credential_response_encryption.clone());
+    if batch_credential_request
+        .credential_requests
+        .iter()
+        .any(|credential_request| credential_request.credential_response_encryption != credential_response_encryption)
+    {
+        return credential_error_response(
+            CredentialErrorCode::InvalidEncryptionParameters,
+            "All Credential Requests must contain the same `credential_response_encryption` parameters.",
+        );
+    }
+    if let Err(error) = credential_issuer_manager
+        .credential_issuer
+        .validate_credential_response_encryption(credential_response_encryption.as_ref())
+    {
+        return credential_error_response(CredentialErrorCode::InvalidEncryptionParameters, error);
+    }
+
     let mut credential_responses = vec![];
     for credential_request in batch_credential_request.credential_requests {
-        // TODO: The bunch of unwrap's here should be replaced with error responses as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-batch-credential-error-resp
-        let proof = credential_issuer_manager
-            .credential_issuer
-            .validate_proof(
-                credential_request.proof.unwrap(),
-                Validator::Subject(credential_issuer_manager.credential_issuer.subject.clone()),
+        match issue_credential(&credential_issuer_manager, access_token.clone(), credential_request).await {
+            Ok(credential) => credential_responses.push(credential),
+            Err(response) => return response,
+        }
+    }
+
+    credential_response(
+        credential_response_encryption.as_ref(),
+        BatchCredentialResponse {
+            credential_responses,
+            c_nonce: Some(generate_nonce(16)),
+            c_nonce_expires_in: Some(C_NONCE_EXPIRES_IN),
+        },
+    )
+}
+
+async fn deferred_credential<S: Storage<CFC>, CFC: CredentialFormatCollection>(
+    State(credential_issuer_manager): State<CredentialIssuerManager<S, CFC>>,
+    AuthBearer(access_token): AuthBearer,
+    Json(deferred_credential_request): Json<DeferredCredentialRequest>,
+) -> impl IntoResponse {
+    let credential_response_encryption = deferred_credential_request.credential_response_encryption.as_ref();
+    if let Err(error) = credential_issuer_manager
+        .credential_issuer
+        .validate_credential_response_encryption(credential_response_encryption)
+    {
+        return credential_error_response(CredentialErrorCode::InvalidEncryptionParameters, error);
+    }
+
+    match credential_issuer_manager
+        .storage
+        .get_deferred_credential_response(access_token, deferred_credential_request.transaction_id.clone())
+    {
+        Some(deferred_credential_response) => {
+            credential_response(credential_response_encryption, deferred_credential_response)
+        }
+        None => credential_error_response(CredentialErrorCode::InvalidTransactionId, "Unknown transaction id."),
+    }
+}
+
+/// Validates the Key Proof of the Credential Request and issues a Credential containing the claims provided by the
+/// storage, bound to the key material of the Holder, unless the storage provides the signed Credential itself.
+async fn issue_credential<S: Storage<CFC>, CFC: CredentialFormatCollection>(
+    credential_issuer_manager: &CredentialIssuerManager<S, CFC>,
+    access_token: String,
+    credential_request: CredentialRequest<CFC>,
+) -> Result<CredentialResponseType, Response> {
+    let credential_issuer = &credential_issuer_manager.credential_issuer;
+
+    let proof = credential_request
+        .proof
+        .ok_or_else(|| credential_error_response(CredentialErrorCode::InvalidProof, "Missing proof."))?;
+    credential_issuer
+        .validate_proof(proof.clone(), Validator::Subject(credential_issuer.subject.clone()))
+        .await
+        .map_err(|error| credential_error_response(CredentialErrorCode::InvalidProof, error))?;
+    let holder_binding = proof
+        .holder_binding()
+        .map_err(|error| credential_error_response(CredentialErrorCode::InvalidProof, error))?;
+
+    // A Credential that is assembled and signed by the storage itself takes precedence over the built-in signer.
+    if let HolderBinding::Did(subject_did) = &holder_binding {
+        if let Some(credential_response) = subject_did.parse().ok().and_then(|subject_did| {
+            credential_issuer_manager.storage.get_credential_response(
+                access_token.clone(),
+                subject_did,
+                credential_issuer.metadata.credential_issuer.clone(),
+                credential_request.credential_format.clone(),
+                credential_issuer.subject.clone(),
             )
-            .await
-            .unwrap();
+        }) {
+            return Ok(credential_response.credential);
+        }
+    }
 
-        credential_responses.push(
-            credential_issuer_manager
-                .storage
-                .get_credential_response(
-                    access_token.clone(),
-                    proof.rfc7519_claims.iss().as_ref().unwrap().parse().unwrap(),
-                    credential_issuer_manager
-                        .credential_issuer
-                        .metadata
-                        .credential_issuer
-                        .clone(),
-                    credential_request.credential_format.clone(),
-                    credential_issuer_manager.credential_issuer.subject.clone(),
-                )
-                .unwrap()
-                .credential,
+    let credential_configuration_id = credential_issuer
+        .metadata
+        .credential_configuration_id(&credential_request.credential_format)
+        .ok_or_else(|| {
+            credential_error_response(
+                CredentialErrorCode::UnsupportedCredentialType,
+                "Unsupported credential type.",
+            )
+        })?
+        .clone();
+
+    let credential_subject = credential_issuer_manager
+        .storage
+        .get_credential_claims(access_token.clone(), credential_configuration_id.clone())
+        .ok_or_else(|| {
+            credential_error_response(
+                CredentialErrorCode::InvalidCredentialRequest,
+                "No claims found for the requested credential.",
+            )
+        })?;
+
+    // The index in the status lists is allocated before signing, since the Credential refers to it. It is only recorded
+    // once the Credential is issued, and released again otherwise.
+    let credential_status = match &credential_issuer_manager.status_list_manager {
+        Some(status_list_manager) => {
+            let mut status_list_manager = status_list_manager.lock().await;
+            let idx = status_list_manager.allocate().map_err(server_error_response)?;
+            match status_list_manager.credential_status(idx) {
+                Ok(credential_status) => Some(credential_status),
+                Err(error) => {
+                    status_list_manager.release(idx).map_err(server_error_response)?;
+                    return Err(server_error_response(error));
+                }
+            }
+        }
+        None => None,
+    };
+
+    let credential = match credential_issuer_manager
+        .credential_signer
+        .sign_with_status(
+            &credential_issuer.metadata,
+            &credential_configuration_id,
+            credential_subject,
+            &holder_binding,
+            credential_status.as_ref(),
+        )
+        .await
+    {
+        Ok(credential) => credential,
+        Err(error) => {
+            if let (Some(credential_status), Some(status_list_manager)) =
+                (&credential_status, &credential_issuer_manager.status_list_manager)
+            {
+                status_list_manager
+                    .lock()
+                    .await
+                    .release(credential_status.status.status_list.idx)
+                    .map_err(server_error_response)?;
+            }
+            return Err(server_error_response(error));
+        }
+    };
+
+    if let Some(credential_status) = &credential_status {
+        credential_issuer_manager.storage.set_status_list_index(
+            access_token.clone(),
+            credential_configuration_id.clone(),
+            credential_status.status.status_list.idx,
         );
     }
 
+    Ok(CredentialResponseType::Immediate {
+        credential,
+        notification_id: credential_issuer_manager
+            .storage
+            .get_notification_id(access_token, credential_configuration_id),
+    })
+}
+
+/// Returns the (Batch or Deferred) Credential Response, encrypted as a JWE in case the Wallet requested so.
+fn credential_response<T: Serialize>(
+    credential_response_encryption: Option<&CredentialResponseEncryptionSpecification>,
+    credential_response: T,
+) -> Response {
+    match credential_response_encryption {
+        Some(CredentialResponseEncryptionSpecification { jwk, alg, enc }) => {
+            match serde_json::to_vec(&credential_response)
+                .map_err(anyhow::Error::from)
+                .and_then(|plaintext| jwe::encrypt(&plaintext, jwk, *alg, *enc, &jwe::PartyInfo::default()))
+            {
+                Ok(jwe) => (
+                    StatusCode::OK,
+                    [(CONTENT_TYPE, "application/jwt"), (CACHE_CONTROL, "no-store")],
+                    jwe,
+                )
+                    .into_response(),
+                Err(error) => credential_error_response(CredentialErrorCode::InvalidEncryptionParameters, error),
+            }
+        }
+        None => (
+            StatusCode::OK,
+            AppendHeaders([("Cache-Control", "no-store")]),
+            Json(credential_response),
+        )
+            .into_response(),
+    }
+}
+
+fn credential_error_response(error: CredentialErrorCode, error_description: impl ToString) -> Response {
     (
-        StatusCode::OK,
+        StatusCode::BAD_REQUEST,
         AppendHeaders([("Cache-Control", "no-store")]),
-        Json(BatchCredentialResponse {
-            credential_responses,
-            c_nonce: None,
-            c_nonce_expires_in: None,
+        Json(CredentialErrorResponse {
+            error,
+            error_description: Some(error_description.to_string()),
         }),
     )
+        .into_response()
+}
+
+/// Returns an error response for failures on the side of the Credential Issuer, such as a failure to sign the
+/// Credential, for which the Credential Error Response does not define an error code.
+fn server_error_response(error: impl ToString) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        AppendHeaders([("Cache-Control", "no-store")]),
+        Json(error.to_string()),
+    )
+        .into_response()
+}
+
+async fn notification<S: Storage<CFC>, CFC: CredentialFormatCollection>(
+    State(credential_issuer_manager): State<CredentialIssuerManager<S, CFC>>,
+    AuthBearer(access_token): AuthBearer,
+    Json(notification_request): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let result = serde_json::from_value::<NotificationRequest>(notification_request)
+        .map_err(|_| NotificationErrorCode::InvalidNotificationRequest)
+        .and_then(|notification_request| {
+            credential_issuer_manager
+                .storage
+                .handle_notification(access_token, notification_request)
+        });
+
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        // An invalid Access Token results in an authorization error response as described here:
+        // https://www.rfc-editor.org/rfc/rfc6750.html#section-3
+        Err(NotificationErrorCode::InvalidToken) => (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, r#"Bearer error="invalid_token""#)],
+            Json(NotificationErrorResponse {
+                error: NotificationErrorCode::InvalidToken,
+            }),
+        )
+            .into_response(),
+        Err(error) => (StatusCode::BAD_REQUEST, Json(NotificationErrorResponse { error })).into_response(),
+    }
+}
+
+/// Publishes the Status List Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list-request
+async fn status_list<S: Storage<CFC>, CFC: CredentialFormatCollection>(
+    State(credential_issuer_manager): State<CredentialIssuerManager<S, CFC>>,
+) -> impl IntoResponse {
+    if credential_issuer_manager.status_list_manager.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match credential_issuer_manager.status_list_token().await {
+        Ok(status_list_token) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, format!("application/{STATUS_LIST_JWT_TYP}"))],
+            status_list_token,
+        )
+            .into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+    }
+}
+
+/// Publishes the Bitstring Status List Credential for the `status_purpose` as described here:
+/// https://www.w3.org/TR/vc-bitstring-status-list/#bitstringstatuslistcredential
+async fn bitstring_status_list<S: Storage<CFC>, CFC: CredentialFormatCollection>(
+    State(credential_issuer_manager): State<CredentialIssuerManager<S, CFC>>,
+    Path(status_purpose): Path<StatusPurpose>,
+) -> impl IntoResponse {
+    if credential_issuer_manager.status_list_manager.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match credential_issuer_manager
+        .bitstring_status_list_credential(status_purpose)
+        .await
+    {
+        Ok(bitstring_status_list_credential) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, "application/vc+jwt")],
+            bitstring_status_list_credential,
+        )
+            .into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+    }
 }
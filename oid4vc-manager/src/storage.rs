@@ -7,6 +7,7 @@ use oid4vci::{
     credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject,
     credential_offer::{AuthorizationCode, PreAuthorizedCode},
     credential_response::CredentialResponse,
+    notification_request::{NotificationErrorCode, NotificationRequest},
     token_request::TokenRequest,
     token_response::TokenResponse,
 };
@@ -22,14 +23,61 @@ where
     fn get_authorization_code(&self) -> Option<AuthorizationCode>;
     fn get_pre_authorized_code(&self) -> Option<PreAuthorizedCode>;
     fn get_token_response(&self, token_request: TokenRequest) -> Option<TokenResponse>;
+    /// Returns the Credential Response for a Credential that is assembled and signed by the storage itself. When `None`
+    /// is returned, the Credential is assembled from the claims returned by [`Storage::get_credential_claims`] and
+    /// signed by the Credential Issuer instead.
     fn get_credential_response(
         &self,
-        access_token: String,
-        subject_did: Url,
-        issuer_did: Url,
-        credential_format: CFC,
-        subject: SigningSubject,
-    ) -> Option<CredentialResponse>;
+        _access_token: String,
+        _subject_did: Url,
+        _issuer_did: Url,
+        _credential_format: CFC,
+        _subject: SigningSubject,
+    ) -> Option<CredentialResponse> {
+        None
+    }
+    /// Returns the claims about the Holder that are included in the `credentialSubject` of the Credential identified by
+    /// `credential_configuration_id`. The Credential itself is assembled and signed by the Credential Issuer.
+    fn get_credential_claims(
+        &self,
+        _access_token: String,
+        _credential_configuration_id: String,
+    ) -> Option<serde_json::Value> {
+        None
+    }
+    /// Returns the `notification_id` that is included in the Credential Response of the Credential identified by
+    /// `credential_configuration_id`. By default, the Credential Response does not contain a `notification_id`.
+    fn get_notification_id(&self, _access_token: String, _credential_configuration_id: String) -> Option<String> {
+        None
+    }
+    /// Returns the Credential Response for a Credential whose issuance was deferred, identified by `transaction_id`.
+    /// By default, deferred issuance is not supported.
+    fn get_deferred_credential_response(
+        &self,
+        _access_token: String,
+        _transaction_id: String,
+    ) -> Option<CredentialResponse> {
+        None
+    }
+    /// Returns whether `notification_id`s are issued, in which case the Notification Endpoint is advertised in the
+    /// Credential Issuer Metadata. By default, no `notification_id`s are issued.
+    fn supports_notifications(&self) -> bool {
+        false
+    }
+    /// Handles a notification sent by the Wallet to the Notification Endpoint, indicating whether the Credential
+    /// identified by `notification_id` was accepted, failed to be stored or was deleted by the End-User. An unknown
+    /// `access_token` results in [`NotificationErrorCode::InvalidToken`]. By default, no `notification_id`s are issued,
+    /// so every notification refers to an unknown `notification_id`.
+    fn handle_notification(
+        &self,
+        _access_token: String,
+        _notification_request: NotificationRequest,
+    ) -> Result<(), NotificationErrorCode> {
+        Err(NotificationErrorCode::InvalidNotificationId)
+    }
+    /// Stores the index `idx` in the status lists of the Credential identified by `credential_configuration_id`, which is
+    /// needed to revoke or suspend the Credential later on.
+    fn set_status_list_index(&self, _access_token: String, _credential_configuration_id: String, _idx: usize) {}
     fn get_state(&self) -> Option<String>;
     fn set_state(&mut self, state: String);
 }
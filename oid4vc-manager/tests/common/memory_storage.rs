@@ -11,6 +11,7 @@ use oid4vci::{
     credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject,
     credential_offer::{AuthorizationCode, PreAuthorizedCode},
     credential_response::{CredentialResponse, CredentialResponseType},
+    notification_request::{NotificationErrorCode, NotificationRequest},
     token_request::TokenRequest,
     token_response::TokenResponse,
     VerifiableCredentialJwt,
@@ -27,6 +28,7 @@ lazy_static! {
     };
     pub static ref ACCESS_TOKEN: String = "czZCaGRSa3F0MzpnWDFmQmF0M2JW".to_string();
     pub static ref C_NONCE: String = "tZignsnFbp".to_string();
+    pub static ref NOTIFICATION_ID: String = "3fwe98js".to_string();
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -141,13 +143,53 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Storage<CFC> for Memory
                     .ok()
                 }))
                 .unwrap(),
-                notification_id: None,
+                notification_id: Some(NOTIFICATION_ID.clone()),
             },
             c_nonce: Some(C_NONCE.clone()),
             c_nonce_expires_in: Some(86400),
         })
     }
 
+    fn get_credential_claims(
+        &self,
+        access_token: String,
+        credential_configuration_id: String,
+    ) -> Option<serde_json::Value> {
+        (access_token == ACCESS_TOKEN.clone())
+            .then(|| match credential_configuration_id.as_str() {
+                "Identity_SD_JWT" => Some(json!({
+                    "given_name": "Ferris",
+                    "family_name": "Crabman"
+                })),
+                "mDL" => Some(json!({
+                    "org.iso.18013.5.1": {
+                        "given_name": "Ferris",
+                        "family_name": "Crabman"
+                    }
+                })),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    fn supports_notifications(&self) -> bool {
+        true
+    }
+
+    fn handle_notification(
+        &self,
+        access_token: String,
+        notification_request: NotificationRequest,
+    ) -> Result<(), NotificationErrorCode> {
+        if access_token != ACCESS_TOKEN.clone() {
+            return Err(NotificationErrorCode::InvalidToken);
+        }
+
+        (notification_request.notification_id == NOTIFICATION_ID.clone())
+            .then_some(())
+            .ok_or(NotificationErrorCode::InvalidNotificationId)
+    }
+
     fn get_state(&self) -> Option<String> {
         None
     }
@@ -0,0 +1,264 @@
+use crate::common::memory_storage::{MemoryStorage, PRE_AUTHORIZED_CODE};
+use did_key::{generate, Ed25519KeyPair};
+use ed25519_dalek::{Signer, SigningKey};
+use jsonwebtoken::{jwk::Jwk, Algorithm};
+use oid4vc_core::Validator;
+use oid4vc_manager::{
+    managers::credential_issuer::CredentialIssuerManager, methods::key_method::KeySubject,
+    servers::credential_issuer::Server,
+};
+use oid4vci::{
+    credential_format_profiles::{
+        iso_mdl::mdoc::{cose_key_from_jwk, IssuerSigned},
+        CredentialFormats, WithParameters,
+    },
+    credential_issuer::credential_configurations_supported::CredentialConfigurationsSupportedObject,
+    credential_request::CredentialRequest,
+    credential_response::{CredentialErrorCode, CredentialErrorResponse, CredentialResponse, CredentialResponseType},
+    token_request::TokenRequest,
+    token_response::TokenResponse,
+    KeyProofType, Wallet,
+};
+use rand::rngs::OsRng;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Returns the public JWK of the `signing_key`.
+fn public_jwk(signing_key: &SigningKey) -> Jwk {
+    serde_json::from_value(json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": base64_url::encode(signing_key.verifying_key().as_bytes())
+    }))
+    .unwrap()
+}
+
+/// Returns a Key Proof that carries the `jwk` in its header instead of a `kid`, signed with the `signing_key`.
+fn jwk_proof(signing_key: &SigningKey, jwk: &Jwk, aud: &str, nonce: &str) -> KeyProofType {
+    let header = json!({ "alg": "EdDSA", "typ": "openid4vci-proof+jwt", "jwk": jwk });
+    let claims = json!({ "aud": aud, "iat": 1571324800, "nonce": nonce });
+    let message = [
+        base64_url::encode(&serde_json::to_vec(&header).unwrap()),
+        base64_url::encode(&serde_json::to_vec(&claims).unwrap()),
+    ]
+    .join(".");
+    let signature = base64_url::encode(&signing_key.sign(message.as_bytes()).to_bytes());
+    KeyProofType::Jwt {
+        jwt: [message, signature].join("."),
+    }
+}
+
+/// Starts a Credential Issuer that also issues SD-JWT VCs and mdocs, and returns it together with a Wallet and an
+/// access token for the Wallet.
+async fn setup() -> (
+    Server<MemoryStorage, CredentialFormats<WithParameters>>,
+    Wallet,
+    TokenResponse,
+) {
+    let mut credential_issuer = Server::<_, CredentialFormats<WithParameters>>::setup(
+        CredentialIssuerManager::new(
+            None,
+            MemoryStorage,
+            Arc::new(KeySubject::from_keypair(
+                generate::<Ed25519KeyPair>(Some(
+                    "this-is-a-very-UNSAFE-issuer-secret-key".as_bytes().try_into().unwrap(),
+                )),
+                None,
+            )),
+        )
+        .unwrap(),
+        None,
+    )
+    .unwrap()
+    .detached(true);
+    let credential_configurations_supported = &mut credential_issuer
+        .credential_issuer_manager
+        .credential_issuer
+        .metadata
+        .credential_configurations_supported;
+    credential_configurations_supported.insert(
+        "Identity_SD_JWT".to_string(),
+        serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+            "format": "vc+sd-jwt",
+            "cryptographic_binding_methods_supported": ["jwk"],
+            "credential_signing_alg_values_supported": ["EdDSA"],
+            "vct": "IdentityCredential"
+        }))
+        .unwrap(),
+    );
+    credential_configurations_supported.insert(
+        "mDL".to_string(),
+        serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+            "format": "mso_mdoc",
+            "cryptographic_binding_methods_supported": ["cose_key"],
+            "credential_signing_alg_values_supported": ["EdDSA"],
+            "doctype": "org.iso.18013.5.1.mDL"
+        }))
+        .unwrap(),
+    );
+    credential_issuer.start_server().await.unwrap();
+
+    // Get an access token.
+    let wallet: Wallet = Wallet::new(Arc::new(KeySubject::new()), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+    let authorization_server_metadata = wallet
+        .get_authorization_server_metadata(
+            credential_issuer
+                .credential_issuer_manager
+                .credential_issuer
+                .metadata
+                .credential_issuer
+                .clone(),
+        )
+        .await
+        .unwrap();
+    let token_response = wallet
+        .get_access_token(
+            authorization_server_metadata.token_endpoint.unwrap(),
+            TokenRequest::PreAuthorizedCode {
+                pre_authorized_code: PRE_AUTHORIZED_CODE.pre_authorized_code.clone(),
+                tx_code: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    (credential_issuer, wallet, token_response)
+}
+
+/// Sends the Credential Request for the `credential_format` with the `proof` to the Credential Endpoint.
+async fn request_credential(
+    credential_issuer: &Server<MemoryStorage, CredentialFormats<WithParameters>>,
+    wallet: &Wallet,
+    token_response: &TokenResponse,
+    credential_format: serde_json::Value,
+    proof: KeyProofType,
+) -> reqwest::Response {
+    wallet
+        .client
+        .post(
+            credential_issuer
+                .credential_issuer_manager
+                .credential_issuer
+                .metadata
+                .credential_endpoint
+                .clone(),
+        )
+        .bearer_auth(token_response.access_token.clone())
+        .json(&CredentialRequest::<CredentialFormats<WithParameters>> {
+            credential_format: serde_json::from_value(credential_format).unwrap(),
+            proof: Some(proof),
+            credential_response_encryption: None,
+        })
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Returns the Credential of an immediate Credential Response.
+async fn credential(response: reqwest::Response) -> serde_json::Value {
+    match response.json::<CredentialResponse>().await.unwrap().credential {
+        CredentialResponseType::Immediate { credential, .. } => credential,
+        _ => panic!("The Credential was not issued immediately."),
+    }
+}
+
+#[tokio::test]
+async fn test_jwk_bound_credential() {
+    let (credential_issuer, wallet, token_response) = setup().await;
+    let aud = credential_issuer
+        .credential_issuer_manager
+        .credential_issuer
+        .metadata
+        .credential_issuer
+        .to_string();
+    let c_nonce = token_response.c_nonce.clone().unwrap();
+    let credential_format = json!({
+        "format": "vc+sd-jwt",
+        "vct": "IdentityCredential"
+    });
+
+    // The Holder is only known by its public key.
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let jwk = public_jwk(&signing_key);
+
+    // A proof that is not signed with the key in its header is rejected.
+    let response = request_credential(
+        &credential_issuer,
+        &wallet,
+        &token_response,
+        credential_format.clone(),
+        jwk_proof(&SigningKey::generate(&mut OsRng), &jwk, &aud, &c_nonce),
+    )
+    .await;
+    assert_eq!(response.status(), 400);
+    assert_eq!(
+        response.json::<CredentialErrorResponse>().await.unwrap().error,
+        CredentialErrorCode::InvalidProof
+    );
+
+    // The Credential is bound to the key in the header of the proof.
+    let credential = credential(
+        request_credential(
+            &credential_issuer,
+            &wallet,
+            &token_response,
+            credential_format,
+            jwk_proof(&signing_key, &jwk, &aud, &c_nonce),
+        )
+        .await,
+    )
+    .await;
+    let issuer_signed_jwt = credential.as_str().unwrap().split('~').next().unwrap();
+    let claims: serde_json::Value =
+        serde_json::from_slice(&base64_url::decode(issuer_signed_jwt.split('.').nth(1).unwrap()).unwrap()).unwrap();
+    assert_eq!(claims["vct"], "IdentityCredential");
+    assert_eq!(claims["cnf"]["jwk"], serde_json::to_value(&jwk).unwrap());
+    assert!(claims.get("sub").is_none());
+}
+
+#[tokio::test]
+async fn test_jwk_bound_mso_mdoc() {
+    let (credential_issuer, wallet, token_response) = setup().await;
+    let aud = credential_issuer
+        .credential_issuer_manager
+        .credential_issuer
+        .metadata
+        .credential_issuer
+        .to_string();
+    let c_nonce = token_response.c_nonce.clone().unwrap();
+    let credential_format = json!({
+        "format": "mso_mdoc",
+        "doctype": "org.iso.18013.5.1.mDL"
+    });
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let jwk = public_jwk(&signing_key);
+
+    // The mdoc is issued with the key in the header of the proof as its device key.
+    let credential = credential(
+        request_credential(
+            &credential_issuer,
+            &wallet,
+            &token_response,
+            credential_format,
+            jwk_proof(&signing_key, &jwk, &aud, &c_nonce),
+        )
+        .await,
+    )
+    .await;
+    let issuer_signed: IssuerSigned = credential.as_str().unwrap().parse().unwrap();
+    let mobile_security_object = issuer_signed
+        .verify(&Validator::Subject(Arc::new(KeySubject::new())))
+        .await
+        .unwrap();
+    assert_eq!(mobile_security_object.doc_type, "org.iso.18013.5.1.mDL");
+    assert_eq!(mobile_security_object.device_key, cose_key_from_jwk(&jwk).unwrap());
+    assert_eq!(
+        issuer_signed.claims().unwrap(),
+        json!({
+            "org.iso.18013.5.1": {
+                "given_name": "Ferris",
+                "family_name": "Crabman"
+            }
+        })
+    );
+}
@@ -1,2 +1,3 @@
 pub mod authorization_code;
+pub mod jwk_proof;
 pub mod pre_authorized_code;
@@ -8,20 +8,25 @@ use oid4vc_manager::{
 };
 use oid4vci::{
     credential_format_profiles::{CredentialFormats, WithParameters},
+    credential_issuer::credential_issuer_metadata::{CredentialIssuerMetadata, CredentialResponseEncryption},
     credential_offer::{CredentialOffer, CredentialOfferParameters, Grants},
     credential_response::{BatchCredentialResponse, CredentialResponse, CredentialResponseType},
+    notification_request::{NotificationEvent, NotificationRequest},
     token_request::TokenRequest,
+    token_response::TokenResponse,
     Wallet,
 };
 use std::sync::Arc;
 
 #[rstest::rstest]
-#[case(false, false)]
-#[case(false, true)]
-#[case(true, false)]
-#[case(true, true)]
+#[case(false, false, false)]
+#[case(false, true, false)]
+#[case(true, false, false)]
+#[case(true, true, false)]
+#[case(false, false, true)]
+#[case(true, false, true)]
 #[tokio::test]
-async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference: bool) {
+async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference: bool, #[case] encryption: bool) {
     // Setup the credential issuer.
     let mut credential_issuer = Server::<_, CredentialFormats<WithParameters>>::setup(
         CredentialIssuerManager::new(
@@ -39,14 +44,33 @@ async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference
     )
     .unwrap()
     .detached(true);
+    if encryption {
+        // Require the credential responses to be encrypted.
+        credential_issuer
+            .credential_issuer_manager
+            .credential_issuer
+            .metadata
+            .credential_response_encryption = Some(CredentialResponseEncryption {
+            alg_values_supported: vec!["ECDH-ES".to_string()],
+            enc_values_supported: vec!["A256GCM".to_string()],
+            encryption_required: true,
+        });
+    }
+    // Sign the credential issuer metadata.
+    credential_issuer
+        .credential_issuer_manager
+        .sign_metadata("did:key", Algorithm::EdDSA)
+        .await
+        .unwrap();
     credential_issuer.start_server().await.unwrap();
 
     // Create a new subject.
     let subject = KeySubject::new();
     let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
 
-    // Create a new wallet.
-    let wallet: Wallet = Wallet::new(Arc::new(subject), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+    // Create a new wallet, which resolves the `did:key` with which the credential issuer metadata is signed.
+    let mut wallet: Wallet = Wallet::new(Arc::new(subject), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+    wallet.did_resolver = Some(Arc::new(KeySubject::new()));
 
     // Get the credential offer url.
     let credential_offer_query = credential_issuer
@@ -81,6 +105,17 @@ async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference
         .await
         .unwrap();
 
+    // The signed metadata has been verified by the wallet.
+    assert!(credential_issuer_metadata.signed_metadata.is_some());
+
+    // A wallet that cannot resolve the key of the signed metadata rejects it.
+    let other_wallet: Wallet =
+        Wallet::new(Arc::new(KeySubject::new()), vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+    assert!(other_wallet
+        .get_credential_issuer_metadata(credential_issuer_url.clone())
+        .await
+        .is_err());
+
     // Create a token request with grant_type `pre_authorized_code`.
     let token_request = match credential_offer.grants {
         Some(Grants {
@@ -114,24 +149,99 @@ async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference
         })
         .collect();
 
+    if encryption {
+        // A credential request without `credential_response_encryption` parameters is refused by the credential issuer.
+        let plaintext_credential_issuer_metadata = CredentialIssuerMetadata {
+            credential_response_encryption: None,
+            ..credential_issuer_metadata.clone()
+        };
+        assert!(wallet
+            .get_credential(
+                plaintext_credential_issuer_metadata,
+                &token_response,
+                credentials.last().unwrap()
+            )
+            .await
+            .is_err());
+    }
+
     if !batch {
         let university_degree_credential_format = credentials.last().unwrap().clone();
 
         // Get the credential.
         let credential_response: CredentialResponse = wallet
             .get_credential(
-                credential_issuer_metadata,
+                credential_issuer_metadata.clone(),
                 &token_response,
                 &university_degree_credential_format,
             )
             .await
             .unwrap();
 
-        let credential = match credential_response.credential {
-            CredentialResponseType::Immediate { credential, .. } => credential,
+        // A fresh `c_nonce` is issued for subsequent Credential Requests.
+        assert!(credential_response.c_nonce.is_some());
+
+        let (credential, notification_id) = match credential_response.credential {
+            CredentialResponseType::Immediate {
+                credential,
+                notification_id,
+            } => (credential, notification_id),
             _ => panic!("Credential was not a JWT VC JSON."),
         };
 
+        // Notify the credential issuer that the credential was accepted.
+        wallet
+            .send_notification(
+                &credential_issuer_metadata,
+                &token_response,
+                NotificationRequest {
+                    notification_id: notification_id.clone().unwrap(),
+                    event: NotificationEvent::CredentialAccepted,
+                    event_description: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        // An unknown notification id is rejected by the credential issuer.
+        assert_eq!(
+            wallet
+                .send_notification(
+                    &credential_issuer_metadata,
+                    &token_response,
+                    NotificationRequest {
+                        notification_id: "unknown".to_string(),
+                        event: NotificationEvent::CredentialDeleted,
+                        event_description: None,
+                    },
+                )
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Failed to send notification: 400 Bad Request"
+        );
+
+        // A notification with an unknown access token is not authorized by the credential issuer.
+        assert_eq!(
+            wallet
+                .send_notification(
+                    &credential_issuer_metadata,
+                    &TokenResponse {
+                        access_token: "unknown".to_string(),
+                        ..token_response.clone()
+                    },
+                    NotificationRequest {
+                        notification_id: notification_id.unwrap(),
+                        event: NotificationEvent::CredentialAccepted,
+                        event_description: None,
+                    },
+                )
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Failed to send notification: 401 Unauthorized"
+        );
+
         // Decode the JWT without performing validation
         let claims = get_jwt_claims(&credential);
 
@@ -166,6 +276,9 @@ async fn test_pre_authorized_code_flow(#[case] batch: bool, #[case] by_reference
             .await
             .unwrap();
 
+        // A fresh `c_nonce` is issued for subsequent Credential Requests.
+        assert!(batch_credential_response.c_nonce.is_some());
+
         let credentials: Vec<_> = batch_credential_response
             .credential_responses
             .into_iter()
@@ -0,0 +1,164 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    sd_jwt::{SdJwt, SdObjectEncoder},
+    Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vp::{
+    oid4vp::{AuthorizationResponseInput, OID4VP},
+    verification::{Check, CheckOutcome},
+    PresentationDefinition,
+};
+use serde_json::json;
+use std::sync::Arc;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn test_presentation_definition_uri() {
+    let mock_server = MockServer::start().await;
+    let server_url = mock_server.uri();
+
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    let mut encoder = SdObjectEncoder::new(
+        json!({
+            "iss": issuer_did,
+            "sub": subject_did,
+            "vct": "IdentityCredential",
+            "given_name": "Ferris",
+            "family_name": "Crabman"
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    );
+    encoder.conceal("/given_name").unwrap();
+    encoder.conceal("/family_name").unwrap();
+    let sd_jwt = SdJwt::encode(issuer, Header::new(Algorithm::EdDSA), encoder, "did:key")
+        .await
+        .unwrap();
+
+    // The relying party publishes its Presentation Definition and client metadata. Each is fetched once by the
+    // provider, which caches them, and once by the relying party itself.
+    Mock::given(method("GET"))
+        .and(path("/presentation_definition"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "Identity Credential request",
+            "input_descriptors": [
+                {
+                    "id": "IdentityCredential",
+                    "constraints": {
+                        "fields": [{ "path": ["$.given_name"] }]
+                    }
+                }
+            ]
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/client_metadata"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "vp_formats": {
+                "jwt_vc_json": { "alg": ["EdDSA"] },
+                "vc+sd-jwt": { "alg": ["EdDSA"] }
+            },
+            "subject_syntax_types_supported": ["did:key"]
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition_uri(
+            format!("{server_url}/presentation_definition")
+                .parse::<url::Url>()
+                .unwrap(),
+        )
+        .client_metadata(ClientMetadataResource::ClientMetadataUri(format!(
+            "{server_url}/client_metadata"
+        )))
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: sd_jwt.into(),
+                presentation_submission: serde_json::from_value(json!({
+                    "id": "Identity Credential submission",
+                    "definition_id": "Identity Credential request",
+                    "descriptor_map": [
+                        {
+                            "id": "IdentityCredential",
+                            "format": "vc+sd-jwt",
+                            "path": "$"
+                        }
+                    ]
+                }))
+                .unwrap(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The relying party resolves its own Presentation Definition to evaluate the presentation submission.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(
+        report.outcome(Check::PresentationSubmission, None),
+        Some(&CheckOutcome::Passed)
+    );
+
+    // A request with both a `presentation_definition` and a `presentation_definition_uri` is rejected.
+    assert!(AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did)
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition(
+            serde_json::from_value::<PresentationDefinition>(json!({ "id": "id", "input_descriptors": [] })).unwrap(),
+        )
+        .presentation_definition_uri(
+            format!("{server_url}/presentation_definition")
+                .parse::<url::Url>()
+                .unwrap(),
+        )
+        .client_metadata(ClientMetadataResource::ClientMetadataUri(format!(
+            "{server_url}/client_metadata"
+        )))
+        .nonce("nonce".to_string())
+        .build()
+        .is_err());
+}
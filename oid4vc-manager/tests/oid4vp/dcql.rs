@@ -0,0 +1,209 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    sd_jwt::{SdJwt, SdObjectEncoder},
+    Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    dcql::DcqlQuery,
+    oid4vp::{AuthorizationResponseInput, VerifiedCredential, OID4VP},
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, Oid4vpParams,
+};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+#[tokio::test]
+async fn test_dcql_presentation() {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The issuer creates an SD-JWT VC of which all claims are selectively disclosable.
+    let mut encoder = SdObjectEncoder::new(
+        json!({
+            "iss": issuer_did,
+            "sub": subject_did,
+            "vct": "IdentityCredential",
+            "given_name": "Ferris",
+            "family_name": "Crabman",
+            "address": {
+                "locality": "Crabtown",
+                "country": "NL"
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    );
+    for pointer in [
+        "/given_name",
+        "/family_name",
+        "/address/locality",
+        "/address/country",
+        "/address",
+    ] {
+        encoder.conceal(pointer).unwrap();
+    }
+    let sd_jwt = SdJwt::encode(issuer, Header::new(Algorithm::EdDSA), encoder, "did:key")
+        .await
+        .unwrap();
+
+    let dcql_query: DcqlQuery = serde_json::from_value(json!({
+        "credentials": [
+            {
+                "id": "identity",
+                "format": "vc+sd-jwt",
+                "meta": {
+                    "vct_values": ["IdentityCredential"]
+                },
+                "claims": [
+                    { "path": ["given_name"] },
+                    { "path": ["address", "country"], "values": ["NL", "BE"] }
+                ]
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .dcql_query(dcql_query)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![
+                    (
+                        ClaimFormatDesignation::JwtVcJson,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                    (
+                        ClaimFormatDesignation::VcSdJwt,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::Dcql {
+                verifiable_presentations: BTreeMap::from([("identity".to_string(), sd_jwt.into())]),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The `vp_token` holds the presentation by the `id` of the Credential Query, with only the requested Disclosures.
+    let Oid4vpParams::Dcql { vp_token } = &authorization_response.extension.oid4vp_parameters else {
+        panic!("Expected a DCQL `vp_token`.");
+    };
+    let [presentation] = vp_token["identity"].as_slice() else {
+        panic!("Expected a single presentation.");
+    };
+    let presented_sd_jwt: SdJwt = presentation.parse().unwrap();
+    assert_eq!(presented_sd_jwt.disclosures.len(), 3);
+
+    // The relying party verifies the presentation and matches it against the Credential Query.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.outcome(Check::DcqlQuery, None), Some(&CheckOutcome::Passed));
+    let [VerifiedCredential::SdJwt { claims, .. }] = report.credentials.as_slice() else {
+        panic!("Expected a single SD-JWT VC.");
+    };
+    assert_eq!(claims["given_name"], "Ferris");
+    assert_eq!(claims["address"], json!({ "country": "NL" }));
+    assert!(claims.get("family_name").is_none());
+
+    // Without the Authorization Request, the presentation is verified, but not matched against the DCQL query.
+    let report = relying_party_manager
+        .validate_response(&authorization_response)
+        .await
+        .unwrap();
+    assert!(matches!(
+        report.outcome(Check::DcqlQuery, None),
+        Some(CheckOutcome::Skipped(_))
+    ));
+    assert!(!report.is_valid());
+
+    // A presentation of which a claim does not have one of the requested values does not answer the query.
+    let mut other_authorization_request = authorization_request.clone();
+    other_authorization_request.body.extension.dcql_query = Some(
+        serde_json::from_value(json!({
+            "credentials": [
+                {
+                    "id": "identity",
+                    "format": "vc+sd-jwt",
+                    "claims": [{ "path": ["address", "country"], "values": ["DE"] }]
+                }
+            ]
+        }))
+        .unwrap(),
+    );
+    let report = relying_party_manager
+        .validate_response_to_request(&other_authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(!report.is_valid());
+    assert!(matches!(
+        report.outcome(Check::DcqlQuery, None),
+        Some(CheckOutcome::Failed(reason)) if reason.contains("identity")
+    ));
+
+    // A Credential Query that does not allow `multiple` Credentials is not answered by two presentations.
+    let mut duplicated_response = authorization_response.clone();
+    let Oid4vpParams::Dcql { vp_token } = &mut duplicated_response.extension.oid4vp_parameters else {
+        panic!("Expected a response to a DCQL query.");
+    };
+    let presentation = vp_token["identity"][0].clone();
+    vp_token.get_mut("identity").unwrap().push(presentation);
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &duplicated_response)
+        .await
+        .unwrap();
+    assert!(matches!(
+        report.outcome(Check::DcqlQuery, None),
+        Some(CheckOutcome::Failed(reason)) if reason.contains("does not allow multiple")
+    ));
+}
@@ -16,7 +16,7 @@ use oid4vci::VerifiableCredentialJwt;
 use oid4vp::{
     authorization_request::ClientMetadataParameters,
     oid4vp::{AuthorizationResponseInput, OID4VP},
-    ClaimFormatDesignation, ClaimFormatProperty, PresentationDefinition,
+    Candidate, ClaimFormatDesignation, ClaimFormatProperty, PresentationDefinition,
 };
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
@@ -108,6 +108,7 @@ async fn test_implicit_flow() {
                 )]
                 .into_iter()
                 .collect(),
+                ..Default::default()
             },
             other: HashMap::from_iter(vec![(
                 "subject_syntax_types_supported".to_string(),
@@ -150,9 +151,13 @@ async fn test_implicit_flow() {
         .unwrap();
 
     // Create presentation submission using the presentation definition and the verifiable credential.
-    let presentation_submission = create_presentation_submission(
+    let (presentation_submission, _) = create_presentation_submission(
         &PRESENTATION_DEFINITION,
-        &vec![serde_json::to_value(&verifiable_credential).unwrap()],
+        &[Candidate {
+            format: ClaimFormatDesignation::JwtVcJson,
+            alg: Some("EdDSA".to_string()),
+            claim: serde_json::to_value(&verifiable_credential).unwrap(),
+        }],
     )
     .unwrap();
 
@@ -180,8 +185,8 @@ async fn test_implicit_flow() {
     let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
         .generate_response(
             &authorization_request,
-            AuthorizationResponseInput {
-                verifiable_presentation,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: verifiable_presentation.into(),
                 presentation_submission,
             },
         )
@@ -190,7 +195,8 @@ async fn test_implicit_flow() {
 
     // Validate the authorization_response.
     assert!(relying_party_manager
-        .validate_response(&authorization_response)
+        .validate_response_to_request(&authorization_request, &authorization_response)
         .await
-        .is_ok());
+        .unwrap()
+        .is_valid());
 }
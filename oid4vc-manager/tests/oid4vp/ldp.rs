@@ -0,0 +1,204 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    data_integrity::{Cryptosuite, DataIntegrityProof, ProofOptions},
+    Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    oid4vp::{AuthorizationResponseInput, VerifiablePresentation, VerifiedCredential, OID4VP},
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, Oid4vpParams, PresentationDefinition,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_ldp_vp_presentation() {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The issuer creates an `ldp_vc` Credential.
+    let credential = DataIntegrityProof::sign(
+        json!({
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://www.w3.org/2018/credentials/examples/v1"
+            ],
+            "type": ["VerifiableCredential", "UniversityDegreeCredential"],
+            "issuer": issuer_did,
+            "issuanceDate": "2024-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": subject_did,
+                "degree": {
+                    "type": "BachelorDegree",
+                    "name": "Bachelor of Science and Arts"
+                }
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+        ProofOptions::new(Cryptosuite::EddsaRdfc2022, "assertionMethod"),
+        issuer,
+        "did:key",
+    )
+    .await
+    .unwrap();
+
+    let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+        "id": "Degree request",
+        "input_descriptors": [
+            {
+                "id": "UniversityDegree",
+                "format": {
+                    "ldp_vc": {
+                        "proof_type": ["DataIntegrityProof"]
+                    }
+                },
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.type"],
+                            "filter": {
+                                "type": "array",
+                                "contains": { "const": "UniversityDegreeCredential" }
+                            }
+                        }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition(presentation_definition)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![
+                    (
+                        ClaimFormatDesignation::JwtVcJson,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                    (
+                        ClaimFormatDesignation::LdpVp,
+                        ClaimFormatProperty::ProofType(vec!["DataIntegrityProof".to_string()]),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: VerifiablePresentation::Ldp(
+                    json!({
+                        "@context": ["https://www.w3.org/2018/credentials/v1"],
+                        "type": ["VerifiablePresentation"],
+                        "verifiableCredential": [credential]
+                    })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+                ),
+                presentation_submission: serde_json::from_value(json!({
+                    "id": "Degree submission",
+                    "definition_id": "Degree request",
+                    "descriptor_map": [
+                        {
+                            "id": "UniversityDegree",
+                            "format": "ldp_vp",
+                            "path": "$",
+                            "path_nested": {
+                                "id": "UniversityDegree",
+                                "format": "ldp_vc",
+                                "path": "$.verifiableCredential[0]"
+                            }
+                        }
+                    ]
+                }))
+                .unwrap(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The relying party verifies the proofs of the Verifiable Presentation and the Credential.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(
+        report.outcome(Check::HolderBinding, Some(0)),
+        Some(&CheckOutcome::Passed)
+    );
+    let [VerifiedCredential::Ldp {
+        credential,
+        presentation_proof,
+    }] = report.credentials.as_slice()
+    else {
+        panic!("Expected a single `ldp_vc` Credential.");
+    };
+    assert_eq!(credential["credentialSubject"]["id"], subject_did.as_str());
+    assert_eq!(presentation_proof.challenge, Some("nonce".to_string()));
+    assert_eq!(presentation_proof.domain, Some(relying_party_did));
+
+    // Tampering with the presented Credential invalidates the presentation proof.
+    let Oid4vpParams::Params {
+        vp_token,
+        presentation_submission,
+    } = &authorization_response.extension.oid4vp_parameters
+    else {
+        panic!("Expected a `vp_token`.");
+    };
+    let mut tampered_presentation: serde_json::Value = serde_json::from_str(vp_token).unwrap();
+    tampered_presentation["verifiableCredential"][0]["credentialSubject"]["degree"]["name"] = json!("PhD");
+    let mut tampered_response = authorization_response.clone();
+    tampered_response.extension.oid4vp_parameters = Oid4vpParams::Params {
+        vp_token: tampered_presentation.to_string(),
+        presentation_submission: presentation_submission.clone(),
+    };
+    assert!(relying_party_manager
+        .validate_response(&tampered_response)
+        .await
+        .is_err());
+}
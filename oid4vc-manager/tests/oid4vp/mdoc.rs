@@ -0,0 +1,234 @@
+use chrono::{Duration, Utc};
+use did_key::{generate, Ed25519KeyPair, KeyMaterial};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    jwe::{ContentEncryptionAlgorithm, EncryptionKey, KeyManagementAlgorithm},
+    Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vci::credential_format_profiles::iso_mdl::mdoc::{
+    cose_key_from_jwk, DeviceResponse, IssuerSigned, ValidityInfo,
+};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    oid4vp::{AuthorizationResponseInput, VerifiedCredential, DIRECT_POST_JWT, OID4VP},
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, Oid4vpParams, PresentationDefinition,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_mdoc_presentation() {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+
+    // Create a new subject, of which the key is used as the device key of the mdoc.
+    let keypair = generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes()));
+    let device_key = cose_key_from_jwk(
+        &serde_json::from_value(json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": base64_url::encode(&keypair.public_key_bytes())
+        }))
+        .unwrap(),
+    )
+    .unwrap();
+    let subject = Arc::new(KeySubject::from_keypair(keypair, None));
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let mut relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The relying party decrypts the Authorization Response, which conveys the `mdoc_generated_nonce`.
+    let encryption_key = EncryptionKey::generate();
+    relying_party_manager.relying_party.encryption_key = Some(encryption_key.clone());
+
+    // The issuer creates an mdoc that is bound to the device key of the subject.
+    let now = Utc::now();
+    let issuer_signed = IssuerSigned::sign(
+        issuer,
+        "did:key",
+        Algorithm::EdDSA,
+        "org.iso.18013.5.1.mDL",
+        &json!({
+            "org.iso.18013.5.1": {
+                "given_name": "Ferris",
+                "family_name": "Crabman",
+                "age_over_18": true
+            }
+        }),
+        device_key,
+        ValidityInfo {
+            signed: now,
+            valid_from: now,
+            valid_until: now + Duration::days(365),
+        },
+    )
+    .await
+    .unwrap();
+
+    let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+        "id": "mDL request",
+        "input_descriptors": [
+            {
+                "id": "org.iso.18013.5.1.mDL",
+                "format": {
+                    "mso_mdoc": {
+                        "alg": ["EdDSA"]
+                    }
+                },
+                "constraints": {
+                    "limit_disclosure": "required",
+                    "fields": [
+                        {
+                            "path": ["$['org.iso.18013.5.1']['given_name']"],
+                            "intent_to_retain": false
+                        },
+                        {
+                            "path": ["$['org.iso.18013.5.1']['age_over_18']"],
+                            "intent_to_retain": false
+                        }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .response_mode(DIRECT_POST_JWT.to_string())
+        .response_uri("https://example.com/response".parse::<url::Url>().unwrap())
+        .presentation_definition(presentation_definition)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![
+                    (
+                        ClaimFormatDesignation::JwtVcJson,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                    (
+                        ClaimFormatDesignation::MsoMdoc,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                jwks: Some(JwkSet {
+                    keys: vec![encryption_key.public_jwk().unwrap()],
+                }),
+                authorization_encrypted_response_alg: Some(KeyManagementAlgorithm::EcdhEs),
+                authorization_encrypted_response_enc: Some(ContentEncryptionAlgorithm::A128Gcm),
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let input = || AuthorizationResponseInput::PresentationExchange {
+        verifiable_presentation: issuer_signed.clone().into(),
+        presentation_submission: serde_json::from_value(json!({
+            "id": "mDL submission",
+            "definition_id": "mDL request",
+            "descriptor_map": [
+                {
+                    "id": "org.iso.18013.5.1.mDL",
+                    "format": "mso_mdoc",
+                    "path": "$"
+                }
+            ]
+        }))
+        .unwrap(),
+    };
+
+    // Without an encrypted Authorization Response, the `mdoc_generated_nonce` cannot be conveyed.
+    let mut unencrypted_request = authorization_request.clone();
+    unencrypted_request.body.extension.response_mode = Some("direct_post".to_string());
+    assert_eq!(
+        provider_manager
+            .generate_response(&unencrypted_request, input())
+            .await
+            .unwrap_err()
+            .to_string(),
+        "An `mso_mdoc` presentation requires the `direct_post.jwt` Response Mode, in which the `mdoc_generated_nonce` is conveyed."
+    );
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(&authorization_request, input())
+        .await
+        .unwrap();
+
+    // The Authorization Response is encrypted, with the `mdoc_generated_nonce` and the `nonce` as `apu` and `apv`.
+    let Oid4vpParams::Jwt { response } = &authorization_response.extension.oid4vp_parameters else {
+        panic!("Expected an encrypted `response`.");
+    };
+    let (plaintext, party_info) = encryption_key.decrypt_with_party_info(response).unwrap();
+    assert!(party_info.apu.is_some());
+    assert_eq!(party_info.apv, Some(b"nonce".to_vec()));
+
+    // Only the requested data elements are presented.
+    let decrypted: AuthorizationResponse<OID4VP> = serde_json::from_slice(&plaintext).unwrap();
+    let Oid4vpParams::Params { vp_token, .. } = &decrypted.extension.oid4vp_parameters else {
+        panic!("Expected a `vp_token`.");
+    };
+    let device_response: DeviceResponse = vp_token.parse().unwrap();
+    assert_eq!(device_response.documents.len(), 1);
+    assert_eq!(
+        device_response.documents[0].issuer_signed.name_spaces["org.iso.18013.5.1"].len(),
+        2
+    );
+
+    // The relying party decrypts the Authorization Response and verifies the Issuer signature, the digests, the device
+    // signature and the validity of the mdoc.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(
+        report.outcome(Check::HolderBinding, Some(0)),
+        Some(&CheckOutcome::Passed)
+    );
+    let [VerifiedCredential::Mdoc(mdoc)] = report.credentials.as_slice() else {
+        panic!("Expected a single mdoc.");
+    };
+    assert_eq!(mdoc.doc_type, "org.iso.18013.5.1.mDL");
+    assert_eq!(
+        mdoc.claims,
+        json!({
+            "org.iso.18013.5.1": {
+                "given_name": "Ferris",
+                "age_over_18": true
+            }
+        })
+    );
+
+    // The device signature is bound to the Authorization Request.
+    assert!(mdoc
+        .verify_device_signature(&relying_party_did, "https://example.com/response", "nonce")
+        .is_ok());
+    assert!(mdoc
+        .verify_device_signature(&relying_party_did, "https://example.com/response", "other-nonce")
+        .is_err());
+    assert!(mdoc
+        .verify_device_signature("did:example:other", "https://example.com/response", "nonce")
+        .is_err());
+}
@@ -1 +1,9 @@
+pub mod by_reference;
+pub mod dcql;
 pub mod implicit;
+pub mod ldp;
+pub mod mdoc;
+pub mod sd_jwt;
+pub mod siopv2_oid4vp;
+pub mod status_list;
+pub mod vcdm2;
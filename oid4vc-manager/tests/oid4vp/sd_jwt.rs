@@ -0,0 +1,262 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    sd_jwt::{SdJwt, SdObjectEncoder},
+    Sign, Subject, Verify,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    oid4vp::{AuthorizationResponseInput, VerifiedCredential, OID4VP},
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, Oid4vpParams, PresentationDefinition,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+
+#[rstest::rstest]
+#[case(false)]
+#[case(true)]
+#[tokio::test]
+async fn test_sd_jwt_presentation(#[case] bound_to_cnf: bool) {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The issuer creates an SD-JWT VC of which all claims are selectively disclosable. It is bound to either the
+    // `sub` or the `cnf` key of the subject.
+    let mut claims = json!({
+        "iss": issuer_did,
+        "sub": subject_did,
+        "vct": "IdentityCredential",
+        "given_name": "Ferris",
+        "family_name": "Crabman",
+        "address": {
+            "locality": "Crabtown",
+            "country": "NL"
+        }
+    });
+    if bound_to_cnf {
+        let subject_kid = subject.key_id("did:key", Algorithm::EdDSA).await.unwrap();
+        let public_key = subject.public_key(&subject_kid).await.unwrap();
+        claims["cnf"] = json!({ "jwk": { "kty": "OKP", "crv": "Ed25519", "x": base64_url::encode(&public_key) } });
+    }
+    let mut encoder = SdObjectEncoder::new(claims.as_object().unwrap().clone());
+    for pointer in [
+        "/given_name",
+        "/family_name",
+        "/address/locality",
+        "/address/country",
+        "/address",
+    ] {
+        encoder.conceal(pointer).unwrap();
+    }
+    encoder.add_decoys("", 2).unwrap();
+    let sd_jwt = SdJwt::encode(issuer, Header::new(Algorithm::EdDSA), encoder, "did:key")
+        .await
+        .unwrap();
+
+    let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+        "id": "Identity Credential request",
+        "input_descriptors": [
+            {
+                "id": "IdentityCredential",
+                "format": {
+                    "vc+sd-jwt": {
+                        "alg": ["EdDSA"]
+                    }
+                },
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.vct"],
+                            "filter": {
+                                "type": "string",
+                                "const": "IdentityCredential"
+                            }
+                        },
+                        {
+                            "path": ["$.given_name"]
+                        },
+                        {
+                            "path": ["$.address.country"]
+                        }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition(presentation_definition)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![
+                    (
+                        ClaimFormatDesignation::JwtVcJson,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                    (
+                        ClaimFormatDesignation::VcSdJwt,
+                        ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: sd_jwt.into(),
+                presentation_submission: serde_json::from_value(json!({
+                    "id": "Identity Credential submission",
+                    "definition_id": "Identity Credential request",
+                    "descriptor_map": [
+                        {
+                            "id": "IdentityCredential",
+                            "format": "vc+sd-jwt",
+                            "path": "$"
+                        }
+                    ]
+                }))
+                .unwrap(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // Only the Disclosures for `given_name`, `address` and `address.country` are presented.
+    let Oid4vpParams::Params { vp_token, .. } = &authorization_response.extension.oid4vp_parameters else {
+        panic!("Expected a `vp_token`.");
+    };
+    let presented_sd_jwt: SdJwt = vp_token.parse().unwrap();
+    assert_eq!(presented_sd_jwt.disclosures.len(), 3);
+    assert!(presented_sd_jwt.key_binding_jwt.is_some());
+
+    // The relying party verifies the SD-JWT, its Disclosures and the Key Binding JWT.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    let [VerifiedCredential::SdJwt { claims, key_binding }] = report.credentials.as_slice() else {
+        panic!("Expected a single SD-JWT VC.");
+    };
+    assert_eq!(claims["given_name"], "Ferris");
+    assert_eq!(claims["address"], json!({ "country": "NL" }));
+    assert!(claims.get("family_name").is_none());
+    let key_binding = key_binding.as_ref().unwrap();
+    // Only an SD-JWT that is bound to its `sub` identifies the Holder.
+    assert_eq!(report.holder, (!bound_to_cnf).then(|| subject_did.clone()));
+    assert_eq!(key_binding.aud, relying_party_did);
+    assert_eq!(key_binding.nonce, "nonce");
+
+    // A response to another Authorization Request is rejected.
+    let mut other_authorization_request = authorization_request.clone();
+    other_authorization_request.body.extension.nonce = "other-nonce".to_string();
+    let report = relying_party_manager
+        .validate_response_to_request(&other_authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(!report.is_valid());
+    assert!(matches!(
+        report.outcome(Check::Nonce, None),
+        Some(CheckOutcome::Failed(_))
+    ));
+
+    // An Input Descriptor that requires limited disclosure only accepts the Disclosures of the requested claims.
+    let mut limited_authorization_request = authorization_request.clone();
+    let mut presentation_definition = json!(limited_authorization_request.body.extension.presentation_definition);
+    presentation_definition["input_descriptors"][0]["constraints"]["limit_disclosure"] = json!("required");
+    limited_authorization_request.body.extension.presentation_definition =
+        serde_json::from_value(presentation_definition.clone()).unwrap();
+    let report = relying_party_manager
+        .validate_response_to_request(&limited_authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert_eq!(
+        report.outcome(Check::LimitDisclosure, Some(0)),
+        Some(&CheckOutcome::Passed)
+    );
+
+    presentation_definition["input_descriptors"][0]["constraints"]["fields"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    limited_authorization_request.body.extension.presentation_definition =
+        serde_json::from_value(presentation_definition).unwrap();
+    let report = relying_party_manager
+        .validate_response_to_request(&limited_authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(!report.is_valid());
+    assert!(matches!(
+        report.outcome(Check::LimitDisclosure, Some(0)),
+        Some(CheckOutcome::Failed(reason)) if reason.contains("$.address")
+    ));
+
+    // Withholding a Disclosure after the Key Binding JWT was created invalidates the `sd_hash`.
+    let mut tampered_response = authorization_response.clone();
+    tampered_response.extension.oid4vp_parameters = Oid4vpParams::Params {
+        vp_token: SdJwt {
+            disclosures: presented_sd_jwt.disclosures[..2].to_vec(),
+            ..presented_sd_jwt.clone()
+        }
+        .to_string(),
+        presentation_submission: match &authorization_response.extension.oid4vp_parameters {
+            Oid4vpParams::Params {
+                presentation_submission,
+                ..
+            } => presentation_submission.clone(),
+            _ => unreachable!(),
+        },
+    };
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &tampered_response)
+        .await
+        .unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.outcome(Check::HolderBinding, Some(0)),
+        Some(&CheckOutcome::Failed(
+            "The `sd_hash` of the Key Binding JWT does not match the SD-JWT.".to_string()
+        ))
+    );
+    assert_eq!(report.holder, None);
+}
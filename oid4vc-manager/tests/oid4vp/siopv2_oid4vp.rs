@@ -0,0 +1,172 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    scope::Scope,
+    sd_jwt::{SdJwt, SdObjectEncoder},
+    Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vp::{
+    oid4vp::AuthorizationResponseInput as PresentationInput,
+    siopv2_oid4vp::{AuthorizationResponseInput, ClientMetadataParameters, SIOPv2OID4VP},
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty,
+};
+use serde_json::json;
+use siopv2::StandardClaimsValues;
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_id_token_and_vp_token() {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    let mut encoder = SdObjectEncoder::new(
+        json!({
+            "iss": issuer_did,
+            "sub": subject_did,
+            "vct": "IdentityCredential",
+            "given_name": "Ferris"
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    );
+    encoder.conceal("/given_name").unwrap();
+    let sd_jwt = SdJwt::encode(issuer, Header::new(Algorithm::EdDSA), encoder, "did:key")
+        .await
+        .unwrap();
+
+    // Create an authorization request with response_type `vp_token id_token`.
+    let authorization_request = AuthorizationRequest::<Object<SIOPv2OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .scope(Scope::openid())
+        .presentation_definition(
+            serde_json::from_value::<oid4vp::PresentationDefinition>(json!({
+                "id": "Identity Credential request",
+                "input_descriptors": [
+                    {
+                        "id": "IdentityCredential",
+                        "constraints": {
+                            "fields": [{ "path": ["$.given_name"] }]
+                        }
+                    }
+                ]
+            }))
+            .unwrap(),
+        )
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![(
+                    ClaimFormatDesignation::JwtVcJson,
+                    ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                )]
+                .into_iter()
+                .collect(),
+                subject_syntax_types_supported: vec!["did:key".parse().unwrap()],
+                id_token_signed_response_alg: Some(Algorithm::EdDSA),
+            },
+            other: HashMap::new(),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<SIOPv2OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput {
+                claims: StandardClaimsValues {
+                    name: Some("Ferris Crabman".to_string()),
+                    ..Default::default()
+                },
+                presentation: PresentationInput::PresentationExchange {
+                    verifiable_presentation: sd_jwt.into(),
+                    presentation_submission: serde_json::from_value(json!({
+                        "id": "Identity Credential submission",
+                        "definition_id": "Identity Credential request",
+                        "descriptor_map": [
+                            {
+                                "id": "IdentityCredential",
+                                "format": "vc+sd-jwt",
+                                "path": "$"
+                            }
+                        ]
+                    }))
+                    .unwrap(),
+                },
+            },
+        )
+        .await
+        .unwrap();
+
+    // The response holds both tokens next to the presentation submission.
+    let response = serde_json::to_value(&authorization_response).unwrap();
+    for parameter in ["id_token", "vp_token", "presentation_submission"] {
+        assert!(response.get(parameter).is_some());
+    }
+    assert_eq!(
+        serde_json::from_value::<AuthorizationResponse<SIOPv2OID4VP>>(response)
+            .unwrap()
+            .extension,
+        authorization_response.extension
+    );
+
+    // The relying party verifies both tokens in one go.
+    let verified_response = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert_eq!(verified_response.id_token.rfc7519_claims.sub, Some(subject_did.clone()));
+    assert_eq!(verified_response.id_token.nonce, Some("nonce".to_string()));
+    assert_eq!(
+        verified_response.id_token.standard_claims.name,
+        Some("Ferris Crabman".to_string())
+    );
+    assert!(verified_response.report.is_valid());
+    assert_eq!(verified_response.report.holder, Some(subject_did));
+    assert_eq!(
+        verified_response.report.outcome(Check::IdToken, None),
+        Some(&CheckOutcome::Passed)
+    );
+
+    // Both tokens are bound to the `nonce` of the Authorization Request.
+    let mut other_authorization_request = authorization_request.clone();
+    other_authorization_request.body.extension.nonce = "other-nonce".to_string();
+    let verified_response = relying_party_manager
+        .validate_response_to_request(&other_authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(matches!(
+        verified_response.report.outcome(Check::IdToken, None),
+        Some(CheckOutcome::Failed(_))
+    ));
+    assert!(matches!(
+        verified_response.report.outcome(Check::Nonce, None),
+        Some(CheckOutcome::Failed(_))
+    ));
+}
@@ -0,0 +1,188 @@
+use crate::common::memory_storage::MemoryStorage;
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    status_list::StatusType,
+    Subject,
+};
+use oid4vc_manager::{
+    managers::credential_issuer::CredentialIssuerManager, methods::key_method::KeySubject,
+    servers::credential_issuer::Server, ProviderManager, RelyingPartyManager,
+};
+use oid4vci::{
+    credential_format_profiles::{CredentialFormats, WithParameters},
+    credential_issuer::credential_signer::{DataModel, HolderBinding},
+};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    oid4vp::{AuthorizationResponseInput, OID4VP},
+    token::vp_jwt::VerifiablePresentationV2,
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, PresentationDefinition,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_revoked_credential_presentation() {
+    // Setup the credential issuer, which publishes its status lists.
+    let mut credential_issuer = Server::<_, CredentialFormats<WithParameters>>::setup(
+        CredentialIssuerManager::new(
+            None,
+            MemoryStorage,
+            Arc::new(KeySubject::from_keypair(
+                generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+                None,
+            )),
+        )
+        .unwrap(),
+        None,
+    )
+    .unwrap()
+    .detached(true);
+    credential_issuer
+        .credential_issuer_manager
+        .enable_status_list(0)
+        .await
+        .unwrap();
+    credential_issuer.credential_issuer_manager.credential_signer.data_model = DataModel::Vcdm20;
+    credential_issuer.start_server().await.unwrap();
+    let credential_issuer_manager = &credential_issuer.credential_issuer_manager;
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party, which fetches the status lists from the credential issuer.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The credential issuer issues a `vc+jwt` Credential that references its status lists.
+    let credential_status = {
+        let mut status_list_manager = credential_issuer_manager
+            .status_list_manager
+            .as_ref()
+            .unwrap()
+            .lock()
+            .await;
+        let idx = status_list_manager.allocate().unwrap();
+        status_list_manager.credential_status(idx).unwrap()
+    };
+    let credential = credential_issuer_manager
+        .credential_signer
+        .sign_with_status(
+            &credential_issuer_manager.credential_issuer.metadata,
+            "UniversityDegree_JWT",
+            json!({ "degree": "Bachelor of Science and Arts" }),
+            &HolderBinding::Did(subject_did.clone()),
+            Some(&credential_status),
+        )
+        .await
+        .unwrap();
+
+    let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+        "id": "Degree request",
+        "input_descriptors": [
+            {
+                "id": "UniversityDegree",
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.type"],
+                            "filter": {
+                                "type": "array",
+                                "contains": { "const": "UniversityDegreeCredential" }
+                            }
+                        }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did)
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition(presentation_definition)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![(
+                    ClaimFormatDesignation::JwtVcJson,
+                    ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: VerifiablePresentationV2::new(vec![credential.as_str().unwrap().to_string()])
+                    .into(),
+                presentation_submission: serde_json::from_value(json!({
+                    "id": "Degree submission",
+                    "definition_id": "Degree request",
+                    "descriptor_map": [
+                        {
+                            "id": "UniversityDegree",
+                            "format": "jwt_vp_json",
+                            "path": "$",
+                            "path_nested": {
+                                "id": "UniversityDegree",
+                                "format": "jwt_vc_json",
+                                "path": "$.verifiableCredential[0]"
+                            }
+                        }
+                    ]
+                }))
+                .unwrap(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The Credential is valid.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert_eq!(report.outcome(Check::Status, Some(0)), Some(&CheckOutcome::Passed));
+    assert!(report.is_valid());
+
+    // Once the credential issuer revokes the Credential, the relying party rejects it.
+    credential_issuer_manager
+        .set_credential_status(credential_status.status.status_list.idx, StatusType::Invalid)
+        .await
+        .unwrap();
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.outcome(Check::Status, Some(0)),
+        Some(&CheckOutcome::Failed("The Credential is revoked.".to_string()))
+    );
+}
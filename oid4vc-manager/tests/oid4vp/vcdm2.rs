@@ -0,0 +1,167 @@
+use did_key::{generate, Ed25519KeyPair};
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    authorization_request::{AuthorizationRequest, Object},
+    authorization_response::AuthorizationResponse,
+    client_metadata::ClientMetadataResource,
+    jwt, Subject,
+};
+use oid4vc_manager::{methods::key_method::KeySubject, ProviderManager, RelyingPartyManager};
+use oid4vci::{JwtCredential, VerifiableCredentialV2};
+use oid4vp::{
+    authorization_request::ClientMetadataParameters,
+    oid4vp::{AuthorizationResponseInput, VerifiedCredential, OID4VP},
+    token::vp_jwt::VerifiablePresentationV2,
+    verification::{Check, CheckOutcome},
+    ClaimFormatDesignation, ClaimFormatProperty, Oid4vpParams, PresentationDefinition,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_vcdm2_presentation() {
+    // Create a new issuer.
+    let issuer = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-issuer-secret-key".as_bytes())),
+        None,
+    ));
+    let issuer_did = issuer.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new subject.
+    let subject = Arc::new(KeySubject::from_keypair(
+        generate::<Ed25519KeyPair>(Some("this-is-a-very-UNSAFE-secret-key".as_bytes())),
+        None,
+    ));
+    let subject_did = subject.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+
+    // Create a new relying party.
+    let relying_party = Arc::new(KeySubject::new());
+    let relying_party_did = relying_party.identifier("did:key", Algorithm::EdDSA).await.unwrap();
+    let relying_party_manager = RelyingPartyManager::new(relying_party, "did:key", vec![Algorithm::EdDSA]).unwrap();
+
+    // The issuer creates a `vc+jwt` Credential.
+    let verifiable_credential: VerifiableCredentialV2 = serde_json::from_value(json!({
+        "@context": ["https://www.w3.org/ns/credentials/v2"],
+        "type": ["VerifiableCredential", "UniversityDegreeCredential"],
+        "issuer": issuer_did,
+        "validFrom": "2024-01-01T00:00:00Z",
+        "credentialSubject": {
+            "id": subject_did,
+            "degree": {
+                "type": "BachelorDegree",
+                "name": "Bachelor of Science and Arts"
+            }
+        }
+    }))
+    .unwrap();
+    let credential = jwt::encode(
+        issuer,
+        VerifiableCredentialV2::header(Algorithm::EdDSA),
+        verifiable_credential.clone(),
+        "did:key",
+    )
+    .await
+    .unwrap();
+
+    let presentation_definition: PresentationDefinition = serde_json::from_value(json!({
+        "id": "Degree request",
+        "input_descriptors": [
+            {
+                "id": "UniversityDegree",
+                "constraints": {
+                    "fields": [
+                        {
+                            "path": ["$.type"],
+                            "filter": {
+                                "type": "array",
+                                "contains": { "const": "UniversityDegreeCredential" }
+                            }
+                        }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let authorization_request = AuthorizationRequest::<Object<OID4VP>>::builder()
+        .client_id(relying_party_did.clone())
+        .redirect_uri("https://example.com".parse::<url::Url>().unwrap())
+        .presentation_definition(presentation_definition)
+        .client_metadata(ClientMetadataResource::ClientMetadata {
+            client_name: None,
+            logo_uri: None,
+            extension: ClientMetadataParameters {
+                vp_formats: vec![(
+                    ClaimFormatDesignation::JwtVcJson,
+                    ClaimFormatProperty::Alg(vec![Algorithm::EdDSA]),
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            other: HashMap::from_iter(vec![(
+                "subject_syntax_types_supported".to_string(),
+                json!(vec!["did:key".to_string()]),
+            )]),
+        })
+        .nonce("nonce".to_string())
+        .build()
+        .unwrap();
+
+    let provider_manager = ProviderManager::new(subject, vec!["did:key"], vec![Algorithm::EdDSA]).unwrap();
+
+    let authorization_response: AuthorizationResponse<OID4VP> = provider_manager
+        .generate_response(
+            &authorization_request,
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation: VerifiablePresentationV2::new(vec![credential]).into(),
+                presentation_submission: serde_json::from_value(json!({
+                    "id": "Degree submission",
+                    "definition_id": "Degree request",
+                    "descriptor_map": [
+                        {
+                            "id": "UniversityDegree",
+                            "format": "jwt_vp_json",
+                            "path": "$",
+                            "path_nested": {
+                                "id": "UniversityDegree",
+                                "format": "jwt_vc_json",
+                                "path": "$.verifiableCredential[0]"
+                            }
+                        }
+                    ]
+                }))
+                .unwrap(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The Verifiable Presentation is secured as a `vp+jwt`.
+    let Oid4vpParams::Params { vp_token, .. } = &authorization_response.extension.oid4vp_parameters else {
+        panic!("Expected a `vp_token`.");
+    };
+    let header = jsonwebtoken::decode_header(vp_token).unwrap();
+    assert_eq!(header.typ, Some("vp+jwt".to_string()));
+    assert_eq!(header.cty, Some("vp".to_string()));
+
+    // The relying party verifies the Verifiable Presentation and the enveloped Credential.
+    let report = relying_party_manager
+        .validate_response_to_request(&authorization_request, &authorization_response)
+        .await
+        .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(
+        report.outcome(Check::HolderBinding, Some(0)),
+        Some(&CheckOutcome::Passed)
+    );
+    let [VerifiedCredential::Jwt(JwtCredential::Vcdm20(verified_credential))] = report.credentials.as_slice() else {
+        panic!("Expected a single `vc+jwt` Credential.");
+    };
+    assert_eq!(verified_credential.as_ref(), &verifiable_credential);
+    assert_eq!(
+        verified_credential.valid_from,
+        Some("2024-01-01T00:00:00Z".parse().unwrap())
+    );
+}
@@ -185,8 +185,11 @@ async fn test_implicit_flow(#[case] did_method: &str) {
         key_subject: KeySubject::from_keypair(generate::<Ed25519KeyPair>(None), None),
     };
 
-    // Create a new provider manager.
-    let provider_manager = ProviderManager::new(Arc::new(subject), vec![did_method], vec![Algorithm::EdDSA]).unwrap();
+    let subject = Arc::new(subject);
+
+    // Create a new provider manager, which resolves the keys of the relying party by its DID.
+    let mut provider_manager = ProviderManager::new(subject.clone(), vec![did_method], vec![Algorithm::EdDSA]).unwrap();
+    provider_manager.provider.did_resolver = Some(subject);
 
     // Create a new RequestUrl which includes a `request_uri` pointing to the mock server's `request_uri` endpoint.
     let authorization_request = AuthorizationRequest::<ByReference> {
@@ -1,8 +1,21 @@
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dif_presentation_exchange::relational_constraints::same_identifier;
 use getset::Getters;
-use oid4vc_core::{builder_fn, RFC7519Claims};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{builder_fn, JsonObject, RFC7519Claims, Validator};
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 
+/// The base context of the W3C Verifiable Credentials Data Model v2.0.
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+/// The `typ` and `cty` of a Verifiable Credential secured with JOSE as described here:
+/// https://www.w3.org/TR/vc-jose-cose/#securing-vcs-with-jose
+pub const VC_JWT_TYP: &str = "vc+jwt";
+pub const VC_JWT_CTY: &str = "vc";
+
+/// Verifiable Credential encoded as a JWT according to the VCDM 1.1, in which the credential is wrapped in the `vc`
+/// claim as described here: https://www.w3.org/TR/vc-data-model/#jwt-encoding
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Getters)]
 pub struct VerifiableCredentialJwt {
     #[serde(flatten)]
@@ -10,18 +23,27 @@ pub struct VerifiableCredentialJwt {
     #[serde(rename = "vc")]
     #[getset(get = "pub")]
     pub verifiable_credential: serde_json::Value,
+    /// Confirmation claim as described here: https://www.rfc-editor.org/rfc/rfc7800.html#section-3.1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub")]
+    pub cnf: Option<serde_json::Value>,
 }
 
 impl VerifiableCredentialJwt {
     pub fn builder() -> VerifiableCredentialJwtBuilder {
         VerifiableCredentialJwtBuilder::new()
     }
+
+    pub fn header(algorithm: Algorithm) -> Header {
+        Header::new(algorithm)
+    }
 }
 
 #[derive(Default)]
 pub struct VerifiableCredentialJwtBuilder {
     rfc7519_claims: RFC7519Claims,
     verifiable_credential: Option<serde_json::Value>,
+    cnf: Option<serde_json::Value>,
 }
 
 impl VerifiableCredentialJwtBuilder {
@@ -35,6 +57,7 @@ impl VerifiableCredentialJwtBuilder {
             verifiable_credential: self
                 .verifiable_credential
                 .ok_or_else(|| anyhow!("verifiable_credential is required"))?,
+            cnf: self.cnf,
         })
     }
 
@@ -46,4 +69,222 @@ impl VerifiableCredentialJwtBuilder {
     builder_fn!(rfc7519_claims, iat, i64);
     builder_fn!(rfc7519_claims, jti, String);
     builder_fn!(verifiable_credential, serde_json::Value);
+    builder_fn!(cnf, serde_json::Value);
+}
+
+/// Verifiable Credential according to the VCDM 2.0 as described here: https://www.w3.org/TR/vc-data-model-2.0/
+/// When secured with JOSE, the credential itself is the payload of the JWT.
+#[skip_serializing_none]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableCredentialV2 {
+    #[serde(rename = "@context")]
+    pub context: Vec<serde_json::Value>,
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Vec<String>,
+    /// Either a URL or an object containing an `id`.
+    pub issuer: serde_json::Value,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub credential_subject: serde_json::Value,
+    pub credential_status: Option<serde_json::Value>,
+    /// Confirmation claim as described here: https://www.rfc-editor.org/rfc/rfc7800.html#section-3.1
+    pub cnf: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub other: JsonObject,
+}
+
+impl VerifiableCredentialV2 {
+    pub fn header(algorithm: Algorithm) -> Header {
+        Header {
+            typ: Some(VC_JWT_TYP.to_string()),
+            cty: Some(VC_JWT_CTY.to_string()),
+            ..Header::new(algorithm)
+        }
+    }
+
+    /// Checks the requirements of the VCDM 2.0 that are not enforced by deserialization.
+    pub fn validate(&self) -> Result<()> {
+        if self.context.first().and_then(serde_json::Value::as_str) != Some(CREDENTIALS_V2_CONTEXT) {
+            return Err(anyhow!("The first `@context` must be `{CREDENTIALS_V2_CONTEXT}`."));
+        }
+        if !self.type_.iter().any(|type_| type_ == "VerifiableCredential") {
+            return Err(anyhow!("The `type` must include `VerifiableCredential`."));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the identifier of the issuer of a Credential: its `iss` claim, or its `issuer` property, which is either a
+/// string or an object with an `id`.
+pub fn issuer(credential: &serde_json::Value) -> Option<&str> {
+    [
+        credential.get("iss"),
+        credential.pointer("/vc/issuer"),
+        credential.get("issuer"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|issuer| {
+        issuer
+            .as_str()
+            .or_else(|| issuer.get("id").and_then(serde_json::Value::as_str))
+    })
+}
+
+/// Checks that the key with which a Credential is secured, identified by the DID URL `verification_method`, belongs to
+/// the issuer of the Credential. The `iss` claim and the `issuer` property must both name the DID when present.
+pub fn verify_issuer(credential: &serde_json::Value, verification_method: &str) -> Result<()> {
+    let issuers = [
+        credential.get("iss"),
+        credential.pointer("/vc/issuer"),
+        credential.get("issuer"),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|issuer| {
+        issuer
+            .as_str()
+            .or_else(|| issuer.get("id").and_then(serde_json::Value::as_str))
+            .ok_or(anyhow!(
+                "The issuer of the Credential must be a string or an object with an `id`."
+            ))
+    })
+    .collect::<Result<Vec<_>>>()?;
+    if issuers.is_empty() {
+        return Err(anyhow!("The Credential does not identify its issuer."));
+    }
+    for issuer in issuers {
+        if !same_identifier(issuer, verification_method) {
+            return Err(anyhow!(
+                "The Credential is issued by `{issuer}`, but secured with `{verification_method}`."
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A Verifiable Credential secured as a JWT, in either of the encodings that are supported.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(untagged)]
+pub enum JwtCredential {
+    /// `jwt_vc_json` Credential according to the VCDM 1.1.
+    Vcdm11(Box<VerifiableCredentialJwt>),
+    /// `vc+jwt` Credential according to the VCDM 2.0 and VC-JOSE-COSE.
+    Vcdm20(Box<VerifiableCredentialV2>),
+}
+
+impl JwtCredential {
+    /// Verifies and decodes the `jwt`. Its encoding is determined by the `typ` header: `vc+jwt` Credentials are decoded
+    /// according to the VCDM 2.0, all other Credentials according to the VCDM 1.1.
+    /// The key with which the `jwt` is signed must belong to the issuer of the Credential.
+    pub async fn decode(validator: &Validator, jwt: &str) -> Result<Self> {
+        let header = jsonwebtoken::decode_header(jwt)?;
+        let kid = header
+            .kid
+            .as_deref()
+            .ok_or(anyhow!("No key identifier found in the header."))?;
+        let credential = if header.typ.as_deref() == Some(VC_JWT_TYP) {
+            let verifiable_credential: VerifiableCredentialV2 = validator.decode(jwt.to_string()).await?;
+            verifiable_credential.validate()?;
+            JwtCredential::Vcdm20(Box::new(verifiable_credential))
+        } else {
+            JwtCredential::Vcdm11(Box::new(validator.decode(jwt.to_string()).await?))
+        };
+        verify_issuer(&serde_json::to_value(&credential)?, kid)?;
+        Ok(credential)
+    }
+
+    pub fn credential_subject(&self) -> Option<&serde_json::Value> {
+        match self {
+            JwtCredential::Vcdm11(credential) => credential.verifiable_credential.get("credentialSubject"),
+            JwtCredential::Vcdm20(credential) => Some(&credential.credential_subject),
+        }
+    }
+
+    pub fn credential_status(&self) -> Option<&serde_json::Value> {
+        match self {
+            JwtCredential::Vcdm11(credential) => credential.verifiable_credential.get("credentialStatus"),
+            JwtCredential::Vcdm20(credential) => credential.credential_status.as_ref(),
+        }
+    }
+
+    pub fn cnf(&self) -> Option<&serde_json::Value> {
+        match self {
+            JwtCredential::Vcdm11(credential) => credential.cnf.as_ref(),
+            JwtCredential::Vcdm20(credential) => credential.cnf.as_ref(),
+        }
+    }
+
+    /// Returns the identifier of the subject, which is the `sub` claim of VCDM 1.1 Credentials or the `id` of the
+    /// `credentialSubject` otherwise.
+    pub fn subject_id(&self) -> Option<&str> {
+        match self {
+            JwtCredential::Vcdm11(credential) if credential.rfc7519_claims.sub.is_some() => {
+                credential.rfc7519_claims.sub.as_deref()
+            }
+            _ => self
+                .credential_subject()
+                .and_then(|credential_subject| credential_subject.get("id"))
+                .and_then(serde_json::Value::as_str),
+        }
+    }
+
+    /// Returns the start of the validity period, which is the `nbf` claim of VCDM 1.1 Credentials.
+    pub fn valid_from(&self) -> Option<DateTime<Utc>> {
+        match self {
+            JwtCredential::Vcdm11(credential) => credential
+                .rfc7519_claims
+                .nbf
+                .and_then(|nbf| DateTime::from_timestamp(nbf, 0)),
+            JwtCredential::Vcdm20(credential) => credential.valid_from,
+        }
+    }
+
+    /// Returns the end of the validity period, which is the `exp` claim of VCDM 1.1 Credentials.
+    pub fn valid_until(&self) -> Option<DateTime<Utc>> {
+        match self {
+            JwtCredential::Vcdm11(credential) => credential
+                .rfc7519_claims
+                .exp
+                .and_then(|exp| DateTime::from_timestamp(exp, 0)),
+            JwtCredential::Vcdm20(credential) => credential.valid_until,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verify_issuer() {
+        let credential = json!({
+            "iss": "did:example:issuer",
+            "vc": { "issuer": "did:example:issuer" }
+        });
+        assert!(verify_issuer(&credential, "did:example:issuer#key-1").is_ok());
+        assert!(verify_issuer(&credential, "did:example:other#key-1").is_err());
+
+        // The `issuer` may also be an object with an `id`.
+        let credential = json!({ "issuer": { "id": "did:example:issuer", "name": "Example University" } });
+        assert!(verify_issuer(&credential, "did:example:issuer#key-1").is_ok());
+
+        // All identifiers of the issuer must name the DID of the key.
+        let credential = json!({
+            "iss": "did:example:issuer",
+            "vc": { "issuer": "https://issuer.example.com" }
+        });
+        assert!(verify_issuer(&credential, "did:example:issuer#key-1").is_err());
+
+        // A Credential without an issuer is rejected.
+        assert!(verify_issuer(&json!({}), "did:example:issuer#key-1").is_err());
+        assert_eq!(issuer(&json!({})), None);
+        assert_eq!(
+            issuer(&json!({ "vc": { "issuer": "did:example:issuer" } })),
+            Some("did:example:issuer")
+        );
+    }
 }
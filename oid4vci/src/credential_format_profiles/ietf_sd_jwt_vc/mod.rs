@@ -0,0 +1 @@
+pub mod vc_sd_jwt;
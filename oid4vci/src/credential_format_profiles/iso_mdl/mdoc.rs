@@ -0,0 +1,1183 @@
+use anyhow::{anyhow, Result};
+use base64_url::base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use ciborium::Value;
+use coset::{iana, AsCborValue, CoseKey, CoseKeyBuilder, CoseSign1, CoseSign1Builder, HeaderBuilder};
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk},
+    Algorithm, DecodingKey, Header,
+};
+use oid4vc_core::{authentication::subject::SigningSubject, Validator};
+use rand::{rngs::OsRng, seq::SliceRandom, RngCore};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Tag of an embedded CBOR data item as described here: https://www.rfc-editor.org/rfc/rfc8949#section-3.4.5.1
+const ENCODED_CBOR: u64 = 24;
+/// Tag of a date/time string as described here: https://www.rfc-editor.org/rfc/rfc8949#section-3.4.1
+const DATE_TIME: u64 = 0;
+
+/// Data element signed by the Issuer as described in ISO/IEC 18013-5, section 8.3.2.1.2.2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuerSignedItem {
+    pub digest_id: u64,
+    pub random: Vec<u8>,
+    pub element_identifier: String,
+    pub element_value: Value,
+}
+
+impl IssuerSignedItem {
+    fn to_cbor_value(&self) -> Value {
+        Value::Map(vec![
+            (text("digestID"), Value::Integer(self.digest_id.into())),
+            (text("random"), Value::Bytes(self.random.clone())),
+            (text("elementIdentifier"), text(&self.element_identifier)),
+            (text("elementValue"), self.element_value.clone()),
+        ])
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self> {
+        Ok(Self {
+            digest_id: get(value, "digestID")?
+                .as_integer()
+                .and_then(|digest_id| u64::try_from(digest_id).ok())
+                .ok_or(anyhow!("`digestID` must be an unsigned integer."))?,
+            random: get(value, "random")?
+                .as_bytes()
+                .cloned()
+                .ok_or(anyhow!("`random` must be a byte string."))?,
+            element_identifier: get(value, "elementIdentifier")?
+                .as_text()
+                .map(ToString::to_string)
+                .ok_or(anyhow!("`elementIdentifier` must be a text string."))?,
+            element_value: get(value, "elementValue")?.clone(),
+        })
+    }
+}
+
+/// An [`IssuerSignedItem`] together with its CBOR encoding, over which its digest is calculated:
+/// `IssuerSignedItemBytes = #6.24(bstr .cbor IssuerSignedItem)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuerSignedItemBytes {
+    pub item: IssuerSignedItem,
+    bytes: Vec<u8>,
+}
+
+impl IssuerSignedItemBytes {
+    pub fn new(item: IssuerSignedItem) -> Result<Self> {
+        Ok(Self {
+            bytes: to_vec(&item.to_cbor_value())?,
+            item,
+        })
+    }
+
+    /// Returns the SHA-256 digest that is included in the `valueDigests` of the [`MobileSecurityObject`].
+    pub fn digest(&self) -> Result<Vec<u8>> {
+        Ok(Sha256::digest(to_vec(&self.to_cbor_value())?).to_vec())
+    }
+
+    pub fn to_cbor_value(&self) -> Value {
+        Value::Tag(ENCODED_CBOR, Box::new(Value::Bytes(self.bytes.clone())))
+    }
+
+    pub fn from_cbor_value(value: &Value) -> Result<Self> {
+        let bytes = embedded_cbor(value)?;
+        Ok(Self {
+            item: IssuerSignedItem::from_cbor_value(&from_slice(&bytes)?)?,
+            bytes,
+        })
+    }
+}
+
+/// Validity of the [`MobileSecurityObject`] as described in ISO/IEC 18013-5, section 9.1.2.4.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidityInfo {
+    pub signed: DateTime<Utc>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+}
+
+/// Mobile Security Object as described in ISO/IEC 18013-5, section 9.1.2.4. It contains the digests of all
+/// [`IssuerSignedItem`]s and the public key of the Holder's device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MobileSecurityObject {
+    pub version: String,
+    pub digest_algorithm: String,
+    /// The digests per namespace, indexed by their `digestID`.
+    pub value_digests: BTreeMap<String, BTreeMap<u64, Vec<u8>>>,
+    pub device_key: CoseKey,
+    pub doc_type: String,
+    pub validity_info: ValidityInfo,
+}
+
+impl MobileSecurityObject {
+    fn to_cbor_value(&self) -> Result<Value> {
+        let value_digests = self
+            .value_digests
+            .iter()
+            .map(|(name_space, digests)| {
+                (
+                    text(name_space),
+                    Value::Map(
+                        digests
+                            .iter()
+                            .map(|(digest_id, digest)| {
+                                (Value::Integer((*digest_id).into()), Value::Bytes(digest.clone()))
+                            })
+                            .collect(),
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(Value::Map(vec![
+            (text("version"), text(&self.version)),
+            (text("digestAlgorithm"), text(&self.digest_algorithm)),
+            (text("valueDigests"), Value::Map(value_digests)),
+            (
+                text("deviceKeyInfo"),
+                Value::Map(vec![(
+                    text("deviceKey"),
+                    self.device_key.clone().to_cbor_value().map_err(cose_error)?,
+                )]),
+            ),
+            (text("docType"), text(&self.doc_type)),
+            (
+                text("validityInfo"),
+                Value::Map(vec![
+                    (text("signed"), date_time(&self.validity_info.signed)),
+                    (text("validFrom"), date_time(&self.validity_info.valid_from)),
+                    (text("validUntil"), date_time(&self.validity_info.valid_until)),
+                ]),
+            ),
+        ]))
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self> {
+        let value_digests = get(value, "valueDigests")?
+            .as_map()
+            .ok_or(anyhow!("`valueDigests` must be a map."))?
+            .iter()
+            .map(|(name_space, digests)| {
+                let digests = digests
+                    .as_map()
+                    .ok_or(anyhow!("The digests of a namespace must be a map."))?
+                    .iter()
+                    .map(|(digest_id, digest)| {
+                        Ok((
+                            digest_id
+                                .as_integer()
+                                .and_then(|digest_id| u64::try_from(digest_id).ok())
+                                .ok_or(anyhow!("`digestID` must be an unsigned integer."))?,
+                            digest
+                                .as_bytes()
+                                .cloned()
+                                .ok_or(anyhow!("Digests must be byte strings."))?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok((as_text(name_space)?, digests))
+            })
+            .collect::<Result<_>>()?;
+
+        let validity_info = get(value, "validityInfo")?;
+
+        Ok(Self {
+            version: as_text(get(value, "version")?)?,
+            digest_algorithm: as_text(get(value, "digestAlgorithm")?)?,
+            value_digests,
+            device_key: CoseKey::from_cbor_value(get(get(value, "deviceKeyInfo")?, "deviceKey")?.clone())
+                .map_err(cose_error)?,
+            doc_type: as_text(get(value, "docType")?)?,
+            validity_info: ValidityInfo {
+                signed: as_date_time(get(validity_info, "signed")?)?,
+                valid_from: as_date_time(get(validity_info, "validFrom")?)?,
+                valid_until: as_date_time(get(validity_info, "validUntil")?)?,
+            },
+        })
+    }
+}
+
+/// Issuer-signed part of an mdoc as described in ISO/IEC 18013-5, section 8.3.2.1.2.2. In OpenID4VCI it is returned as
+/// a base64url-encoded CBOR structure: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#appendix-A.2.4
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuerSigned {
+    pub name_spaces: BTreeMap<String, Vec<IssuerSignedItemBytes>>,
+    /// COSE_Sign1 of which the payload is the [`MobileSecurityObject`].
+    pub issuer_auth: CoseSign1,
+}
+
+impl IssuerSigned {
+    /// Creates the [`IssuerSignedItem`]s for the namespaced `claims` (e.g. `{"org.iso.18013.5.1": {"given_name":
+    /// "John"}}`) and signs the resulting [`MobileSecurityObject`], which binds the mdoc to the Holder's `device_key`.
+    pub async fn sign(
+        signer: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+        doc_type: impl Into<String>,
+        claims: &serde_json::Value,
+        device_key: CoseKey,
+        validity_info: ValidityInfo,
+    ) -> Result<Self> {
+        let mut name_spaces = BTreeMap::new();
+        let mut value_digests = BTreeMap::new();
+        for (name_space, elements) in claims.as_object().ok_or(anyhow!("The claims must be a JSON object."))? {
+            let elements = elements
+                .as_object()
+                .ok_or(anyhow!("The claims of namespace `{name_space}` must be a JSON object."))?;
+
+            // Digest IDs are assigned in random order so that they do not reveal the order of the data elements.
+            let mut digest_ids: Vec<u64> = (0..elements.len() as u64).collect();
+            digest_ids.shuffle(&mut OsRng);
+
+            let mut items = vec![];
+            let mut digests = BTreeMap::new();
+            for ((element_identifier, element_value), digest_id) in elements.iter().zip(digest_ids) {
+                let mut random = vec![0u8; 16];
+                OsRng.fill_bytes(&mut random);
+                let item = IssuerSignedItemBytes::new(IssuerSignedItem {
+                    digest_id,
+                    random,
+                    element_identifier: element_identifier.clone(),
+                    element_value: Value::serialized(element_value)?,
+                })?;
+                digests.insert(digest_id, item.digest()?);
+                items.push(item);
+            }
+            name_spaces.insert(name_space.clone(), items);
+            value_digests.insert(name_space.clone(), digests);
+        }
+
+        let mobile_security_object = MobileSecurityObject {
+            version: "1.0".to_string(),
+            digest_algorithm: "SHA-256".to_string(),
+            value_digests,
+            device_key,
+            doc_type: doc_type.into(),
+            validity_info,
+        };
+        let payload = to_vec(&Value::Tag(
+            ENCODED_CBOR,
+            Box::new(Value::Bytes(to_vec(&mobile_security_object.to_cbor_value()?)?)),
+        ))?;
+
+        // ISO/IEC 18013-5 identifies the Issuer by the `x5chain` of its Document Signer certificate. Signers without a
+        // certificate chain are identified by a `kid` instead, which is integrity protected.
+        let (protected, unprotected) = match signer.certificate_chain(subject_syntax_type, algorithm).await {
+            Some(certificate_chain) => (
+                HeaderBuilder::new().algorithm(cose_algorithm(algorithm)?),
+                HeaderBuilder::new().value(iana::HeaderParameter::X5Chain as i64, x5chain(&certificate_chain)?),
+            ),
+            None => {
+                let key_id = signer
+                    .key_id(subject_syntax_type, algorithm)
+                    .await
+                    .ok_or(anyhow!("No key identifier found."))?;
+                (
+                    HeaderBuilder::new()
+                        .algorithm(cose_algorithm(algorithm)?)
+                        .key_id(key_id.into_bytes()),
+                    HeaderBuilder::new(),
+                )
+            }
+        };
+        let issuer_auth = CoseSign1Builder::new()
+            .protected(protected.build())
+            .unprotected(unprotected.build())
+            .payload(payload)
+            .build();
+        let signature = signer
+            .sign_bytes(&issuer_auth.tbs_data(&[]), subject_syntax_type, algorithm)
+            .await?;
+
+        Ok(Self {
+            name_spaces,
+            issuer_auth: CoseSign1 {
+                signature,
+                ..issuer_auth
+            },
+        })
+    }
+
+    /// Returns the [`MobileSecurityObject`] WITHOUT verifying the signature of the Issuer.
+    pub fn mobile_security_object(&self) -> Result<MobileSecurityObject> {
+        let payload = self
+            .issuer_auth
+            .payload
+            .as_ref()
+            .ok_or(anyhow!("The `issuerAuth` does not contain a payload."))?;
+        MobileSecurityObject::from_cbor_value(&from_slice(&embedded_cbor(&from_slice(payload)?)?)?)
+    }
+
+    /// Returns the data elements per namespace as JSON, e.g. `{"org.iso.18013.5.1": {"given_name": "John"}}`.
+    pub fn claims(&self) -> Result<serde_json::Value> {
+        self.name_spaces
+            .iter()
+            .map(|(name_space, items)| {
+                let elements = items
+                    .iter()
+                    .map(|item| {
+                        Ok((
+                            item.item.element_identifier.clone(),
+                            item.item.element_value.deserialized::<serde_json::Value>()?,
+                        ))
+                    })
+                    .collect::<Result<serde_json::Map<_, _>>>()?;
+                Ok((name_space.clone(), serde_json::Value::Object(elements)))
+            })
+            .collect::<Result<serde_json::Map<_, _>>>()
+            .map(serde_json::Value::Object)
+    }
+
+    /// Returns the DER encoded certificates of the `x5chain` of the `issuerAuth`, starting with the Document Signer
+    /// certificate, as described here: https://www.rfc-editor.org/rfc/rfc9360.html#section-2
+    pub fn x5chain(&self) -> Result<Option<Vec<Vec<u8>>>> {
+        let label = coset::Label::Int(iana::HeaderParameter::X5Chain as i64);
+        let Some((_, x5chain)) = self
+            .issuer_auth
+            .protected
+            .header
+            .rest
+            .iter()
+            .chain(self.issuer_auth.unprotected.rest.iter())
+            .find(|(l, _)| l == &label)
+        else {
+            return Ok(None);
+        };
+        match x5chain {
+            Value::Bytes(certificate) => Ok(Some(vec![certificate.clone()])),
+            Value::Array(certificates) => certificates
+                .iter()
+                .map(|certificate| {
+                    certificate
+                        .as_bytes()
+                        .cloned()
+                        .ok_or(anyhow!("The certificates of the `x5chain` must be byte strings."))
+                })
+                .collect::<Result<_>>()
+                .map(Some),
+            _ => Err(anyhow!(
+                "The `x5chain` must be a byte string or an array of byte strings."
+            )),
+        }
+    }
+
+    /// Verifies the signature of the Issuer and verifies that the digests of all [`IssuerSignedItem`]s are included in
+    /// the [`MobileSecurityObject`], which is returned. The public key of the Issuer is resolved by the `validator`
+    /// from the `x5chain` of the `issuerAuth`, e.g. by an [`oid4vc_core::x509::X509Verifier`] that validates the chain
+    /// against its trust anchors, or otherwise from its protected `kid`.
+    pub async fn verify(&self, validator: &Validator) -> Result<MobileSecurityObject> {
+        let algorithm = match &self.issuer_auth.protected.header.alg {
+            Some(coset::Algorithm::Assigned(iana::Algorithm::EdDSA)) => Algorithm::EdDSA,
+            Some(coset::Algorithm::Assigned(iana::Algorithm::ES256)) => Algorithm::ES256,
+            alg => return Err(anyhow!("Unsupported algorithm: {alg:?}")),
+        };
+        let mut header = Header::new(algorithm);
+        match self.x5chain()? {
+            Some(x5chain) => {
+                header.x5c = Some(x5chain.iter().map(|certificate| STANDARD.encode(certificate)).collect())
+            }
+            None if !self.issuer_auth.protected.header.key_id.is_empty() => {
+                header.kid = Some(String::from_utf8(self.issuer_auth.protected.header.key_id.clone())?)
+            }
+            None => {
+                return Err(anyhow!(
+                    "The `issuerAuth` contains neither an `x5chain` nor a protected `kid`."
+                ))
+            }
+        }
+        let public_key = validator.resolve_public_key(&header).await?;
+        let decoding_key = match algorithm {
+            Algorithm::EdDSA => DecodingKey::from_ed_der(&public_key),
+            _ => DecodingKey::from_ec_der(&public_key),
+        };
+        self.issuer_auth.verify_signature(&[], |signature, tbs_data| {
+            jsonwebtoken::crypto::verify(&base64_url::encode(signature), tbs_data, &decoding_key, algorithm)?
+                .then_some(())
+                .ok_or(anyhow!("Invalid `issuerAuth` signature."))
+        })?;
+
+        let mobile_security_object = self.mobile_security_object()?;
+        for (name_space, items) in &self.name_spaces {
+            for item in items {
+                if mobile_security_object
+                    .value_digests
+                    .get(name_space)
+                    .and_then(|digests| digests.get(&item.item.digest_id))
+                    != Some(&item.digest()?)
+                {
+                    return Err(anyhow!(
+                        "The digest of `{}` in namespace `{name_space}` does not match.",
+                        item.item.element_identifier
+                    ));
+                }
+            }
+        }
+
+        Ok(mobile_security_object)
+    }
+
+    pub fn to_cbor_value(&self) -> Result<Value> {
+        Ok(Value::Map(vec![
+            (
+                text("nameSpaces"),
+                Value::Map(
+                    self.name_spaces
+                        .iter()
+                        .map(|(name_space, items)| {
+                            (
+                                text(name_space),
+                                Value::Array(items.iter().map(IssuerSignedItemBytes::to_cbor_value).collect()),
+                            )
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                text("issuerAuth"),
+                self.issuer_auth.clone().to_cbor_value().map_err(cose_error)?,
+            ),
+        ]))
+    }
+
+    pub fn from_cbor_value(value: &Value) -> Result<Self> {
+        let name_spaces = match value.as_map().and_then(|map| find(map, "nameSpaces")) {
+            Some(name_spaces) => name_spaces
+                .as_map()
+                .ok_or(anyhow!("`nameSpaces` must be a map."))?
+                .iter()
+                .map(|(name_space, items)| {
+                    let items = items
+                        .as_array()
+                        .ok_or(anyhow!("The items of a namespace must be an array."))?
+                        .iter()
+                        .map(IssuerSignedItemBytes::from_cbor_value)
+                        .collect::<Result<_>>()?;
+                    Ok((as_text(name_space)?, items))
+                })
+                .collect::<Result<_>>()?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            name_spaces,
+            issuer_auth: CoseSign1::from_cbor_value(get(value, "issuerAuth")?.clone()).map_err(cose_error)?,
+        })
+    }
+
+    /// Returns the base64url-encoded CBOR encoding as used in the Credential Response.
+    pub fn to_base64url(&self) -> Result<String> {
+        Ok(base64_url::encode(&to_vec(&self.to_cbor_value()?)?))
+    }
+}
+
+impl FromStr for IssuerSigned {
+    type Err = anyhow::Error;
+
+    /// Parses the base64url-encoded CBOR encoding as used in the Credential Response.
+    fn from_str(issuer_signed: &str) -> Result<Self> {
+        Self::from_cbor_value(&from_slice(&base64_url::decode(issuer_signed)?)?)
+    }
+}
+
+/// SessionTranscript for presentations over OpenID4VP as described in ISO/IEC 18013-7, Annex B.4.4:
+/// `[null, null, [clientIdHash, responseUriHash, nonce]]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionTranscript(Value);
+
+impl SessionTranscript {
+    pub fn oid4vp(client_id: &str, response_uri: &str, nonce: &str, mdoc_generated_nonce: &str) -> Result<Self> {
+        let hash = |value: &str| -> Result<Value> {
+            Ok(Value::Bytes(
+                Sha256::digest(to_vec(&Value::Array(vec![text(value), text(mdoc_generated_nonce)]))?).to_vec(),
+            ))
+        };
+
+        Ok(Self(Value::Array(vec![
+            Value::Null,
+            Value::Null,
+            Value::Array(vec![hash(client_id)?, hash(response_uri)?, text(nonce)]),
+        ])))
+    }
+
+    /// Returns the `DeviceAuthenticationBytes` over which the `deviceSignature` is created, as described in ISO/IEC
+    /// 18013-5, section 9.1.3.4. Data elements signed by the device are not supported, so the `DeviceNameSpaces` are
+    /// always empty.
+    fn device_authentication_bytes(&self, doc_type: &str) -> Result<Vec<u8>> {
+        let device_authentication = Value::Array(vec![
+            text("DeviceAuthentication"),
+            self.0.clone(),
+            text(doc_type),
+            empty_device_name_spaces()?,
+        ]);
+        to_vec(&Value::Tag(
+            ENCODED_CBOR,
+            Box::new(Value::Bytes(to_vec(&device_authentication)?)),
+        ))
+    }
+}
+
+/// Generates the nonce that the Wallet adds to the [`SessionTranscript`]. ISO/IEC 18013-7 conveys it in the `apu`
+/// header of the encrypted Authorization Response.
+pub fn generate_mdoc_generated_nonce() -> String {
+    let mut mdoc_generated_nonce = vec![0u8; 16];
+    OsRng.fill_bytes(&mut mdoc_generated_nonce);
+    base64_url::encode(&mdoc_generated_nonce)
+}
+
+/// Document as described in ISO/IEC 18013-5, section 8.3.2.1.2.2. It contains the data elements that are disclosed by
+/// the Holder together with the `deviceSignature`, which proves possession of the device key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub doc_type: String,
+    pub issuer_signed: IssuerSigned,
+    /// COSE_Sign1 with a detached payload over the `DeviceAuthenticationBytes`.
+    pub device_signature: CoseSign1,
+}
+
+impl Document {
+    /// Creates a [`Document`] for the `issuer_signed` mdoc, signed by the `signer` that holds the device key.
+    pub async fn sign(
+        issuer_signed: IssuerSigned,
+        signer: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+        session_transcript: &SessionTranscript,
+    ) -> Result<Self> {
+        let doc_type = issuer_signed.mobile_security_object()?.doc_type;
+
+        let device_signature = CoseSign1Builder::new()
+            .protected(HeaderBuilder::new().algorithm(cose_algorithm(algorithm)?).build())
+            .build();
+        let signature = signer
+            .sign_bytes(
+                &device_signature.tbs_detached_data(&session_transcript.device_authentication_bytes(&doc_type)?, &[]),
+                subject_syntax_type,
+                algorithm,
+            )
+            .await?;
+
+        Ok(Self {
+            doc_type,
+            issuer_signed,
+            device_signature: CoseSign1 {
+                signature,
+                ..device_signature
+            },
+        })
+    }
+
+    /// Verifies the [`IssuerSigned`] structure and checks that the mdoc is currently valid. The device signature is NOT
+    /// verified, see [`Document::verify_device_signature`].
+    pub async fn verify(&self, validator: &Validator) -> Result<MobileSecurityObject> {
+        let mobile_security_object = self.issuer_signed.verify(validator).await?;
+
+        if mobile_security_object.doc_type != self.doc_type {
+            return Err(anyhow!("The `docType` does not match the Mobile Security Object."));
+        }
+        let now = Utc::now();
+        let validity_info = &mobile_security_object.validity_info;
+        if now < validity_info.valid_from || now > validity_info.valid_until {
+            return Err(anyhow!("The mdoc is not valid at this time."));
+        }
+
+        Ok(mobile_security_object)
+    }
+
+    /// Verifies the `deviceSignature` over the `session_transcript` with the device key from the Mobile Security
+    /// Object.
+    pub fn verify_device_signature(&self, session_transcript: &SessionTranscript) -> Result<()> {
+        let device_key = self.issuer_signed.mobile_security_object()?.device_key;
+        let (decoding_key, algorithm) = decoding_key_from_cose_key(&device_key)?;
+        if self.device_signature.protected.header.alg != Some(coset::Algorithm::Assigned(cose_algorithm(algorithm)?)) {
+            return Err(anyhow!(
+                "The `deviceSignature` algorithm does not match the device key."
+            ));
+        }
+
+        self.device_signature.verify_detached_signature(
+            &session_transcript.device_authentication_bytes(&self.doc_type)?,
+            &[],
+            |signature, tbs_data| {
+                jsonwebtoken::crypto::verify(&base64_url::encode(signature), tbs_data, &decoding_key, algorithm)?
+                    .then_some(())
+                    .ok_or(anyhow!("Invalid `deviceSignature`."))
+            },
+        )
+    }
+
+    fn to_cbor_value(&self) -> Result<Value> {
+        Ok(Value::Map(vec![
+            (text("docType"), text(&self.doc_type)),
+            (text("issuerSigned"), self.issuer_signed.to_cbor_value()?),
+            (
+                text("deviceSigned"),
+                Value::Map(vec![
+                    (text("nameSpaces"), empty_device_name_spaces()?),
+                    (
+                        text("deviceAuth"),
+                        Value::Map(vec![(
+                            text("deviceSignature"),
+                            self.device_signature.clone().to_cbor_value().map_err(cose_error)?,
+                        )]),
+                    ),
+                ]),
+            ),
+        ]))
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self> {
+        Ok(Self {
+            doc_type: as_text(get(value, "docType")?)?,
+            issuer_signed: IssuerSigned::from_cbor_value(get(value, "issuerSigned")?)?,
+            device_signature: CoseSign1::from_cbor_value(
+                get(get(get(value, "deviceSigned")?, "deviceAuth")?, "deviceSignature")?.clone(),
+            )
+            .map_err(cose_error)?,
+        })
+    }
+}
+
+/// DeviceResponse as described in ISO/IEC 18013-5, section 8.3.2.1.2.2. In OpenID4VP it is presented as a
+/// base64url-encoded CBOR structure in the `vp_token`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceResponse {
+    pub version: String,
+    pub documents: Vec<Document>,
+    pub status: u64,
+}
+
+impl DeviceResponse {
+    pub fn new(documents: Vec<Document>) -> Self {
+        Self {
+            version: "1.0".to_string(),
+            documents,
+            status: 0,
+        }
+    }
+
+    /// Creates a [`DeviceResponse`] for the `issuer_signed` mdoc in response to an OpenID4VP Authorization Request, as
+    /// described in ISO/IEC 18013-7, Annex B. The `mdoc_generated_nonce` is not part of the [`DeviceResponse`], so it
+    /// needs to be conveyed to the Verifier separately.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn oid4vp(
+        issuer_signed: IssuerSigned,
+        signer: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+        client_id: &str,
+        response_uri: &str,
+        nonce: &str,
+        mdoc_generated_nonce: &str,
+    ) -> Result<Self> {
+        let session_transcript = SessionTranscript::oid4vp(client_id, response_uri, nonce, mdoc_generated_nonce)?;
+        let document = Document::sign(
+            issuer_signed,
+            signer,
+            subject_syntax_type,
+            algorithm,
+            &session_transcript,
+        )
+        .await?;
+
+        Ok(Self::new(vec![document]))
+    }
+
+    pub fn to_base64url(&self) -> Result<String> {
+        let documents = self
+            .documents
+            .iter()
+            .map(Document::to_cbor_value)
+            .collect::<Result<_>>()?;
+        Ok(base64_url::encode(&to_vec(&Value::Map(vec![
+            (text("version"), text(&self.version)),
+            (text("documents"), Value::Array(documents)),
+            (text("status"), Value::Integer(self.status.into())),
+        ]))?))
+    }
+}
+
+impl FromStr for DeviceResponse {
+    type Err = anyhow::Error;
+
+    fn from_str(device_response: &str) -> Result<Self> {
+        let value = from_slice(&base64_url::decode(device_response)?)?;
+        Ok(Self {
+            version: as_text(get(&value, "version")?)?,
+            documents: match value.as_map().and_then(|map| find(map, "documents")) {
+                Some(documents) => documents
+                    .as_array()
+                    .ok_or(anyhow!("`documents` must be an array."))?
+                    .iter()
+                    .map(Document::from_cbor_value)
+                    .collect::<Result<_>>()?,
+                None => vec![],
+            },
+            status: get(&value, "status")?
+                .as_integer()
+                .and_then(|status| u64::try_from(status).ok())
+                .ok_or(anyhow!("`status` must be an unsigned integer."))?,
+        })
+    }
+}
+
+/// Converts a public key JWK into a COSE_Key as described here: https://www.rfc-editor.org/rfc/rfc9053#section-7
+pub fn cose_key_from_jwk(jwk: &Jwk) -> Result<CoseKey> {
+    match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(parameters) if parameters.curve == EllipticCurve::P256 => {
+            Ok(CoseKeyBuilder::new_ec2_pub_key(
+                iana::EllipticCurve::P_256,
+                base64_url::decode(&parameters.x)?,
+                base64_url::decode(&parameters.y)?,
+            )
+            .build())
+        }
+        AlgorithmParameters::OctetKeyPair(parameters) if parameters.curve == EllipticCurve::Ed25519 => {
+            Ok(CoseKeyBuilder::new_okp_key()
+                .param(
+                    iana::OkpKeyParameter::Crv as i64,
+                    Value::from(iana::EllipticCurve::Ed25519 as i64),
+                )
+                .param(
+                    iana::OkpKeyParameter::X as i64,
+                    Value::Bytes(base64_url::decode(&parameters.x)?),
+                )
+                .build())
+        }
+        _ => Err(anyhow!("Only P-256 and Ed25519 keys are supported.")),
+    }
+}
+
+/// Converts a COSE_Key into a key with which signatures of the corresponding algorithm can be verified.
+fn decoding_key_from_cose_key(cose_key: &CoseKey) -> Result<(DecodingKey, Algorithm)> {
+    let param = |label: i64| {
+        cose_key
+            .params
+            .iter()
+            .find(|(l, _)| l == &coset::Label::Int(label))
+            .map(|(_, value)| value)
+            .ok_or(anyhow!("Missing COSE_Key parameter: {label}"))
+    };
+    let bytes = |label: i64| -> Result<Vec<u8>> {
+        param(label)?
+            .as_bytes()
+            .cloned()
+            .ok_or(anyhow!("COSE_Key parameter {label} must be a byte string."))
+    };
+
+    match cose_key.kty {
+        coset::KeyType::Assigned(iana::KeyType::EC2)
+            if param(iana::Ec2KeyParameter::Crv as i64)? == &Value::from(iana::EllipticCurve::P_256 as i64) =>
+        {
+            // Uncompressed point: 0x04 || x || y.
+            let public_key = [
+                vec![0x04],
+                bytes(iana::Ec2KeyParameter::X as i64)?,
+                bytes(iana::Ec2KeyParameter::Y as i64)?,
+            ]
+            .concat();
+            Ok((DecodingKey::from_ec_der(&public_key), Algorithm::ES256))
+        }
+        coset::KeyType::Assigned(iana::KeyType::OKP)
+            if param(iana::OkpKeyParameter::Crv as i64)? == &Value::from(iana::EllipticCurve::Ed25519 as i64) =>
+        {
+            Ok((
+                DecodingKey::from_ed_der(&bytes(iana::OkpKeyParameter::X as i64)?),
+                Algorithm::EdDSA,
+            ))
+        }
+        _ => Err(anyhow!("Only P-256 and Ed25519 device keys are supported.")),
+    }
+}
+
+/// Converts the base64 encoded DER certificates of an `x5c` certificate chain into an `x5chain` as described here:
+/// https://www.rfc-editor.org/rfc/rfc9360.html#section-2
+fn x5chain(certificate_chain: &[String]) -> Result<Value> {
+    let mut certificates = certificate_chain
+        .iter()
+        .map(|certificate| Ok(Value::Bytes(STANDARD.decode(certificate)?)))
+        .collect::<Result<Vec<_>>>()?;
+    match certificates.len() {
+        0 => Err(anyhow!("The certificate chain is empty.")),
+        1 => Ok(certificates.remove(0)),
+        _ => Ok(Value::Array(certificates)),
+    }
+}
+
+fn cose_algorithm(algorithm: Algorithm) -> Result<iana::Algorithm> {
+    match algorithm {
+        Algorithm::EdDSA => Ok(iana::Algorithm::EdDSA),
+        Algorithm::ES256 => Ok(iana::Algorithm::ES256),
+        _ => Err(anyhow!("Unsupported algorithm: {algorithm:?}")),
+    }
+}
+
+/// `DeviceNameSpacesBytes = #6.24(bstr .cbor {})`.
+fn empty_device_name_spaces() -> Result<Value> {
+    Ok(Value::Tag(
+        ENCODED_CBOR,
+        Box::new(Value::Bytes(to_vec(&Value::Map(vec![]))?)),
+    ))
+}
+
+fn text(text: &str) -> Value {
+    Value::Text(text.to_string())
+}
+
+fn date_time(date_time: &DateTime<Utc>) -> Value {
+    Value::Tag(
+        DATE_TIME,
+        Box::new(Value::Text(date_time.to_rfc3339_opts(SecondsFormat::Secs, true))),
+    )
+}
+
+fn as_text(value: &Value) -> Result<String> {
+    value
+        .as_text()
+        .map(ToString::to_string)
+        .ok_or(anyhow!("Expected a text string."))
+}
+
+fn as_date_time(value: &Value) -> Result<DateTime<Utc>> {
+    match value {
+        Value::Tag(DATE_TIME, date_time) => Ok(DateTime::parse_from_rfc3339(&as_text(date_time)?)?.with_timezone(&Utc)),
+        _ => Err(anyhow!("Expected a date/time string.")),
+    }
+}
+
+fn embedded_cbor(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::Tag(ENCODED_CBOR, bytes) => bytes
+            .as_bytes()
+            .cloned()
+            .ok_or(anyhow!("Embedded CBOR must be a byte string.")),
+        _ => Err(anyhow!("Expected embedded CBOR.")),
+    }
+}
+
+fn find<'a>(map: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    map.iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, value)| value)
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Result<&'a Value> {
+    value
+        .as_map()
+        .and_then(|map| find(map, key))
+        .ok_or(anyhow!("Missing `{key}`."))
+}
+
+fn to_vec(value: &Value) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    ciborium::ser::into_writer(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn from_slice(bytes: &[u8]) -> Result<Value> {
+    Ok(ciborium::de::from_reader(bytes)?)
+}
+
+fn cose_error(error: coset::CoseError) -> anyhow::Error {
+    anyhow!("{error:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use oid4vc_core::test_utils::{MockVerifier, TestSubject, TEST_KEYPAIR};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn device_key() -> CoseKey {
+        cose_key_from_jwk(
+            &serde_json::from_value(json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+                "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+            }))
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_issuer_signed() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let validity_info = ValidityInfo {
+            signed: now,
+            valid_from: now,
+            valid_until: now + Duration::days(365),
+        };
+        let claims = json!({
+            "org.iso.18013.5.1": {
+                "given_name": "John",
+                "family_name": "Doe",
+                "age_over_18": true
+            },
+            "org.iso.18013.5.1.aamva": {
+                "organ_donor": 1
+            }
+        });
+
+        let issuer_signed = IssuerSigned::sign(
+            Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap()),
+            "did:test",
+            Algorithm::EdDSA,
+            "org.iso.18013.5.1.mDL",
+            &claims,
+            device_key(),
+            validity_info.clone(),
+        )
+        .await
+        .unwrap();
+
+        // The mdoc survives the base64url-encoded CBOR roundtrip as used in the Credential Response.
+        let issuer_signed: IssuerSigned = issuer_signed.to_base64url().unwrap().parse().unwrap();
+        assert_eq!(issuer_signed.claims().unwrap(), claims);
+
+        let mobile_security_object = issuer_signed
+            .verify(&Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .unwrap();
+        assert_eq!(mobile_security_object.doc_type, "org.iso.18013.5.1.mDL");
+        assert_eq!(mobile_security_object.digest_algorithm, "SHA-256");
+        assert_eq!(mobile_security_object.device_key, device_key());
+        assert_eq!(mobile_security_object.validity_info, validity_info);
+        assert_eq!(mobile_security_object.value_digests["org.iso.18013.5.1"].len(), 3);
+        assert_eq!(
+            String::from_utf8(issuer_signed.issuer_auth.protected.header.key_id.clone()).unwrap(),
+            "did:test:123#key-1"
+        );
+
+        // Tampering with a data element invalidates its digest.
+        let mut tampered = issuer_signed.clone();
+        let item = &mut tampered.name_spaces.get_mut("org.iso.18013.5.1").unwrap()[0];
+        *item = IssuerSignedItemBytes::new(IssuerSignedItem {
+            element_value: Value::Text("Mallory".to_string()),
+            ..item.item.clone()
+        })
+        .unwrap();
+        assert!(tampered
+            .verify(&Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_issuer_signed_x5chain() {
+        use oid4vc_core::{
+            authentication::sign::ExternalSign,
+            x509::{TrustAnchors, X509Verifier},
+            Sign, Subject, Verify,
+        };
+        use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, PKCS_ECDSA_P256_SHA256};
+
+        /// A Document Signer of which the key is certified by an IACA root certificate.
+        struct DocumentSigner {
+            key_pair: KeyPair,
+            certificate: Vec<u8>,
+        }
+
+        #[async_trait::async_trait]
+        impl Sign for DocumentSigner {
+            async fn key_id(&self, _subject_syntax_type: &str, _algorithm: Algorithm) -> Option<String> {
+                None
+            }
+
+            async fn sign(&self, message: &str, subject_syntax_type: &str, algorithm: Algorithm) -> Result<Vec<u8>> {
+                self.sign_bytes(message.as_bytes(), subject_syntax_type, algorithm)
+                    .await
+            }
+
+            async fn sign_bytes(
+                &self,
+                message: &[u8],
+                _subject_syntax_type: &str,
+                algorithm: Algorithm,
+            ) -> Result<Vec<u8>> {
+                let encoding_key = jsonwebtoken::EncodingKey::from_ec_pem(self.key_pair.serialize_pem().as_bytes())?;
+                Ok(base64_url::decode(&jsonwebtoken::crypto::sign(
+                    message,
+                    &encoding_key,
+                    algorithm,
+                )?)?)
+            }
+
+            async fn certificate_chain(
+                &self,
+                _subject_syntax_type: &str,
+                _algorithm: Algorithm,
+            ) -> Option<Vec<String>> {
+                Some(vec![STANDARD.encode(&self.certificate)])
+            }
+
+            fn external_signer(&self) -> Option<Arc<dyn ExternalSign>> {
+                None
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl Verify for DocumentSigner {
+            async fn public_key(&self, _kid: &str) -> Result<Vec<u8>> {
+                Err(anyhow!("Not supported."))
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl Subject for DocumentSigner {
+            async fn identifier(&self, _subject_syntax_type: &str, _algorithm: Algorithm) -> Result<String> {
+                Err(anyhow!("Not supported."))
+            }
+        }
+
+        let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let root = params.self_signed(&root_key).unwrap();
+        let key_pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let certificate = CertificateParams::new(vec![])
+            .unwrap()
+            .signed_by(&key_pair, &root, &root_key)
+            .unwrap();
+
+        let now = Utc::now();
+        let issuer_signed = IssuerSigned::sign(
+            Arc::new(DocumentSigner {
+                key_pair,
+                certificate: certificate.der().to_vec(),
+            }),
+            "did:test",
+            Algorithm::ES256,
+            "org.iso.18013.5.1.mDL",
+            &json!({ "org.iso.18013.5.1": { "given_name": "John" } }),
+            device_key(),
+            ValidityInfo {
+                signed: now,
+                valid_from: now,
+                valid_until: now + Duration::days(1),
+            },
+        )
+        .await
+        .unwrap();
+        let issuer_signed: IssuerSigned = issuer_signed.to_base64url().unwrap().parse().unwrap();
+        assert_eq!(issuer_signed.x5chain().unwrap(), Some(vec![certificate.der().to_vec()]));
+        assert!(issuer_signed.issuer_auth.protected.header.key_id.is_empty());
+
+        // The `x5chain` must be issued by a trust anchor.
+        assert!(issuer_signed
+            .verify(&Validator::Verifier(Arc::new(X509Verifier::default())))
+            .await
+            .is_err());
+        let mut trust_anchors = TrustAnchors::default();
+        trust_anchors.add(root.der()).unwrap();
+        assert!(issuer_signed
+            .verify(&Validator::Verifier(Arc::new(X509Verifier::new(trust_anchors))))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_device_response() {
+        let now = Utc::now();
+        let subject = Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap());
+        // The Holder's device key is the key of the test subject.
+        let device_key = cose_key_from_jwk(
+            &serde_json::from_value(json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": base64_url::encode(&TEST_KEYPAIR.verifying_key().to_bytes())
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        let issuer_signed = IssuerSigned::sign(
+            subject.clone(),
+            "did:test",
+            Algorithm::EdDSA,
+            "org.iso.18013.5.1.mDL",
+            &json!({ "org.iso.18013.5.1": { "given_name": "John" } }),
+            device_key,
+            ValidityInfo {
+                signed: now,
+                valid_from: now,
+                valid_until: now + Duration::days(1),
+            },
+        )
+        .await
+        .unwrap();
+
+        let mdoc_generated_nonce = generate_mdoc_generated_nonce();
+        let device_response = DeviceResponse::oid4vp(
+            issuer_signed,
+            subject,
+            "did:test",
+            Algorithm::EdDSA,
+            "client_id",
+            "https://example.com/response",
+            "nonce",
+            &mdoc_generated_nonce,
+        )
+        .await
+        .unwrap();
+        let device_response: DeviceResponse = device_response.to_base64url().unwrap().parse().unwrap();
+        assert_eq!(device_response.version, "1.0");
+        assert_eq!(device_response.status, 0);
+
+        let document = &device_response.documents[0];
+        assert_eq!(document.doc_type, "org.iso.18013.5.1.mDL");
+        assert!(document
+            .verify(&Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .is_ok());
+
+        let session_transcript = SessionTranscript::oid4vp(
+            "client_id",
+            "https://example.com/response",
+            "nonce",
+            &mdoc_generated_nonce,
+        )
+        .unwrap();
+        assert!(document.verify_device_signature(&session_transcript).is_ok());
+
+        // The device signature is bound to the OpenID4VP request.
+        let session_transcript = SessionTranscript::oid4vp(
+            "client_id",
+            "https://example.com/response",
+            "other",
+            &mdoc_generated_nonce,
+        )
+        .unwrap();
+        assert!(document.verify_device_signature(&session_transcript).is_err());
+    }
+
+    #[test]
+    fn test_cose_key_from_jwk() {
+        let cose_key = cose_key_from_jwk(
+            &serde_json::from_value(json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cose_key.kty, coset::KeyType::Assigned(iana::KeyType::OKP));
+
+        assert_eq!(device_key().kty, coset::KeyType::Assigned(iana::KeyType::EC2));
+        assert!(cose_key_from_jwk(
+            &serde_json::from_value(json!({
+                "kty": "EC",
+                "crv": "P-384",
+                "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+                "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+            }))
+            .unwrap()
+        )
+        .is_err());
+    }
+}
@@ -1 +1,2 @@
+pub mod mdoc;
 pub mod mso_mdoc;
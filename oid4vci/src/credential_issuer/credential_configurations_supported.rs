@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use super::display::{resolve_display, ClaimDisplay, CredentialDisplay};
 use crate::{
     credential_format_profiles::{CredentialFormatCollection, CredentialFormats, WithParameters},
     proof::KeyProofMetadata,
@@ -27,7 +28,141 @@ where
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     pub proof_types_supported: HashMap<ProofType, KeyProofMetadata>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    pub display: Vec<serde_json::Value>,
+    pub display: Vec<CredentialDisplay>,
+}
+
+/// Description of a claim in the `credentialSubject` or `claims` parameter of a supported Credential as described here:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#appendix-A.1.1.2-3.1.2.2
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClaimDescription {
+    pub mandatory: Option<bool>,
+    pub value_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub display: Vec<ClaimDisplay>,
+}
+
+/// A claim of a supported Credential together with its label in the preferred locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedClaim {
+    /// Path to the claim, e.g. `["degree", "type"]` or `["org.iso.18013.5.1", "given_name"]` for `mso_mdoc`.
+    pub path: Vec<String>,
+    pub label: Option<String>,
+    pub mandatory: bool,
+    pub value_type: Option<String>,
+}
+
+impl CredentialConfigurationsSupportedObject<CredentialFormats<WithParameters>> {
+    /// Returns the display properties of the Credential that best match the `preferred_locales`.
+    pub fn localized_display(&self, preferred_locales: &[&str]) -> Option<&CredentialDisplay> {
+        resolve_display(&self.display, preferred_locales)
+    }
+
+    /// Returns the claims of the Credential with their labels in the preferred locale. The claims are sorted
+    /// according to the `order` parameter, claims not listed in `order` keep their original position after the ones
+    /// that are. An `order` entry refers to a claim by its name, its `~` separated path (e.g.
+    /// `org.iso.18013.5.1~given_name`) or one of its display names. The `order` of the W3C Verifiable Credential
+    /// formats consists of a single entry. Malformed claim descriptions result in an error.
+    pub fn localized_claims(&self, preferred_locales: &[&str]) -> anyhow::Result<Vec<LocalizedClaim>> {
+        let (claims, order) = match &self.credential_format {
+            CredentialFormats::JwtVcJson(parameters) => (
+                &parameters
+                    .parameters
+                    .credential_definition
+                    .credential_subject
+                    .credential_subject,
+                parameters.parameters.order.clone().into_iter().collect(),
+            ),
+            CredentialFormats::JwtVcJsonLd(parameters) => (
+                &parameters
+                    .parameters
+                    .credential_definition
+                    .credential_subject
+                    .credential_subject,
+                parameters.parameters.order.clone().into_iter().collect(),
+            ),
+            CredentialFormats::LdpVc(parameters) => (
+                &parameters
+                    .parameters
+                    .credential_definition
+                    .credential_subject
+                    .credential_subject,
+                parameters.parameters.order.clone().into_iter().collect(),
+            ),
+            CredentialFormats::MsoMdoc(parameters) => (
+                &parameters.parameters.claims,
+                parameters.parameters.order.clone().unwrap_or_default(),
+            ),
+            CredentialFormats::VcSdJwt(parameters) => (
+                &parameters.parameters.claims,
+                parameters.parameters.order.clone().unwrap_or_default(),
+            ),
+            CredentialFormats::Unknown => return Ok(vec![]),
+        };
+
+        let mut claim_descriptions = vec![];
+        if let Some(claims) = claims {
+            collect_claim_descriptions(claims, &[], &mut claim_descriptions)?;
+        }
+
+        let position = |(path, claim_description): &(Vec<String>, ClaimDescription)| {
+            order.iter().position(|entry| {
+                path.last() == Some(entry)
+                    || &path.join("~") == entry
+                    || claim_description
+                        .display
+                        .iter()
+                        .any(|display| display.name.as_ref() == Some(entry))
+            })
+        };
+        // `None` is ordered before `Some`, so claims that are not listed in `order` are mapped to `usize::MAX`.
+        claim_descriptions.sort_by_key(|claim| position(claim).unwrap_or(usize::MAX));
+
+        Ok(claim_descriptions
+            .into_iter()
+            .map(|(path, claim_description)| LocalizedClaim {
+                label: resolve_display(&claim_description.display, preferred_locales)
+                    .and_then(|display| display.name.clone()),
+                mandatory: claim_description.mandatory.unwrap_or(false),
+                value_type: claim_description.value_type,
+                path,
+            })
+            .collect())
+    }
+}
+
+/// Recursively collects the claim descriptions. Objects that only contain nested claims (such as `mso_mdoc`
+/// namespaces) are not included themselves.
+fn collect_claim_descriptions(
+    claims: &serde_json::Value,
+    path: &[String],
+    claim_descriptions: &mut Vec<(Vec<String>, ClaimDescription)>,
+) -> anyhow::Result<()> {
+    const CLAIM_DESCRIPTION_PARAMETERS: [&str; 3] = ["mandatory", "value_type", "display"];
+
+    if let Some(claims) = claims.as_object() {
+        for (name, claim) in claims {
+            let path = [path, std::slice::from_ref(name)].concat();
+            let claim_description: ClaimDescription = serde_json::from_value(claim.clone())
+                .map_err(|error| anyhow::anyhow!("Malformed description of the claim `{}`: {error}", path.join("~")))?;
+
+            let nested_claims: serde_json::Map<_, _> = claim
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter(|(name, nested_claim)| {
+                    !CLAIM_DESCRIPTION_PARAMETERS.contains(&name.as_str()) && nested_claim.is_object()
+                })
+                .map(|(name, nested_claim)| (name.clone(), nested_claim.clone()))
+                .collect();
+
+            if nested_claims.is_empty() || !claim_description.display.is_empty() {
+                claim_descriptions.push((path.clone(), claim_description));
+            }
+            collect_claim_descriptions(&serde_json::Value::Object(nested_claims), &path, claim_descriptions)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -37,6 +172,7 @@ mod tests {
         w3c_verifiable_credentials::{jwt_vc_json, ldp_vc, CredentialSubject},
         CredentialFormats, Parameters,
     };
+    use crate::credential_issuer::display::Logo;
     use jsonwebtoken::Algorithm;
     use serde::de::DeserializeOwned;
     use serde_json::json;
@@ -118,16 +254,18 @@ mod tests {
                         )]
                         .into_iter()
                         .collect(),
-                        display: vec![json!({
-                            "name": "University Credential",
-                            "locale": "en-US",
-                            "logo": {
-                                "url": "https://university.example.edu/public/logo.png",
-                                "alt_text": "a square logo of a university"
-                            },
-                            "background_color": "#12107c",
-                            "text_color": "#FFFFFF"
-                        })]
+                        display: vec![CredentialDisplay {
+                            name: "University Credential".to_string(),
+                            locale: Some("en-US".to_string()),
+                            logo: Some(Logo {
+                                uri: "https://university.example.edu/public/logo.png".parse().unwrap(),
+                                alt_text: Some("a square logo of a university".to_string())
+                            }),
+                            description: None,
+                            background_color: Some("#12107c".to_string()),
+                            background_image: None,
+                            text_color: Some("#FFFFFF".to_string())
+                        }]
                     }
                 )]
                 .into_iter()
@@ -190,17 +328,18 @@ mod tests {
                         cryptographic_binding_methods_supported: vec!["did:example".to_string()],
                         credential_signing_alg_values_supported: vec!["Ed25519Signature2018".to_string()],
                         proof_types_supported: HashMap::new(),
-                        display: vec![json!({
-                                "name": "University Credential",
-                                "locale": "en-US",
-                                "logo": {
-                                    "url": "https://university.example.edu/public/logo.png",
-                                    "alt_text": "a square logo of a university"
-                                },
-                                "background_color": "#12107c",
-                                "text_color": "#FFFFFF"
-                            }
-                        )]
+                        display: vec![CredentialDisplay {
+                            name: "University Credential".to_string(),
+                            locale: Some("en-US".to_string()),
+                            logo: Some(Logo {
+                                uri: "https://university.example.edu/public/logo.png".parse().unwrap(),
+                                alt_text: Some("a square logo of a university".to_string())
+                            }),
+                            description: None,
+                            background_color: Some("#12107c".to_string()),
+                            background_image: None,
+                            text_color: Some("#FFFFFF".to_string())
+                        }]
                     },
                 )]
                 .into_iter()
@@ -260,26 +399,30 @@ mod tests {
                         ],
                         proof_types_supported: HashMap::new(),
                         display: vec![
-                            json!({
-                                "name": "Mobile Driving License",
-                                "locale": "en-US",
-                                "logo": {
-                                    "url": "https://state.example.org/public/mdl.png",
-                                    "alt_text": "state mobile driving license"
-                                },
-                                "background_color": "#12107c",
-                                "text_color": "#FFFFFF"
-                            }),
-                            json!({
-                                "name": "モバイル運転免許証",
-                                "locale": "ja-JP",
-                                "logo": {
-                                    "url": "https://state.example.org/public/mdl.png",
-                                    "alt_text": "米国州発行のモバイル運転免許証"
-                                },
-                                "background_color": "#12107c",
-                                "text_color": "#FFFFFF"
-                            })
+                            CredentialDisplay {
+                                name: "Mobile Driving License".to_string(),
+                                locale: Some("en-US".to_string()),
+                                logo: Some(Logo {
+                                    uri: "https://state.example.org/public/mdl.png".parse().unwrap(),
+                                    alt_text: Some("state mobile driving license".to_string())
+                                }),
+                                description: None,
+                                background_color: Some("#12107c".to_string()),
+                                background_image: None,
+                                text_color: Some("#FFFFFF".to_string())
+                            },
+                            CredentialDisplay {
+                                name: "モバイル運転免許証".to_string(),
+                                locale: Some("ja-JP".to_string()),
+                                logo: Some(Logo {
+                                    uri: "https://state.example.org/public/mdl.png".parse().unwrap(),
+                                    alt_text: Some("米国州発行のモバイル運転免許証".to_string())
+                                }),
+                                description: None,
+                                background_color: Some("#12107c".to_string()),
+                                background_image: None,
+                                text_color: Some("#FFFFFF".to_string())
+                            }
                         ]
                     }
                 )]
@@ -289,4 +432,110 @@ mod tests {
             json_example::<TestWrapper>("tests/examples/credential_metadata_mso_mdoc.json")
         );
     }
+
+    #[test]
+    fn test_localized_claims() {
+        let mut credential_configurations_supported =
+            json_example::<TestWrapper>("tests/examples/credential_metadata_mso_mdoc.json")
+                .credential_configurations_supported;
+        let credential_configuration = credential_configurations_supported
+            .get_mut("org.iso.18013.5.1.mDL")
+            .unwrap();
+
+        assert_eq!(
+            credential_configuration
+                .localized_display(&["ja-JP"])
+                .map(|display| display.name.as_str()),
+            Some("モバイル運転免許証")
+        );
+
+        if let CredentialFormats::MsoMdoc(parameters) = &mut credential_configuration.credential_format {
+            parameters.parameters.order = Some(vec!["org.iso.18013.5.1~family_name".to_string(), "名前".to_string()]);
+        }
+
+        assert_eq!(
+            credential_configuration.localized_claims(&["ja-JP", "en-US"]).unwrap(),
+            vec![
+                LocalizedClaim {
+                    path: vec!["org.iso.18013.5.1".to_string(), "family_name".to_string()],
+                    label: Some("Surname".to_string()),
+                    mandatory: false,
+                    value_type: None,
+                },
+                LocalizedClaim {
+                    path: vec!["org.iso.18013.5.1".to_string(), "given_name".to_string()],
+                    label: Some("名前".to_string()),
+                    mandatory: false,
+                    value_type: None,
+                },
+                LocalizedClaim {
+                    path: vec!["org.iso.18013.5.1".to_string(), "birth_date".to_string()],
+                    label: None,
+                    mandatory: true,
+                    value_type: None,
+                },
+                LocalizedClaim {
+                    path: vec!["org.iso.18013.5.1.aamva".to_string(), "organ_donor".to_string()],
+                    label: None,
+                    mandatory: false,
+                    value_type: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_localized_claims_vc_sd_jwt() {
+        let credential_configurations_supported =
+            json_example::<TestWrapper>("tests/examples/credential_metadata_sd_jwt_vc.json")
+                .credential_configurations_supported;
+        let credential_configuration = credential_configurations_supported
+            .get("SD_JWT_VC_example_in_OpenID4VCI")
+            .unwrap();
+
+        match &credential_configuration.credential_format {
+            CredentialFormats::VcSdJwt(parameters) => {
+                assert_eq!(parameters.parameters.vct, "SD_JWT_VC_example_in_OpenID4VCI")
+            }
+            _ => panic!("Expected a `vc+sd-jwt` credential configuration."),
+        }
+
+        let claims = credential_configuration.localized_claims(&["de-DE"]).unwrap();
+        assert_eq!(
+            claims
+                .iter()
+                .map(|claim| (claim.path.join("~"), claim.label.as_deref()))
+                .take(2)
+                .collect::<Vec<_>>(),
+            vec![
+                ("given_name".to_string(), Some("Vorname")),
+                ("family_name".to_string(), Some("Nachname"))
+            ]
+        );
+        assert!(claims
+            .iter()
+            .any(|claim| claim.path == vec!["address".to_string(), "street_address".to_string()]));
+        assert_eq!(claims.len(), 12);
+    }
+    #[test]
+    fn test_localized_claims_malformed() {
+        let mut credential_configurations_supported =
+            json_example::<TestWrapper>("tests/examples/credential_metadata_mso_mdoc.json")
+                .credential_configurations_supported;
+        let credential_configuration = credential_configurations_supported
+            .get_mut("org.iso.18013.5.1.mDL")
+            .unwrap();
+
+        if let CredentialFormats::MsoMdoc(parameters) = &mut credential_configuration.credential_format {
+            parameters.parameters.claims.as_mut().unwrap()["org.iso.18013.5.1"]["birth_date"]["mandatory"] =
+                json!("yes");
+        }
+
+        // A malformed claim description is not silently ignored.
+        assert!(credential_configuration
+            .localized_claims(&["en-US"])
+            .unwrap_err()
+            .to_string()
+            .starts_with("Malformed description of the claim `org.iso.18013.5.1~birth_date`"));
+    }
 }
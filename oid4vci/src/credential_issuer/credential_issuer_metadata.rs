@@ -1,11 +1,22 @@
-use super::credential_configurations_supported::CredentialConfigurationsSupportedObject;
+use super::{
+    credential_configurations_supported::CredentialConfigurationsSupportedObject,
+    display::{resolve_display, CredentialIssuerDisplay},
+};
 use crate::credential_format_profiles::{CredentialFormatCollection, CredentialFormats, WithParameters};
+use anyhow::{anyhow, Result};
 use derivative::Derivative;
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::{authentication::subject::SigningSubject, jwt, JsonObject, RFC7519Claims, Validator};
 use reqwest::Url;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+/// Information about whether the Credential Issuer supports encryption of the Credential and Batch Credential Response
+/// as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#section-11.2.3-2.7
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CredentialResponseEncryption {
     pub alg_values_supported: Vec<String>,
@@ -36,10 +47,119 @@ where
     pub credential_response_encryption: Option<CredentialResponseEncryption>,
     pub credential_identifiers_supported: Option<bool>,
     pub signed_metadata: Option<String>,
-    pub display: Option<Vec<serde_json::Value>>,
+    pub display: Option<Vec<CredentialIssuerDisplay>>,
     pub credential_configurations_supported: HashMap<String, CredentialConfigurationsSupportedObject<CFC>>,
 }
 
+/// Claims of the `signed_metadata` JWT as described here:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#section-11.2.3-2.11
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignedMetadataClaims {
+    #[serde(flatten)]
+    pub rfc7519_claims: RFC7519Claims,
+    #[serde(flatten)]
+    pub metadata: JsonObject,
+}
+
+impl<CFC: CredentialFormatCollection> CredentialIssuerMetadata<CFC> {
+    /// Returns the display properties of the Credential Issuer that best match the `preferred_locales`.
+    pub fn localized_display(&self, preferred_locales: &[&str]) -> Option<&CredentialIssuerDisplay> {
+        self.display
+            .as_deref()
+            .and_then(|display| resolve_display(display, preferred_locales))
+    }
+
+    /// Returns the identifier of the credential configuration that matches the requested `credential_format`. Formats
+    /// are matched on their `format` and their format-specific type identifier (i.e. the `credential_definition.type`
+    /// for W3C Verifiable Credentials or the `doctype` for ISO mdoc).
+    pub fn credential_configuration_id(&self, credential_format: &CFC) -> Option<&String> {
+        let type_identifier = |credential_format: &serde_json::Value| {
+            (
+                credential_format["format"].clone(),
+                credential_format["credential_definition"]["type"].clone(),
+                credential_format["doctype"].clone(),
+                credential_format["vct"].clone(),
+            )
+        };
+        let requested = type_identifier(&serde_json::to_value(credential_format).ok()?);
+
+        self.credential_configurations_supported
+            .iter()
+            .find(|(_, credential_configuration)| {
+                serde_json::to_value(&credential_configuration.credential_format)
+                    .is_ok_and(|credential_format| type_identifier(&credential_format) == requested)
+            })
+            .map(|(credential_configuration_id, _)| credential_configuration_id)
+    }
+
+    /// Returns a `signed_metadata` JWT containing the metadata parameters, signed by the Credential Issuer's `signer`.
+    pub async fn sign(
+        &self,
+        signer: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+    ) -> Result<String> {
+        let mut metadata = match serde_json::to_value(self)? {
+            serde_json::Value::Object(metadata) => metadata,
+            _ => unreachable!("Credential Issuer Metadata is always a JSON object."),
+        };
+        metadata.remove("signed_metadata");
+
+        let claims = SignedMetadataClaims {
+            rfc7519_claims: RFC7519Claims {
+                iss: Some(self.credential_issuer.to_string()),
+                sub: Some(self.credential_issuer.to_string()),
+                iat: Some(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64),
+                ..Default::default()
+            },
+            metadata,
+        };
+
+        jwt::encode(signer, Header::new(algorithm), claims, subject_syntax_type).await
+    }
+}
+
+impl<CFC: CredentialFormatCollection + DeserializeOwned> CredentialIssuerMetadata<CFC> {
+    /// Validates the `signed_metadata` JWT (if present) and returns the metadata in which the signed metadata
+    /// parameters take precedence over the unsigned ones.
+    pub async fn verify_signed_metadata(self, validator: Validator) -> Result<Self> {
+        let signed_metadata = match &self.signed_metadata {
+            Some(signed_metadata) => signed_metadata.clone(),
+            None => return Ok(self),
+        };
+
+        let SignedMetadataClaims {
+            rfc7519_claims,
+            metadata,
+        } = validator.decode(signed_metadata).await?;
+
+        let is_credential_issuer = |claim: &Option<String>| {
+            claim
+                .as_ref()
+                .and_then(|claim| claim.parse::<Url>().ok())
+                .is_some_and(|claim| claim == self.credential_issuer)
+        };
+        if !is_credential_issuer(&rfc7519_claims.iss) {
+            return Err(anyhow!(
+                "The `iss` claim of the signed metadata does not match the Credential Issuer."
+            ));
+        }
+        if !is_credential_issuer(&rfc7519_claims.sub) {
+            return Err(anyhow!(
+                "The `sub` claim of the signed metadata does not match the Credential Issuer."
+            ));
+        }
+
+        let mut merged_metadata = match serde_json::to_value(&self)? {
+            serde_json::Value::Object(merged_metadata) => merged_metadata,
+            _ => unreachable!("Credential Issuer Metadata is always a JSON object."),
+        };
+        merged_metadata.extend(metadata);
+
+        Ok(serde_json::from_value(serde_json::Value::Object(merged_metadata))?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,13 +168,13 @@ mod tests {
             w3c_verifiable_credentials::{jwt_vc_json, CredentialSubject},
             CredentialFormats, Parameters, WithParameters,
         },
+        credential_issuer::display::{CredentialDisplay, Logo},
         proof::KeyProofMetadata,
         ProofType,
     };
-    use jsonwebtoken::Algorithm;
-    use serde::de::DeserializeOwned;
+    use oid4vc_core::test_utils::{MockVerifier, TestSubject};
     use serde_json::json;
-    use std::{fs::File, path::Path};
+    use std::{fs::File, path::Path, sync::Arc};
 
     fn json_example<T>(path: &str) -> T
     where
@@ -94,14 +214,16 @@ mod tests {
                 credential_identifiers_supported: None,
                 signed_metadata: None,
                 display: Some(vec![
-                    json!({
-                        "name": "Example University",
-                        "locale": "en-US"
-                    }),
-                    json!({
-                        "name": "Example Université",
-                        "locale": "fr-FR"
-                    })
+                    CredentialIssuerDisplay {
+                        name: Some("Example University".to_string()),
+                        locale: Some("en-US".to_string()),
+                        logo: None,
+                    },
+                    CredentialIssuerDisplay {
+                        name: Some("Example Université".to_string()),
+                        locale: Some("fr-FR".to_string()),
+                        logo: None,
+                    }
                 ]),
                 credential_configurations_supported: vec![(
                     "UniversityDegreeCredential".to_string(),
@@ -157,16 +279,18 @@ mod tests {
                         )]
                         .into_iter()
                         .collect(),
-                        display: vec![json!({
-                            "name": "University Credential",
-                            "locale": "en-US",
-                            "logo": {
-                                "url": "https://university.example.edu/public/logo.png",
-                                "alt_text": "a square logo of a university"
-                            },
-                            "background_color": "#12107c",
-                            "text_color": "#FFFFFF"
-                        })],
+                        display: vec![CredentialDisplay {
+                            name: "University Credential".to_string(),
+                            locale: Some("en-US".to_string()),
+                            logo: Some(Logo {
+                                uri: "https://university.example.edu/public/logo.png".parse().unwrap(),
+                                alt_text: Some("a square logo of a university".to_string())
+                            }),
+                            description: None,
+                            background_color: Some("#12107c".to_string()),
+                            background_image: None,
+                            text_color: Some("#FFFFFF".to_string())
+                        }],
                     },
                 ),]
                 .into_iter()
@@ -175,4 +299,58 @@ mod tests {
             json_example::<CredentialIssuerMetadata>("tests/examples/credential_issuer_metadata_jwt_vc_json.json")
         );
     }
+
+    #[tokio::test]
+    async fn test_signed_metadata() {
+        let credential_issuer_metadata: CredentialIssuerMetadata = CredentialIssuerMetadata {
+            credential_issuer: "https://credential-issuer.example.com".parse().unwrap(),
+            display: Some(vec![CredentialIssuerDisplay {
+                name: Some("Example University".to_string()),
+                locale: Some("en-US".to_string()),
+                logo: None,
+            }]),
+            ..Default::default()
+        };
+
+        let signed_metadata = credential_issuer_metadata
+            .sign(Arc::new(TestSubject::default()), "did:test", Algorithm::EdDSA)
+            .await
+            .unwrap();
+
+        // The unsigned values are tampered with, the signed values take precedence.
+        let credential_issuer_metadata = CredentialIssuerMetadata {
+            display: Some(vec![CredentialIssuerDisplay {
+                name: Some("Malicious University".to_string()),
+                locale: Some("en-US".to_string()),
+                logo: None,
+            }]),
+            signed_metadata: Some(signed_metadata.clone()),
+            ..credential_issuer_metadata
+        };
+
+        let verified_metadata = credential_issuer_metadata
+            .clone()
+            .verify_signed_metadata(Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .unwrap();
+        assert_eq!(
+            verified_metadata.display,
+            Some(vec![CredentialIssuerDisplay {
+                name: Some("Example University".to_string()),
+                locale: Some("en-US".to_string()),
+                logo: None,
+            }])
+        );
+        assert_eq!(verified_metadata.signed_metadata, Some(signed_metadata.clone()));
+
+        // Signed metadata of a different Credential Issuer is rejected.
+        let credential_issuer_metadata = CredentialIssuerMetadata {
+            credential_issuer: "https://malicious-issuer.example.com".parse().unwrap(),
+            ..credential_issuer_metadata
+        };
+        assert!(credential_issuer_metadata
+            .verify_signed_metadata(Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .is_err());
+    }
 }
@@ -0,0 +1,908 @@
+use super::credential_issuer_metadata::CredentialIssuerMetadata;
+use crate::{
+    credential::{VerifiableCredentialV2, CREDENTIALS_V2_CONTEXT},
+    credential_format_profiles::{
+        iso_mdl::mdoc::{cose_key_from_jwk, IssuerSigned, ValidityInfo},
+        w3c_verifiable_credentials::ldp_vc::CredentialDefinition as LdpVcCredentialDefinition,
+        CredentialFormatCollection, CredentialFormats, WithParameters,
+    },
+    VerifiableCredentialJwt,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use jsonwebtoken::{jwk::Jwk, Algorithm, Header};
+use oid4vc_core::{
+    authentication::subject::SigningSubject,
+    data_integrity::{Cryptosuite, DataIntegrityProof, ProofOptions},
+    jwt,
+    sd_jwt::{SdJwt, SdObjectEncoder},
+    status_list::CredentialStatus,
+};
+use serde_json::json;
+use std::str::FromStr;
+
+/// The key material of the Holder to which a Credential is bound, as derived from the Key Proof of a Credential Request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HolderBinding {
+    /// The Holder is identified by a DID, which is included as the `credentialSubject.id` of the Credential.
+    Did(String),
+    /// The Holder is identified by a public key, which is included in the `cnf` claim of the Credential.
+    Jwk(Box<Jwk>),
+}
+
+/// The claims that are common to the Credentials of all formats.
+struct CommonClaims<'a> {
+    issuer: String,
+    issuance_date: DateTime<Utc>,
+    expiration_date: Option<DateTime<Utc>>,
+    status: Option<&'a CredentialStatus>,
+}
+
+/// The version of the W3C Verifiable Credentials Data Model according to which `jwt_vc_json` Credentials are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DataModel {
+    /// The credential is wrapped in the `vc` claim as described here:
+    /// https://www.w3.org/TR/vc-data-model/#jwt-encoding
+    #[default]
+    Vcdm11,
+    /// The credential is the payload of a `vc+jwt` as described here:
+    /// https://www.w3.org/TR/vc-jose-cose/#securing-vcs-with-jose
+    Vcdm20,
+}
+
+/// Assembles Credentials from the claims about a Holder and signs them on behalf of the Credential Issuer. Supported
+/// formats are `jwt_vc_json`, `ldp_vc`, `vc+sd-jwt` and `mso_mdoc`.
+#[derive(Clone)]
+pub struct CredentialSigner {
+    pub subject: SigningSubject,
+    pub subject_syntax_type: String,
+    /// The period of time after issuance in which the Credential is valid, 365 days by default. When `None` the
+    /// Credential does not expire, except for `mso_mdoc` Credentials, which are always valid for a limited period.
+    pub expires_in: Option<Duration>,
+    /// The number of decoy digests that are added to `vc+sd-jwt` Credentials.
+    pub decoy_digests: usize,
+    /// The data model according to which `jwt_vc_json` Credentials are encoded.
+    pub data_model: DataModel,
+}
+
+impl CredentialSigner {
+    pub fn new(subject: SigningSubject, subject_syntax_type: impl Into<String>) -> Self {
+        Self {
+            subject,
+            subject_syntax_type: subject_syntax_type.into(),
+            expires_in: Some(Duration::days(365)),
+            decoy_digests: 3,
+            data_model: DataModel::default(),
+        }
+    }
+
+    /// Returns the signed Credential for the `credential_configuration_id`, containing the claims of the
+    /// `credential_subject` and bound to the Holder's key material.
+    pub async fn sign<CFC: CredentialFormatCollection>(
+        &self,
+        credential_issuer_metadata: &CredentialIssuerMetadata<CFC>,
+        credential_configuration_id: &str,
+        credential_subject: serde_json::Value,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        self.sign_with_status(
+            credential_issuer_metadata,
+            credential_configuration_id,
+            credential_subject,
+            holder_binding,
+            None,
+        )
+        .await
+    }
+
+    /// Returns the signed Credential like [`CredentialSigner::sign`], including the references to the `status` of the
+    /// Credential in the status lists of the Credential Issuer. SD-JWT VCs contain the `status` claim and W3C Verifiable
+    /// Credentials the `credentialStatus` entries. `ldp_vc` Credentials only contain the entries when they use the VCDM
+    /// 2.0 context, which defines the terms that are secured by the proof. `mso_mdoc` Credentials do not contain any
+    /// status.
+    pub async fn sign_with_status<CFC: CredentialFormatCollection>(
+        &self,
+        credential_issuer_metadata: &CredentialIssuerMetadata<CFC>,
+        credential_configuration_id: &str,
+        credential_subject: serde_json::Value,
+        holder_binding: &HolderBinding,
+        status: Option<&CredentialStatus>,
+    ) -> Result<serde_json::Value> {
+        let credential_configuration = credential_issuer_metadata
+            .credential_configurations_supported
+            .get(credential_configuration_id)
+            .ok_or(anyhow!(
+                "Unknown credential configuration: {credential_configuration_id}"
+            ))?;
+
+        // For `ldp_vc` the supported values are cryptosuites instead of JWS algorithms.
+        let algorithm = credential_configuration
+            .credential_signing_alg_values_supported
+            .iter()
+            .find_map(|algorithm| {
+                Algorithm::from_str(algorithm).ok().or_else(|| {
+                    serde_json::from_value::<Cryptosuite>(json!(algorithm))
+                        .ok()
+                        .map(|cryptosuite| cryptosuite.algorithm())
+                })
+            })
+            .ok_or(anyhow!("No supported credential signing algorithm found."))?;
+
+        let credential_subject = match credential_subject {
+            serde_json::Value::Object(credential_subject) => credential_subject,
+            _ => return Err(anyhow!("The credential subject must be a JSON object.")),
+        };
+
+        // The issuer is identified by the DID of the key with which the Credential is secured, so that Verifiers can
+        // check that the key belongs to the issuer.
+        let issuance_date = Utc::now();
+        let common_claims = CommonClaims {
+            issuer: self.subject.identifier(&self.subject_syntax_type, algorithm).await?,
+            issuance_date,
+            expiration_date: self.expires_in.map(|expires_in| issuance_date + expires_in),
+            status,
+        };
+
+        match serde_json::from_value::<CredentialFormats<WithParameters>>(serde_json::to_value(
+            &credential_configuration.credential_format,
+        )?)? {
+            CredentialFormats::JwtVcJson(parameters) => {
+                self.sign_jwt_vc_json(
+                    parameters.parameters.credential_definition.type_,
+                    algorithm,
+                    common_claims,
+                    credential_subject,
+                    holder_binding,
+                )
+                .await
+            }
+            CredentialFormats::VcSdJwt(parameters) => {
+                self.sign_vc_sd_jwt(
+                    parameters.parameters.vct,
+                    algorithm,
+                    common_claims,
+                    credential_subject,
+                    holder_binding,
+                )
+                .await
+            }
+            CredentialFormats::LdpVc(parameters) => {
+                self.sign_ldp_vc(
+                    parameters.parameters.credential_definition,
+                    algorithm,
+                    common_claims,
+                    credential_subject,
+                    holder_binding,
+                )
+                .await
+            }
+            CredentialFormats::MsoMdoc(parameters) => {
+                self.sign_mso_mdoc(
+                    parameters.parameters.doctype,
+                    algorithm,
+                    common_claims,
+                    credential_subject,
+                    holder_binding,
+                )
+                .await
+            }
+            _ => Err(anyhow!(
+                "The credential configuration is not a `jwt_vc_json`, `ldp_vc`, `vc+sd-jwt` or `mso_mdoc` configuration."
+            )),
+        }
+    }
+
+    async fn sign_jwt_vc_json(
+        &self,
+        type_: Vec<String>,
+        algorithm: Algorithm,
+        CommonClaims {
+            issuer,
+            issuance_date,
+            expiration_date,
+            status,
+        }: CommonClaims<'_>,
+        mut credential_subject: serde_json::Map<String, serde_json::Value>,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        if self.data_model == DataModel::Vcdm20 {
+            return self
+                .sign_vc_jwt(
+                    type_,
+                    algorithm,
+                    CommonClaims {
+                        issuer,
+                        issuance_date,
+                        expiration_date,
+                        status,
+                    },
+                    credential_subject,
+                    holder_binding,
+                )
+                .await;
+        }
+
+        let verifiable_credential_jwt = VerifiableCredentialJwt::builder()
+            .iss(issuer.clone())
+            .iat(issuance_date.timestamp())
+            .nbf(issuance_date.timestamp());
+        let verifiable_credential_jwt = match expiration_date {
+            Some(expiration_date) => verifiable_credential_jwt.exp(expiration_date.timestamp()),
+            None => verifiable_credential_jwt,
+        };
+        let verifiable_credential_jwt = match holder_binding {
+            HolderBinding::Did(did) => {
+                credential_subject.insert("id".to_string(), json!(did));
+                verifiable_credential_jwt.sub(did.clone())
+            }
+            HolderBinding::Jwk(jwk) => verifiable_credential_jwt.cnf(json!({ "jwk": jwk })),
+        };
+
+        let mut verifiable_credential = json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": type_,
+            "issuer": issuer,
+            "issuanceDate": issuance_date.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "credentialSubject": credential_subject,
+        });
+        if let Some(expiration_date) = expiration_date {
+            verifiable_credential["expirationDate"] = json!(expiration_date.to_rfc3339_opts(SecondsFormat::Secs, true));
+        }
+        if let Some(status) = status {
+            verifiable_credential["credentialStatus"] = json!(status.credential_status);
+        }
+
+        let credential = jwt::encode(
+            self.subject.clone(),
+            VerifiableCredentialJwt::header(algorithm),
+            verifiable_credential_jwt
+                .verifiable_credential(verifiable_credential)
+                .build()?,
+            &self.subject_syntax_type,
+        )
+        .await?;
+
+        Ok(json!(credential))
+    }
+
+    /// Returns a VCDM 2.0 Verifiable Credential secured with JOSE as described here:
+    /// https://www.w3.org/TR/vc-jose-cose/#securing-vcs-with-jose
+    async fn sign_vc_jwt(
+        &self,
+        type_: Vec<String>,
+        algorithm: Algorithm,
+        CommonClaims {
+            issuer,
+            issuance_date: valid_from,
+            expiration_date: valid_until,
+            status,
+        }: CommonClaims<'_>,
+        mut credential_subject: serde_json::Map<String, serde_json::Value>,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        let cnf = match holder_binding {
+            HolderBinding::Did(did) => {
+                credential_subject.insert("id".to_string(), json!(did));
+                None
+            }
+            HolderBinding::Jwk(jwk) => Some(json!({ "jwk": jwk })),
+        };
+
+        let verifiable_credential = VerifiableCredentialV2 {
+            context: vec![json!(CREDENTIALS_V2_CONTEXT)],
+            id: None,
+            type_,
+            issuer: json!(issuer),
+            valid_from: Some(valid_from),
+            valid_until,
+            credential_subject: serde_json::Value::Object(credential_subject),
+            credential_status: status.map(|status| json!(status.credential_status)),
+            cnf,
+            other: Default::default(),
+        };
+
+        let credential = jwt::encode(
+            self.subject.clone(),
+            VerifiableCredentialV2::header(algorithm),
+            verifiable_credential,
+            &self.subject_syntax_type,
+        )
+        .await?;
+
+        Ok(json!(credential))
+    }
+
+    /// Returns a Verifiable Credential secured with a Data Integrity proof as described here:
+    /// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#appendix-A.1.2
+    async fn sign_ldp_vc(
+        &self,
+        credential_definition: LdpVcCredentialDefinition,
+        algorithm: Algorithm,
+        CommonClaims {
+            issuer,
+            issuance_date,
+            expiration_date,
+            status,
+        }: CommonClaims<'_>,
+        mut credential_subject: serde_json::Map<String, serde_json::Value>,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        match holder_binding {
+            HolderBinding::Did(did) => credential_subject.insert("id".to_string(), json!(did)),
+            HolderBinding::Jwk(_) => {
+                return Err(anyhow!(
+                    "`ldp_vc` Credentials can only be bound to a DID, which requires a `kid` in the proof."
+                ))
+            }
+        };
+
+        let mut verifiable_credential = serde_json::Map::new();
+        verifiable_credential.insert("@context".to_string(), json!(credential_definition.context));
+        verifiable_credential.insert("type".to_string(), json!(credential_definition.type_));
+        verifiable_credential.insert("issuer".to_string(), json!(issuer));
+        verifiable_credential.insert(
+            "issuanceDate".to_string(),
+            json!(issuance_date.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        );
+        if let Some(expiration_date) = expiration_date {
+            verifiable_credential.insert(
+                "expirationDate".to_string(),
+                json!(expiration_date.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            );
+        }
+        verifiable_credential.insert("credentialSubject".to_string(), json!(credential_subject));
+        if let Some(status) = status.filter(|_| {
+            credential_definition
+                .context
+                .iter()
+                .any(|context| context == CREDENTIALS_V2_CONTEXT)
+        }) {
+            verifiable_credential.insert("credentialStatus".to_string(), json!(status.credential_status));
+        }
+
+        let credential = DataIntegrityProof::sign(
+            verifiable_credential,
+            ProofOptions::new(Cryptosuite::try_from(algorithm)?, "assertionMethod"),
+            self.subject.clone(),
+            &self.subject_syntax_type,
+        )
+        .await?;
+
+        Ok(serde_json::Value::Object(credential))
+    }
+
+    /// Returns an SD-JWT VC as described here:
+    /// https://www.ietf.org/archive/id/draft-ietf-oauth-sd-jwt-vc-03.html#name-data-format
+    /// All claims of the `credential_subject` are selectively disclosable.
+    async fn sign_vc_sd_jwt(
+        &self,
+        vct: String,
+        algorithm: Algorithm,
+        CommonClaims {
+            issuer,
+            issuance_date,
+            expiration_date,
+            status,
+        }: CommonClaims<'_>,
+        credential_subject: serde_json::Map<String, serde_json::Value>,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        let claim_names: Vec<String> = credential_subject.keys().cloned().collect();
+
+        let mut claims = credential_subject;
+        claims.insert("iss".to_string(), json!(issuer));
+        claims.insert("iat".to_string(), json!(issuance_date.timestamp()));
+        claims.insert("nbf".to_string(), json!(issuance_date.timestamp()));
+        if let Some(expiration_date) = expiration_date {
+            claims.insert("exp".to_string(), json!(expiration_date.timestamp()));
+        }
+        claims.insert("vct".to_string(), json!(vct));
+        if let Some(status) = status {
+            claims.insert("status".to_string(), json!(status.status));
+        }
+        match holder_binding {
+            HolderBinding::Did(did) => claims.insert("sub".to_string(), json!(did)),
+            HolderBinding::Jwk(jwk) => claims.insert("cnf".to_string(), json!({ "jwk": jwk })),
+        };
+
+        let mut encoder = SdObjectEncoder::new(claims);
+        for claim_name in claim_names {
+            encoder.conceal(&format!("/{}", claim_name.replace('~', "~0").replace('/', "~1")))?;
+        }
+        encoder.add_decoys("", self.decoy_digests)?;
+
+        let mut header = Header::new(algorithm);
+        header.typ = Some("vc+sd-jwt".to_string());
+        let sd_jwt = SdJwt::encode(self.subject.clone(), header, encoder, &self.subject_syntax_type).await?;
+
+        Ok(json!(sd_jwt.to_string()))
+    }
+
+    /// Returns the base64url-encoded `IssuerSigned` structure of an mdoc as described here:
+    /// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#appendix-A.2.4
+    /// The `credential_subject` contains the data elements per namespace, e.g. `{"org.iso.18013.5.1": {"given_name":
+    /// "John"}}`.
+    async fn sign_mso_mdoc(
+        &self,
+        doctype: String,
+        algorithm: Algorithm,
+        CommonClaims {
+            issuance_date,
+            expiration_date,
+            ..
+        }: CommonClaims<'_>,
+        credential_subject: serde_json::Map<String, serde_json::Value>,
+        holder_binding: &HolderBinding,
+    ) -> Result<serde_json::Value> {
+        let device_key = match holder_binding {
+            HolderBinding::Jwk(jwk) => cose_key_from_jwk(jwk)?,
+            HolderBinding::Did(_) => {
+                return Err(anyhow!(
+                    "`mso_mdoc` Credentials can only be bound to a public key, which requires a `jwk` in the proof."
+                ))
+            }
+        };
+
+        let issuer_signed = IssuerSigned::sign(
+            self.subject.clone(),
+            &self.subject_syntax_type,
+            algorithm,
+            doctype,
+            &serde_json::Value::Object(credential_subject),
+            device_key,
+            ValidityInfo {
+                signed: issuance_date,
+                valid_from: issuance_date,
+                valid_until: expiration_date.unwrap_or(issuance_date + Duration::days(365)),
+            },
+        )
+        .await?;
+
+        Ok(json!(issuer_signed.to_base64url()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        credential::JwtCredential,
+        credential_issuer::{
+            credential_configurations_supported::CredentialConfigurationsSupportedObject,
+            status_list_manager::StatusListManager,
+        },
+    };
+    use oid4vc_core::{
+        test_utils::{MockVerifier, TestSubject},
+        Validator,
+    };
+    use std::sync::Arc;
+
+    fn credential_issuer_metadata() -> CredentialIssuerMetadata {
+        CredentialIssuerMetadata {
+            credential_issuer: "https://credential-issuer.example.com".parse().unwrap(),
+            credential_configurations_supported: vec![
+                (
+                    "UniversityDegree_JWT".to_string(),
+                    serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+                        "format": "jwt_vc_json",
+                        "credential_signing_alg_values_supported": ["EdDSA"],
+                        "credential_definition": {
+                            "type": ["VerifiableCredential", "UniversityDegreeCredential"]
+                        }
+                    }))
+                    .unwrap(),
+                ),
+                (
+                    "Identity_SD_JWT".to_string(),
+                    serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+                        "format": "vc+sd-jwt",
+                        "credential_signing_alg_values_supported": ["EdDSA"],
+                        "vct": "IdentityCredential"
+                    }))
+                    .unwrap(),
+                ),
+                (
+                    "UniversityDegree_LDP_VC".to_string(),
+                    serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+                        "format": "ldp_vc",
+                        "credential_signing_alg_values_supported": ["eddsa-rdfc-2022"],
+                        "credential_definition": {
+                            "@context": [
+                                "https://www.w3.org/2018/credentials/v1",
+                                "https://www.w3.org/2018/credentials/examples/v1"
+                            ],
+                            "type": ["VerifiableCredential", "UniversityDegreeCredential"]
+                        }
+                    }))
+                    .unwrap(),
+                ),
+                (
+                    "mDL".to_string(),
+                    serde_json::from_value::<CredentialConfigurationsSupportedObject>(json!({
+                        "format": "mso_mdoc",
+                        "credential_signing_alg_values_supported": ["EdDSA"],
+                        "doctype": "org.iso.18013.5.1.mDL"
+                    }))
+                    .unwrap(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign() {
+        let mut signer = CredentialSigner::new(Arc::new(TestSubject::default()), "did:test");
+        signer.expires_in = Some(Duration::days(365));
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "UniversityDegree_JWT",
+                json!({
+                    "given_name": "Ferris",
+                    "family_name": "Crabman"
+                }),
+                &HolderBinding::Did("did:example:holder".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let verifiable_credential_jwt: VerifiableCredentialJwt = Validator::Verifier(Arc::new(MockVerifier::new()))
+            .decode(credential.as_str().unwrap().to_string())
+            .await
+            .unwrap();
+
+        let issuance_date = verifiable_credential_jwt.verifiable_credential["issuanceDate"].clone();
+        let expiration_date = verifiable_credential_jwt.verifiable_credential["expirationDate"].clone();
+        assert!(expiration_date.is_string());
+
+        assert_eq!(
+            verifiable_credential_jwt.rfc7519_claims.iss,
+            Some("did:test:123".to_string())
+        );
+        assert_eq!(
+            verifiable_credential_jwt.rfc7519_claims.sub,
+            Some("did:example:holder".to_string())
+        );
+        assert_eq!(verifiable_credential_jwt.cnf, None);
+        assert_eq!(
+            verifiable_credential_jwt.verifiable_credential,
+            json!({
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiableCredential", "UniversityDegreeCredential"],
+                "issuer": "did:test:123",
+                "issuanceDate": issuance_date,
+                "expirationDate": expiration_date,
+                "credentialSubject": {
+                    "id": "did:example:holder",
+                    "given_name": "Ferris",
+                    "family_name": "Crabman"
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_jwk_holder_binding() {
+        let signer = CredentialSigner::new(Arc::new(TestSubject::default()), "did:test");
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+            "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+        }))
+        .unwrap();
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "UniversityDegree_JWT",
+                json!({ "given_name": "Ferris" }),
+                &HolderBinding::Jwk(Box::new(jwk.clone())),
+            )
+            .await
+            .unwrap();
+
+        let verifiable_credential_jwt: VerifiableCredentialJwt = Validator::Verifier(Arc::new(MockVerifier::new()))
+            .decode(credential.as_str().unwrap().to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(verifiable_credential_jwt.rfc7519_claims.sub, None);
+        assert_eq!(verifiable_credential_jwt.cnf, Some(json!({ "jwk": jwk })));
+        assert_eq!(
+            verifiable_credential_jwt.verifiable_credential["credentialSubject"],
+            json!({ "given_name": "Ferris" })
+        );
+
+        // Unknown credential configurations are rejected.
+        assert!(signer
+            .sign(
+                &credential_issuer_metadata(),
+                "DriverLicense_JWT",
+                json!({}),
+                &HolderBinding::Jwk(Box::new(jwk))
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_vc_jwt() {
+        let mut signer = CredentialSigner::new(
+            Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap()),
+            "did:test",
+        );
+        signer.expires_in = Some(Duration::days(365));
+        signer.data_model = DataModel::Vcdm20;
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "UniversityDegree_JWT",
+                json!({ "given_name": "Ferris" }),
+                &HolderBinding::Did("did:example:holder".to_string()),
+            )
+            .await
+            .unwrap();
+        let credential = credential.as_str().unwrap();
+
+        let header = jsonwebtoken::decode_header(credential).unwrap();
+        assert_eq!(header.typ, Some("vc+jwt".to_string()));
+        assert_eq!(header.cty, Some("vc".to_string()));
+
+        let verifiable_credential =
+            match JwtCredential::decode(&Validator::Verifier(Arc::new(MockVerifier::new())), credential)
+                .await
+                .unwrap()
+            {
+                JwtCredential::Vcdm20(verifiable_credential) => verifiable_credential,
+                JwtCredential::Vcdm11(_) => panic!("Expected a VCDM 2.0 credential."),
+            };
+
+        assert_eq!(verifiable_credential.context, vec![json!(CREDENTIALS_V2_CONTEXT)]);
+        assert_eq!(
+            verifiable_credential.type_,
+            vec!["VerifiableCredential", "UniversityDegreeCredential"]
+        );
+        assert_eq!(verifiable_credential.issuer, json!("did:test:123"));
+        assert_eq!(
+            verifiable_credential.valid_until.unwrap() - verifiable_credential.valid_from.unwrap(),
+            Duration::days(365)
+        );
+        assert_eq!(
+            verifiable_credential.credential_subject,
+            json!({
+                "id": "did:example:holder",
+                "given_name": "Ferris"
+            })
+        );
+        assert_eq!(verifiable_credential.cnf, None);
+    }
+
+    #[tokio::test]
+    async fn test_sign_vc_sd_jwt() {
+        let signer = CredentialSigner::new(Arc::new(TestSubject::default()), "did:test");
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+            "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+        }))
+        .unwrap();
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "Identity_SD_JWT",
+                json!({
+                    "given_name": "Ferris",
+                    "family_name": "Crabman"
+                }),
+                &HolderBinding::Jwk(Box::new(jwk.clone())),
+            )
+            .await
+            .unwrap();
+
+        let sd_jwt: SdJwt = credential.as_str().unwrap().parse().unwrap();
+        assert_eq!(
+            jsonwebtoken::decode_header(&sd_jwt.jwt).unwrap().typ,
+            Some("vc+sd-jwt".to_string())
+        );
+        assert_eq!(sd_jwt.disclosures.len(), 2);
+
+        // The claims are only included as digests, together with the decoy digests.
+        let payload: serde_json::Value = Validator::Verifier(Arc::new(MockVerifier::new()))
+            .decode(sd_jwt.jwt.clone())
+            .await
+            .unwrap();
+        assert_eq!(payload["_sd"].as_array().unwrap().len(), 5);
+        assert!(payload.get("given_name").is_none());
+
+        let claims = sd_jwt
+            .decode(&Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .unwrap();
+        assert_eq!(claims["iss"], "did:test:123");
+        assert_eq!(claims["vct"], "IdentityCredential");
+        assert_eq!(claims["cnf"], json!({ "jwk": jwk }));
+        assert_eq!(claims["given_name"], "Ferris");
+        assert_eq!(claims["family_name"], "Crabman");
+        // By default, the Credential expires after 365 days.
+        assert_eq!(
+            claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap(),
+            Duration::days(365).num_seconds()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_status() {
+        let signer = CredentialSigner::new(Arc::new(TestSubject::default()), "did:test");
+        let mut status_list_manager = StatusListManager::new(
+            "did:test:123",
+            "https://credential-issuer.example.com/status_list".parse().unwrap(),
+            0,
+        )
+        .unwrap();
+        let idx = status_list_manager.allocate().unwrap();
+        let credential_status = status_list_manager.credential_status(idx).unwrap();
+        let validator = Validator::Verifier(Arc::new(MockVerifier::new()));
+
+        // SD-JWT VCs contain the non-selectively disclosable `status` claim.
+        let credential = signer
+            .sign_with_status(
+                &credential_issuer_metadata(),
+                "Identity_SD_JWT",
+                json!({ "given_name": "Ferris" }),
+                &HolderBinding::Did("did:example:holder".to_string()),
+                Some(&credential_status),
+            )
+            .await
+            .unwrap();
+        let sd_jwt: SdJwt = credential.as_str().unwrap().parse().unwrap();
+        assert_eq!(sd_jwt.disclosures.len(), 1);
+        let payload: serde_json::Value = validator.decode(sd_jwt.jwt.clone()).await.unwrap();
+        assert_eq!(
+            payload["status"],
+            json!({
+                "status_list": {
+                    "idx": idx,
+                    "uri": "https://credential-issuer.example.com/status_list"
+                }
+            })
+        );
+
+        // W3C Verifiable Credentials contain the `credentialStatus` entries.
+        let credential = signer
+            .sign_with_status(
+                &credential_issuer_metadata(),
+                "UniversityDegree_JWT",
+                json!({ "given_name": "Ferris" }),
+                &HolderBinding::Did("did:example:holder".to_string()),
+                Some(&credential_status),
+            )
+            .await
+            .unwrap();
+        let verifiable_credential_jwt: VerifiableCredentialJwt = validator
+            .decode(credential.as_str().unwrap().to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            verifiable_credential_jwt.verifiable_credential["credentialStatus"],
+            json!(credential_status.credential_status)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_mso_mdoc() {
+        let signer = CredentialSigner::new(
+            Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap()),
+            "did:test",
+        );
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+            "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+        }))
+        .unwrap();
+        let claims = json!({
+            "org.iso.18013.5.1": {
+                "given_name": "Ferris",
+                "family_name": "Crabman"
+            }
+        });
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "mDL",
+                claims.clone(),
+                &HolderBinding::Jwk(Box::new(jwk.clone())),
+            )
+            .await
+            .unwrap();
+
+        let issuer_signed: IssuerSigned = credential.as_str().unwrap().parse().unwrap();
+        assert_eq!(issuer_signed.claims().unwrap(), claims);
+
+        let mobile_security_object = issuer_signed
+            .verify(&Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .unwrap();
+        assert_eq!(mobile_security_object.doc_type, "org.iso.18013.5.1.mDL");
+        assert_eq!(mobile_security_object.device_key, cose_key_from_jwk(&jwk).unwrap());
+        assert_eq!(
+            mobile_security_object.validity_info.valid_until - mobile_security_object.validity_info.valid_from,
+            Duration::days(365)
+        );
+
+        // mdocs cannot be bound to a DID.
+        assert!(signer
+            .sign(
+                &credential_issuer_metadata(),
+                "mDL",
+                claims,
+                &HolderBinding::Did("did:example:holder".to_string())
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_ldp_vc() {
+        let signer = CredentialSigner::new(
+            Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap()),
+            "did:test",
+        );
+
+        let credential = signer
+            .sign(
+                &credential_issuer_metadata(),
+                "UniversityDegree_LDP_VC",
+                json!({
+                    "degree": {
+                        "type": "BachelorDegree",
+                        "name": "Bachelor of Science and Arts"
+                    }
+                }),
+                &HolderBinding::Did("did:example:holder".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let credential = credential.as_object().unwrap();
+        assert_eq!(credential["issuer"], "did:test:123");
+        assert_eq!(credential["credentialSubject"]["id"], "did:example:holder");
+        assert_eq!(credential["proof"]["cryptosuite"], "eddsa-rdfc-2022");
+        assert_eq!(credential["proof"]["proofPurpose"], "assertionMethod");
+        assert_eq!(credential["proof"]["verificationMethod"], "did:test:123#key-1");
+
+        let proof = DataIntegrityProof::verify(credential, &Validator::Verifier(Arc::new(MockVerifier::new())))
+            .await
+            .unwrap();
+        assert_eq!(proof.cryptosuite, Cryptosuite::EddsaRdfc2022);
+
+        // `ldp_vc` Credentials cannot be bound to a JWK.
+        assert!(signer
+            .sign(
+                &credential_issuer_metadata(),
+                "UniversityDegree_LDP_VC",
+                json!({}),
+                &HolderBinding::Jwk(Box::new(
+                    serde_json::from_value(json!({
+                        "kty": "OKP",
+                        "crv": "Ed25519",
+                        "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+                    }))
+                    .unwrap()
+                ))
+            )
+            .await
+            .is_err());
+    }
+}
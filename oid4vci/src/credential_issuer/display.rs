@@ -0,0 +1,190 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// Display properties of a Credential Issuer as described here:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#section-11.2.3-2.12
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct CredentialIssuerDisplay {
+    pub name: Option<String>,
+    pub locale: Option<String>,
+    pub logo: Option<Logo>,
+}
+
+/// Display properties of a supported Credential as described here:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#section-11.2.3-2.11.2.5
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct CredentialDisplay {
+    pub name: String,
+    pub locale: Option<String>,
+    pub logo: Option<Logo>,
+    pub description: Option<String>,
+    pub background_color: Option<String>,
+    pub background_image: Option<BackgroundImage>,
+    pub text_color: Option<String>,
+}
+
+/// Display properties of a Credential claim as described here:
+/// https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#appendix-A.1.1.2-3.1.2.2.1
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClaimDisplay {
+    pub name: Option<String>,
+    pub locale: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Logo {
+    // Earlier drafts of the specification used `url` instead of `uri`.
+    #[serde(alias = "url")]
+    pub uri: Url,
+    pub alt_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundImage {
+    pub uri: Url,
+}
+
+/// Display objects that can be selected based on their `locale`.
+pub trait Localized {
+    fn locale(&self) -> Option<&str>;
+}
+
+impl Localized for CredentialIssuerDisplay {
+    fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+}
+
+impl Localized for CredentialDisplay {
+    fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+}
+
+impl Localized for ClaimDisplay {
+    fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+}
+
+/// Picks the display object that best matches the `preferred_locales`, which are ordered from most to least preferred
+/// (e.g. `["nl-NL", "en"]`). Locales are language tags as defined in [RFC 5646](https://www.rfc-editor.org/rfc/rfc5646)
+/// and are compared case-insensitively. For each preferred locale an exact match is preferred over a match on the
+/// primary language subtag only. If none of the preferred locales match, the display object without a `locale` is
+/// returned, or otherwise the first display object.
+pub fn resolve_display<'a, T: Localized>(display: &'a [T], preferred_locales: &[&str]) -> Option<&'a T> {
+    let primary_language = |locale: &str| locale.split('-').next().unwrap_or_default().to_lowercase();
+
+    preferred_locales
+        .iter()
+        .find_map(|preferred_locale| {
+            display
+                .iter()
+                .find(|display| {
+                    display
+                        .locale()
+                        .is_some_and(|locale| locale.eq_ignore_ascii_case(preferred_locale))
+                })
+                .or_else(|| {
+                    display.iter().find(|display| {
+                        display
+                            .locale()
+                            .is_some_and(|locale| primary_language(locale) == primary_language(preferred_locale))
+                    })
+                })
+        })
+        .or_else(|| display.iter().find(|display| display.locale().is_none()))
+        .or_else(|| display.first())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_credential_display_serde() {
+        let credential_display: CredentialDisplay = serde_json::from_value(json!({
+            "name": "University Credential",
+            "locale": "en-US",
+            "logo": {
+                "url": "https://university.example.edu/public/logo.png",
+                "alt_text": "a square logo of a university"
+            },
+            "description": "Proof of a university degree",
+            "background_color": "#12107c",
+            "background_image": {
+                "uri": "https://university.example.edu/public/background.png"
+            },
+            "text_color": "#FFFFFF"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            credential_display,
+            CredentialDisplay {
+                name: "University Credential".to_string(),
+                locale: Some("en-US".to_string()),
+                logo: Some(Logo {
+                    uri: "https://university.example.edu/public/logo.png".parse().unwrap(),
+                    alt_text: Some("a square logo of a university".to_string()),
+                }),
+                description: Some("Proof of a university degree".to_string()),
+                background_color: Some("#12107c".to_string()),
+                background_image: Some(BackgroundImage {
+                    uri: "https://university.example.edu/public/background.png".parse().unwrap(),
+                }),
+                text_color: Some("#FFFFFF".to_string()),
+            }
+        );
+
+        // The `logo` is serialized using the `uri` parameter.
+        assert_eq!(
+            serde_json::to_value(credential_display).unwrap()["logo"],
+            json!({
+                "uri": "https://university.example.edu/public/logo.png",
+                "alt_text": "a square logo of a university"
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolve_display() {
+        let display: Vec<ClaimDisplay> = serde_json::from_value(json!([
+            { "name": "Given Name", "locale": "en-US" },
+            { "name": "Prénom", "locale": "fr-FR" },
+            { "name": "名前", "locale": "ja-JP" },
+            { "name": "Given Name" }
+        ]))
+        .unwrap();
+        let name =
+            |preferred_locales: &[&str]| resolve_display(&display, preferred_locales).and_then(|d| d.name.clone());
+
+        assert_eq!(name(&["fr-FR"]), Some("Prénom".to_string()));
+        assert_eq!(name(&["ja-jp"]), Some("名前".to_string()));
+        // Falls back on the primary language subtag.
+        assert_eq!(name(&["fr-CA"]), Some("Prénom".to_string()));
+        assert_eq!(name(&["fr"]), Some("Prénom".to_string()));
+        // The preferred locales are tried in order.
+        assert_eq!(name(&["de-DE", "ja-JP", "en-US"]), Some("名前".to_string()));
+        // Falls back on the display object without a locale.
+        assert_eq!(
+            resolve_display(&display, &["de-DE"]),
+            Some(&ClaimDisplay {
+                name: Some("Given Name".to_string()),
+                locale: None
+            })
+        );
+        // Falls back on the first display object.
+        assert_eq!(
+            resolve_display(&display[..2], &["de-DE"]).and_then(|d| d.locale.as_deref()),
+            Some("en-US")
+        );
+        assert_eq!(resolve_display::<ClaimDisplay>(&[], &["en-US"]), None);
+    }
+}
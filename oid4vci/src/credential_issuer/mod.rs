@@ -1,12 +1,19 @@
 pub mod authorization_server_metadata;
 pub mod credential_configurations_supported;
 pub mod credential_issuer_metadata;
+pub mod credential_signer;
+pub mod display;
+pub mod status_list_manager;
 
 use self::{
     authorization_server_metadata::AuthorizationServerMetadata, credential_issuer_metadata::CredentialIssuerMetadata,
 };
-use crate::{credential_format_profiles::CredentialFormatCollection, proof::ProofOfPossession, KeyProofType};
-use oid4vc_core::{authentication::subject::SigningSubject, Validator};
+use crate::{
+    credential_format_profiles::CredentialFormatCollection,
+    credential_request::CredentialResponseEncryptionSpecification, proof::ProofOfPossession, KeyProofType,
+};
+use anyhow::anyhow;
+use oid4vc_core::{authentication::subject::SigningSubject, jwt, Validator};
 
 #[derive(Clone)]
 pub struct CredentialIssuer<CFC>
@@ -19,10 +26,47 @@ where
 }
 
 impl<CFC: CredentialFormatCollection> CredentialIssuer<CFC> {
+    /// Validates the Key Proof. A proof with a `kid` is verified with the key that the `validator` resolves, a proof
+    /// without a `kid` with the `jwk` in its header.
     pub async fn validate_proof(&self, proof: KeyProofType, validator: Validator) -> anyhow::Result<ProofOfPossession> {
         match proof {
-            KeyProofType::Jwt { jwt, .. } => validator.decode(jwt).await,
+            KeyProofType::Jwt { jwt, .. } => {
+                let header = jsonwebtoken::decode_header(&jwt)?;
+                match (header.kid, header.jwk) {
+                    (None, Some(jwk)) => jwt::decode(&jwt, jwt::public_key_from_jwk(&jwk)?, header.alg),
+                    _ => validator.decode(jwt).await,
+                }
+            }
             KeyProofType::Cwt { .. } => unimplemented!("CWT is not supported yet"),
         }
     }
+
+    /// Validates the `credential_response_encryption` parameters of a Credential Request against the
+    /// `credential_response_encryption` metadata of the Credential Issuer.
+    pub fn validate_credential_response_encryption(
+        &self,
+        credential_response_encryption: Option<&CredentialResponseEncryptionSpecification>,
+    ) -> anyhow::Result<()> {
+        match (
+            &self.metadata.credential_response_encryption,
+            credential_response_encryption,
+        ) {
+            (Some(metadata), None) if metadata.encryption_required => Err(anyhow!(
+                "The Credential Issuer requires the Credential Response to be encrypted."
+            )),
+            (_, None) => Ok(()),
+            (None, Some(_)) => Err(anyhow!(
+                "The Credential Issuer does not support encryption of the Credential Response."
+            )),
+            (Some(metadata), Some(specification)) => {
+                if !metadata.alg_values_supported.contains(&specification.alg.to_string()) {
+                    Err(anyhow!("Unsupported `alg` value: {}", specification.alg))
+                } else if !metadata.enc_values_supported.contains(&specification.enc.to_string()) {
+                    Err(anyhow!("Unsupported `enc` value: {}", specification.enc))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
 }
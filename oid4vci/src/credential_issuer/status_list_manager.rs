@@ -0,0 +1,288 @@
+use crate::credential::{VerifiableCredentialV2, CREDENTIALS_V2_CONTEXT};
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use jsonwebtoken::Algorithm;
+use oid4vc_core::{
+    authentication::subject::SigningSubject,
+    jwt,
+    status_list::{
+        Bitstring, BitstringStatusListEntry, CredentialStatus, Status, StatusList, StatusListReference,
+        StatusListToken, StatusPurpose, StatusType, MIN_BITSTRING_LENGTH,
+    },
+    RFC7519Claims,
+};
+use rand::Rng;
+use reqwest::Url;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// Keeps track of the status of the Credentials that are issued by a Credential Issuer. Each Credential is allocated
+/// an index, at which its status is published both in a Token Status List and in the Bitstring Status Lists for
+/// revocation and suspension.
+#[derive(Debug, Clone)]
+pub struct StatusListManager {
+    /// The identifier of the Credential Issuer, which is the `iss` of the published status lists.
+    pub issuer: String,
+    /// The URL at which the Status List Token is published. The Bitstring Status List Credentials are published at
+    /// `{uri}/revocation` and `{uri}/suspension`.
+    pub uri: Url,
+    /// The period of time for which a published status list is valid and may be cached.
+    pub ttl: Duration,
+    status_list: StatusList,
+    revocation_list: Bitstring,
+    suspension_list: Bitstring,
+    allocated: BTreeSet<usize>,
+}
+
+impl StatusListManager {
+    /// Returns a manager for status lists with room for `size` Credentials. The `size` is rounded up to the minimum
+    /// length of a Bitstring Status List to provide group privacy.
+    pub fn new(issuer: impl Into<String>, uri: Url, size: usize) -> Result<Self> {
+        let size = size.max(MIN_BITSTRING_LENGTH);
+        Ok(Self {
+            issuer: issuer.into(),
+            uri,
+            ttl: Duration::hours(1),
+            status_list: StatusList::new(2, size)?,
+            revocation_list: Bitstring::new(size),
+            suspension_list: Bitstring::new(size),
+            allocated: BTreeSet::new(),
+        })
+    }
+
+    /// Allocates an unused index at random, so that the index does not reveal the order of issuance.
+    pub fn allocate(&mut self) -> Result<usize> {
+        let size = self.status_list.len();
+        if self.allocated.len() >= size {
+            return Err(anyhow!("The status list is full."));
+        }
+        let mut rng = rand::thread_rng();
+        loop {
+            let idx = rng.gen_range(0..size);
+            if self.allocated.insert(idx) {
+                return Ok(idx);
+            }
+        }
+    }
+
+    /// Releases the index of a Credential that is not issued after all, so that it can be allocated again.
+    pub fn release(&mut self, idx: usize) -> Result<()> {
+        if !self.allocated.remove(&idx) {
+            return Err(anyhow!("The index {idx} is not allocated."));
+        }
+        self.status_list.set(idx, StatusType::Valid)?;
+        self.revocation_list.set(idx, false)?;
+        self.suspension_list.set(idx, false)
+    }
+
+    pub fn status(&self, idx: usize) -> Result<StatusType> {
+        self.status_list.get(idx)
+    }
+
+    /// Changes the status of the Credential at `idx`. A Credential that is revoked cannot be reinstated.
+    pub fn set_status(&mut self, idx: usize, status_type: StatusType) -> Result<()> {
+        if !self.allocated.contains(&idx) {
+            return Err(anyhow!("The index {idx} is not allocated."));
+        }
+        if self.status(idx)? == StatusType::Invalid && status_type != StatusType::Invalid {
+            return Err(anyhow!("The Credential at index {idx} is revoked."));
+        }
+        let (revoked, suspended) = match status_type {
+            StatusType::Valid => (false, false),
+            StatusType::Invalid => (true, false),
+            StatusType::Suspended => (false, true),
+            StatusType::ApplicationSpecific(_) => {
+                return Err(anyhow!("Application specific statuses are not supported."));
+            }
+        };
+        self.status_list.set(idx, status_type)?;
+        self.revocation_list.set(idx, revoked)?;
+        self.suspension_list.set(idx, suspended)
+    }
+
+    /// Returns the URL at which the Bitstring Status List Credential for the `status_purpose` is published.
+    pub fn bitstring_status_list_credential_uri(&self, status_purpose: StatusPurpose) -> Result<Url> {
+        let mut uri = self.uri.clone();
+        uri.path_segments_mut()
+            .map_err(|_| anyhow!("The status list URI cannot be a base."))?
+            .pop_if_empty()
+            .push(&status_purpose.to_string());
+        Ok(uri)
+    }
+
+    /// Returns the references to the status of the Credential at `idx`, which are included in the Credential.
+    pub fn credential_status(&self, idx: usize) -> Result<CredentialStatus> {
+        if !self.allocated.contains(&idx) {
+            return Err(anyhow!("The index {idx} is not allocated."));
+        }
+        Ok(CredentialStatus {
+            status: Status {
+                status_list: StatusListReference {
+                    idx,
+                    uri: self.uri.clone(),
+                },
+            },
+            credential_status: [StatusPurpose::Revocation, StatusPurpose::Suspension]
+                .into_iter()
+                .map(|status_purpose| {
+                    Ok(BitstringStatusListEntry::new(
+                        self.bitstring_status_list_credential_uri(status_purpose)?,
+                        status_purpose,
+                        idx,
+                    ))
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Returns the signed Status List Token as described here:
+    /// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-status-list-token-in-jwt-fo
+    pub async fn status_list_token(
+        &self,
+        subject: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+    ) -> Result<String> {
+        let now = Utc::now();
+        jwt::encode(
+            subject,
+            StatusListToken::header(algorithm),
+            StatusListToken {
+                rfc7519_claims: RFC7519Claims {
+                    iss: Some(self.issuer.clone()),
+                    sub: Some(self.uri.to_string()),
+                    iat: Some(now.timestamp()),
+                    exp: Some((now + self.ttl).timestamp()),
+                    ..Default::default()
+                },
+                ttl: Some(self.ttl.num_seconds() as u64),
+                status_list: self.status_list.clone(),
+            },
+            subject_syntax_type,
+        )
+        .await
+    }
+
+    /// Returns the Bitstring Status List Credential for the `status_purpose`, secured as a `vc+jwt`, as described here:
+    /// https://www.w3.org/TR/vc-bitstring-status-list/#bitstringstatuslistcredential
+    pub async fn bitstring_status_list_credential(
+        &self,
+        status_purpose: StatusPurpose,
+        subject: SigningSubject,
+        subject_syntax_type: &str,
+        algorithm: Algorithm,
+    ) -> Result<String> {
+        let uri = self.bitstring_status_list_credential_uri(status_purpose)?;
+        let bitstring = match status_purpose {
+            StatusPurpose::Revocation => &self.revocation_list,
+            StatusPurpose::Suspension => &self.suspension_list,
+        };
+        let now = Utc::now();
+        jwt::encode(
+            subject,
+            VerifiableCredentialV2::header(algorithm),
+            VerifiableCredentialV2 {
+                context: vec![json!(CREDENTIALS_V2_CONTEXT)],
+                id: Some(uri.to_string()),
+                type_: vec![
+                    "VerifiableCredential".to_string(),
+                    "BitstringStatusListCredential".to_string(),
+                ],
+                issuer: json!(self.issuer),
+                valid_from: Some(now),
+                valid_until: Some(now + self.ttl),
+                credential_subject: json!({
+                    "id": format!("{uri}#list"),
+                    "type": "BitstringStatusList",
+                    "statusPurpose": status_purpose,
+                    "encodedList": bitstring.encode()?,
+                }),
+                credential_status: None,
+                cnf: None,
+                other: Default::default(),
+            },
+            subject_syntax_type,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oid4vc_core::{
+        test_utils::{MockVerifier, TestSubject},
+        Validator,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_status_list_manager() {
+        let mut status_list_manager = StatusListManager::new(
+            "https://credential-issuer.example.com",
+            "https://credential-issuer.example.com/status_list".parse().unwrap(),
+            16,
+        )
+        .unwrap();
+
+        let idx = status_list_manager.allocate().unwrap();
+        let released = status_list_manager.allocate().unwrap();
+        assert_ne!(released, idx);
+        status_list_manager.release(released).unwrap();
+        assert!(status_list_manager.credential_status(released).is_err());
+        assert!(status_list_manager.release(released).is_err());
+        assert_eq!(status_list_manager.status(idx).unwrap(), StatusType::Valid);
+
+        let credential_status = status_list_manager.credential_status(idx).unwrap();
+        assert_eq!(credential_status.status.status_list.idx, idx);
+        assert_eq!(
+            credential_status
+                .credential_status
+                .iter()
+                .map(|entry| entry.status_list_credential.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "https://credential-issuer.example.com/status_list/revocation",
+                "https://credential-issuer.example.com/status_list/suspension"
+            ]
+        );
+
+        // Suspended Credentials can be reinstated, revoked Credentials cannot.
+        status_list_manager.set_status(idx, StatusType::Suspended).unwrap();
+        status_list_manager.set_status(idx, StatusType::Valid).unwrap();
+        status_list_manager.set_status(idx, StatusType::Invalid).unwrap();
+        assert!(status_list_manager.set_status(idx, StatusType::Valid).is_err());
+        let unallocated = (0..).find(|idx| !status_list_manager.allocated.contains(idx)).unwrap();
+        assert!(status_list_manager
+            .set_status(unallocated, StatusType::Invalid)
+            .is_err());
+
+        let subject = Arc::new(TestSubject::default());
+        let validator = Validator::Verifier(Arc::new(MockVerifier::new()));
+
+        let status_list_token = status_list_manager
+            .status_list_token(subject.clone(), "did:test", Algorithm::EdDSA)
+            .await
+            .unwrap();
+        let status_list_token: StatusListToken = validator.decode(status_list_token).await.unwrap();
+        assert_eq!(
+            status_list_token.rfc7519_claims.sub,
+            Some("https://credential-issuer.example.com/status_list".to_string())
+        );
+        assert_eq!(status_list_token.status_list.get(idx).unwrap(), StatusType::Invalid);
+
+        let bitstring_status_list_credential = status_list_manager
+            .bitstring_status_list_credential(StatusPurpose::Revocation, subject, "did:test", Algorithm::EdDSA)
+            .await
+            .unwrap();
+        let bitstring_status_list_credential: VerifiableCredentialV2 =
+            validator.decode(bitstring_status_list_credential).await.unwrap();
+        let bitstring = Bitstring::decode(
+            bitstring_status_list_credential.credential_subject["encodedList"]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(bitstring.get(idx).unwrap());
+    }
+}
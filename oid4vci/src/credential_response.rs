@@ -33,6 +33,27 @@ pub enum CredentialResponseType {
     },
 }
 
+/// Credential Error Response as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-credential-error-response
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CredentialErrorResponse {
+    pub error: CredentialErrorCode,
+    pub error_description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialErrorCode {
+    InvalidCredentialRequest,
+    UnsupportedCredentialType,
+    UnsupportedCredentialFormat,
+    InvalidProof,
+    InvalidEncryptionParameters,
+    /// Deferred Credential Error Response as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-deferred-credential-error-r
+    IssuancePending,
+    InvalidTransactionId,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,11 +7,12 @@ pub mod credential_issuer;
 pub mod credential_offer;
 pub mod credential_request;
 pub mod credential_response;
+pub mod notification_request;
 pub mod proof;
 pub mod token_request;
 pub mod token_response;
 pub mod wallet;
 
-pub use credential::{VerifiableCredentialJwt, VerifiableCredentialJwtBuilder};
+pub use credential::{JwtCredential, VerifiableCredentialJwt, VerifiableCredentialJwtBuilder, VerifiableCredentialV2};
 pub use proof::{KeyProofType, ProofType};
 pub use wallet::Wallet;
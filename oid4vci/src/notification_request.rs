@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// Notification Request as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-notification-request
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct NotificationRequest {
+    pub notification_id: String,
+    pub event: NotificationEvent,
+    pub event_description: Option<String>,
+}
+
+/// Type of the notification event as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#section-10.1-2.2
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// The Credential was successfully stored in the Wallet.
+    CredentialAccepted,
+    /// The Credential could not be stored in the Wallet for any other reason than `credential_deleted`.
+    CredentialFailure,
+    /// The unsuccessful Credential issuance was caused by a user action.
+    CredentialDeleted,
+}
+
+/// Notification Error Response as described here: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0-13.html#name-notification-error-response
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct NotificationErrorResponse {
+    pub error: NotificationErrorCode,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationErrorCode {
+    InvalidNotificationId,
+    InvalidNotificationRequest,
+    /// The Access Token is invalid, which results in an authorization error response as described here:
+    /// https://www.rfc-editor.org/rfc/rfc6750.html#section-3.1
+    InvalidToken,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_notification_request_serde() {
+        let json = json!({
+            "notification_id": "3fwe98js",
+            "event": "credential_failure",
+            "event_description": "Could not store the Credential. Out of storage."
+        });
+
+        let notification_request: NotificationRequest = serde_json::from_value(json.clone()).unwrap();
+
+        // Assert that the json Value is deserialized into the correct type.
+        assert_eq!(
+            notification_request,
+            NotificationRequest {
+                notification_id: "3fwe98js".to_string(),
+                event: NotificationEvent::CredentialFailure,
+                event_description: Some("Could not store the Credential. Out of storage.".to_string()),
+            }
+        );
+
+        // Assert that the `NotificationRequest` can be serialized back into the original json Value.
+        assert_eq!(serde_json::to_value(notification_request).unwrap(), json);
+
+        assert_eq!(
+            serde_json::to_value(NotificationRequest {
+                notification_id: "3fwe98js".to_string(),
+                event: NotificationEvent::CredentialAccepted,
+                event_description: None,
+            })
+            .unwrap(),
+            json!({
+                "notification_id": "3fwe98js",
+                "event": "credential_accepted"
+            })
+        );
+    }
+
+    #[test]
+    fn test_notification_error_response_serde() {
+        assert_eq!(
+            serde_json::from_value::<NotificationErrorResponse>(json!({ "error": "invalid_notification_id" })).unwrap(),
+            NotificationErrorResponse {
+                error: NotificationErrorCode::InvalidNotificationId
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(NotificationErrorResponse {
+                error: NotificationErrorCode::InvalidToken
+            })
+            .unwrap(),
+            json!({ "error": "invalid_token" })
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::credential_issuer::credential_signer::HolderBinding;
 use jsonwebtoken::{Algorithm, Header};
 use oid4vc_core::{builder_fn, jwt, RFC7519Claims, Subject};
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,25 @@ impl KeyProofType {
     pub fn builder() -> KeyProofTypeBuilder {
         KeyProofTypeBuilder::default()
     }
+
+    /// Returns the key material of the Holder to which the Credential should be bound, based on the `kid` or `jwk`
+    /// header parameter of the Key Proof.
+    pub fn holder_binding(&self) -> anyhow::Result<HolderBinding> {
+        match self {
+            KeyProofType::Jwt { jwt } => {
+                let header = jsonwebtoken::decode_header(jwt)?;
+                match (header.kid, header.jwk) {
+                    // A DID URL `kid` refers to a key of the Holder's DID document.
+                    (Some(kid), _) => Ok(HolderBinding::Did(
+                        kid.split('#').next().unwrap_or_default().to_string(),
+                    )),
+                    (None, Some(jwk)) => Ok(HolderBinding::Jwk(Box::new(jwk))),
+                    (None, None) => Err(anyhow::anyhow!("The Key Proof does not contain a `kid` or `jwk`.")),
+                }
+            }
+            KeyProofType::Cwt { .. } => Err(anyhow::anyhow!("CWT is not supported yet")),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
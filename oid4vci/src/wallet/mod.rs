@@ -7,20 +7,27 @@ use crate::credential_issuer::{
     authorization_server_metadata::AuthorizationServerMetadata, credential_issuer_metadata::CredentialIssuerMetadata,
 };
 use crate::credential_offer::CredentialOfferParameters;
-use crate::credential_request::{BatchCredentialRequest, CredentialRequest};
+use crate::credential_request::{
+    BatchCredentialRequest, CredentialRequest, CredentialResponseEncryptionSpecification, DeferredCredentialRequest,
+};
 use crate::credential_response::BatchCredentialResponse;
+use crate::notification_request::NotificationRequest;
 use crate::proof::{KeyProofType, ProofType};
 use crate::{credential_response::CredentialResponse, token_request::TokenRequest, token_response::TokenResponse};
 use anyhow::{anyhow, Result};
 use jsonwebtoken::Algorithm;
 use oid4vc_core::authentication::subject::SigningSubject;
-use oid4vc_core::SubjectSyntaxType;
+use oid4vc_core::jwe::{ContentEncryptionAlgorithm, EncryptionKey, KeyManagementAlgorithm};
+use oid4vc_core::x509::{TrustAnchors, X509Verifier};
+use oid4vc_core::{SubjectSyntaxType, Validator, Verify};
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Url;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::policies::ExponentialBackoff;
 use reqwest_retry::RetryTransientMiddleware;
 use serde::de::DeserializeOwned;
 use std::str::FromStr;
+use std::sync::Arc;
 
 pub struct Wallet<CFC = CredentialFormats<WithParameters>>
 where
@@ -30,6 +37,10 @@ where
     pub supported_subject_syntax_types: Vec<SubjectSyntaxType>,
     pub client: ClientWithMiddleware,
     pub proof_signing_alg_values_supported: Vec<Algorithm>,
+    /// Resolves the keys with which the `signed_metadata` of Credential Issuers is signed by their DID URL.
+    pub did_resolver: Option<Arc<dyn Verify>>,
+    /// The root certificates by which the `x5c` certificate chains of `signed_metadata` must be issued.
+    pub trust_anchors: TrustAnchors,
     phantom: std::marker::PhantomData<CFC>,
 }
 
@@ -55,6 +66,8 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
                 .collect::<Result<_>>()?,
             client,
             proof_signing_alg_values_supported,
+            did_resolver: None,
+            trust_anchors: TrustAnchors::default(),
             phantom: std::marker::PhantomData,
         })
     }
@@ -105,13 +118,51 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
             .push(".well-known")
             .push("openid-credential-issuer");
 
-        self.client
+        let credential_issuer_metadata = self
+            .client
             .get(openid_credential_issuer_endpoint)
             .send()
             .await?
             .json::<CredentialIssuerMetadata<CFC>>()
             .await
-            .map_err(|_| anyhow::anyhow!("Failed to get credential issuer metadata"))
+            .map_err(|_| anyhow::anyhow!("Failed to get credential issuer metadata"))?;
+
+        let validator = match &credential_issuer_metadata.signed_metadata {
+            Some(signed_metadata) => self.signed_metadata_validator(signed_metadata, &credential_issuer_url)?,
+            None => return Ok(credential_issuer_metadata),
+        };
+        credential_issuer_metadata.verify_signed_metadata(validator).await
+    }
+
+    /// Returns the [`Validator`] for the `signed_metadata` of the Credential Issuer. Metadata that is signed with an
+    /// `x5c` certificate chain must be signed by a certificate for the Credential Issuer that is issued by one of the
+    /// `trust_anchors`, other metadata with a key that the `did_resolver` resolves.
+    fn signed_metadata_validator(&self, signed_metadata: &str, credential_issuer_url: &Url) -> Result<Validator> {
+        let header = jsonwebtoken::decode_header(signed_metadata)?;
+        match header.x5c {
+            Some(x5c) => {
+                let chain = self.trust_anchors.verify(&x5c)?;
+                let is_credential_issuer = chain.uris()?.contains(&credential_issuer_url.to_string())
+                    || credential_issuer_url.host_str().is_some_and(|host| {
+                        chain
+                            .dns_names()
+                            .is_ok_and(|dns_names| dns_names.contains(&host.to_string()))
+                    });
+                if !is_credential_issuer {
+                    return Err(anyhow!(
+                        "The certificate of the signed metadata is not issued for the Credential Issuer."
+                    ));
+                }
+                Ok(Validator::Verifier(Arc::new(X509Verifier::new(
+                    self.trust_anchors.clone(),
+                ))))
+            }
+            None => self
+                .did_resolver
+                .clone()
+                .map(Validator::Verifier)
+                .ok_or(anyhow!("No DID resolver is configured to verify the signed metadata.")),
+        }
     }
 
     pub async fn get_authorization_code(
@@ -199,6 +250,74 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
             .ok_or(anyhow::anyhow!("No supported subject syntax types found."))
     }
 
+    /// Selects `alg` and `enc` values supported by both the Wallet and the Credential Issuer and generates an ephemeral
+    /// key that the Credential Issuer will use to encrypt the Credential Response. Returns `None` when the Credential
+    /// Issuer does not support encryption of the Credential Response.
+    fn select_credential_response_encryption(
+        &self,
+        credential_issuer_metadata: &CredentialIssuerMetadata<CFC>,
+    ) -> Result<Option<(EncryptionKey, CredentialResponseEncryptionSpecification)>> {
+        let credential_response_encryption = match &credential_issuer_metadata.credential_response_encryption {
+            Some(credential_response_encryption) => credential_response_encryption,
+            None => return Ok(None),
+        };
+
+        let alg = credential_response_encryption
+            .alg_values_supported
+            .iter()
+            .find_map(|alg| serde_json::from_value::<KeyManagementAlgorithm>(serde_json::json!(alg)).ok());
+        let enc = credential_response_encryption
+            .enc_values_supported
+            .iter()
+            .find_map(|enc| serde_json::from_value::<ContentEncryptionAlgorithm>(serde_json::json!(enc)).ok());
+
+        match (alg, enc) {
+            (Some(alg), Some(enc)) => {
+                let encryption_key = EncryptionKey::generate();
+                let jwk = encryption_key.public_jwk()?;
+                Ok(Some((
+                    encryption_key,
+                    CredentialResponseEncryptionSpecification { jwk, alg, enc },
+                )))
+            }
+            _ if credential_response_encryption.encryption_required => Err(anyhow::anyhow!(
+                "The Credential Issuer requires encryption, but no supported `alg` and `enc` values were found."
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses a (Batch or Deferred) Credential Response, decrypting it first in case it was returned as a JWE.
+    async fn parse_credential_response<T: DeserializeOwned>(
+        response: reqwest::Response,
+        encryption_key: Option<&EncryptionKey>,
+    ) -> Result<T> {
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Credential Issuer returned an error response: {}: {}",
+                response.status(),
+                response.text().await?
+            ));
+        }
+
+        let is_encrypted = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| content_type.starts_with("application/jwt"))
+            .unwrap_or(false);
+
+        match (encryption_key, is_encrypted) {
+            (Some(encryption_key), true) => {
+                let plaintext = encryption_key.decrypt(&response.text().await?)?;
+                serde_json::from_slice(&plaintext).map_err(|e| e.into())
+            }
+            (None, false) => response.json().await.map_err(|e| e.into()),
+            (Some(_), false) => Err(anyhow::anyhow!("Expected an encrypted Credential Response.")),
+            (None, true) => Err(anyhow::anyhow!("Received an unexpected encrypted Credential Response.")),
+        }
+    }
+
     pub async fn get_credential(
         &self,
         credential_issuer_metadata: CredentialIssuerMetadata<CFC>,
@@ -209,6 +328,9 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
 
         let signing_algorithm = self.select_signing_algorithm(credential_configuration)?;
         let subject_syntax_type = self.select_subject_syntax_type(credential_configuration)?;
+        let (encryption_key, credential_response_encryption) = self
+            .select_credential_response_encryption(&credential_issuer_metadata)?
+            .unzip();
 
         let credential_request = CredentialRequest {
             credential_format,
@@ -237,17 +359,18 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
                     .build()
                     .await?,
             ),
+            credential_response_encryption,
         };
 
-        self.client
+        let response = self
+            .client
             .post(credential_issuer_metadata.credential_endpoint)
             .bearer_auth(token_response.access_token.clone())
             .json(&credential_request)
             .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| e.into())
+            .await?;
+
+        Self::parse_credential_response(response, encryption_key.as_ref()).await
     }
 
     pub async fn get_batch_credential(
@@ -263,6 +386,9 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
 
         let signing_algorithm = self.select_signing_algorithm(credential_configuration)?;
         let subject_syntax_type = self.select_subject_syntax_type(credential_configuration)?;
+        let (encryption_key, credential_response_encryption) = self
+            .select_credential_response_encryption(&credential_issuer_metadata)?
+            .unzip();
 
         let proof = Some(
             KeyProofType::builder()
@@ -296,18 +422,152 @@ impl<CFC: CredentialFormatCollection + DeserializeOwned> Wallet<CFC> {
                 .map(|credential_configuration| CredentialRequest {
                     credential_format: credential_configuration.credential_format.to_owned(),
                     proof: proof.clone(),
+                    credential_response_encryption: credential_response_encryption.clone(),
                 })
                 .collect(),
         };
 
-        self.client
+        let response = self
+            .client
             .post(credential_issuer_metadata.batch_credential_endpoint.unwrap())
             .bearer_auth(token_response.access_token.clone())
             .json(&batch_credential_request)
             .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| e.into())
+            .await?;
+
+        Self::parse_credential_response(response, encryption_key.as_ref()).await
+    }
+
+    pub async fn get_deferred_credential(
+        &self,
+        credential_issuer_metadata: &CredentialIssuerMetadata<CFC>,
+        token_response: &TokenResponse,
+        transaction_id: String,
+    ) -> Result<CredentialResponse> {
+        let deferred_credential_endpoint = credential_issuer_metadata
+            .deferred_credential_endpoint
+            .clone()
+            .ok_or(anyhow::anyhow!("No deferred credential endpoint found."))?;
+        let (encryption_key, credential_response_encryption) = self
+            .select_credential_response_encryption(credential_issuer_metadata)?
+            .unzip();
+
+        let response = self
+            .client
+            .post(deferred_credential_endpoint)
+            .bearer_auth(token_response.access_token.clone())
+            .json(&DeferredCredentialRequest {
+                transaction_id,
+                credential_response_encryption,
+            })
+            .send()
+            .await?;
+
+        Self::parse_credential_response(response, encryption_key.as_ref()).await
+    }
+
+    pub async fn send_notification(
+        &self,
+        credential_issuer_metadata: &CredentialIssuerMetadata<CFC>,
+        token_response: &TokenResponse,
+        notification_request: NotificationRequest,
+    ) -> Result<()> {
+        let notification_endpoint = credential_issuer_metadata
+            .notification_endpoint
+            .clone()
+            .ok_or(anyhow::anyhow!("No notification endpoint found."))?;
+
+        let response = self
+            .client
+            .post(notification_endpoint)
+            .bearer_auth(token_response.access_token.clone())
+            .json(&notification_request)
+            .send()
+            .await?;
+
+        anyhow::ensure!(
+            response.status().is_success(),
+            "Failed to send notification: {}",
+            response.status()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64_url::base64::{engine::general_purpose::STANDARD, Engine};
+    use oid4vc_core::test_utils::TestSubject;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, PKCS_ECDSA_P256_SHA256};
+
+    #[test]
+    fn test_signed_metadata_validator() {
+        let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let root = params.self_signed(&root_key).unwrap();
+        let issuer_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let certificate = CertificateParams::new(vec!["credential-issuer.example.com".to_string()])
+            .unwrap()
+            .signed_by(&issuer_key, &root, &root_key)
+            .unwrap();
+
+        let mut header = jsonwebtoken::Header::new(Algorithm::ES256);
+        header.x5c = Some(vec![STANDARD.encode(certificate.der())]);
+        let signed_metadata = jsonwebtoken::encode(
+            &header,
+            &serde_json::json!({ "iss": "https://credential-issuer.example.com/" }),
+            &jsonwebtoken::EncodingKey::from_ec_pem(issuer_key.serialize_pem().as_bytes()).unwrap(),
+        )
+        .unwrap();
+        let credential_issuer_url: Url = "https://credential-issuer.example.com".parse().unwrap();
+
+        let mut wallet: Wallet = Wallet::new(
+            Arc::new(TestSubject::default()),
+            vec!["did:test"],
+            vec![Algorithm::EdDSA],
+        )
+        .unwrap();
+
+        // The certificate chain must be issued by a trust anchor.
+        assert!(wallet
+            .signed_metadata_validator(&signed_metadata, &credential_issuer_url)
+            .is_err());
+        wallet.trust_anchors.add(root.der()).unwrap();
+        assert!(wallet
+            .signed_metadata_validator(&signed_metadata, &credential_issuer_url)
+            .is_ok());
+
+        // The certificate must be issued for the Credential Issuer.
+        assert_eq!(
+            wallet
+                .signed_metadata_validator(&signed_metadata, &"https://other.example.com".parse().unwrap())
+                .err()
+                .unwrap()
+                .to_string(),
+            "The certificate of the signed metadata is not issued for the Credential Issuer."
+        );
+
+        // Without an `x5c` certificate chain, the key must be resolved by the DID resolver.
+        let signed_metadata = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &serde_json::json!({}),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        assert_eq!(
+            wallet
+                .signed_metadata_validator(&signed_metadata, &credential_issuer_url)
+                .err()
+                .unwrap()
+                .to_string(),
+            "No DID resolver is configured to verify the signed metadata."
+        );
+        wallet.did_resolver = Some(Arc::new(TestSubject::default()));
+        assert!(wallet
+            .signed_metadata_validator(&signed_metadata, &credential_issuer_url)
+            .is_ok());
     }
 }
@@ -1,63 +1,90 @@
+use crate::dcql::DcqlQuery;
 use crate::oid4vp::OID4VP;
 use anyhow::{anyhow, Result};
 use dif_presentation_exchange::presentation_definition::ClaimFormatProperty;
 use dif_presentation_exchange::{ClaimFormatDesignation, PresentationDefinition};
 use is_empty::IsEmpty;
+use jsonwebtoken::jwk::JwkSet;
 use monostate::MustBe;
 use oid4vc_core::authorization_request::Object;
 use oid4vc_core::builder_fn;
+pub use oid4vc_core::client_id_scheme::ClientIdScheme;
+use oid4vc_core::jwe::{ContentEncryptionAlgorithm, KeyManagementAlgorithm};
 use oid4vc_core::{
     authorization_request::AuthorizationRequest, client_metadata::ClientMetadataResource, scope::Scope, RFC7519Claims,
 };
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use std::collections::HashMap;
 
-/// The Client ID Scheme enables the use of different mechanisms to obtain and validate the Verifier's metadata. As
-/// described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-verifier-metadata-managemen
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-#[serde(rename_all = "snake_case")]
-pub enum ClientIdScheme {
-    #[serde(rename = "pre-registered")]
-    PreRegistered,
-    RedirectUri,
-    EntityId,
-    Did,
-    VerifierAttestation,
-    X509SanDns,
-    X509SanUri,
-}
-
-/// [`AuthorizationRequest`] claims specific to [`OID4VP`].
+/// [`AuthorizationRequest`] claims specific to [`OID4VP`]. The Credentials are requested with either a
+/// `presentation_definition`, a `presentation_definition_uri` or a `dcql_query`.
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct AuthorizationRequestParameters {
     pub response_type: MustBe!("vp_token"),
-    pub presentation_definition: PresentationDefinition,
+    pub presentation_definition: Option<PresentationDefinition>,
+    /// A URL from which the Wallet fetches the Presentation Definition, which is replaced by the fetched
+    /// `presentation_definition` once it is resolved.
+    /// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-presentation_definition_uri
+    pub presentation_definition_uri: Option<url::Url>,
+    /// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0.html#name-new-parameters
+    pub dcql_query: Option<DcqlQuery>,
     pub client_id_scheme: Option<ClientIdScheme>,
     pub response_mode: Option<String>,
+    /// The URL to which the Wallet sends the Authorization Response when using the `direct_post` Response Mode.
+    /// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-response-mode-direct_post
+    pub response_uri: Option<url::Url>,
     pub scope: Option<Scope>,
     pub nonce: String,
     #[serde(flatten)]
     pub client_metadata: ClientMetadataResource<ClientMetadataParameters>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 pub struct ClientMetadataParameters {
     /// Object defining the formats and proof types of Verifiable Presentations and Verifiable Credentials that a
     /// Verifier supports.
     /// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-additional-verifier-metadat
     pub vp_formats: HashMap<ClaimFormatDesignation, ClaimFormatProperty>,
+    /// The keys of the Verifier, one of which is used to encrypt the Authorization Response with the `direct_post.jwt`
+    /// Response Mode.
+    /// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-response-mode-direct_postjw
+    pub jwks: Option<JwkSet>,
+    /// As described here: https://openid.net/specs/oauth-v2-jarm-final.html#name-client-metadata
+    pub authorization_encrypted_response_alg: Option<KeyManagementAlgorithm>,
+    /// As described here: https://openid.net/specs/oauth-v2-jarm-final.html#name-client-metadata
+    pub authorization_encrypted_response_enc: Option<ContentEncryptionAlgorithm>,
+}
+
+/// Ensures that the Credentials are requested in exactly one way.
+pub(crate) fn ensure_single_query(
+    presentation_definition: &Option<PresentationDefinition>,
+    presentation_definition_uri: &Option<url::Url>,
+    dcql_query: &Option<DcqlQuery>,
+) -> Result<()> {
+    match (presentation_definition, presentation_definition_uri, dcql_query) {
+        (Some(_), None, None) | (None, Some(_), None) | (None, None, Some(_)) => Ok(()),
+        _ => Err(anyhow!(
+            "Exactly one of presentation_definition, presentation_definition_uri or dcql_query parameters is required."
+        )),
+    }
 }
 
 #[derive(Debug, Default, IsEmpty)]
 pub struct AuthorizationRequestBuilder {
     rfc7519_claims: RFC7519Claims,
     presentation_definition: Option<PresentationDefinition>,
+    presentation_definition_uri: Option<url::Url>,
+    dcql_query: Option<DcqlQuery>,
     client_id_scheme: Option<ClientIdScheme>,
     client_id: Option<String>,
     redirect_uri: Option<url::Url>,
     state: Option<String>,
     scope: Option<Scope>,
     response_mode: Option<String>,
+    response_uri: Option<url::Url>,
     nonce: Option<String>,
     client_metadata: Option<ClientMetadataResource<ClientMetadataParameters>>,
     custom_url_scheme: Option<String>,
@@ -72,6 +99,7 @@ impl AuthorizationRequestBuilder {
     builder_fn!(rfc7519_claims, iat, i64);
     builder_fn!(rfc7519_claims, jti, String);
     builder_fn!(response_mode, String);
+    builder_fn!(response_uri, url::Url);
     builder_fn!(client_id, String);
     builder_fn!(scope, Scope);
     builder_fn!(redirect_uri, url::Url);
@@ -79,6 +107,8 @@ impl AuthorizationRequestBuilder {
     builder_fn!(client_metadata, ClientMetadataResource<ClientMetadataParameters>);
     builder_fn!(state, String);
     builder_fn!(presentation_definition, PresentationDefinition);
+    builder_fn!(presentation_definition_uri, url::Url);
+    builder_fn!(dcql_query, DcqlQuery);
     builder_fn!(client_id_scheme, ClientIdScheme);
     builder_fn!(custom_url_scheme, String);
 
@@ -86,15 +116,21 @@ impl AuthorizationRequestBuilder {
         match (self.client_id.take(), self.is_empty()) {
             (None, _) => Err(anyhow!("client_id parameter is required.")),
             (Some(client_id), false) => {
+                let (presentation_definition, presentation_definition_uri, dcql_query) = (
+                    self.presentation_definition.take(),
+                    self.presentation_definition_uri.take(),
+                    self.dcql_query.take(),
+                );
+                ensure_single_query(&presentation_definition, &presentation_definition_uri, &dcql_query)?;
                 let extension = AuthorizationRequestParameters {
                     response_type: MustBe!("vp_token"),
-                    presentation_definition: self
-                        .presentation_definition
-                        .take()
-                        .ok_or_else(|| anyhow!("presentation_definition parameter is required."))?,
+                    presentation_definition,
+                    presentation_definition_uri,
+                    dcql_query,
                     client_id_scheme: self.client_id_scheme.take(),
                     scope: self.scope.take(),
                     response_mode: self.response_mode.take(),
+                    response_uri: self.response_uri.take(),
                     nonce: self
                         .nonce
                         .take()
@@ -221,7 +257,8 @@ mod tests {
                             )
                         ]
                         .into_iter()
-                        .collect()
+                        .collect(),
+                        ..Default::default()
                     },
                     other: HashMap::from_iter(vec![("application_type".to_string(), serde_json::json!("web"))]),
                 }),
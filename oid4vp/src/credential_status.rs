@@ -0,0 +1,306 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dif_presentation_exchange::relational_constraints::same_identifier;
+use oid4vc_core::{
+    data_integrity::DataIntegrityProof,
+    status_list::{
+        Bitstring, BitstringStatusListEntry, Status, StatusListToken, StatusListTransport, StatusPurpose, StatusType,
+        MAX_STATUS_LIST_SIZE, STATUS_LIST_JWT_TYP,
+    },
+    JsonObject, Validator,
+};
+use oid4vci::{
+    credential::{issuer as credential_issuer, verify_issuer},
+    JwtCredential,
+};
+use reqwest::header::ACCEPT;
+use reqwest_middleware::ClientBuilder;
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use url::Url;
+
+/// The maximum size of a fetched status list, which leaves room for the base64url encoding of a status list of
+/// [`MAX_STATUS_LIST_SIZE`] bytes that does not compress at all.
+const MAX_RESPONSE_SIZE: usize = 2 * MAX_STATUS_LIST_SIZE as usize;
+
+/// Fetches status lists over HTTP. Responses that exceed the maximum size are rejected.
+#[derive(Debug, Default, Clone)]
+pub struct HttpStatusListTransport;
+
+#[async_trait]
+impl StatusListTransport for HttpStatusListTransport {
+    async fn fetch(&self, uri: &Url) -> Result<String> {
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+        let mut response = client
+            .get(uri.clone())
+            .header(
+                ACCEPT,
+                "application/statuslist+jwt, application/vc+jwt, application/vc+ld+json, application/json",
+            )
+            .send()
+            .await?
+            .error_for_status()?;
+        let too_large = || anyhow!("The status list at `{uri}` exceeds the maximum size of {MAX_RESPONSE_SIZE} bytes.");
+        if response
+            .content_length()
+            .is_some_and(|content_length| content_length > MAX_RESPONSE_SIZE as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > MAX_RESPONSE_SIZE {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8(body)?)
+    }
+}
+
+/// Verifies that a presented Credential is neither revoked nor suspended. The `status` claim references a Token Status
+/// List, the `credential_status` contains one or more Bitstring Status List entries. Other kinds of `credentialStatus`
+/// are not supported. The status lists must be issued by the `issuer` of the Credential.
+pub async fn verify_credential_status(
+    status: Option<&serde_json::Value>,
+    credential_status: Option<&serde_json::Value>,
+    issuer: &str,
+    validator: &Validator,
+    transport: &dyn StatusListTransport,
+) -> Result<()> {
+    if let Some(status) = status {
+        let status: Status = serde_json::from_value(status.clone())?;
+        verify_token_status(&status, issuer, validator, transport).await?;
+    }
+
+    let entries = match credential_status {
+        Some(serde_json::Value::Array(entries)) => entries.clone(),
+        Some(entry) => vec![entry.clone()],
+        None => vec![],
+    };
+    for entry in entries {
+        let entry: BitstringStatusListEntry = serde_json::from_value(entry)
+            .map_err(|_| anyhow!("Only `BitstringStatusListEntry` credential statuses are supported."))?;
+        verify_bitstring_status(&entry, issuer, validator, transport).await?;
+    }
+    Ok(())
+}
+
+/// Evaluates the status of a Referenced Token as described here:
+/// https://www.ietf.org/archive/id/draft-ietf-oauth-status-list-06.html#name-validation-rules
+async fn verify_token_status(
+    Status { status_list }: &Status,
+    issuer: &str,
+    validator: &Validator,
+    transport: &dyn StatusListTransport,
+) -> Result<()> {
+    let status_list_token = transport.fetch(&status_list.uri).await?;
+    let header = jsonwebtoken::decode_header(&status_list_token)?;
+    if header.typ.as_deref() != Some(STATUS_LIST_JWT_TYP) {
+        return Err(anyhow!(
+            "The Status List Token must have the `{STATUS_LIST_JWT_TYP}` type."
+        ));
+    }
+    let kid = header.kid.ok_or(anyhow!("No key identifier found in the header."))?;
+    let status_list_token: StatusListToken = validator.decode(status_list_token).await?;
+
+    verify_issuer(&serde_json::to_value(&status_list_token)?, &kid)?;
+    if !status_list_token
+        .rfc7519_claims
+        .iss
+        .as_deref()
+        .is_some_and(|iss| same_identifier(iss, issuer))
+    {
+        return Err(anyhow!(
+            "The Status List Token is not issued by the issuer of the Credential."
+        ));
+    }
+
+    if status_list_token.rfc7519_claims.sub.as_deref() != Some(status_list.uri.as_str()) {
+        return Err(anyhow!("The `sub` of the Status List Token does not match its URI."));
+    }
+    if status_list_token
+        .rfc7519_claims
+        .exp
+        .is_some_and(|exp| exp <= Utc::now().timestamp())
+    {
+        return Err(anyhow!("The Status List Token is expired."));
+    }
+
+    match status_list_token.status_list.get(status_list.idx)? {
+        StatusType::Valid => Ok(()),
+        StatusType::Invalid => Err(anyhow!("The Credential is revoked.")),
+        StatusType::Suspended => Err(anyhow!("The Credential is suspended.")),
+        StatusType::ApplicationSpecific(status) => Err(anyhow!("The Credential has the status {status:#04x}.")),
+    }
+}
+
+/// Evaluates a Bitstring Status List entry as described here:
+/// https://www.w3.org/TR/vc-bitstring-status-list/#validate-algorithm
+async fn verify_bitstring_status(
+    entry: &BitstringStatusListEntry,
+    issuer: &str,
+    validator: &Validator,
+    transport: &dyn StatusListTransport,
+) -> Result<()> {
+    let status_list_credential = transport.fetch(&entry.status_list_credential).await?;
+
+    // The Bitstring Status List Credential is either secured with a Data Integrity proof or as a JWT.
+    let (credential, credential_subject, valid_until) = if status_list_credential.trim_start().starts_with('{') {
+        let credential: JsonObject = serde_json::from_str(&status_list_credential)?;
+        let proof = DataIntegrityProof::verify(&credential, validator).await?;
+        let credential = serde_json::Value::Object(credential);
+        verify_issuer(&credential, &proof.verification_method)?;
+        let valid_until = credential
+            .get("validUntil")
+            .map(|valid_until| serde_json::from_value::<DateTime<Utc>>(valid_until.clone()))
+            .transpose()?;
+        let credential_subject = credential.get("credentialSubject").cloned();
+        (credential, credential_subject, valid_until)
+    } else {
+        let credential = JwtCredential::decode(validator, &status_list_credential).await?;
+        let credential_subject = credential.credential_subject().cloned();
+        let valid_until = credential.valid_until();
+        (serde_json::to_value(credential)?, credential_subject, valid_until)
+    };
+
+    if !credential_issuer(&credential).is_some_and(|status_list_issuer| same_identifier(status_list_issuer, issuer)) {
+        return Err(anyhow!(
+            "The Bitstring Status List Credential is not issued by the issuer of the Credential."
+        ));
+    }
+
+    if valid_until.is_some_and(|valid_until| valid_until <= Utc::now()) {
+        return Err(anyhow!("The Bitstring Status List Credential is expired."));
+    }
+    let credential_subject = credential_subject.ok_or(anyhow!(
+        "The Bitstring Status List Credential has no `credentialSubject`."
+    ))?;
+    if credential_subject["type"] != "BitstringStatusList" {
+        return Err(anyhow!("The `credentialSubject` must be a `BitstringStatusList`."));
+    }
+    if credential_subject["statusPurpose"] != serde_json::json!(entry.status_purpose) {
+        return Err(anyhow!(
+            "The `statusPurpose` of the Bitstring Status List does not match the `{}` entry.",
+            entry.status_purpose
+        ));
+    }
+
+    let bitstring = Bitstring::decode(
+        credential_subject["encodedList"]
+            .as_str()
+            .ok_or(anyhow!("The Bitstring Status List has no `encodedList`."))?,
+    )?;
+    match (bitstring.get(entry.index()?)?, entry.status_purpose) {
+        (false, _) => Ok(()),
+        (true, StatusPurpose::Revocation) => Err(anyhow!("The Credential is revoked.")),
+        (true, StatusPurpose::Suspension) => Err(anyhow!("The Credential is suspended.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::Algorithm;
+    use oid4vc_core::test_utils::{MockVerifier, TestSubject};
+    use oid4vci::credential_issuer::status_list_manager::StatusListManager;
+    use serde_json::json;
+    use std::{collections::HashMap, sync::Arc};
+
+    struct MockTransport(HashMap<Url, String>);
+
+    #[async_trait]
+    impl StatusListTransport for MockTransport {
+        async fn fetch(&self, uri: &Url) -> Result<String> {
+            self.0.get(uri).cloned().ok_or(anyhow!("Not found: {uri}"))
+        }
+    }
+
+    async fn publish(status_list_manager: &StatusListManager) -> MockTransport {
+        let subject = Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key-1".to_string()).unwrap());
+        let mut status_lists = HashMap::from([(
+            status_list_manager.uri.clone(),
+            status_list_manager
+                .status_list_token(subject.clone(), "did:test", Algorithm::EdDSA)
+                .await
+                .unwrap(),
+        )]);
+        for status_purpose in [StatusPurpose::Revocation, StatusPurpose::Suspension] {
+            status_lists.insert(
+                status_list_manager
+                    .bitstring_status_list_credential_uri(status_purpose)
+                    .unwrap(),
+                status_list_manager
+                    .bitstring_status_list_credential(status_purpose, subject.clone(), "did:test", Algorithm::EdDSA)
+                    .await
+                    .unwrap(),
+            );
+        }
+        MockTransport(status_lists)
+    }
+
+    #[tokio::test]
+    async fn test_verify_credential_status() {
+        let mut status_list_manager = StatusListManager::new(
+            "did:test:123",
+            "https://credential-issuer.example.com/status_list".parse().unwrap(),
+            0,
+        )
+        .unwrap();
+        let idx = status_list_manager.allocate().unwrap();
+        let credential_status = status_list_manager.credential_status(idx).unwrap();
+        let status = json!(credential_status.status);
+        let credential_status = json!(credential_status.credential_status);
+        let validator = Validator::Verifier(Arc::new(MockVerifier::new()));
+        let issuer = "did:test:123";
+
+        let transport = publish(&status_list_manager).await;
+        verify_credential_status(Some(&status), Some(&credential_status), issuer, &validator, &transport)
+            .await
+            .unwrap();
+
+        for (status_type, error) in [
+            (StatusType::Suspended, "The Credential is suspended."),
+            (StatusType::Invalid, "The Credential is revoked."),
+        ] {
+            status_list_manager.set_status(idx, status_type).unwrap();
+            let transport = publish(&status_list_manager).await;
+
+            let result = verify_credential_status(Some(&status), None, issuer, &validator, &transport).await;
+            assert_eq!(result.unwrap_err().to_string(), error);
+            let result = verify_credential_status(None, Some(&credential_status), issuer, &validator, &transport).await;
+            assert_eq!(result.unwrap_err().to_string(), error);
+        }
+
+        // A Status List Token that is published at another URI is rejected.
+        let mut transport = publish(&status_list_manager).await;
+        let status_list_token = transport.0.remove(&status_list_manager.uri).unwrap();
+        transport
+            .0
+            .insert("https://example.com/status_list".parse().unwrap(), status_list_token);
+        let status = json!({ "status_list": { "idx": idx, "uri": "https://example.com/status_list" } });
+        assert!(
+            verify_credential_status(Some(&status), None, issuer, &validator, &transport)
+                .await
+                .is_err()
+        );
+
+        // Status lists that are not issued by the issuer of the Credential are rejected.
+        let transport = publish(&status_list_manager).await;
+        let status = json!({ "status_list": { "idx": idx, "uri": status_list_manager.uri } });
+        let result = verify_credential_status(Some(&status), None, "did:test:other", &validator, &transport).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The Status List Token is not issued by the issuer of the Credential."
+        );
+        let result =
+            verify_credential_status(None, Some(&credential_status), "did:test:other", &validator, &transport).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The Bitstring Status List Credential is not issued by the issuer of the Credential."
+        );
+    }
+}
@@ -0,0 +1,491 @@
+use dif_presentation_exchange::ClaimFormatDesignation;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use std::collections::{BTreeMap, HashSet};
+
+/// A query for Credentials in the Digital Credentials Query Language, as described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0.html#name-digital-credentials-query-l
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DcqlQuery {
+    pub credentials: Vec<CredentialQuery>,
+    /// The combinations of Credentials that are requested. When absent, all Credential Queries must be answered.
+    pub credential_sets: Option<Vec<CredentialSetQuery>>,
+}
+
+/// A request for a single Credential, which is answered with a presentation under its `id`.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CredentialQuery {
+    pub id: String,
+    pub format: ClaimFormatDesignation,
+    pub meta: Option<CredentialQueryMeta>,
+    /// The claims that are requested. When absent, no selectively disclosable claims are requested.
+    pub claims: Option<Vec<ClaimsQuery>>,
+    /// The combinations of the `claims`, identified by their `id`, that satisfy the Credential Query, in order of
+    /// preference. When absent, all `claims` are requested.
+    pub claim_sets: Option<Vec<Vec<String>>>,
+    /// Whether more than one Credential may be presented for the Credential Query. When absent, only a single
+    /// Credential may be presented.
+    pub multiple: Option<bool>,
+}
+
+/// Format-specific constraints on the Credential.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct CredentialQueryMeta {
+    /// The accepted `vct` values of an SD-JWT VC.
+    pub vct_values: Option<Vec<String>>,
+    /// The `docType` of an mdoc.
+    pub doctype_value: Option<String>,
+    /// The accepted sets of `type` values of a W3C Verifiable Credential, each of which must all be present.
+    pub type_values: Option<Vec<Vec<String>>>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ClaimsQuery {
+    /// Required when the Credential Query has `claim_sets`.
+    pub id: Option<String>,
+    pub path: Vec<ClaimPathElement>,
+    /// The values of which the claim must have one.
+    pub values: Option<Vec<Value>>,
+}
+
+/// An element of a claims path pointer, as described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0.html#name-claims-path-pointer
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum ClaimPathElement {
+    /// Selects the member with the given name of an object.
+    Key(String),
+    /// Selects the element at the given index of an array.
+    Index(usize),
+    /// Selects all elements of an array. It is represented as `null`.
+    Wildcard(()),
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CredentialSetQuery {
+    /// The combinations of Credential Queries, identified by their `id`, that satisfy the set.
+    pub options: Vec<Vec<String>>,
+    /// Whether the set must be satisfied. Defaults to `true`.
+    pub required: Option<bool>,
+    pub purpose: Option<Value>,
+}
+
+impl CredentialSetQuery {
+    pub fn is_required(&self) -> bool {
+        self.required.unwrap_or(true)
+    }
+}
+
+/// A Credential that is evaluated against a Credential Query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DcqlCandidate {
+    pub format: ClaimFormatDesignation,
+    /// The `docType` of an mdoc.
+    pub doc_type: Option<String>,
+    /// The claims of the Credential. The data elements of an mdoc are grouped by their namespace, and the claims of a
+    /// VCDM 1.1 JWT Credential are those of its `vc` claim.
+    pub claims: Value,
+}
+
+/// A claim of a Credential that is located by a Claims Query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimMatch {
+    pub id: Option<String>,
+    pub path: Vec<ClaimPathElement>,
+    pub values: Vec<Value>,
+}
+
+/// The reason why a Credential does not satisfy a Credential Query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DcqlRejection {
+    /// The format of the Credential is not the requested one.
+    Format,
+    /// The Credential does not meet the `meta` constraints.
+    Meta,
+    /// The claim at `path` is missing.
+    MissingClaim { path: Vec<ClaimPathElement> },
+    /// The claim at `path` has none of the requested values.
+    Values { path: Vec<ClaimPathElement> },
+    /// None of the `claim_sets` can be satisfied.
+    ClaimSets,
+}
+
+impl std::fmt::Display for DcqlRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DcqlRejection::Format => write!(f, "The format of the Credential is not requested."),
+            DcqlRejection::Meta => write!(f, "The type of the Credential is not requested."),
+            DcqlRejection::MissingClaim { path } => write!(f, "The claim `{}` is missing.", display_path(path)),
+            DcqlRejection::Values { path } => {
+                write!(
+                    f,
+                    "The claim `{}` has none of the requested values.",
+                    display_path(path)
+                )
+            }
+            DcqlRejection::ClaimSets => write!(f, "None of the claim sets can be satisfied."),
+        }
+    }
+}
+
+fn display_path(path: &[ClaimPathElement]) -> String {
+    serde_json::to_string(path).unwrap_or_default()
+}
+
+/// Selects the values that the claims `path` points to, as described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0.html#name-semantics-for-json-based-cr
+pub fn select_claims<'a>(claims: &'a Value, path: &[ClaimPathElement]) -> Vec<&'a Value> {
+    path.iter().fold(vec![claims], |selected, element| {
+        selected
+            .into_iter()
+            .flat_map(|value| match (element, value) {
+                (ClaimPathElement::Key(key), Value::Object(object)) => object.get(key).into_iter().collect(),
+                (ClaimPathElement::Index(index), Value::Array(array)) => array.get(*index).into_iter().collect(),
+                (ClaimPathElement::Wildcard(()), Value::Array(array)) => array.iter().collect(),
+                _ => vec![],
+            })
+            .collect()
+    })
+}
+
+impl ClaimsQuery {
+    fn evaluate(&self, claims: &Value) -> Result<ClaimMatch, DcqlRejection> {
+        let selected = select_claims(claims, &self.path);
+        if selected.is_empty() {
+            return Err(DcqlRejection::MissingClaim {
+                path: self.path.clone(),
+            });
+        }
+        if let Some(values) = &self.values {
+            if !selected.iter().any(|value| values.contains(value)) {
+                return Err(DcqlRejection::Values {
+                    path: self.path.clone(),
+                });
+            }
+        }
+        Ok(ClaimMatch {
+            id: self.id.clone(),
+            path: self.path.clone(),
+            values: selected.into_iter().cloned().collect(),
+        })
+    }
+}
+
+impl CredentialQuery {
+    pub fn allows_multiple(&self) -> bool {
+        self.multiple.unwrap_or_default()
+    }
+
+    fn meets_meta(&self, candidate: &DcqlCandidate) -> bool {
+        let Some(meta) = &self.meta else {
+            return true;
+        };
+        let vct = candidate.claims.get("vct").and_then(Value::as_str);
+        let types: Vec<&str> = match candidate.claims.get("type") {
+            Some(Value::String(r#type)) => vec![r#type.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        meta.vct_values.as_ref().map_or(true, |vct_values| {
+            vct.is_some_and(|vct| vct_values.iter().any(|value| value == vct))
+        }) && meta
+            .doctype_value
+            .as_ref()
+            .map_or(true, |doctype_value| candidate.doc_type.as_ref() == Some(doctype_value))
+            && meta.type_values.as_ref().map_or(true, |type_values| {
+                type_values
+                    .iter()
+                    .any(|required| required.iter().all(|r#type| types.contains(&r#type.as_str())))
+            })
+    }
+
+    /// Evaluates whether the `candidate` satisfies the Credential Query. Returns the claims that are located by the
+    /// first of the `claim_sets` that is satisfied, or by all `claims` when there are no `claim_sets`.
+    pub fn match_candidate(&self, candidate: &DcqlCandidate) -> Result<Vec<ClaimMatch>, DcqlRejection> {
+        if candidate.format != self.format {
+            return Err(DcqlRejection::Format);
+        }
+        if !self.meets_meta(candidate) {
+            return Err(DcqlRejection::Meta);
+        }
+        let claims = self.claims.as_deref().unwrap_or_default();
+        match &self.claim_sets {
+            None => claims
+                .iter()
+                .map(|claims_query| claims_query.evaluate(&candidate.claims))
+                .collect(),
+            Some(claim_sets) => claim_sets
+                .iter()
+                .find_map(|claim_set| {
+                    claim_set
+                        .iter()
+                        .map(|id| {
+                            claims
+                                .iter()
+                                .find(|claims_query| claims_query.id.as_ref() == Some(id))
+                                .ok_or(DcqlRejection::ClaimSets)
+                                .and_then(|claims_query| claims_query.evaluate(&candidate.claims))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                })
+                .ok_or(DcqlRejection::ClaimSets),
+        }
+    }
+
+    /// Returns the paths of all claims that are requested, regardless of the `claim_sets`.
+    pub fn requested_paths(&self) -> impl Iterator<Item = &[ClaimPathElement]> {
+        self.claims
+            .iter()
+            .flatten()
+            .map(|claims_query| claims_query.path.as_slice())
+    }
+}
+
+/// The evaluation of a single candidate against a Credential Query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialMatch {
+    /// The index of the candidate.
+    pub candidate: usize,
+    pub result: Result<Vec<ClaimMatch>, DcqlRejection>,
+}
+
+/// The evaluation of every candidate against a Credential Query, from which the Holder can choose the Credential to
+/// present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialQueryMatches {
+    /// The `id` of the Credential Query.
+    pub id: String,
+    pub matches: Vec<CredentialMatch>,
+}
+
+impl CredentialQueryMatches {
+    /// Returns the indices of the candidates that satisfy the Credential Query.
+    pub fn accepted(&self) -> impl Iterator<Item = usize> + '_ {
+        self.matches
+            .iter()
+            .filter(|credential_match| credential_match.result.is_ok())
+            .map(|credential_match| credential_match.candidate)
+    }
+}
+
+impl DcqlQuery {
+    /// Evaluates every candidate against every Credential Query.
+    pub fn match_candidates(&self, candidates: &[DcqlCandidate]) -> Vec<CredentialQueryMatches> {
+        self.credentials
+            .iter()
+            .map(|credential_query| CredentialQueryMatches {
+                id: credential_query.id.clone(),
+                matches: candidates
+                    .iter()
+                    .enumerate()
+                    .map(|(index, candidate)| CredentialMatch {
+                        candidate: index,
+                        result: credential_query.match_candidate(candidate),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Returns whether answering the Credential Queries with the given `id`s satisfies the query.
+    pub fn is_satisfied_by(&self, answered: &HashSet<&str>) -> bool {
+        match &self.credential_sets {
+            None => self
+                .credentials
+                .iter()
+                .all(|credential_query| answered.contains(credential_query.id.as_str())),
+            Some(credential_sets) => credential_sets
+                .iter()
+                .filter(|credential_set| credential_set.is_required())
+                .all(|credential_set| {
+                    credential_set
+                        .options
+                        .iter()
+                        .any(|option| option.iter().all(|id| answered.contains(id.as_str())))
+                }),
+        }
+    }
+
+    /// Selects the Credential Queries that should be answered, given the `id`s of those that can be answered: all of
+    /// them when there are no `credential_sets`, or else the first option that can be answered of every required
+    /// set. Returns `None` when the query cannot be satisfied.
+    pub fn select_credential_queries(&self, answerable: &HashSet<&str>) -> Option<HashSet<&str>> {
+        match &self.credential_sets {
+            None => self.is_satisfied_by(answerable).then(|| {
+                self.credentials
+                    .iter()
+                    .map(|credential_query| credential_query.id.as_str())
+                    .collect()
+            }),
+            Some(credential_sets) => credential_sets
+                .iter()
+                .filter(|credential_set| credential_set.is_required())
+                .try_fold(HashSet::new(), |mut selected, credential_set| {
+                    let option = credential_set
+                        .options
+                        .iter()
+                        .find(|option| option.iter().all(|id| answerable.contains(id.as_str())))?;
+                    selected.extend(option.iter().map(String::as_str));
+                    Some(selected)
+                }),
+        }
+    }
+
+    /// Selects a candidate for every Credential Query that should be answered, which is the first candidate that
+    /// satisfies it. Returns the index of the candidate by the `id` of the Credential Query, or `None` when the query
+    /// cannot be satisfied.
+    pub fn select_credentials(&self, candidates: &[DcqlCandidate]) -> Option<BTreeMap<String, usize>> {
+        let matches = self.match_candidates(candidates);
+        let first_matches: BTreeMap<&str, usize> = matches
+            .iter()
+            .filter_map(|credential_query_matches| {
+                credential_query_matches
+                    .accepted()
+                    .next()
+                    .map(|index| (credential_query_matches.id.as_str(), index))
+            })
+            .collect();
+        let selected = self.select_credential_queries(&first_matches.keys().copied().collect())?;
+        Some(
+            first_matches
+                .into_iter()
+                .filter(|(id, _)| selected.contains(id))
+                .map(|(id, index)| (id.to_string(), index))
+                .collect(),
+        )
+    }
+
+    pub fn credential_query(&self, id: &str) -> Option<&CredentialQuery> {
+        self.credentials
+            .iter()
+            .find(|credential_query| credential_query.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn dcql_query() -> DcqlQuery {
+        serde_json::from_value(json!({
+            "credentials": [
+                {
+                    "id": "pid",
+                    "format": "vc+sd-jwt",
+                    "meta": { "vct_values": ["https://credentials.example.com/identity_credential"] },
+                    "claims": [
+                        { "id": "given_name", "path": ["given_name"] },
+                        { "id": "nationality", "path": ["nationalities", null], "values": ["DE", "NL"] },
+                        { "id": "locality", "path": ["address", "locality"] }
+                    ],
+                    "claim_sets": [["given_name", "locality"], ["given_name", "nationality"]]
+                },
+                {
+                    "id": "mdl",
+                    "format": "mso_mdoc",
+                    "meta": { "doctype_value": "org.iso.18013.5.1.mDL" },
+                    "claims": [{ "path": ["org.iso.18013.5.1", "family_name"] }]
+                },
+                {
+                    "id": "photo_card",
+                    "format": "mso_mdoc",
+                    "meta": { "doctype_value": "org.iso.23220.photoid.1" }
+                }
+            ],
+            "credential_sets": [
+                { "options": [["pid"], ["mdl"]] },
+                { "options": [["photo_card"]], "required": false }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn pid(claims: Value) -> DcqlCandidate {
+        DcqlCandidate {
+            format: ClaimFormatDesignation::VcSdJwt,
+            doc_type: None,
+            claims,
+        }
+    }
+
+    #[test]
+    fn test_serialize_dcql_query() {
+        let dcql_query = dcql_query();
+        assert_eq!(
+            dcql_query.credentials[0].claims.as_ref().unwrap()[1].path,
+            vec![
+                ClaimPathElement::Key("nationalities".to_string()),
+                ClaimPathElement::Wildcard(())
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&dcql_query.credentials[0].claims.as_ref().unwrap()[1]).unwrap(),
+            json!({ "id": "nationality", "path": ["nationalities", null], "values": ["DE", "NL"] })
+        );
+    }
+
+    #[test]
+    fn test_match_candidates() {
+        let dcql_query = dcql_query();
+        let pid_query = dcql_query.credential_query("pid").unwrap();
+        let vct = "https://credentials.example.com/identity_credential";
+
+        // The first claim set cannot be satisfied, so the second one is used.
+        assert_eq!(
+            pid_query.match_candidate(&pid(
+                json!({ "vct": vct, "given_name": "Erika", "nationalities": ["FR", "DE"] })
+            )),
+            Ok(vec![
+                ClaimMatch {
+                    id: Some("given_name".to_string()),
+                    path: vec![ClaimPathElement::Key("given_name".to_string())],
+                    values: vec![json!("Erika")],
+                },
+                ClaimMatch {
+                    id: Some("nationality".to_string()),
+                    path: vec![
+                        ClaimPathElement::Key("nationalities".to_string()),
+                        ClaimPathElement::Wildcard(())
+                    ],
+                    values: vec![json!("FR"), json!("DE")],
+                }
+            ])
+        );
+        assert_eq!(
+            pid_query.match_candidate(&pid(
+                json!({ "vct": vct, "given_name": "Erika", "nationalities": ["FR"] })
+            )),
+            Err(DcqlRejection::ClaimSets)
+        );
+        assert_eq!(
+            pid_query.match_candidate(&pid(json!({ "vct": "other", "given_name": "Erika" }))),
+            Err(DcqlRejection::Meta)
+        );
+
+        let mdl = DcqlCandidate {
+            format: ClaimFormatDesignation::MsoMdoc,
+            doc_type: Some("org.iso.18013.5.1.mDL".to_string()),
+            claims: json!({ "org.iso.18013.5.1": { "family_name": "Mustermann" } }),
+        };
+        let candidates = vec![pid(json!({ "vct": vct, "given_name": "Erika" })), mdl];
+        let matches = dcql_query.match_candidates(&candidates);
+        assert_eq!(matches[0].accepted().count(), 0);
+        assert_eq!(matches[1].accepted().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(matches[2].accepted().count(), 0);
+
+        // The mDL answers the required Credential Set, the photo card is optional.
+        assert_eq!(
+            dcql_query.select_credentials(&candidates),
+            Some(BTreeMap::from([("mdl".to_string(), 1)]))
+        );
+        assert!(dcql_query.select_credentials(&candidates[..1]).is_none());
+    }
+}
@@ -1,10 +1,20 @@
+// `Option::is_none_or`, which this lint suggests, is only stable since Rust 1.82.
+#![allow(clippy::unnecessary_map_or)]
+
 pub mod authorization_request;
+pub mod credential_status;
+pub mod dcql;
 pub mod oid4vp;
 pub mod oid4vp_params;
+pub mod selective_disclosure;
+pub mod siopv2_oid4vp;
 pub mod token;
+pub mod verification;
 
 pub use dif_presentation_exchange::{
-    evaluate_input, ClaimFormatDesignation, ClaimFormatProperty, InputDescriptor, InputDescriptorMappingObject,
-    PathNested, PresentationDefinition, PresentationSubmission,
+    evaluate_candidate, evaluate_input, evaluate_relational_constraints, evaluate_submission, match_candidate,
+    match_candidates, Candidate, CandidateMatch, ClaimFormatDesignation, ClaimFormatProperty, FieldQueryResult,
+    InputDescriptor, InputDescriptorMappingObject, InputDescriptorMatches, PathNested, PresentationDefinition,
+    PresentationSubmission, Rejection,
 };
 pub use {oid4vp_params::Oid4vpParams, token::vp_token::VpToken};
@@ -1,29 +1,53 @@
-use crate::authorization_request::{
-    AuthorizationRequestBuilder, AuthorizationRequestParameters, ClientMetadataParameters,
-};
+use crate::authorization_request::{AuthorizationRequestBuilder, AuthorizationRequestParameters};
+use crate::credential_status::{verify_credential_status, HttpStatusListTransport};
+use crate::dcql::DcqlCandidate;
 use crate::oid4vp_params::{serde_oid4vp_response, Oid4vpParams};
-use crate::token::vp_token::VpToken;
-use chrono::{Duration, Utc};
+use crate::selective_disclosure::{
+    excess_data_elements, excess_disclosures, select_data_elements, select_disclosures, RequestedClaims,
+};
+use crate::token::{
+    vp_jwt::{VerifiablePresentationV2, VP_JWT_TYP},
+    vp_token::VpToken,
+};
+use crate::verification::{
+    bound_to_holder, bound_to_key, matches_request, within_validity_period, Check, CheckOutcome, VerificationReport,
+};
+use chrono::{DateTime, Duration, Utc};
 use dif_presentation_exchange::presentation_definition::ClaimFormatProperty;
 pub use dif_presentation_exchange::{
-    evaluate_input, ClaimFormatDesignation, InputDescriptor, InputDescriptorMappingObject, PathNested,
-    PresentationDefinition, PresentationSubmission,
+    evaluate_input, evaluate_submission, ClaimFormatDesignation, InputDescriptor, InputDescriptorMappingObject,
+    PathNested, PresentationDefinition, PresentationSubmission,
 };
-use futures::future::join_all;
 use identity_credential::{credential::Jwt, presentation::Presentation};
+use jsonwebtoken::jwk::{AlgorithmParameters, PublicKeyUse};
 use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::authorization_request::Object;
 use oid4vc_core::client_metadata::ClientMetadataResource;
-use oid4vc_core::openid4vc_extension::{OpenID4VC, RequestHandle, ResponseHandle};
+use oid4vc_core::data_integrity::{Cryptosuite, DataIntegrityProof, ProofOptions};
+use oid4vc_core::jwe::{self, PartyInfo};
+use oid4vc_core::openid4vc_extension::{OpenID4VC, RequestHandle, ResponseHandle, VerificationContext};
+use oid4vc_core::resolver::ReferenceResolver;
+use oid4vc_core::sd_jwt::{KeyBindingJwtClaims, SdJwt};
+use oid4vc_core::status_list::StatusListTransport;
 use oid4vc_core::{authorization_response::AuthorizationResponse, jwt, openid4vc_extension::Extension, Subject};
-use oid4vc_core::{SubjectSyntaxType, Validator};
-use oid4vci::VerifiableCredentialJwt;
-use reqwest_middleware::ClientBuilder;
-use reqwest_retry::policies::ExponentialBackoff;
-use reqwest_retry::RetryTransientMiddleware;
+use oid4vc_core::{JsonObject, RFC7519Claims, SubjectSyntaxType, Validator};
+use oid4vci::credential_format_profiles::iso_mdl::mdoc::{
+    generate_mdoc_generated_nonce, DeviceResponse, Document, IssuerSigned, SessionTranscript,
+};
+use oid4vci::{
+    credential::{issuer as credential_issuer, verify_issuer},
+    JwtCredential,
+};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 
+/// The Response Mode in which the Authorization Response is encrypted and sent to the `response_uri`.
+/// As described here: https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-response-mode-direct_postjw
+pub const DIRECT_POST_JWT: &str = "direct_post.jwt";
+
 /// This is the [`RequestHandle`] for the [`OID4VP`] extension.
 #[derive(Debug, PartialEq, Clone)]
 pub struct RequestHandler {}
@@ -37,8 +61,9 @@ impl RequestHandle for RequestHandler {
 pub struct ResponseHandler {}
 impl ResponseHandle for ResponseHandler {
     type Input = AuthorizationResponseInput;
+    type Tokens = Presentations;
     type Parameters = AuthorizationResponseParameters;
-    type ResponseItem = Vec<VerifiableCredentialJwt>;
+    type ResponseItem = VerificationReport;
 }
 
 /// This is the [`Extension`] implementation for the [`OID4VP`] extension.
@@ -56,7 +81,7 @@ impl Extension for OID4VP {
         user_input: &<Self::ResponseHandle as ResponseHandle>::Input,
         subject_syntax_type: impl TryInto<SubjectSyntaxType>,
         signing_algorithm: impl TryInto<Algorithm>,
-    ) -> anyhow::Result<Vec<String>> {
+    ) -> anyhow::Result<Presentations> {
         let signing_algorithm = signing_algorithm
             .try_into()
             .map_err(|_| anyhow::anyhow!("Failed to convert the signing algorithm"))?;
@@ -69,51 +94,141 @@ impl Extension for OID4VP {
             .identifier(&subject_syntax_type_string, signing_algorithm)
             .await?;
 
-        let vp_token = VpToken::builder()
-            .iss(subject_identifier.clone())
-            .sub(subject_identifier)
-            .aud(client_id)
-            .nonce(extension_parameters.nonce.to_owned())
-            // TODO: make this configurable.
-            .exp((Utc::now() + Duration::minutes(10)).timestamp())
-            .iat((Utc::now()).timestamp())
-            .verifiable_presentation(user_input.verifiable_presentation.clone())
-            .build()?;
+        // All mdocs in the response are bound to the same `mdoc_generated_nonce`.
+        let presents_mdoc = match user_input {
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation,
+                ..
+            } => matches!(verifiable_presentation, VerifiablePresentation::Mdoc(_)),
+            AuthorizationResponseInput::Dcql {
+                verifiable_presentations,
+            } => verifiable_presentations
+                .values()
+                .any(|verifiable_presentation| matches!(verifiable_presentation, VerifiablePresentation::Mdoc(_))),
+        };
+        let mdoc_generated_nonce = presents_mdoc.then(generate_mdoc_generated_nonce);
 
-        let jwt = jwt::encode(
-            subject.clone(),
-            Header::new(signing_algorithm),
-            vp_token,
-            &subject_syntax_type_string,
-        )
-        .await?;
-        Ok(vec![jwt])
+        let presenter = Presenter {
+            subject,
+            subject_identifier,
+            subject_syntax_type: subject_syntax_type_string,
+            signing_algorithm,
+            client_id,
+            extension_parameters,
+            mdoc_generated_nonce: mdoc_generated_nonce.as_deref(),
+        };
+
+        match user_input {
+            AuthorizationResponseInput::PresentationExchange {
+                verifiable_presentation,
+                presentation_submission,
+            } => {
+                let presentation_definition =
+                    extension_parameters
+                        .presentation_definition
+                        .as_ref()
+                        .ok_or(anyhow::anyhow!(
+                            "The Authorization Request does not contain a `presentation_definition`."
+                        ))?;
+
+                // Only SD-JWT VCs and mdocs can be presented with less than all their claims.
+                let input_descriptor = match verifiable_presentation {
+                    VerifiablePresentation::SdJwt(_) => Some(input_descriptor(
+                        ClaimFormatDesignation::VcSdJwt,
+                        presentation_definition,
+                        presentation_submission,
+                    )?),
+                    VerifiablePresentation::Mdoc(_) => Some(input_descriptor(
+                        ClaimFormatDesignation::MsoMdoc,
+                        presentation_definition,
+                        presentation_submission,
+                    )?),
+                    _ => {
+                        if let Some(input_descriptor) = presentation_definition
+                            .input_descriptors()
+                            .iter()
+                            .filter(|input_descriptor| input_descriptor.requires_limited_disclosure())
+                            .find(|input_descriptor| {
+                                presentation_submission
+                                    .descriptor_map
+                                    .iter()
+                                    .any(|descriptor| &descriptor.id == input_descriptor.id())
+                            })
+                        {
+                            return Err(anyhow::anyhow!(
+                                "The Input Descriptor `{}` requires limited disclosure, which the presented Credential does not support.",
+                                input_descriptor.id()
+                            ));
+                        }
+                        None
+                    }
+                };
+
+                Ok(Presentations {
+                    vp_tokens: vec![
+                        presenter
+                            .present(
+                                verifiable_presentation,
+                                input_descriptor.map(|input_descriptor| input_descriptor as &dyn RequestedClaims),
+                            )
+                            .await?,
+                    ],
+                    mdoc_generated_nonce,
+                })
+            }
+            AuthorizationResponseInput::Dcql {
+                verifiable_presentations,
+            } => {
+                let dcql_query = extension_parameters.dcql_query.as_ref().ok_or(anyhow::anyhow!(
+                    "The Authorization Request does not contain a `dcql_query`."
+                ))?;
+
+                let mut presentations = vec![];
+                for (id, verifiable_presentation) in verifiable_presentations {
+                    let credential_query = dcql_query
+                        .credential_query(id)
+                        .ok_or(anyhow::anyhow!("The DCQL query has no Credential Query `{id}`."))?;
+                    presentations.push(
+                        presenter
+                            .present(verifiable_presentation, Some(credential_query as &dyn RequestedClaims))
+                            .await?,
+                    );
+                }
+                Ok(Presentations {
+                    vp_tokens: presentations,
+                    mdoc_generated_nonce,
+                })
+            }
+        }
+    }
+
+    /// Resolves the `client_metadata_uri` and the `presentation_definition_uri`. The resolved Presentation Definition is
+    /// validated before it is used.
+    async fn resolve_references(
+        extension_parameters: &mut <Self::RequestHandle as RequestHandle>::Parameters,
+        resolver: &ReferenceResolver,
+    ) -> anyhow::Result<()> {
+        extension_parameters.client_metadata = extension_parameters.client_metadata.resolve(resolver).await?;
+        if let Some(presentation_definition_uri) = extension_parameters.presentation_definition_uri.take() {
+            let presentation_definition: PresentationDefinition =
+                resolver.resolve(&presentation_definition_uri).await?;
+            presentation_definition.validate().map_err(anyhow::Error::msg)?;
+            extension_parameters.presentation_definition = Some(presentation_definition);
+        }
+        Ok(())
     }
 
     // TODO: combine this function with `get_relying_party_supported_syntax_types`.
     async fn get_relying_party_supported_algorithms(
         authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
     ) -> anyhow::Result<Vec<Algorithm>> {
-        let client_metadata = match &authorization_request.client_metadata {
-            // Fetch the client metadata from the given URI.
-            ClientMetadataResource::ClientMetadataUri(client_metadata_uri) => {
-                let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
-                let client = ClientBuilder::new(reqwest::Client::new())
-                    .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-                    .build();
-                let client_metadata: ClientMetadataResource<ClientMetadataParameters> =
-                    client.get(client_metadata_uri).send().await?.json().await?;
-                client_metadata
-            }
-            client_metadata => client_metadata.clone(),
-        };
-
         // TODO: in this current solution we assume that if there is a`ClaimFormatDesignation::JwtVcJson` `alg` present
         // in the client_metadata that this same `alg` will apply for the signing of all the credentials and the VP as
         // well as the Proof of Possession.
-        match client_metadata {
-            // Fetch the client metadata from the given URI.
-            ClientMetadataResource::ClientMetadataUri(_) => unreachable!(),
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => {
+                Err(anyhow::anyhow!("The `client_metadata_uri` is not resolved."))
+            }
             ClientMetadataResource::ClientMetadata { extension, .. } => extension
                 .vp_formats
                 .get(&ClaimFormatDesignation::JwtVcJson)
@@ -129,21 +244,10 @@ impl Extension for OID4VP {
     async fn get_relying_party_supported_syntax_types(
         authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
     ) -> anyhow::Result<Vec<SubjectSyntaxType>> {
-        let client_metadata = match &authorization_request.client_metadata {
-            ClientMetadataResource::ClientMetadataUri(client_metadata_uri) => {
-                let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
-                let client = ClientBuilder::new(reqwest::Client::new())
-                    .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-                    .build();
-                let client_metadata: ClientMetadataResource<ClientMetadataParameters> =
-                    client.get(client_metadata_uri).send().await?.json().await?;
-                client_metadata
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => {
+                Err(anyhow::anyhow!("The `client_metadata_uri` is not resolved."))
             }
-            client_metadata => client_metadata.clone(),
-        };
-
-        match client_metadata {
-            ClientMetadataResource::ClientMetadataUri(_) => unreachable!(),
             ClientMetadataResource::ClientMetadata { other, .. } => {
                 let subject_syntax_types_supported: Vec<SubjectSyntaxType> = other
                     // TODO(ngdil): this is a custom implementation at the moment as `subject_syntax_types_supported` is
@@ -171,20 +275,69 @@ impl Extension for OID4VP {
         }
     }
 
+    /// With the `direct_post.jwt` Response Mode, the Authorization Response is encrypted with a key from the `jwks` of
+    /// the Client Metadata. The `mdoc_generated_nonce` and the `nonce` are conveyed in its `apu` and `apv` headers, as
+    /// described in ISO/IEC 18013-7, Annex B.4.3.3.
     fn build_authorization_response(
-        jwts: Vec<String>,
+        presentations: Presentations,
         user_input: <Self::ResponseHandle as ResponseHandle>::Input,
+        extension_parameters: &<Self::RequestHandle as RequestHandle>::Parameters,
         redirect_uri: String,
         state: Option<String>,
     ) -> anyhow::Result<AuthorizationResponse<Self>> {
-        Ok(AuthorizationResponse {
+        let authorization_response = AuthorizationResponse {
             redirect_uri,
             state,
             extension: AuthorizationResponseParameters {
-                oid4vp_parameters: Oid4vpParams::Params {
-                    vp_token: jwts.first().unwrap().to_owned(),
-                    presentation_submission: user_input.presentation_submission,
-                },
+                oid4vp_parameters: oid4vp_parameters(presentations.vp_tokens, user_input)?,
+            },
+        };
+        if extension_parameters.response_mode.as_deref() != Some(DIRECT_POST_JWT) {
+            return Ok(authorization_response);
+        }
+
+        let ClientMetadataResource::ClientMetadata {
+            extension: client_metadata,
+            ..
+        } = &extension_parameters.client_metadata
+        else {
+            return Err(anyhow::anyhow!("The `client_metadata_uri` is not resolved."));
+        };
+        let (Some(alg), Some(enc)) = (
+            client_metadata.authorization_encrypted_response_alg,
+            client_metadata.authorization_encrypted_response_enc,
+        ) else {
+            return Err(anyhow::anyhow!(
+                "The Client Metadata does not specify how to encrypt the Authorization Response."
+            ));
+        };
+        let jwk = client_metadata
+            .jwks
+            .iter()
+            .flat_map(|jwks| &jwks.keys)
+            .find(|jwk| {
+                matches!(jwk.algorithm, AlgorithmParameters::EllipticCurve(_))
+                    && matches!(jwk.common.public_key_use, None | Some(PublicKeyUse::Encryption))
+            })
+            .ok_or(anyhow::anyhow!(
+                "The Client Metadata does not contain a key to encrypt the Authorization Response with."
+            ))?;
+
+        let response = jwe::encrypt(
+            &serde_json::to_vec(&authorization_response)?,
+            jwk,
+            alg,
+            enc,
+            &PartyInfo {
+                apu: presentations.mdoc_generated_nonce.map(String::into_bytes),
+                apv: Some(extension_parameters.nonce.clone().into_bytes()),
+            },
+        )?;
+        Ok(AuthorizationResponse {
+            redirect_uri: authorization_response.redirect_uri,
+            state: None,
+            extension: AuthorizationResponseParameters {
+                oid4vp_parameters: Oid4vpParams::Jwt { response },
             },
         })
     }
@@ -192,33 +345,844 @@ impl Extension for OID4VP {
     async fn decode_authorization_response(
         validator: Validator,
         response: &AuthorizationResponse<Self>,
+        verification_context: &VerificationContext<Self>,
     ) -> anyhow::Result<<Self::ResponseHandle as ResponseHandle>::ResponseItem> {
-        let vp_token: VpToken = match &response.extension.oid4vp_parameters {
-            Oid4vpParams::Jwt { .. } => todo!(),
-            Oid4vpParams::Params { vp_token, .. } => validator.decode(vp_token.to_owned()).await?,
+        let status_list_transport = verification_context
+            .status_list_transport
+            .clone()
+            .unwrap_or_else(|| Arc::new(HttpStatusListTransport));
+
+        let authorization_request = verification_context.authorization_request.as_ref();
+        let mut report = VerificationReport::default();
+
+        // An encrypted Authorization Response conveys the `mdoc_generated_nonce` in its `apu` header.
+        let (response, mdoc_generated_nonce) = match &response.extension.oid4vp_parameters {
+            Oid4vpParams::Jwt { response } => {
+                let (plaintext, party_info) = verification_context
+                    .encryption_key
+                    .as_ref()
+                    .ok_or(anyhow::anyhow!(
+                        "No key is configured to decrypt the Authorization Response."
+                    ))?
+                    .decrypt_with_party_info(response)?;
+                (
+                    Cow::Owned(serde_json::from_slice::<AuthorizationResponse<Self>>(&plaintext)?),
+                    party_info.apu.map(String::from_utf8).transpose()?,
+                )
+            }
+            _ => (Cow::Borrowed(response), None),
         };
 
-        join_all(
-            vp_token
-                .verifiable_presentation()
-                .verifiable_credential
-                .iter()
-                .map(|vc| validator.decode(vc.as_str().to_owned()))
-                .collect::<Vec<_>>(),
-        )
-        .await
-        .into_iter()
-        .collect()
+        let (vp_token, presentation_submission) = match &response.extension.oid4vp_parameters {
+            Oid4vpParams::Jwt { .. } => {
+                return Err(anyhow::anyhow!("The decrypted Authorization Response is encrypted."));
+            }
+            Oid4vpParams::Params {
+                vp_token,
+                presentation_submission,
+            } => (vp_token, presentation_submission),
+            Oid4vpParams::Dcql { vp_token } => {
+                let dcql_query = authorization_request.and_then(|request| request.extension.dcql_query.as_ref());
+                let mut errors = vec![];
+                let mut answered = HashSet::new();
+
+                for (id, presentations) in vp_token {
+                    let credential_query = dcql_query.and_then(|dcql_query| dcql_query.credential_query(id));
+                    if dcql_query.is_some() && credential_query.is_none() {
+                        errors.push(format!("The DCQL query has no Credential Query `{id}`."));
+                        continue;
+                    }
+
+                    // A Credential Query is only answered by at least one verified Credential that matches it, and
+                    // by more than one only when it allows `multiple` Credentials.
+                    let mut matched = 0;
+                    let mut rejected = false;
+                    for presentation in presentations {
+                        let format = credential_query
+                            .map(|credential_query| credential_query.format.clone())
+                            .unwrap_or_else(|| detect_format(presentation));
+                        let first_credential = report.credentials.len();
+                        let (_, holder) = PresentationVerification {
+                            validator: &validator,
+                            status_list_transport: status_list_transport.as_ref(),
+                            authorization_request,
+                            presentation_submission: None,
+                            mdoc_generated_nonce: mdoc_generated_nonce.as_deref(),
+                            report: &mut report,
+                        }
+                        .verify(format.clone(), presentation)
+                        .await?;
+                        if report.holder.is_none() {
+                            report.holder = holder;
+                        }
+
+                        let Some(credential_query) = credential_query else {
+                            continue;
+                        };
+                        for credential in &report.credentials[first_credential..] {
+                            match credential_query.match_candidate(&dcql_candidate(&format, credential)?) {
+                                Ok(_) => matched += 1,
+                                Err(rejection) => {
+                                    rejected = true;
+                                    errors.push(format!("Credential Query `{id}`: {rejection}"));
+                                }
+                            }
+                        }
+                    }
+                    let Some(credential_query) = credential_query else {
+                        continue;
+                    };
+                    if matched > 1 && !credential_query.allows_multiple() {
+                        rejected = true;
+                        errors.push(format!(
+                            "Credential Query `{id}` does not allow multiple Credentials to be presented."
+                        ));
+                    }
+                    if matched > 0 && !rejected {
+                        answered.insert(id.as_str());
+                    }
+                }
+
+                let outcome = match (authorization_request, dcql_query) {
+                    (None, _) => CheckOutcome::Skipped("The Authorization Request is not known.".to_string()),
+                    (Some(_), None) => {
+                        CheckOutcome::Failed("The Authorization Request does not contain a `dcql_query`.".to_string())
+                    }
+                    (Some(_), Some(dcql_query)) => {
+                        if !dcql_query.is_satisfied_by(&answered) {
+                            errors.push("The presented Credentials do not satisfy the DCQL query.".to_string());
+                        }
+                        if errors.is_empty() {
+                            CheckOutcome::Passed
+                        } else {
+                            CheckOutcome::Failed(errors.join(" "))
+                        }
+                    }
+                };
+                report.record(Check::DcqlQuery, None, outcome);
+                return Ok(report);
+            }
+        };
+
+        let format = presentation_submission
+            .descriptor_map
+            .iter()
+            .map(|descriptor| &descriptor.format)
+            .find(|format| {
+                matches!(
+                    format,
+                    ClaimFormatDesignation::VcSdJwt | ClaimFormatDesignation::LdpVp | ClaimFormatDesignation::MsoMdoc
+                )
+            })
+            .cloned()
+            .unwrap_or(ClaimFormatDesignation::JwtVpJson);
+        let (presentation, holder) = PresentationVerification {
+            validator: &validator,
+            status_list_transport: status_list_transport.as_ref(),
+            authorization_request,
+            presentation_submission: Some(presentation_submission),
+            mdoc_generated_nonce: mdoc_generated_nonce.as_deref(),
+            report: &mut report,
+        }
+        .verify(format, vp_token)
+        .await?;
+        report.holder.clone_from(&holder);
+
+        let outcome = match authorization_request {
+            None => CheckOutcome::Skipped("The Authorization Request is not known.".to_string()),
+            Some(request) => match &request.extension.presentation_definition {
+                None => CheckOutcome::Failed(
+                    "The Authorization Request does not contain a `presentation_definition`.".to_string(),
+                ),
+                Some(presentation_definition) => evaluate_submission(
+                    presentation_definition,
+                    presentation_submission,
+                    &presentation,
+                    holder.as_deref(),
+                )
+                .error_message()
+                .map_or(CheckOutcome::Passed, CheckOutcome::Failed),
+            },
+        };
+        report.record(Check::PresentationSubmission, None, outcome);
+        Ok(report)
     }
 }
 
+/// Presents a Verifiable Presentation on behalf of the `subject`, binding it to the `nonce` and `client_id` of the
+/// Authorization Request.
+struct Presenter<'a> {
+    subject: Arc<dyn Subject>,
+    subject_identifier: String,
+    subject_syntax_type: String,
+    signing_algorithm: Algorithm,
+    client_id: &'a str,
+    extension_parameters: &'a AuthorizationRequestParameters,
+    /// The nonce to which the device signatures of presented mdocs are bound.
+    mdoc_generated_nonce: Option<&'a str>,
+}
+
+impl Presenter<'_> {
+    /// SD-JWT VCs and mdocs are presented with only the claims that are `requested`, either by the matching Input
+    /// Descriptor or by the Credential Query.
+    async fn present(
+        &self,
+        verifiable_presentation: &VerifiablePresentation,
+        requested: Option<&dyn RequestedClaims>,
+    ) -> anyhow::Result<String> {
+        let subject = self.subject.clone();
+        let subject_identifier = self.subject_identifier.clone();
+        let subject_syntax_type = self.subject_syntax_type.as_str();
+        let signing_algorithm = self.signing_algorithm;
+        let client_id = self.client_id;
+        let extension_parameters = self.extension_parameters;
+        let requested = || requested.ok_or(anyhow::anyhow!("The claims to disclose are not known."));
+
+        let verifiable_presentation = match verifiable_presentation {
+            VerifiablePresentation::Jwt(verifiable_presentation) => verifiable_presentation,
+            VerifiablePresentation::SdJwt(sd_jwt) => {
+                let sd_jwt = select_disclosures(sd_jwt, requested()?)?
+                    .bind(
+                        subject,
+                        signing_algorithm,
+                        subject_syntax_type,
+                        client_id,
+                        extension_parameters.nonce.to_owned(),
+                    )
+                    .await?;
+                return Ok(sd_jwt.to_string());
+            }
+            VerifiablePresentation::Ldp(verifiable_presentation) => {
+                let mut verifiable_presentation = verifiable_presentation.clone();
+                verifiable_presentation
+                    .entry("holder")
+                    .or_insert(serde_json::Value::String(subject_identifier));
+
+                let mut options = ProofOptions::new(Cryptosuite::try_from(signing_algorithm)?, "authentication");
+                options.challenge = Some(extension_parameters.nonce.to_owned());
+                options.domain = Some(client_id.to_string());
+                let verifiable_presentation =
+                    DataIntegrityProof::sign(verifiable_presentation, options, subject, subject_syntax_type).await?;
+                return Ok(serde_json::to_string(&verifiable_presentation)?);
+            }
+            VerifiablePresentation::Mdoc(issuer_signed) => {
+                let response_uri = extension_parameters
+                    .response_uri
+                    .as_ref()
+                    .ok_or(anyhow::anyhow!("An `mso_mdoc` presentation requires a `response_uri`."))?;
+                if extension_parameters.response_mode.as_deref() != Some(DIRECT_POST_JWT) {
+                    return Err(anyhow::anyhow!(
+                        "An `mso_mdoc` presentation requires the `direct_post.jwt` Response Mode, in which the `mdoc_generated_nonce` is conveyed."
+                    ));
+                }
+                let mdoc_generated_nonce = self
+                    .mdoc_generated_nonce
+                    .ok_or(anyhow::anyhow!("The `mdoc_generated_nonce` is missing."))?;
+
+                let device_response = DeviceResponse::oid4vp(
+                    select_data_elements(issuer_signed, requested()?)?,
+                    subject,
+                    subject_syntax_type,
+                    signing_algorithm,
+                    client_id,
+                    response_uri.as_str(),
+                    &extension_parameters.nonce,
+                    mdoc_generated_nonce,
+                )
+                .await?;
+                return device_response.to_base64url();
+            }
+            VerifiablePresentation::Vcdm20(verifiable_presentation) => {
+                let mut verifiable_presentation = verifiable_presentation.as_ref().clone();
+                verifiable_presentation.holder.get_or_insert(subject_identifier.clone());
+                verifiable_presentation.rfc7519_claims.iss = Some(subject_identifier);
+                verifiable_presentation.rfc7519_claims.aud = Some(client_id.to_string());
+                // TODO: make this configurable.
+                verifiable_presentation.rfc7519_claims.exp = Some((Utc::now() + Duration::minutes(10)).timestamp());
+                verifiable_presentation.rfc7519_claims.iat = Some(Utc::now().timestamp());
+                verifiable_presentation.nonce = Some(extension_parameters.nonce.to_owned());
+
+                return jwt::encode(
+                    subject,
+                    VerifiablePresentationV2::header(signing_algorithm),
+                    verifiable_presentation,
+                    subject_syntax_type,
+                )
+                .await;
+            }
+        };
+
+        let vp_token = VpToken::builder()
+            .iss(subject_identifier.clone())
+            .sub(subject_identifier)
+            .aud(client_id)
+            .nonce(extension_parameters.nonce.to_owned())
+            // TODO: make this configurable.
+            .exp((Utc::now() + Duration::minutes(10)).timestamp())
+            .iat((Utc::now()).timestamp())
+            .verifiable_presentation(verifiable_presentation.as_ref().clone())
+            .build()?;
+
+        jwt::encode(subject, Header::new(signing_algorithm), vp_token, subject_syntax_type).await
+    }
+}
+
+/// Verifies a `vp_token` and records the outcome of each check in the [`VerificationReport`]. Signatures that cannot
+/// be verified are returned as errors, since nothing in the presentation can be relied upon in that case. Each format
+/// returns the verified presentation against which the `descriptor_map` of the presentation submission is evaluated,
+/// together with the identifier of the Holder that is proven to have created it, if the format identifies one.
+struct PresentationVerification<'a> {
+    validator: &'a Validator,
+    status_list_transport: &'a dyn StatusListTransport,
+    authorization_request: Option<&'a Object<OID4VP>>,
+    /// The presentation submission of a response to a `presentation_definition`, which is absent in a response to a
+    /// `dcql_query`.
+    presentation_submission: Option<&'a PresentationSubmission>,
+    /// The `mdoc_generated_nonce` from the `apu` header of an encrypted Authorization Response.
+    mdoc_generated_nonce: Option<&'a str>,
+    report: &'a mut VerificationReport,
+}
+
+impl PresentationVerification<'_> {
+    fn nonce(&self) -> Option<&str> {
+        self.authorization_request
+            .map(|request| request.extension.nonce.as_str())
+    }
+
+    fn client_id(&self) -> Option<&str> {
+        self.authorization_request.map(|request| request.client_id.as_str())
+    }
+
+    /// Returns the Input Descriptor to which a Credential of the `format` is submitted, if it requires limited
+    /// disclosure. A `doc_type` selects the Input Descriptor of an mdoc by its `id`.
+    fn limiting_input_descriptor(
+        &self,
+        format: ClaimFormatDesignation,
+        doc_type: Option<&str>,
+    ) -> Option<&InputDescriptor> {
+        let presentation_definition = self.authorization_request?.extension.presentation_definition.as_ref()?;
+        let presentation_submission = self.presentation_submission?;
+        doc_type
+            .and_then(|doc_type| {
+                presentation_definition
+                    .input_descriptors()
+                    .iter()
+                    .find(|input_descriptor| input_descriptor.id() == doc_type)
+            })
+            .or_else(|| input_descriptor(format, presentation_definition, presentation_submission).ok())
+            .filter(|input_descriptor| input_descriptor.requires_limited_disclosure())
+    }
+
+    /// Records the outcome of the limited disclosure check for the Credential at `idx`, given the claims that are
+    /// disclosed without being requested.
+    fn record_limited_disclosure(&mut self, idx: usize, excess: anyhow::Result<Vec<String>>) {
+        let outcome = match excess {
+            Ok(excess) if excess.is_empty() => CheckOutcome::Passed,
+            Ok(excess) => CheckOutcome::Failed(format!(
+                "The claims `{}` are disclosed, but not requested.",
+                excess.join("`, `")
+            )),
+            Err(error) => CheckOutcome::Failed(error.to_string()),
+        };
+        self.report.record(Check::LimitDisclosure, Some(idx), outcome);
+    }
+
+    /// Records the outcome of the presentation-level `nonce` and `aud` checks.
+    fn record_request_binding(&mut self, nonce: Option<&str>, audience: Option<&str>) {
+        let outcome = matches_request(self.nonce(), nonce, "nonce");
+        self.report.record(Check::Nonce, None, outcome);
+        let outcome = matches_request(self.client_id(), audience, "aud");
+        self.report.record(Check::Audience, None, outcome);
+    }
+
+    /// Records the outcome of the status check for the Credential at `idx`, if it references a status list. The status
+    /// lists must be issued by the `issuer` of the Credential.
+    async fn record_status(
+        &mut self,
+        idx: usize,
+        issuer: Option<String>,
+        status: Option<&serde_json::Value>,
+        credential_status: Option<&serde_json::Value>,
+    ) {
+        if status.is_none() && credential_status.is_none() {
+            return;
+        }
+        let outcome = match issuer {
+            Some(issuer) => match verify_credential_status(
+                status,
+                credential_status,
+                &issuer,
+                self.validator,
+                self.status_list_transport,
+            )
+            .await
+            {
+                Ok(()) => CheckOutcome::Passed,
+                Err(error) => CheckOutcome::Failed(error.to_string()),
+            },
+            None => CheckOutcome::Failed("The Credential does not identify its issuer.".to_string()),
+        };
+        self.report.record(Check::Status, Some(idx), outcome);
+    }
+
+    /// Verifies the `vp_token` according to the `format` in which it is presented.
+    async fn verify(
+        self,
+        format: ClaimFormatDesignation,
+        vp_token: &str,
+    ) -> anyhow::Result<(serde_json::Value, Option<String>)> {
+        match format {
+            ClaimFormatDesignation::VcSdJwt => self.sd_jwt(vp_token).await,
+            ClaimFormatDesignation::LdpVp | ClaimFormatDesignation::LdpVc => self.ldp(vp_token).await,
+            ClaimFormatDesignation::MsoMdoc => self.mdoc(vp_token).await,
+            _ => self.jwt(vp_token).await,
+        }
+    }
+
+    /// An SD-JWT VC is presented as is, including the Disclosures and a Key Binding JWT.
+    async fn sd_jwt(mut self, vp_token: &str) -> anyhow::Result<(serde_json::Value, Option<String>)> {
+        let sd_jwt: SdJwt = vp_token.parse()?;
+        let claims = sd_jwt.decode(self.validator).await?;
+        // The Key Binding JWT must be signed with the key to which the SD-JWT is bound: the `cnf` key, or else a key of
+        // the `sub`.
+        let (key_binding, holder_binding) = match sd_jwt.verify_key_binding(&claims, self.validator).await {
+            Ok(key_binding) => (Some(key_binding), CheckOutcome::Passed),
+            Err(error) => (None, CheckOutcome::Failed(error.to_string())),
+        };
+        self.record_request_binding(
+            key_binding.as_ref().map(|key_binding| key_binding.nonce.as_str()),
+            key_binding.as_ref().map(|key_binding| key_binding.aud.as_str()),
+        );
+
+        let timestamp = |claim: &str| {
+            claims
+                .get(claim)
+                .and_then(serde_json::Value::as_i64)
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        };
+        let validity_period = within_validity_period(timestamp("nbf"), timestamp("exp"));
+        let status = claims.get("status").cloned();
+        let issuer = claims
+            .get("iss")
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned);
+        let excess_disclosures = self
+            .limiting_input_descriptor(ClaimFormatDesignation::VcSdJwt, None)
+            .map(|input_descriptor| excess_disclosures(&sd_jwt, input_descriptor));
+        let presentation = serde_json::Value::Object(claims.clone());
+        // Only when the SD-JWT is bound to its `sub`, the Key Binding JWT proves that the SD-JWT is presented by it.
+        let holder = match (&key_binding, claims.get("cnf")) {
+            (Some(_), None) => claims
+                .get("sub")
+                .and_then(serde_json::Value::as_str)
+                .map(ToOwned::to_owned),
+            _ => None,
+        };
+        let idx = self
+            .report
+            .push_credential(VerifiedCredential::SdJwt { claims, key_binding });
+        self.report.record(Check::HolderBinding, Some(idx), holder_binding);
+        self.report.record(Check::ValidityPeriod, Some(idx), validity_period);
+        self.record_status(idx, issuer, status.as_ref(), None).await;
+        if let Some(excess_disclosures) = excess_disclosures {
+            self.record_limited_disclosure(idx, excess_disclosures);
+        }
+        Ok((presentation, holder))
+    }
+
+    /// A Verifiable Presentation secured with a Data Integrity proof contains Credentials that are secured in the same
+    /// way.
+    async fn ldp(mut self, vp_token: &str) -> anyhow::Result<(serde_json::Value, Option<String>)> {
+        let verifiable_presentation: JsonObject = serde_json::from_str(vp_token)?;
+        let presentation_proof = DataIntegrityProof::verify(&verifiable_presentation, self.validator).await?;
+        if presentation_proof.proof_purpose != "authentication" {
+            return Err(anyhow::anyhow!(
+                "The presentation proof must have the `authentication` purpose."
+            ));
+        }
+        self.record_request_binding(
+            presentation_proof.challenge.as_deref(),
+            presentation_proof.domain.as_deref(),
+        );
+
+        let credentials = match verifiable_presentation.get("verifiableCredential") {
+            Some(serde_json::Value::Array(credentials)) => credentials.clone(),
+            Some(credential) => vec![credential.clone()],
+            None => vec![],
+        };
+        for credential in credentials {
+            let credential = match credential {
+                serde_json::Value::Object(credential) => credential,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Only `ldp_vc` Credentials can be presented in an `ldp_vp`."
+                    ))
+                }
+            };
+            let credential_proof = DataIntegrityProof::verify(&credential, self.validator).await?;
+            if credential_proof.proof_purpose != "assertionMethod" {
+                return Err(anyhow::anyhow!(
+                    "The credential proof must have the `assertionMethod` purpose."
+                ));
+            }
+            verify_issuer(
+                &serde_json::Value::Object(credential.clone()),
+                &credential_proof.verification_method,
+            )?;
+
+            let date = |claim: &str| {
+                credential
+                    .get(claim)
+                    .map(|date| serde_json::from_value::<DateTime<Utc>>(date.clone()))
+                    .transpose()
+            };
+            let validity_period = within_validity_period(
+                date("validFrom")?.or(date("issuanceDate")?),
+                date("validUntil")?.or(date("expirationDate")?),
+            );
+            let holder_binding = bound_to_holder(
+                Some(&presentation_proof.verification_method),
+                credential
+                    .get("credentialSubject")
+                    .and_then(|credential_subject| credential_subject.get("id"))
+                    .and_then(serde_json::Value::as_str),
+            );
+            let credential_status = credential.get("credentialStatus").cloned();
+            let issuer = credential_issuer(&serde_json::Value::Object(credential.clone())).map(ToOwned::to_owned);
+            let idx = self.report.push_credential(VerifiedCredential::Ldp {
+                credential,
+                presentation_proof: presentation_proof.clone(),
+            });
+            self.report.record(Check::HolderBinding, Some(idx), holder_binding);
+            self.report.record(Check::ValidityPeriod, Some(idx), validity_period);
+            self.record_status(idx, issuer, None, credential_status.as_ref()).await;
+        }
+        Ok((
+            serde_json::Value::Object(verifiable_presentation),
+            Some(presentation_proof.verification_method),
+        ))
+    }
+
+    /// An mdoc is presented as a base64url-encoded DeviceResponse. The device signature covers the `client_id`,
+    /// `response_uri` and `nonce` of the Authorization Request, so it proves both the possession of the device key and
+    /// that the mdoc is presented in response to the request.
+    async fn mdoc(mut self, vp_token: &str) -> anyhow::Result<(serde_json::Value, Option<String>)> {
+        let device_response: DeviceResponse = vp_token.parse()?;
+        let mut claims = vec![];
+        for document in device_response.documents {
+            let mobile_security_object = document.issuer_signed.verify(self.validator).await?;
+            if mobile_security_object.doc_type != document.doc_type {
+                return Err(anyhow::anyhow!(
+                    "The `docType` does not match the Mobile Security Object."
+                ));
+            }
+            let validity_info = &mobile_security_object.validity_info;
+            let validity_period =
+                within_validity_period(Some(validity_info.valid_from), Some(validity_info.valid_until));
+
+            let verified_mdoc = VerifiedMdoc {
+                doc_type: document.doc_type.clone(),
+                claims: document.issuer_signed.claims()?,
+                document,
+                mdoc_generated_nonce: self.mdoc_generated_nonce.map(ToOwned::to_owned),
+            };
+            let device_signature = match self.authorization_request {
+                None => CheckOutcome::Skipped("The Authorization Request is not known.".to_string()),
+                Some(request) => match &request.extension.response_uri {
+                    None => CheckOutcome::Failed("An `mso_mdoc` presentation requires a `response_uri`.".to_string()),
+                    Some(response_uri) => match verified_mdoc.verify_device_signature(
+                        &request.client_id,
+                        response_uri.as_str(),
+                        &request.extension.nonce,
+                    ) {
+                        Ok(()) => CheckOutcome::Passed,
+                        Err(error) => CheckOutcome::Failed(error.to_string()),
+                    },
+                },
+            };
+
+            let excess_data_elements = self
+                .limiting_input_descriptor(ClaimFormatDesignation::MsoMdoc, Some(&verified_mdoc.doc_type))
+                .map(|input_descriptor| excess_data_elements(&verified_mdoc.document.issuer_signed, input_descriptor));
+            claims.push(verified_mdoc.claims.clone());
+            let idx = self
+                .report
+                .push_credential(VerifiedCredential::Mdoc(Box::new(verified_mdoc)));
+            for check in [Check::Nonce, Check::Audience, Check::HolderBinding] {
+                self.report.record(check, Some(idx), device_signature.clone());
+            }
+            self.report.record(Check::ValidityPeriod, Some(idx), validity_period);
+            if let Some(excess_data_elements) = excess_data_elements {
+                self.record_limited_disclosure(idx, excess_data_elements);
+            }
+        }
+        // The data elements of a single mdoc are addressed directly, those of multiple mdocs by their index. An mdoc
+        // does not identify its Holder.
+        let presentation = match <[_; 1]>::try_from(claims) {
+            Ok([claims]) => claims,
+            Err(claims) => serde_json::Value::Array(claims),
+        };
+        Ok((presentation, None))
+    }
+
+    /// A VCDM 2.0 Verifiable Presentation is the payload of a `vp+jwt` and envelops the Credentials, whereas a VCDM 1.1
+    /// Verifiable Presentation is wrapped in the `vp` claim. Both may contain Credentials of either encoding.
+    async fn jwt(mut self, vp_token: &str) -> anyhow::Result<(serde_json::Value, Option<String>)> {
+        // The Holder is the signer of the Verifiable Presentation, rather than the unverified `iss` or `holder`.
+        let (kid, _) = jwt::extract_header(vp_token)?;
+        let (rfc7519_claims, nonce, credentials): (RFC7519Claims, Option<String>, Vec<String>) =
+            if jsonwebtoken::decode_header(vp_token)?.typ.as_deref() == Some(VP_JWT_TYP) {
+                let verifiable_presentation: VerifiablePresentationV2 =
+                    self.validator.decode(vp_token.to_owned()).await?;
+                let credentials = verifiable_presentation
+                    .credentials()?
+                    .into_iter()
+                    .map(ToOwned::to_owned)
+                    .collect();
+                (
+                    verifiable_presentation.rfc7519_claims,
+                    verifiable_presentation.nonce,
+                    credentials,
+                )
+            } else {
+                let vp_token: VpToken = self.validator.decode(vp_token.to_owned()).await?;
+                let credentials = vp_token
+                    .verifiable_presentation()
+                    .verifiable_credential
+                    .iter()
+                    .map(|vc| vc.as_str().to_owned())
+                    .collect();
+                (vp_token.rfc7519_claims().clone(), vp_token.nonce().clone(), credentials)
+            };
+
+        self.record_request_binding(nonce.as_deref(), rfc7519_claims.aud.as_deref());
+        let timestamp = |timestamp: Option<i64>| timestamp.and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+        self.report.record(
+            Check::ValidityPeriod,
+            None,
+            within_validity_period(timestamp(rfc7519_claims.nbf), timestamp(rfc7519_claims.exp)),
+        );
+
+        for credential in credentials {
+            let credential = JwtCredential::decode(self.validator, &credential).await?;
+            let holder_binding = match (credential.subject_id(), credential.cnf()) {
+                (None, Some(cnf)) => bound_to_key(cnf, &self.validator.public_key(&kid).await?),
+                (subject_id, _) => bound_to_holder(Some(&kid), subject_id),
+            };
+            let validity_period = within_validity_period(credential.valid_from(), credential.valid_until());
+            let credential_status = credential.credential_status().cloned();
+            let issuer = credential_issuer(&serde_json::to_value(&credential)?).map(ToOwned::to_owned);
+            let idx = self.report.push_credential(VerifiedCredential::Jwt(credential));
+            self.report.record(Check::HolderBinding, Some(idx), holder_binding);
+            self.report.record(Check::ValidityPeriod, Some(idx), validity_period);
+            self.record_status(idx, issuer, None, credential_status.as_ref()).await;
+        }
+        Ok((serde_json::Value::String(vp_token.to_owned()), Some(kid)))
+    }
+}
+
+/// Determines the format of a presentation in a response to a `dcql_query` by its encoding, for when the Credential
+/// Query it answers is not known.
+fn detect_format(presentation: &str) -> ClaimFormatDesignation {
+    if presentation.starts_with('{') {
+        ClaimFormatDesignation::LdpVp
+    } else if presentation.contains('~') {
+        ClaimFormatDesignation::VcSdJwt
+    } else if presentation.split('.').count() == 3 {
+        ClaimFormatDesignation::JwtVpJson
+    } else {
+        ClaimFormatDesignation::MsoMdoc
+    }
+}
+
+/// Returns the claims of a verified Credential against which the Credential Query is matched. The claims of a VCDM 1.1
+/// Credential are those of its `vc` claim.
+fn dcql_candidate(format: &ClaimFormatDesignation, credential: &VerifiedCredential) -> anyhow::Result<DcqlCandidate> {
+    let (claims, doc_type) = match credential {
+        VerifiedCredential::Jwt(credential) => {
+            let mut claims = serde_json::to_value(credential)?;
+            (
+                claims.get_mut("vc").map(serde_json::Value::take).unwrap_or(claims),
+                None,
+            )
+        }
+        VerifiedCredential::SdJwt { claims, .. } => (serde_json::Value::Object(claims.clone()), None),
+        VerifiedCredential::Ldp { credential, .. } => (serde_json::Value::Object(credential.clone()), None),
+        VerifiedCredential::Mdoc(mdoc) => (mdoc.claims.clone(), Some(mdoc.doc_type.clone())),
+    };
+    Ok(DcqlCandidate {
+        format: format.clone(),
+        doc_type,
+        claims,
+    })
+}
+
+/// Returns the Input Descriptor of the `presentation_definition` to which a credential of the given `format` is
+/// submitted.
+fn input_descriptor<'a>(
+    format: ClaimFormatDesignation,
+    presentation_definition: &'a PresentationDefinition,
+    presentation_submission: &PresentationSubmission,
+) -> anyhow::Result<&'a InputDescriptor> {
+    presentation_submission
+        .descriptor_map
+        .iter()
+        .find(|descriptor| descriptor.format == format)
+        .and_then(|descriptor| {
+            presentation_definition
+                .input_descriptors()
+                .iter()
+                .find(|input_descriptor| input_descriptor.id() == &descriptor.id)
+        })
+        .ok_or(anyhow::anyhow!(
+            "No input descriptor found for the `{}` credential.",
+            serde_json::to_value(format)?.as_str().unwrap_or_default()
+        ))
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct AuthorizationResponseParameters {
     #[serde(flatten, with = "serde_oid4vp_response")]
     pub oid4vp_parameters: Oid4vpParams,
 }
 
-pub struct AuthorizationResponseInput {
-    pub verifiable_presentation: Presentation<Jwt>,
-    pub presentation_submission: PresentationSubmission,
+/// The presentations that are generated for the `vp_token`, in the order of the [`AuthorizationResponseInput`].
+pub struct Presentations {
+    pub vp_tokens: Vec<String>,
+    /// The nonce to which the device signatures of presented mdocs are bound, which is conveyed in the `apu` header of
+    /// the encrypted Authorization Response.
+    pub mdoc_generated_nonce: Option<String>,
+}
+
+/// Returns the `vp_token` parameters for the `vp_tokens` that are generated for the `user_input`.
+pub(crate) fn oid4vp_parameters(
+    vp_tokens: Vec<String>,
+    user_input: AuthorizationResponseInput,
+) -> anyhow::Result<Oid4vpParams> {
+    Ok(match user_input {
+        AuthorizationResponseInput::PresentationExchange {
+            presentation_submission,
+            ..
+        } => Oid4vpParams::Params {
+            vp_token: vp_tokens
+                .into_iter()
+                .next()
+                .ok_or(anyhow::anyhow!("The `vp_token` is missing."))?,
+            presentation_submission,
+        },
+        // The presentations are generated in the order of the `id`s of the Credential Queries.
+        AuthorizationResponseInput::Dcql {
+            verifiable_presentations,
+        } => Oid4vpParams::Dcql {
+            vp_token: verifiable_presentations
+                .into_keys()
+                .zip(vp_tokens)
+                .map(|(id, vp_token)| (id, vec![vp_token]))
+                .collect(),
+        },
+    })
+}
+
+/// The presentations with which the Wallet answers the Authorization Request.
+pub enum AuthorizationResponseInput {
+    /// A Verifiable Presentation answering a `presentation_definition`, together with the presentation submission that
+    /// maps its Credentials to the Input Descriptors.
+    PresentationExchange {
+        verifiable_presentation: VerifiablePresentation,
+        presentation_submission: PresentationSubmission,
+    },
+    /// The Verifiable Presentations answering a `dcql_query`, by the `id` of the Credential Query they answer.
+    Dcql {
+        verifiable_presentations: BTreeMap<String, VerifiablePresentation>,
+    },
+}
+
+/// The Verifiable Presentation that is included in the `vp_token`.
+pub enum VerifiablePresentation {
+    Jwt(Box<Presentation<Jwt>>),
+    /// An SD-JWT VC including all its Disclosures. Only the Disclosures that are requested by the matching Input
+    /// Descriptor are presented, together with a Key Binding JWT.
+    SdJwt(SdJwt),
+    /// An unsecured Verifiable Presentation containing `ldp_vc` Credentials. It is secured with a Data Integrity proof
+    /// of which the `challenge` and `domain` are the `nonce` and `client_id` of the Authorization Request.
+    Ldp(JsonObject),
+    /// An mdoc including all its data elements. Only the data elements that are requested by the matching Input
+    /// Descriptor are presented, in a DeviceResponse that is signed with the device key.
+    Mdoc(Box<IssuerSigned>),
+    /// A VCDM 2.0 Verifiable Presentation enveloping `vc+jwt` Credentials, which is secured as a `vp+jwt`.
+    Vcdm20(Box<VerifiablePresentationV2>),
+}
+
+impl From<Presentation<Jwt>> for VerifiablePresentation {
+    fn from(verifiable_presentation: Presentation<Jwt>) -> Self {
+        VerifiablePresentation::Jwt(Box::new(verifiable_presentation))
+    }
+}
+
+impl From<VerifiablePresentationV2> for VerifiablePresentation {
+    fn from(verifiable_presentation: VerifiablePresentationV2) -> Self {
+        VerifiablePresentation::Vcdm20(Box::new(verifiable_presentation))
+    }
+}
+
+impl From<SdJwt> for VerifiablePresentation {
+    fn from(sd_jwt: SdJwt) -> Self {
+        VerifiablePresentation::SdJwt(sd_jwt)
+    }
+}
+
+impl From<IssuerSigned> for VerifiablePresentation {
+    fn from(issuer_signed: IssuerSigned) -> Self {
+        VerifiablePresentation::Mdoc(Box::new(issuer_signed))
+    }
+}
+
+/// A Verifiable Credential of which the signature is verified by the Relying Party.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum VerifiedCredential {
+    /// A `jwt_vc_json` Credential according to the VCDM 1.1 or a `vc+jwt` Credential according to the VCDM 2.0.
+    Jwt(JwtCredential),
+    /// The disclosed claims of an SD-JWT VC together with the claims of its Key Binding JWT, unless the Key Binding JWT
+    /// could not be verified.
+    SdJwt {
+        claims: JsonObject,
+        key_binding: Option<KeyBindingJwtClaims>,
+    },
+    /// An `ldp_vc` Credential of which the Data Integrity proof is verified, together with the proof of the Verifiable
+    /// Presentation in which it was presented.
+    Ldp {
+        credential: JsonObject,
+        presentation_proof: DataIntegrityProof,
+    },
+    /// An mdoc of which the Issuer signature and the digests of the disclosed data elements are verified.
+    Mdoc(Box<VerifiedMdoc>),
+}
+
+/// The disclosed data elements of an mdoc per namespace. The device signature is bound to the Authorization Request, so
+/// it is only verified when the Authorization Request is known.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct VerifiedMdoc {
+    pub doc_type: String,
+    pub claims: serde_json::Value,
+    #[serde(skip)]
+    pub document: Document,
+    /// The `mdoc_generated_nonce` from the `apu` header of the encrypted Authorization Response.
+    #[serde(skip)]
+    pub mdoc_generated_nonce: Option<String>,
+}
+
+impl VerifiedMdoc {
+    /// Verifies the device signature over the SessionTranscript that is derived from the Authorization Request.
+    pub fn verify_device_signature(&self, client_id: &str, response_uri: &str, nonce: &str) -> anyhow::Result<()> {
+        let mdoc_generated_nonce = self.mdoc_generated_nonce.as_deref().ok_or(anyhow::anyhow!(
+            "The `mdoc_generated_nonce` is not known, as the Authorization Response is not encrypted."
+        ))?;
+        self.document.verify_device_signature(&SessionTranscript::oid4vp(
+            client_id,
+            response_uri,
+            nonce,
+            mdoc_generated_nonce,
+        )?)
+    }
 }
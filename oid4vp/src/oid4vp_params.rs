@@ -1,8 +1,10 @@
 use dif_presentation_exchange::PresentationSubmission;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Represents the parameters of an OpenID4VP response. It can hold a Verifiable Presentation Token and a Presentation
-/// Submission, or a JWT containing them.
+/// Submission, the presentations that answer a DCQL query by the `id` of the Credential Query, or a JWT containing
+/// them.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum Oid4vpParams {
@@ -13,6 +15,9 @@ pub enum Oid4vpParams {
         vp_token: String,
         presentation_submission: PresentationSubmission,
     },
+    Dcql {
+        vp_token: BTreeMap<String, Vec<String>>,
+    },
 }
 
 /// Custom serializer and deserializer for [`Oid4vpParams`].
@@ -28,7 +33,11 @@ pub mod serde_oid4vp_response {
         S: serde::Serializer,
     {
         match oid4vp_response {
-            Oid4vpParams::Jwt { response } => response.serialize(serializer),
+            Oid4vpParams::Jwt { response } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("response", response)?;
+                map.end()
+            }
             Oid4vpParams::Params {
                 vp_token,
                 presentation_submission,
@@ -41,6 +50,14 @@ pub mod serde_oid4vp_response {
                 )?;
                 map.end()
             }
+            Oid4vpParams::Dcql { vp_token } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    "vp_token",
+                    &serde_json::to_string(&vp_token).map_err(ser::Error::custom)?,
+                )?;
+                map.end()
+            }
         }
     }
 
@@ -50,7 +67,20 @@ pub mod serde_oid4vp_response {
     {
         let oid4vp_response = serde_json::Value::deserialize(deserializer)?;
         match oid4vp_response {
-            serde_json::Value::String(response) => Ok(Oid4vpParams::Jwt { response }),
+            serde_json::Value::Object(map) if map.contains_key("response") => Ok(Oid4vpParams::Jwt {
+                response: map["response"]
+                    .as_str()
+                    .ok_or_else(|| de::Error::custom("`response` parameter must be a string."))?
+                    .to_string(),
+            }),
+            serde_json::Value::Object(map) if !map.contains_key("presentation_submission") => {
+                let vp_token = map
+                    .get("vp_token")
+                    .ok_or_else(|| de::Error::custom("`vp_token` parameter is required."))?;
+                Ok(Oid4vpParams::Dcql {
+                    vp_token: dcql_vp_token(vp_token).map_err(de::Error::custom)?,
+                })
+            }
             serde_json::Value::Object(map) => {
                 let vp_token = map.get("vp_token").ok_or_else(|| {
                     de::Error::custom(
@@ -74,4 +104,84 @@ pub mod serde_oid4vp_response {
             _ => Err(de::Error::custom("Invalid `oid4vp_response` parameter.")),
         }
     }
+
+    /// Parses the `vp_token` of a response to a DCQL query, which is a JSON object, possibly encoded as a string. Each
+    /// Credential Query is answered with either a single presentation or an array of presentations.
+    fn dcql_vp_token(vp_token: &serde_json::Value) -> Result<BTreeMap<String, Vec<String>>, String> {
+        let vp_token = match vp_token {
+            serde_json::Value::String(vp_token) => serde_json::from_str(vp_token).map_err(|error| error.to_string())?,
+            vp_token => vp_token.clone(),
+        };
+        let serde_json::Value::Object(vp_token) = vp_token else {
+            return Err(
+                "`vp_token` parameter must be an object when no `presentation_submission` is given.".to_string(),
+            );
+        };
+        vp_token
+            .into_iter()
+            .map(|(id, presentations)| {
+                let presentations: Vec<String> = match presentations {
+                    serde_json::Value::String(presentation) => vec![presentation],
+                    presentations => serde_json::from_value(presentations).map_err(|error| error.to_string())?,
+                };
+                if presentations.is_empty() {
+                    return Err(format!(
+                        "Credential Query `{id}` must be answered with at least one presentation."
+                    ));
+                }
+                Ok((id, presentations))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Response {
+        #[serde(flatten, with = "serde_oid4vp_response")]
+        oid4vp_parameters: Oid4vpParams,
+    }
+
+    #[test]
+    fn test_dcql_vp_token() {
+        let response = Response {
+            oid4vp_parameters: Oid4vpParams::Dcql {
+                vp_token: BTreeMap::from([("pid".to_string(), vec!["eyJhbGciOiJFUzI1NiJ9...~".to_string()])]),
+            },
+        };
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            serialized,
+            json!({ "vp_token": r#"{"pid":["eyJhbGciOiJFUzI1NiJ9...~"]}"# })
+        );
+        assert_eq!(serde_json::from_value::<Response>(serialized).unwrap(), response);
+
+        // A single presentation per Credential Query is accepted as well.
+        assert_eq!(
+            serde_json::from_value::<Response>(json!({ "vp_token": { "pid": "eyJhbGciOiJFUzI1NiJ9...~" } })).unwrap(),
+            response
+        );
+
+        // A Credential Query cannot be answered without a presentation.
+        assert!(serde_json::from_value::<Response>(json!({ "vp_token": { "pid": [] } })).is_err());
+    }
+
+    #[test]
+    fn test_jwt_response() {
+        let response = Response {
+            oid4vp_parameters: Oid4vpParams::Jwt {
+                response: "eyJhbGciOiJFQ0RILUVTIn0..iv.ciphertext.tag".to_string(),
+            },
+        };
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            serialized,
+            json!({ "response": "eyJhbGciOiJFQ0RILUVTIn0..iv.ciphertext.tag" })
+        );
+        assert_eq!(serde_json::from_value::<Response>(serialized).unwrap(), response);
+    }
 }
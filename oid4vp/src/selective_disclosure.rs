@@ -0,0 +1,211 @@
+use crate::dcql::{ClaimPathElement, CredentialQuery};
+use anyhow::{anyhow, Result};
+use dif_presentation_exchange::InputDescriptor;
+use oid4vc_core::sd_jwt::SdJwt;
+use oid4vci::credential_format_profiles::iso_mdl::mdoc::IssuerSigned;
+
+/// The claims that a Verifier requests from a single Credential, either by the `fields` of an Input Descriptor or by
+/// the `claims` of a DCQL Credential Query.
+pub trait RequestedClaims {
+    /// Returns the locations of the requested claims as their segments, in which `*` stands for any array element.
+    fn requested_paths(&self) -> Result<Vec<Vec<String>>>;
+}
+
+impl RequestedClaims for InputDescriptor {
+    fn requested_paths(&self) -> Result<Vec<Vec<String>>> {
+        self.constraints()
+            .fields()
+            .iter()
+            .flatten()
+            .flat_map(|field| field.path())
+            .map(|path| json_path_segments(path).ok_or(anyhow!("Unsupported JSONPath expression: {path}")))
+            .collect()
+    }
+}
+
+impl RequestedClaims for CredentialQuery {
+    fn requested_paths(&self) -> Result<Vec<Vec<String>>> {
+        Ok(CredentialQuery::requested_paths(self)
+            .map(|path| {
+                path.iter()
+                    .map(|element| match element {
+                        ClaimPathElement::Key(key) => key.clone(),
+                        ClaimPathElement::Index(index) => index.to_string(),
+                        ClaimPathElement::Wildcard(()) => "*".to_string(),
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// Returns the SD-JWT with only the Disclosures that are needed to satisfy the `requested` claims. A Disclosure is
+/// needed when it contains a requested claim, a claim nested within a requested claim, or a claim that a requested
+/// claim is nested within.
+pub fn select_disclosures(sd_jwt: &SdJwt, requested: &(impl RequestedClaims + ?Sized)) -> Result<SdJwt> {
+    let requested_paths = requested.requested_paths()?;
+
+    let disclosures = sd_jwt
+        .disclosure_paths()?
+        .into_iter()
+        .filter(|(disclosure_path, _)| is_requested(&requested_paths, disclosure_path))
+        .map(|(_, disclosure)| disclosure.clone())
+        .collect();
+
+    Ok(SdJwt {
+        jwt: sd_jwt.jwt.clone(),
+        disclosures,
+        key_binding_jwt: None,
+    })
+}
+
+/// Returns the locations of the claims of which the Disclosures are not needed to satisfy the `fields` of the
+/// `input_descriptor`, which must not be presented when the Input Descriptor requires limited disclosure.
+pub fn excess_disclosures(sd_jwt: &SdJwt, input_descriptor: &InputDescriptor) -> Result<Vec<String>> {
+    let requested_paths = input_descriptor.requested_paths()?;
+
+    Ok(sd_jwt
+        .disclosure_paths()?
+        .into_iter()
+        .filter(|(disclosure_path, _)| !is_requested(&requested_paths, disclosure_path))
+        .map(|(disclosure_path, _)| format!("$.{}", disclosure_path.join(".")))
+        .collect())
+}
+
+fn is_requested(requested_paths: &[Vec<String>], disclosure_path: &[String]) -> bool {
+    requested_paths.iter().any(|requested_path| {
+        requested_path
+            .iter()
+            .zip(disclosure_path)
+            .all(|(requested, disclosed)| requested == "*" || requested == disclosed)
+    })
+}
+
+/// Returns the mdoc with only the data elements that are `requested`. Data elements are requested by their namespace
+/// and identifier, e.g. `$['org.iso.18013.5.1']['given_name']`, as described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#appendix-A.3.1-4
+pub fn select_data_elements(
+    issuer_signed: &IssuerSigned,
+    requested: &(impl RequestedClaims + ?Sized),
+) -> Result<IssuerSigned> {
+    let requested_paths = requested_data_elements(requested)?;
+
+    let name_spaces = issuer_signed
+        .name_spaces
+        .iter()
+        .map(|(name_space, items)| {
+            let items = items
+                .iter()
+                .filter(|item| is_requested_data_element(&requested_paths, name_space, &item.item.element_identifier))
+                .cloned()
+                .collect::<Vec<_>>();
+            (name_space.clone(), items)
+        })
+        .filter(|(_, items)| !items.is_empty())
+        .collect();
+
+    Ok(IssuerSigned {
+        name_spaces,
+        issuer_auth: issuer_signed.issuer_auth.clone(),
+    })
+}
+
+/// Returns the data elements of the mdoc that are not requested by the `fields` of the `input_descriptor`, which must
+/// not be presented when the Input Descriptor requires limited disclosure.
+pub fn excess_data_elements(issuer_signed: &IssuerSigned, input_descriptor: &InputDescriptor) -> Result<Vec<String>> {
+    let requested_paths = requested_data_elements(input_descriptor)?;
+
+    Ok(issuer_signed
+        .name_spaces
+        .iter()
+        .flat_map(|(name_space, items)| {
+            items
+                .iter()
+                .map(move |item| (name_space, &item.item.element_identifier))
+        })
+        .filter(|(name_space, element_identifier)| {
+            !is_requested_data_element(&requested_paths, name_space, element_identifier)
+        })
+        .map(|(name_space, element_identifier)| format!("$['{name_space}']['{element_identifier}']"))
+        .collect())
+}
+
+fn requested_data_elements(requested: &(impl RequestedClaims + ?Sized)) -> Result<Vec<(String, String)>> {
+    requested
+        .requested_paths()?
+        .into_iter()
+        .map(|path| match path.as_slice() {
+            [name_space, element_identifier] => Ok((name_space.clone(), element_identifier.clone())),
+            _ => Err(anyhow!("Unsupported mdoc data element path: {}", path.join("."))),
+        })
+        .collect()
+}
+
+fn is_requested_data_element(requested_paths: &[(String, String)], name_space: &str, element_identifier: &str) -> bool {
+    requested_paths
+        .iter()
+        .any(|(requested_name_space, requested_element_identifier)| {
+            requested_name_space == name_space
+                && (requested_element_identifier == "*" || requested_element_identifier == element_identifier)
+        })
+}
+
+/// Splits a JSONPath expression consisting of member names, array indices and wildcards (e.g. `$.address.locality`,
+/// `$['family_name']` or `$.nationalities[*]`) into its segments. Returns `None` for other expressions.
+fn json_path_segments(path: &str) -> Option<Vec<String>> {
+    let mut segments = vec![];
+    let mut rest = path.strip_prefix('$')?;
+
+    while !rest.is_empty() {
+        if let Some(member) = rest.strip_prefix('.') {
+            let end = member.find(['.', '[']).unwrap_or(member.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(member[..end].to_string());
+            rest = &member[end..];
+        } else if let Some(subscript) = rest.strip_prefix('[') {
+            let end = subscript.find(']')?;
+            let segment = &subscript[..end];
+            let segment = match segment.strip_prefix(['\'', '"']) {
+                Some(quoted) => quoted.strip_suffix(['\'', '"'])?,
+                None if segment == "*" || segment.parse::<usize>().is_ok() => segment,
+                None => return None,
+            };
+            segments.push(segment.to_string());
+            rest = &subscript[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_path_segments() {
+        assert_eq!(
+            json_path_segments("$.address.locality"),
+            Some(vec!["address".to_string(), "locality".to_string()])
+        );
+        assert_eq!(
+            json_path_segments("$['family_name']"),
+            Some(vec!["family_name".to_string()])
+        );
+        assert_eq!(
+            json_path_segments("$.nationalities[*]"),
+            Some(vec!["nationalities".to_string(), "*".to_string()])
+        );
+        assert_eq!(
+            json_path_segments("$.nationalities[1]"),
+            Some(vec!["nationalities".to_string(), "1".to_string()])
+        );
+        assert_eq!(json_path_segments("$"), Some(vec![]));
+        assert_eq!(json_path_segments("$..given_name"), None);
+        assert_eq!(json_path_segments("$.nationalities[?(@ == 'DE')]"), None);
+    }
+}
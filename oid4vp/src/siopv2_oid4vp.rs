@@ -0,0 +1,547 @@
+use crate::authorization_request::{ensure_single_query, ClientIdScheme};
+use crate::dcql::DcqlQuery;
+use crate::oid4vp::{self, OID4VP};
+use crate::verification::{matches_request, Check, CheckOutcome, VerificationReport};
+use anyhow::{anyhow, Result};
+use dif_presentation_exchange::presentation_definition::ClaimFormatProperty;
+use dif_presentation_exchange::relational_constraints::same_identifier;
+use dif_presentation_exchange::{ClaimFormatDesignation, PresentationDefinition};
+use is_empty::IsEmpty;
+use jsonwebtoken::Algorithm;
+use monostate::MustBe;
+use oid4vc_core::authorization_request::{AuthorizationRequest, Object};
+use oid4vc_core::authorization_response::AuthorizationResponse;
+use oid4vc_core::client_metadata::ClientMetadataResource;
+use oid4vc_core::openid4vc_extension::{Extension, OpenID4VC, RequestHandle, ResponseHandle, VerificationContext};
+use oid4vc_core::resolver::ReferenceResolver;
+use oid4vc_core::{builder_fn, scope::Scope, RFC7519Claims, Subject, SubjectSyntaxType, Validator};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use siopv2::{siopv2::SIOPv2, ClaimRequests, IdToken, StandardClaimsValues};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// This is the [`RequestHandle`] for the [`SIOPv2OID4VP`] extension.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RequestHandler {}
+impl RequestHandle for RequestHandler {
+    type Parameters = AuthorizationRequestParameters;
+    type Builder = AuthorizationRequestBuilder;
+}
+
+/// This is the [`ResponseHandle`] for the [`SIOPv2OID4VP`] extension.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ResponseHandler {}
+impl ResponseHandle for ResponseHandler {
+    type Input = AuthorizationResponseInput;
+    type Tokens = (String, oid4vp::Presentations);
+    type Parameters = AuthorizationResponseParameters;
+    type ResponseItem = VerifiedResponse;
+}
+
+/// This is the [`Extension`] implementation for requests with the `vp_token id_token` Response Type, which ask for
+/// both a Self-Issued ID Token and a VP Token in a single response, as described here:
+/// https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-combining-this-specificatio
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SIOPv2OID4VP;
+impl OpenID4VC for SIOPv2OID4VP {}
+impl Extension for SIOPv2OID4VP {
+    type RequestHandle = RequestHandler;
+    type ResponseHandle = ResponseHandler;
+
+    async fn resolve_references(
+        extension_parameters: &mut <Self::RequestHandle as RequestHandle>::Parameters,
+        resolver: &ReferenceResolver,
+    ) -> anyhow::Result<()> {
+        extension_parameters.client_metadata = extension_parameters.client_metadata.resolve(resolver).await?;
+        if let Some(presentation_definition_uri) = extension_parameters.presentation_definition_uri.take() {
+            let presentation_definition: PresentationDefinition =
+                resolver.resolve(&presentation_definition_uri).await?;
+            presentation_definition.validate().map_err(anyhow::Error::msg)?;
+            extension_parameters.presentation_definition = Some(presentation_definition);
+        }
+        Ok(())
+    }
+
+    /// Generates the ID Token followed by the VP Token, both signed by the `subject` with the same key and bound to the
+    /// same `nonce`.
+    async fn generate_token(
+        subject: Arc<dyn Subject>,
+        client_id: &str,
+        extension_parameters: &<Self::RequestHandle as RequestHandle>::Parameters,
+        user_input: &<Self::ResponseHandle as ResponseHandle>::Input,
+        subject_syntax_type: impl TryInto<SubjectSyntaxType>,
+        signing_algorithm: impl TryInto<Algorithm>,
+    ) -> anyhow::Result<<Self::ResponseHandle as ResponseHandle>::Tokens> {
+        let signing_algorithm: Algorithm = signing_algorithm
+            .try_into()
+            .map_err(|_| anyhow!("Failed to convert the signing algorithm"))?;
+        let subject_syntax_type: SubjectSyntaxType = subject_syntax_type
+            .try_into()
+            .map_err(|_| anyhow!("Failed to convert the subject syntax type"))?;
+
+        let id_token = SIOPv2::generate_token(
+            subject.clone(),
+            client_id,
+            &extension_parameters.siopv2()?,
+            &user_input.claims,
+            subject_syntax_type.clone(),
+            signing_algorithm,
+        )
+        .await?;
+        let vp_token = OID4VP::generate_token(
+            subject,
+            client_id,
+            &extension_parameters.oid4vp()?,
+            &user_input.presentation,
+            subject_syntax_type,
+            signing_algorithm,
+        )
+        .await?;
+
+        let id_token = id_token.into_iter().next().ok_or(anyhow!("The ID Token is missing."))?;
+
+        Ok((id_token, vp_token))
+    }
+
+    /// The ID Token and the VP Token are signed with the same algorithm, so it is one of the algorithms that the
+    /// Relying Party supports for Verifiable Presentations, restricted to the `id_token_signed_response_alg` if given.
+    async fn get_relying_party_supported_algorithms(
+        authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
+    ) -> anyhow::Result<Vec<Algorithm>> {
+        let algorithms = OID4VP::get_relying_party_supported_algorithms(&authorization_request.oid4vp()?).await?;
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => Err(anyhow!("The `client_metadata_uri` is not resolved.")),
+            ClientMetadataResource::ClientMetadata { extension, .. } => match extension.id_token_signed_response_alg {
+                Some(id_token_signed_response_alg) => Ok(algorithms
+                    .into_iter()
+                    .filter(|algorithm| *algorithm == id_token_signed_response_alg)
+                    .collect()),
+                None => Ok(algorithms),
+            },
+        }
+    }
+
+    async fn get_relying_party_supported_syntax_types(
+        authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
+    ) -> anyhow::Result<Vec<SubjectSyntaxType>> {
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => Err(anyhow!("The `client_metadata_uri` is not resolved.")),
+            ClientMetadataResource::ClientMetadata { extension, .. } => {
+                Ok(extension.subject_syntax_types_supported.clone())
+            }
+        }
+    }
+
+    /// Encrypted Authorization Responses are not supported, so the `direct_post.jwt` Response Mode is rejected.
+    fn build_authorization_response(
+        (id_token, presentations): <Self::ResponseHandle as ResponseHandle>::Tokens,
+        user_input: <Self::ResponseHandle as ResponseHandle>::Input,
+        extension_parameters: &<Self::RequestHandle as RequestHandle>::Parameters,
+        redirect_uri: String,
+        state: Option<String>,
+    ) -> anyhow::Result<AuthorizationResponse<Self>> {
+        if extension_parameters.response_mode.as_deref() == Some(oid4vp::DIRECT_POST_JWT) {
+            return Err(anyhow!(
+                "The `direct_post.jwt` Response Mode is not supported for requests with the `vp_token id_token` Response Type."
+            ));
+        }
+
+        Ok(AuthorizationResponse {
+            redirect_uri,
+            state,
+            extension: AuthorizationResponseParameters {
+                id_token,
+                vp_token: oid4vp::AuthorizationResponseParameters {
+                    oid4vp_parameters: oid4vp::oid4vp_parameters(presentations.vp_tokens, user_input.presentation)?,
+                },
+            },
+        })
+    }
+
+    /// Verifies the ID Token and the VP Token, and checks that the ID Token is issued by the Holder of the
+    /// presentation for the same `nonce`.
+    async fn decode_authorization_response(
+        validator: Validator,
+        response: &AuthorizationResponse<Self>,
+        verification_context: &VerificationContext<Self>,
+    ) -> anyhow::Result<<Self::ResponseHandle as ResponseHandle>::ResponseItem> {
+        let id_token: IdToken = validator.decode(response.extension.id_token.clone()).await?;
+
+        let authorization_request = verification_context
+            .authorization_request
+            .as_ref()
+            .map(|request| {
+                Ok::<_, anyhow::Error>(Object::<OID4VP> {
+                    rfc7519_claims: request.rfc7519_claims.clone(),
+                    client_id: request.client_id.clone(),
+                    redirect_uri: request.redirect_uri.clone(),
+                    state: request.state.clone(),
+                    extension: request.extension.oid4vp()?,
+                })
+            })
+            .transpose()?;
+        let (nonce, client_id) = authorization_request
+            .as_ref()
+            .map(|request| (request.extension.nonce.clone(), request.client_id.clone()))
+            .unzip();
+
+        let mut report = OID4VP::decode_authorization_response(
+            validator,
+            &AuthorizationResponse {
+                redirect_uri: response.redirect_uri.clone(),
+                state: response.state.clone(),
+                extension: response.extension.vp_token.clone(),
+            },
+            &VerificationContext {
+                authorization_request,
+                status_list_transport: verification_context.status_list_transport.clone(),
+                encryption_key: verification_context.encryption_key.clone(),
+            },
+        )
+        .await?;
+
+        let outcome = check_id_token(
+            &id_token,
+            report.holder.as_deref(),
+            nonce.as_deref(),
+            client_id.as_deref(),
+        );
+        report.record(Check::IdToken, None, outcome);
+
+        Ok(VerifiedResponse { id_token, report })
+    }
+}
+
+/// Checks that the ID Token is self-issued, i.e. its `iss` is its `sub`, as described here:
+/// https://openid.net/specs/openid-connect-self-issued-v2-1_0.html#name-self-issued-id-token-validation, that it is
+/// issued by the `holder` of the presentation, and that its `nonce` and `aud` match the Authorization Request.
+fn check_id_token(
+    id_token: &IdToken,
+    holder: Option<&str>,
+    nonce: Option<&str>,
+    client_id: Option<&str>,
+) -> CheckOutcome {
+    let subject = match (
+        id_token.rfc7519_claims.iss.as_deref(),
+        id_token.rfc7519_claims.sub.as_deref(),
+    ) {
+        (_, None) => return CheckOutcome::Failed("The ID Token does not contain a `sub`.".to_string()),
+        (Some(issuer), Some(subject)) if issuer == subject => subject,
+        (issuer, Some(subject)) => {
+            return CheckOutcome::Failed(format!(
+                "The ID Token is not self-issued: its `iss` `{}` is not its `sub` `{subject}`.",
+                issuer.unwrap_or_default()
+            ))
+        }
+    };
+    if let Some(holder) = holder.filter(|holder| !same_identifier(subject, holder)) {
+        return CheckOutcome::Failed(format!(
+            "The ID Token is issued by `{subject}`, but the presentation is created by `{holder}`."
+        ));
+    }
+
+    let outcomes = [
+        matches_request(nonce, id_token.nonce.as_deref(), "nonce"),
+        matches_request(client_id, id_token.rfc7519_claims.aud.as_deref(), "aud"),
+    ];
+    outcomes
+        .iter()
+        .find(|outcome| matches!(outcome, CheckOutcome::Failed(_)))
+        .or_else(|| {
+            outcomes
+                .iter()
+                .find(|outcome| matches!(outcome, CheckOutcome::Skipped(_)))
+        })
+        .cloned()
+        .unwrap_or(CheckOutcome::Passed)
+}
+
+/// [`AuthorizationRequest`] claims of a request for both a Self-Issued ID Token and a VP Token. They combine the
+/// parameters of [`SIOPv2`] and [`OID4VP`] requests.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AuthorizationRequestParameters {
+    pub response_type: MustBe!("vp_token id_token"),
+    pub scope: Scope,
+    pub presentation_definition: Option<PresentationDefinition>,
+    pub presentation_definition_uri: Option<url::Url>,
+    pub dcql_query: Option<DcqlQuery>,
+    pub client_id_scheme: Option<ClientIdScheme>,
+    pub response_mode: Option<String>,
+    pub response_uri: Option<url::Url>,
+    pub nonce: String,
+    pub claims: Option<ClaimRequests>,
+    #[serde(flatten)]
+    pub client_metadata: ClientMetadataResource<ClientMetadataParameters>,
+}
+
+impl AuthorizationRequestParameters {
+    /// Returns the parameters of the ID Token part of the request.
+    pub fn siopv2(&self) -> Result<siopv2::authorization_request::AuthorizationRequestParameters> {
+        self.with_response_type("id_token")
+    }
+
+    /// Returns the parameters of the VP Token part of the request.
+    pub fn oid4vp(&self) -> Result<crate::authorization_request::AuthorizationRequestParameters> {
+        self.with_response_type("vp_token")
+    }
+
+    fn with_response_type<T: DeserializeOwned>(&self, response_type: &str) -> Result<T> {
+        let mut parameters = serde_json::to_value(self)?;
+        parameters["response_type"] = response_type.into();
+        Ok(serde_json::from_value(parameters)?)
+    }
+}
+
+/// The Client Metadata of a Relying Party that requests both a Self-Issued ID Token and a VP Token.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ClientMetadataParameters {
+    pub vp_formats: HashMap<ClaimFormatDesignation, ClaimFormatProperty>,
+    pub subject_syntax_types_supported: Vec<SubjectSyntaxType>,
+    pub id_token_signed_response_alg: Option<Algorithm>,
+}
+
+#[derive(Debug, Default, IsEmpty)]
+pub struct AuthorizationRequestBuilder {
+    rfc7519_claims: RFC7519Claims,
+    presentation_definition: Option<PresentationDefinition>,
+    presentation_definition_uri: Option<url::Url>,
+    dcql_query: Option<DcqlQuery>,
+    client_id_scheme: Option<ClientIdScheme>,
+    client_id: Option<String>,
+    redirect_uri: Option<url::Url>,
+    state: Option<String>,
+    scope: Option<Scope>,
+    response_mode: Option<String>,
+    response_uri: Option<url::Url>,
+    nonce: Option<String>,
+    claims: Option<Result<ClaimRequests>>,
+    client_metadata: Option<ClientMetadataResource<ClientMetadataParameters>>,
+    custom_url_scheme: Option<String>,
+}
+
+impl AuthorizationRequestBuilder {
+    pub fn claims<T: TryInto<ClaimRequests>>(mut self, value: T) -> Self {
+        self.claims = Some(value.try_into().map_err(|_| anyhow!("failed to convert")));
+        self
+    }
+
+    builder_fn!(rfc7519_claims, iss, String);
+    builder_fn!(rfc7519_claims, sub, String);
+    builder_fn!(rfc7519_claims, aud, String);
+    builder_fn!(rfc7519_claims, exp, i64);
+    builder_fn!(rfc7519_claims, nbf, i64);
+    builder_fn!(rfc7519_claims, iat, i64);
+    builder_fn!(rfc7519_claims, jti, String);
+    builder_fn!(response_mode, String);
+    builder_fn!(response_uri, url::Url);
+    builder_fn!(client_id, String);
+    builder_fn!(scope, Scope);
+    builder_fn!(redirect_uri, url::Url);
+    builder_fn!(nonce, String);
+    builder_fn!(client_metadata, ClientMetadataResource<ClientMetadataParameters>);
+    builder_fn!(state, String);
+    builder_fn!(presentation_definition, PresentationDefinition);
+    builder_fn!(presentation_definition_uri, url::Url);
+    builder_fn!(dcql_query, DcqlQuery);
+    builder_fn!(client_id_scheme, ClientIdScheme);
+    builder_fn!(custom_url_scheme, String);
+
+    pub fn build(mut self) -> Result<AuthorizationRequest<Object<SIOPv2OID4VP>>> {
+        match (self.client_id.take(), self.is_empty()) {
+            (None, _) => Err(anyhow!("client_id parameter is required.")),
+            (Some(client_id), false) => {
+                let (presentation_definition, presentation_definition_uri, dcql_query) = (
+                    self.presentation_definition.take(),
+                    self.presentation_definition_uri.take(),
+                    self.dcql_query.take(),
+                );
+                ensure_single_query(&presentation_definition, &presentation_definition_uri, &dcql_query)?;
+                let extension = AuthorizationRequestParameters {
+                    response_type: MustBe!("vp_token id_token"),
+                    scope: self
+                        .scope
+                        .take()
+                        .ok_or_else(|| anyhow!("scope parameter is required."))?,
+                    presentation_definition,
+                    presentation_definition_uri,
+                    dcql_query,
+                    client_id_scheme: self.client_id_scheme.take(),
+                    response_mode: self.response_mode.take(),
+                    response_uri: self.response_uri.take(),
+                    nonce: self
+                        .nonce
+                        .take()
+                        .ok_or_else(|| anyhow!("nonce parameter is required."))?,
+                    claims: self.claims.take().transpose()?,
+                    client_metadata: self
+                        .client_metadata
+                        .take()
+                        .ok_or_else(|| anyhow!("client_metadata or client_metadata_uri is required."))?,
+                };
+
+                Ok(AuthorizationRequest::<Object<SIOPv2OID4VP>> {
+                    custom_url_scheme: self.custom_url_scheme.take().unwrap_or("openid".to_string()),
+                    body: Object::<SIOPv2OID4VP> {
+                        rfc7519_claims: self.rfc7519_claims,
+                        client_id,
+                        redirect_uri: self
+                            .redirect_uri
+                            .take()
+                            .ok_or_else(|| anyhow!("redirect_uri parameter is required."))?,
+                        state: self.state.take(),
+                        extension,
+                    },
+                })
+            }
+            _ => Err(anyhow!(
+                "one of either request_uri, request or other parameters should be set"
+            )),
+        }
+    }
+}
+
+/// The claims of the Self-Issued ID Token together with the presentations of the VP Token.
+pub struct AuthorizationResponseInput {
+    pub claims: StandardClaimsValues,
+    pub presentation: oid4vp::AuthorizationResponseInput,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AuthorizationResponseParameters {
+    pub id_token: String,
+    #[serde(flatten)]
+    pub vp_token: oid4vp::AuthorizationResponseParameters,
+}
+
+/// The verified ID Token together with the [`VerificationReport`] of the VP Token, which includes the outcome of the
+/// [`Check::IdToken`].
+#[derive(Serialize, Debug, PartialEq)]
+pub struct VerifiedResponse {
+    pub id_token: IdToken,
+    pub report: VerificationReport,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_token() -> IdToken {
+        IdToken::builder()
+            .iss("did:example:holder".to_string())
+            .sub("did:example:holder".to_string())
+            .aud("did:example:client".to_string())
+            .exp(9999999999i64)
+            .iat(0)
+            .nonce("nonce".to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_check_id_token() {
+        assert_eq!(
+            check_id_token(
+                &id_token(),
+                Some("did:example:holder#key-1"),
+                Some("nonce"),
+                Some("did:example:client")
+            ),
+            CheckOutcome::Passed
+        );
+
+        // Without an Authorization Request, the `nonce` and `aud` cannot be checked.
+        assert_eq!(
+            check_id_token(&id_token(), Some("did:example:holder"), None, None),
+            CheckOutcome::Skipped("The Authorization Request is not known.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_id_token_mismatched_subject() {
+        assert_eq!(
+            check_id_token(
+                &id_token(),
+                Some("did:example:other"),
+                Some("nonce"),
+                Some("did:example:client")
+            ),
+            CheckOutcome::Failed(
+                "The ID Token is issued by `did:example:holder`, but the presentation is created by `did:example:other`."
+                    .to_string()
+            )
+        );
+
+        let not_self_issued = IdToken {
+            rfc7519_claims: RFC7519Claims {
+                iss: Some("did:example:issuer".to_string()),
+                ..id_token().rfc7519_claims
+            },
+            ..id_token()
+        };
+        assert_eq!(
+            check_id_token(
+                &not_self_issued,
+                Some("did:example:holder"),
+                Some("nonce"),
+                Some("did:example:client")
+            ),
+            CheckOutcome::Failed(
+                "The ID Token is not self-issued: its `iss` `did:example:issuer` is not its `sub` `did:example:holder`."
+                    .to_string()
+            )
+        );
+
+        let without_subject = IdToken {
+            rfc7519_claims: RFC7519Claims {
+                sub: None,
+                ..id_token().rfc7519_claims
+            },
+            ..id_token()
+        };
+        assert_eq!(
+            check_id_token(&without_subject, None, Some("nonce"), Some("did:example:client")),
+            CheckOutcome::Failed("The ID Token does not contain a `sub`.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_id_token_mismatched_nonce_and_audience() {
+        assert_eq!(
+            check_id_token(
+                &id_token(),
+                Some("did:example:holder"),
+                Some("other_nonce"),
+                Some("did:example:client")
+            ),
+            CheckOutcome::Failed("The `nonce` `nonce` does not match the Authorization Request.".to_string())
+        );
+        assert_eq!(
+            check_id_token(
+                &id_token(),
+                Some("did:example:holder"),
+                Some("nonce"),
+                Some("did:example:other_client")
+            ),
+            CheckOutcome::Failed(
+                "The `aud` `did:example:client` does not match the Authorization Request.".to_string()
+            )
+        );
+
+        let without_nonce = IdToken {
+            nonce: None,
+            ..id_token()
+        };
+        assert_eq!(
+            check_id_token(
+                &without_nonce,
+                Some("did:example:holder"),
+                Some("nonce"),
+                Some("did:example:client")
+            ),
+            CheckOutcome::Failed("The presentation does not contain a `nonce`.".to_string())
+        );
+    }
+}
@@ -1,2 +1,3 @@
+pub mod vp_jwt;
 pub mod vp_token;
 pub mod vp_token_builder;
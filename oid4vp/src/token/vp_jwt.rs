@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use jsonwebtoken::{Algorithm, Header};
+use oid4vc_core::RFC7519Claims;
+use oid4vci::credential::{CREDENTIALS_V2_CONTEXT, VC_JWT_TYP};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// The `typ` and `cty` of a Verifiable Presentation secured with JOSE as described here:
+/// https://www.w3.org/TR/vc-jose-cose/#securing-vps-with-jose
+pub const VP_JWT_TYP: &str = "vp+jwt";
+pub const VP_JWT_CTY: &str = "vp";
+
+/// Verifiable Presentation according to the VCDM 2.0 which is secured as the payload of a `vp+jwt` as described here:
+/// https://www.w3.org/TR/vc-jose-cose/#securing-vps-with-jose
+/// The `aud` and `nonce` claims bind the Verifiable Presentation to the Authorization Request.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiablePresentationV2 {
+    #[serde(rename = "@context")]
+    pub context: Vec<serde_json::Value>,
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Vec<String>,
+    pub holder: Option<String>,
+    #[serde(default)]
+    pub verifiable_credential: Vec<EnvelopedVerifiableCredential>,
+    #[serde(flatten)]
+    pub rfc7519_claims: RFC7519Claims,
+    pub nonce: Option<String>,
+}
+
+impl VerifiablePresentationV2 {
+    /// Returns a Verifiable Presentation that envelops the given `vc+jwt` Credentials.
+    pub fn new(credentials: Vec<String>) -> Self {
+        Self {
+            context: vec![serde_json::json!(CREDENTIALS_V2_CONTEXT)],
+            id: None,
+            type_: vec!["VerifiablePresentation".to_string()],
+            holder: None,
+            verifiable_credential: credentials
+                .into_iter()
+                .map(EnvelopedVerifiableCredential::new)
+                .collect(),
+            rfc7519_claims: RFC7519Claims::default(),
+            nonce: None,
+        }
+    }
+
+    pub fn header(algorithm: Algorithm) -> Header {
+        Header {
+            typ: Some(VP_JWT_TYP.to_string()),
+            cty: Some(VP_JWT_CTY.to_string()),
+            ..Header::new(algorithm)
+        }
+    }
+
+    /// Returns the enveloped Credentials.
+    pub fn credentials(&self) -> Result<Vec<&str>> {
+        self.verifiable_credential
+            .iter()
+            .map(EnvelopedVerifiableCredential::credential)
+            .collect()
+    }
+}
+
+/// A `vc+jwt` Credential that is enveloped in a Verifiable Presentation as described here:
+/// https://www.w3.org/TR/vc-data-model-2.0/#enveloped-verifiable-credentials
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct EnvelopedVerifiableCredential {
+    #[serde(rename = "@context")]
+    pub context: serde_json::Value,
+    /// The Credential as a `data:` URL.
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+impl EnvelopedVerifiableCredential {
+    pub fn new(credential: String) -> Self {
+        Self {
+            context: serde_json::json!(CREDENTIALS_V2_CONTEXT),
+            id: format!("data:application/{VC_JWT_TYP},{credential}"),
+            type_: "EnvelopedVerifiableCredential".to_string(),
+        }
+    }
+
+    pub fn credential(&self) -> Result<&str> {
+        self.id
+            .strip_prefix(&format!("data:application/{VC_JWT_TYP},"))
+            .ok_or(anyhow!("Only `{VC_JWT_TYP}` Credentials can be enveloped."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_enveloped_verifiable_credential() {
+        let mut verifiable_presentation =
+            VerifiablePresentationV2::new(vec!["eyJhbGciOiJFZERTQSJ9.e30.c2ln".to_string()]);
+        verifiable_presentation.holder = Some("did:example:holder".to_string());
+        verifiable_presentation.nonce = Some("n-0S6_WzA2Mj".to_string());
+
+        assert_eq!(
+            serde_json::to_value(&verifiable_presentation).unwrap(),
+            json!({
+                "@context": ["https://www.w3.org/ns/credentials/v2"],
+                "type": ["VerifiablePresentation"],
+                "holder": "did:example:holder",
+                "verifiableCredential": [{
+                    "@context": "https://www.w3.org/ns/credentials/v2",
+                    "id": "data:application/vc+jwt,eyJhbGciOiJFZERTQSJ9.e30.c2ln",
+                    "type": "EnvelopedVerifiableCredential"
+                }],
+                "nonce": "n-0S6_WzA2Mj"
+            })
+        );
+        assert_eq!(
+            verifiable_presentation.credentials().unwrap(),
+            vec!["eyJhbGciOiJFZERTQSJ9.e30.c2ln"]
+        );
+
+        let enveloped_credential = EnvelopedVerifiableCredential {
+            id: "data:application/vc+sd-jwt,eyJhbGciOiJFZERTQSJ9.e30.c2ln~".to_string(),
+            ..EnvelopedVerifiableCredential::new(String::new())
+        };
+        assert!(enveloped_credential.credential().is_err());
+    }
+}
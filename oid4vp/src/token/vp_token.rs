@@ -14,6 +14,7 @@ pub struct VpToken {
     #[serde(rename = "vp")]
     #[getset(get = "pub")]
     pub(super) verifiable_presentation: Presentation<Jwt>,
+    #[getset(get = "pub")]
     pub(super) nonce: Option<String>,
 }
 
@@ -0,0 +1,235 @@
+use crate::oid4vp::VerifiedCredential;
+use chrono::{DateTime, Utc};
+use oid4vc_core::jwt;
+use serde::Serialize;
+
+/// The checks that the Relying Party performs on top of verifying the signatures of a presentation and its
+/// Credentials.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// The `nonce` of the presentation matches the `nonce` of the Authorization Request.
+    Nonce,
+    /// The presentation is intended for the Client that sent the Authorization Request.
+    Audience,
+    /// The Credential is bound to the Holder that created the presentation.
+    HolderBinding,
+    /// The current time is within the validity period of the presentation or the Credential.
+    ValidityPeriod,
+    /// The Credential is neither revoked nor suspended.
+    Status,
+    /// The presentation submission answers the presentation definition of the Authorization Request.
+    PresentationSubmission,
+    /// The Credential discloses no claims other than those requested by an Input Descriptor that requires limited
+    /// disclosure.
+    LimitDisclosure,
+    /// The presented Credentials answer the DCQL query of the Authorization Request.
+    DcqlQuery,
+    /// The Self-Issued ID Token that accompanies the presentation is issued by its Holder and bound to the `nonce` and
+    /// the `client_id` of the Authorization Request.
+    IdToken,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "outcome", content = "reason", rename_all = "snake_case")]
+pub enum CheckOutcome {
+    Passed,
+    Failed(String),
+    /// The check could not be performed, for instance because there is no Authorization Request to compare against.
+    Skipped(String),
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct CheckResult {
+    pub check: Check,
+    /// The index of the Credential in [`VerificationReport::credentials`] that is checked, or `None` when the check
+    /// concerns the presentation as a whole.
+    pub credential: Option<usize>,
+    #[serde(flatten)]
+    pub outcome: CheckOutcome,
+}
+
+/// The result of the verification of an Authorization Response. The signatures of the presentation and the
+/// Credentials are verified before the report is created, so the `credentials` are always authentic, but they should
+/// only be relied upon when the report [`is_valid`](VerificationReport::is_valid). A report of a response that is not
+/// compared with its Authorization Request skips the checks against the request, so it is never valid.
+#[derive(Serialize, Debug, PartialEq, Clone, Default)]
+pub struct VerificationReport {
+    pub checks: Vec<CheckResult>,
+    pub credentials: Vec<VerifiedCredential>,
+    /// The identifier of the Holder that is proven to have created the presentation, if its format identifies one.
+    pub holder: Option<String>,
+}
+
+impl VerificationReport {
+    /// Returns `true` when all checks passed. A check that is skipped does not prove anything, so a report with skipped
+    /// checks is not valid either.
+    pub fn is_valid(&self) -> bool {
+        self.failures().next().is_none() && self.skipped().next().is_none()
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|result| matches!(result.outcome, CheckOutcome::Skipped(_)))
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|result| matches!(result.outcome, CheckOutcome::Failed(_)))
+    }
+
+    /// Returns the outcomes of the `check` for the presentation (`None`) or the Credential at the given index.
+    pub fn outcome(&self, check: Check, credential: Option<usize>) -> Option<&CheckOutcome> {
+        self.checks
+            .iter()
+            .find(|result| result.check == check && result.credential == credential)
+            .map(|result| &result.outcome)
+    }
+
+    pub(crate) fn record(&mut self, check: Check, credential: Option<usize>, outcome: CheckOutcome) {
+        self.checks.push(CheckResult {
+            check,
+            credential,
+            outcome,
+        });
+    }
+
+    /// Adds a Credential to the report and returns its index.
+    pub(crate) fn push_credential(&mut self, credential: VerifiedCredential) -> usize {
+        self.credentials.push(credential);
+        self.credentials.len() - 1
+    }
+}
+
+fn no_authorization_request() -> CheckOutcome {
+    CheckOutcome::Skipped("The Authorization Request is not known.".to_string())
+}
+
+/// Compares a claim of the presentation with the value that is `expected` according to the Authorization Request.
+pub(crate) fn matches_request(expected: Option<&str>, actual: Option<&str>, claim: &str) -> CheckOutcome {
+    match (expected, actual) {
+        (None, _) => no_authorization_request(),
+        (Some(expected), Some(actual)) if expected == actual => CheckOutcome::Passed,
+        (Some(_), Some(actual)) => CheckOutcome::Failed(format!(
+            "The `{claim}` `{actual}` does not match the Authorization Request."
+        )),
+        (Some(_), None) => CheckOutcome::Failed(format!("The presentation does not contain a `{claim}`.")),
+    }
+}
+
+/// Checks that the current time lies within the validity period.
+pub(crate) fn within_validity_period(
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
+) -> CheckOutcome {
+    let now = Utc::now();
+    match (valid_from, valid_until) {
+        (Some(valid_from), _) if now < valid_from => CheckOutcome::Failed(format!("Not valid before {valid_from}.")),
+        (_, Some(valid_until)) if valid_until <= now => CheckOutcome::Failed(format!("Expired at {valid_until}.")),
+        _ => CheckOutcome::Passed,
+    }
+}
+
+/// Checks that the subject of a Credential is the `holder` of the presentation. Both are compared as DIDs, so a DID
+/// URL that refers to a key of the holder matches as well.
+pub(crate) fn bound_to_holder(holder: Option<&str>, subject: Option<&str>) -> CheckOutcome {
+    let did = |identifier: &str| identifier.split('#').next().unwrap_or_default().to_owned();
+    match (holder, subject) {
+        (_, None) => CheckOutcome::Skipped("The Credential does not identify its subject.".to_string()),
+        (None, Some(_)) => CheckOutcome::Failed("The presentation does not identify its Holder.".to_string()),
+        (Some(holder), Some(subject)) if did(holder) == did(subject) => CheckOutcome::Passed,
+        (Some(holder), Some(subject)) => CheckOutcome::Failed(format!(
+            "The Credential is issued to `{subject}`, but presented by `{holder}`."
+        )),
+    }
+}
+
+/// Checks that the `cnf` claim of a Credential confirms the `holder_key` with which the presentation is signed.
+pub(crate) fn bound_to_key(cnf: &serde_json::Value, holder_key: &[u8]) -> CheckOutcome {
+    let key = cnf.get("jwk").map(|jwk| {
+        serde_json::from_value(jwk.clone())
+            .map_err(anyhow::Error::from)
+            .and_then(|jwk| jwt::public_key_from_jwk(&jwk))
+    });
+    match key {
+        None => CheckOutcome::Failed("The `cnf` claim of the Credential does not contain a `jwk`.".to_string()),
+        Some(Err(e)) => CheckOutcome::Failed(format!("The `cnf` key of the Credential is invalid: {e}")),
+        Some(Ok(key)) if key == holder_key => CheckOutcome::Passed,
+        Some(Ok(_)) => CheckOutcome::Failed(
+            "The Credential is bound to another key than the one the presentation is signed with.".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_checks() {
+        assert_eq!(
+            matches_request(Some("nonce"), Some("nonce"), "nonce"),
+            CheckOutcome::Passed
+        );
+        assert!(matches!(
+            matches_request(Some("nonce"), Some("other"), "nonce"),
+            CheckOutcome::Failed(_)
+        ));
+        assert!(matches!(
+            matches_request(None, Some("nonce"), "nonce"),
+            CheckOutcome::Skipped(_)
+        ));
+
+        let now = Utc::now();
+        assert_eq!(
+            within_validity_period(Some(now - Duration::days(1)), Some(now + Duration::days(1))),
+            CheckOutcome::Passed
+        );
+        assert!(matches!(
+            within_validity_period(None, Some(now - Duration::days(1))),
+            CheckOutcome::Failed(_)
+        ));
+        assert!(matches!(
+            within_validity_period(Some(now + Duration::days(1)), None),
+            CheckOutcome::Failed(_)
+        ));
+
+        assert_eq!(
+            bound_to_holder(Some("did:example:holder#key-1"), Some("did:example:holder")),
+            CheckOutcome::Passed
+        );
+        assert!(matches!(
+            bound_to_holder(Some("did:example:holder"), Some("did:example:other")),
+            CheckOutcome::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_bound_to_key() {
+        let cnf = serde_json::json!({
+            "jwk": { "kty": "OKP", "crv": "Ed25519", "x": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA" }
+        });
+        assert_eq!(bound_to_key(&cnf, &[0; 32]), CheckOutcome::Passed);
+        assert!(matches!(bound_to_key(&cnf, &[1; 32]), CheckOutcome::Failed(_)));
+        assert!(matches!(
+            bound_to_key(&serde_json::json!({ "kid": "key" }), &[0; 32]),
+            CheckOutcome::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let mut report = VerificationReport::default();
+        report.record(Check::HolderBinding, Some(0), CheckOutcome::Passed);
+        assert!(report.is_valid());
+
+        // A report with a skipped check is not valid, since the check does not prove anything.
+        report.record(Check::Nonce, None, no_authorization_request());
+        assert!(!report.is_valid());
+        assert_eq!(report.skipped().count(), 1);
+        assert_eq!(report.failures().count(), 0);
+    }
+}
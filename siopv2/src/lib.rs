@@ -1,3 +1,6 @@
+// `Option::is_none_or`, which this lint suggests, is only stable since Rust 1.82.
+#![allow(clippy::unnecessary_map_or)]
+
 pub mod authorization_request;
 pub mod claims;
 pub mod provider;
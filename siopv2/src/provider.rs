@@ -1,17 +1,33 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use anyhow::Result;
 use jsonwebtoken::Algorithm;
 use oid4vc_core::{
     authentication::subject::SigningSubject,
-    authorization_request::{AuthorizationRequest, Body, ByReference, ByValue, Object},
+    authorization_request::{AuthorizationRequest, ByReference, ByValue, Object},
     authorization_response::AuthorizationResponse,
+    client_id_scheme::ClientIdScheme,
+    jwt,
     openid4vc_extension::{Extension, ResponseHandle},
-    SubjectSyntaxType, Validator,
+    resolver::ReferenceResolver,
+    verifier_attestation::VerifierAttestation,
+    x509::{TrustAnchors, X509Verifier},
+    JsonObject, SubjectSyntaxType, Validator, Verify,
 };
 use reqwest::StatusCode;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde_json::Value;
+
+/// A Client that is registered with the [`Provider`] before it sends any [`AuthorizationRequest`].
+#[derive(Clone, Default)]
+pub struct RegisteredClient {
+    /// The `redirect_uri`s and `response_uri`s the Client is allowed to use.
+    pub redirect_uris: Vec<url::Url>,
+    /// Resolves the keys with which the Client signs its request objects. Without it, only unsigned requests are
+    /// accepted from the Client.
+    pub verifier: Option<Arc<dyn Verify>>,
+}
 
 /// A Self-Issued OpenID Provider (SIOP), which is responsible for generating and signing [`IdToken`]'s in response to
 /// [`AuthorizationRequest`]'s from [crate::relying_party::RelyingParty]'s (RPs). The [`Provider`] acts as a trusted intermediary between the RPs and
@@ -20,6 +36,16 @@ pub struct Provider {
     pub subject: SigningSubject,
     pub supported_subject_syntax_types: Vec<SubjectSyntaxType>,
     pub supported_signing_algorithms: Vec<Algorithm>,
+    /// Resolves the parameters of Authorization Requests that are passed by reference.
+    pub resolver: ReferenceResolver,
+    /// Resolves the keys of Clients with the `did` Client ID Scheme. When absent, such Clients are rejected.
+    pub did_resolver: Option<Arc<dyn Verify>>,
+    /// The pre-registered Clients by their `client_id`.
+    pub registered_clients: HashMap<String, RegisteredClient>,
+    /// The root certificates that Clients with the `x509_san_dns` and `x509_san_uri` Client ID Schemes are trusted by.
+    pub trust_anchors: TrustAnchors,
+    /// Resolves the keys of the trusted issuers of Verifier Attestations by their identifier.
+    pub attestation_issuers: HashMap<String, Arc<dyn Verify>>,
     client: ClientWithMiddleware,
 }
 
@@ -37,6 +63,11 @@ impl Provider {
 
         Ok(Provider {
             subject,
+            resolver: ReferenceResolver::default(),
+            did_resolver: None,
+            registered_clients: HashMap::new(),
+            trust_anchors: TrustAnchors::default(),
+            attestation_issuers: HashMap::new(),
             client,
             supported_subject_syntax_types: supported_subject_syntax_types
                 .into_iter()
@@ -50,46 +81,200 @@ impl Provider {
         })
     }
 
-    /// TODO: Add more validation rules.
     /// Takes a String and tries to parse it into an [`AuthorizationRequest<Object>`]. If the parsing fails, it tries to
     /// parse the [`AuthorizationRequest<Object>`] from the `request` parameter of the [`AuthorizationRequest<ByValue>`]
-    /// or from the `request_uri` parameter of the [`AuthorizationRequest<ByReference>`].
+    /// or from the `request_uri` parameter of the [`AuthorizationRequest<ByReference>`]. The Client is authenticated
+    /// according to its Client ID Scheme, which also determines the key that a request object must be signed with.
     pub async fn validate_request(&self, authorization_request: String) -> Result<AuthorizationRequest<Object>> {
-        let validator = Validator::Subject(self.subject.clone());
+        if let Ok(authorization_request) = authorization_request.parse::<AuthorizationRequest<Object>>() {
+            self.authenticate_client(&authorization_request, None).await?;
+            return Ok(authorization_request);
+        }
 
-        let authorization_request = if let Ok(authorization_request) =
-            authorization_request.parse::<AuthorizationRequest<Object>>()
+        let (client_id, request_object) = if let Ok(authorization_request) =
+            AuthorizationRequest::<ByValue>::from_str(&authorization_request)
+        {
+            (authorization_request.body.client_id, authorization_request.body.request)
+        } else if let Ok(authorization_request) = AuthorizationRequest::<ByReference>::from_str(&authorization_request)
         {
-            authorization_request
+            let request_object = self
+                .resolver
+                .request_object(&authorization_request.body.request_uri)
+                .await?;
+            (authorization_request.body.client_id, request_object)
         } else {
-            let (client_id, authorization_request) =
-                if let Ok(authorization_request) = AuthorizationRequest::<ByValue>::from_str(&authorization_request) {
-                    let client_id = authorization_request.body.client_id().clone();
-                    let authorization_request: AuthorizationRequest<Object> = validator
-                        .decode(authorization_request.body.request.to_owned())
-                        .await
-                        .unwrap();
-
-                    (client_id, authorization_request)
-                } else if let Ok(authorization_request) =
-                    AuthorizationRequest::<ByReference>::from_str(&authorization_request)
-                {
-                    let client_id = authorization_request.body.client_id().clone();
-                    let builder = self.client.get(authorization_request.body.request_uri.clone());
-                    let request_value = builder.send().await?.text().await?;
-                    let authorization_request: AuthorizationRequest<Object> = validator.decode(request_value).await?;
-
-                    (client_id, authorization_request)
-                } else {
-                    return Err(anyhow::anyhow!("Invalid authorization request."));
-                };
-            anyhow::ensure!(
-                authorization_request.body.client_id == *client_id,
-                "Client id mismatch."
-            );
-            authorization_request
+            return Err(anyhow::anyhow!("Invalid authorization request."));
+        };
+
+        let (header, authorization_request): (JsonObject, AuthorizationRequest<Object>) =
+            jwt::decode_unverified(&request_object)?;
+        anyhow::ensure!(authorization_request.body.client_id == client_id, "Client id mismatch.");
+
+        let signed = header.get("alg").and_then(Value::as_str) != Some("none");
+        match self
+            .authenticate_client(&authorization_request, signed.then_some(&header))
+            .await?
+        {
+            Some(validator) => validator.decode(request_object).await,
+            None => Ok(authorization_request),
+        }
+    }
+
+    /// Authenticates the Client of an [`AuthorizationRequest`] according to its Client ID Scheme, as described here:
+    /// https://openid.net/specs/openid-4-verifiable-presentations-1_0-20.html#name-verifier-metadata-managemen. The
+    /// `header` is the header of the request object, if the request is signed. Returns the [`Validator`] with which the
+    /// signature of the request object must be verified.
+    async fn authenticate_client(
+        &self,
+        authorization_request: &AuthorizationRequest<Object>,
+        header: Option<&JsonObject>,
+    ) -> Result<Option<Validator>> {
+        let body = &authorization_request.body;
+        let client_id_scheme = match body.extension.get("client_id_scheme") {
+            Some(client_id_scheme) => serde_json::from_value(client_id_scheme.clone())?,
+            None => ClientIdScheme::infer(&body.client_id, &body.redirect_uri),
         };
+        let response_uri = body.extension.get("response_uri").and_then(Value::as_str);
 
+        match client_id_scheme {
+            ClientIdScheme::RedirectUri => {
+                anyhow::ensure!(
+                    header.is_none(),
+                    "Requests with the `redirect_uri` Client ID Scheme must not be signed."
+                );
+                anyhow::ensure!(
+                    body.client_id == body.redirect_uri.as_str() || Some(body.client_id.as_str()) == response_uri,
+                    "The `client_id` must be the `redirect_uri` or the `response_uri`."
+                );
+                Ok(None)
+            }
+            ClientIdScheme::Did => {
+                let header = header.ok_or(anyhow::anyhow!(
+                    "Requests with the `did` Client ID Scheme must be signed."
+                ))?;
+                anyhow::ensure!(
+                    body.client_id.starts_with("did:"),
+                    "The `client_id` `{}` is not a DID.",
+                    body.client_id
+                );
+                let kid = header
+                    .get("kid")
+                    .and_then(Value::as_str)
+                    .ok_or(anyhow::anyhow!("No key identifier found in the header."))?;
+                anyhow::ensure!(
+                    kid.split('#').next() == Some(body.client_id.as_str()),
+                    "The key `{kid}` is not a key of `{}`.",
+                    body.client_id
+                );
+                let did_resolver = self.did_resolver.clone().ok_or(anyhow::anyhow!(
+                    "No DID resolver is configured for Clients with the `did` Client ID Scheme."
+                ))?;
+                Ok(Some(Validator::Verifier(did_resolver)))
+            }
+            ClientIdScheme::PreRegistered => {
+                let client = self
+                    .registered_clients
+                    .get(&body.client_id)
+                    .ok_or(anyhow::anyhow!("The Client `{}` is not registered.", body.client_id))?;
+                anyhow::ensure!(
+                    client.redirect_uris.contains(&body.redirect_uri)
+                        && response_uri.map_or(true, |response_uri| client
+                            .redirect_uris
+                            .iter()
+                            .any(|uri| uri.as_str() == response_uri)),
+                    "The redirect URI is not registered for the Client `{}`.",
+                    body.client_id
+                );
+                match header {
+                    Some(_) => client
+                        .verifier
+                        .clone()
+                        .map(|verifier| Some(Validator::Verifier(verifier)))
+                        .ok_or(anyhow::anyhow!(
+                            "No key is registered for the Client `{}`.",
+                            body.client_id
+                        )),
+                    None => Ok(None),
+                }
+            }
+            client_id_scheme @ (ClientIdScheme::X509SanDns | ClientIdScheme::X509SanUri) => {
+                let header = header.ok_or(anyhow::anyhow!(
+                    "Requests with the `{}` Client ID Scheme must be signed.",
+                    client_id_scheme.as_str()
+                ))?;
+                let x5c: Vec<String> = serde_json::from_value(
+                    header
+                        .get("x5c")
+                        .cloned()
+                        .ok_or(anyhow::anyhow!("No `x5c` certificate chain found in the header."))?,
+                )?;
+                let chain = self.trust_anchors.verify(&x5c)?;
+                let uri = response_uri.unwrap_or(body.redirect_uri.as_str());
+                if client_id_scheme == ClientIdScheme::X509SanDns {
+                    anyhow::ensure!(
+                        chain.dns_names()?.contains(&body.client_id),
+                        "The `client_id` `{}` is not a DNS name of the certificate.",
+                        body.client_id
+                    );
+                    anyhow::ensure!(
+                        url::Url::parse(uri)?.host_str() == Some(body.client_id.as_str()),
+                        "The host of the redirect URI must be the `client_id`."
+                    );
+                } else {
+                    anyhow::ensure!(
+                        chain.uris()?.contains(&body.client_id),
+                        "The `client_id` `{}` is not a URI of the certificate.",
+                        body.client_id
+                    );
+                    anyhow::ensure!(uri == body.client_id, "The redirect URI must be the `client_id`.");
+                }
+                Ok(Some(Validator::Verifier(Arc::new(X509Verifier::new(
+                    self.trust_anchors.clone(),
+                )))))
+            }
+            ClientIdScheme::VerifierAttestation => {
+                let header = header.ok_or(anyhow::anyhow!(
+                    "Requests with the `verifier_attestation` Client ID Scheme must be signed."
+                ))?;
+                let verifier_attestation = header
+                    .get("jwt")
+                    .and_then(Value::as_str)
+                    .ok_or(anyhow::anyhow!("No Verifier Attestation found in the header."))?;
+                let verifier_attestation =
+                    VerifierAttestation::verify(verifier_attestation, &self.attestation_issuers).await?;
+                anyhow::ensure!(
+                    verifier_attestation.rfc7519_claims.sub.as_ref() == Some(&body.client_id),
+                    "The `client_id` `{}` is not the subject of the Verifier Attestation.",
+                    body.client_id
+                );
+                if let Some(redirect_uris) = &verifier_attestation.redirect_uris {
+                    anyhow::ensure!(
+                        redirect_uris.contains(&body.redirect_uri)
+                            && response_uri.map_or(true, |response_uri| redirect_uris
+                                .iter()
+                                .any(|uri| uri.as_str() == response_uri)),
+                        "The redirect URI is not attested for the Client `{}`.",
+                        body.client_id
+                    );
+                }
+                // The request object must be signed with the key that is attested in the `cnf` claim.
+                Ok(Some(Validator::Verifier(Arc::new(verifier_attestation))))
+            }
+            client_id_scheme => Err(anyhow::anyhow!(
+                "The `{}` Client ID Scheme is not supported.",
+                client_id_scheme.as_str()
+            )),
+        }
+    }
+
+    /// Returns a copy of the [`AuthorizationRequest`] in which the extension-specific parameters that are passed by
+    /// reference are replaced with the resources they refer to.
+    pub async fn resolve_references<E: Extension>(
+        &self,
+        authorization_request: &AuthorizationRequest<Object<E>>,
+    ) -> Result<AuthorizationRequest<Object<E>>> {
+        let mut authorization_request = authorization_request.clone();
+        E::resolve_references(&mut authorization_request.body.extension, &self.resolver).await?;
         Ok(authorization_request)
     }
 
@@ -97,6 +282,7 @@ impl Provider {
         &self,
         authorization_request: &AuthorizationRequest<Object<E>>,
     ) -> Result<Algorithm> {
+        let authorization_request = self.resolve_references(authorization_request).await?;
         let relying_party_supported_algorithms =
             E::get_relying_party_supported_algorithms(&authorization_request.body.extension).await?;
 
@@ -111,6 +297,7 @@ impl Provider {
         &self,
         authorization_request: &AuthorizationRequest<Object<E>>,
     ) -> Result<SubjectSyntaxType> {
+        let authorization_request = self.resolve_references(authorization_request).await?;
         let relying_party_supported_syntax_types =
             E::get_relying_party_supported_syntax_types(&authorization_request.body.extension).await?;
 
@@ -128,13 +315,14 @@ impl Provider {
         authorization_request: &AuthorizationRequest<Object<E>>,
         input: <E::ResponseHandle as ResponseHandle>::Input,
     ) -> Result<AuthorizationResponse<E>> {
+        let authorization_request = &self.resolve_references(authorization_request).await?;
         let redirect_uri = authorization_request.body.redirect_uri.to_string();
         let state = authorization_request.body.state.clone();
 
         let signing_algorithm = self.get_matching_signing_algorithm(authorization_request).await?;
         let subject_syntax_type = self.get_matching_subject_syntax_type(authorization_request).await?;
 
-        let jwts = E::generate_token(
+        let tokens = E::generate_token(
             self.subject.clone(),
             &authorization_request.body.client_id,
             &authorization_request.body.extension,
@@ -144,7 +332,13 @@ impl Provider {
         )
         .await?;
 
-        E::build_authorization_response(jwts, input, redirect_uri, state)
+        E::build_authorization_response(
+            tokens,
+            input,
+            &authorization_request.body.extension,
+            redirect_uri,
+            state,
+        )
     }
 
     pub async fn send_response<E: Extension>(
@@ -165,7 +359,6 @@ impl Provider {
 mod tests {
     use super::*;
     use crate::{siopv2::SIOPv2, test_utils::TestSubject};
-    use std::sync::Arc;
 
     #[tokio::test]
     async fn test_provider() {
@@ -180,7 +373,7 @@ mod tests {
             siopv2://idtoken?\
                 scope=openid\
                 &response_type=id_token\
-                &client_id=did%3Aexample%3AEiDrihTRe0GMdc3K16kgJB3Xbl9Hb8oqVHjzm6ufHcYDGA\
+                &client_id=https%3A%2F%2Fclient.example.org%2Fcb\
                 &redirect_uri=https%3A%2F%2Fclient.example.org%2Fcb\
                 &response_mode=direct_post\
                 &client_metadata=%7B%22subject_syntax_types_supported%22%3A\
@@ -202,4 +395,287 @@ mod tests {
             .await
             .is_ok());
     }
+
+    #[tokio::test]
+    async fn test_client_id_scheme() {
+        let subject = TestSubject::new("did:test:123".to_string(), "key_id".to_string()).unwrap();
+        let mut provider = Provider::new(Arc::new(subject), vec!["did:test"], vec![Algorithm::EdDSA]).unwrap();
+
+        let request_url = |client_id: &str, client_id_scheme: Option<&str>| {
+            let mut url = url::Url::parse("siopv2://idtoken").unwrap();
+            url.query_pairs_mut()
+                .append_pair("scope", "openid")
+                .append_pair("response_type", "id_token")
+                .append_pair("client_id", client_id)
+                .append_pair("redirect_uri", "https://client.example.org/cb")
+                .append_pair("nonce", "n-0S6_WzA2Mj");
+            if let Some(client_id_scheme) = client_id_scheme {
+                url.query_pairs_mut().append_pair("client_id_scheme", client_id_scheme);
+            }
+            url.to_string()
+        };
+
+        // A `redirect_uri` Client must use its `redirect_uri` as `client_id`.
+        assert!(provider
+            .validate_request(request_url("https://other.example.org/cb", Some("redirect_uri")))
+            .await
+            .is_err());
+
+        // A `did` Client must sign its requests.
+        assert_eq!(
+            provider
+                .validate_request(request_url("did:example:123", None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Requests with the `did` Client ID Scheme must be signed."
+        );
+
+        // The keys of a `did` Client can only be resolved with a DID resolver.
+        let subject =
+            Arc::new(TestSubject::new("did:test:123".to_string(), "did:test:123#key_id".to_string()).unwrap());
+        let authorization_request: AuthorizationRequest<Object> = serde_json::from_value(serde_json::json!({
+            "scope": "openid",
+            "response_type": "id_token",
+            "client_id": "did:test:123",
+            "redirect_uri": "https://client.example.org/cb",
+            "nonce": "n-0S6_WzA2Mj"
+        }))
+        .unwrap();
+        let signed_request_url = AuthorizationRequest {
+            custom_url_scheme: "siopv2".to_string(),
+            body: oid4vc_core::authorization_request::ByValue {
+                client_id: "did:test:123".to_string(),
+                request: crate::relying_party::RelyingParty::new(subject.clone(), "did:test")
+                    .unwrap()
+                    .encode(&authorization_request, Algorithm::EdDSA)
+                    .await
+                    .unwrap(),
+            },
+        }
+        .to_string();
+        assert_eq!(
+            provider
+                .validate_request(signed_request_url.clone())
+                .await
+                .unwrap_err()
+                .to_string(),
+            "No DID resolver is configured for Clients with the `did` Client ID Scheme."
+        );
+        provider.did_resolver = Some(subject);
+        assert!(provider.validate_request(signed_request_url).await.is_ok());
+
+        // Only registered Clients are accepted with the `pre-registered` Client ID Scheme.
+        assert_eq!(
+            provider
+                .validate_request(request_url("client", None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The Client `client` is not registered."
+        );
+        provider.registered_clients.insert(
+            "client".to_string(),
+            RegisteredClient {
+                redirect_uris: vec!["https://client.example.org/cb".parse().unwrap()],
+                verifier: None,
+            },
+        );
+        assert!(provider
+            .validate_request(request_url("client", Some("pre-registered")))
+            .await
+            .is_ok());
+
+        assert_eq!(
+            provider
+                .validate_request(request_url("client.example.org", Some("x509_san_dns")))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Requests with the `x509_san_dns` Client ID Scheme must be signed."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_x509_client_id_scheme() {
+        use oid4vc_core::x509::TrustAnchors;
+        use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, PKCS_ECDSA_P256_SHA256};
+
+        // Create a root certificate and a certificate for the Client's DNS name that is issued by it.
+        let root_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let root = params.self_signed(&root_key).unwrap();
+        let client_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let client = CertificateParams::new(vec!["client.example.org".to_string()])
+            .unwrap()
+            .signed_by(&client_key, &root, &root_key)
+            .unwrap();
+
+        let request_url = |client_id: &str| {
+            let mut header = jsonwebtoken::Header::new(Algorithm::ES256);
+            header.x5c = Some(vec![base64_url::base64::Engine::encode(
+                &base64_url::base64::engine::general_purpose::STANDARD,
+                client.der(),
+            )]);
+            let request_object = jsonwebtoken::encode(
+                &header,
+                &serde_json::json!({
+                    "scope": "openid",
+                    "response_type": "id_token",
+                    "client_id": client_id,
+                    "client_id_scheme": "x509_san_dns",
+                    "redirect_uri": "https://client.example.org/cb",
+                    "nonce": "n-0S6_WzA2Mj"
+                }),
+                &jsonwebtoken::EncodingKey::from_ec_pem(client_key.serialize_pem().as_bytes()).unwrap(),
+            )
+            .unwrap();
+            let mut url = url::Url::parse("siopv2://idtoken").unwrap();
+            url.query_pairs_mut()
+                .append_pair("client_id", client_id)
+                .append_pair("request", &request_object);
+            url.to_string()
+        };
+
+        let subject = TestSubject::new("did:test:123".to_string(), "key_id".to_string()).unwrap();
+        let mut provider = Provider::new(Arc::new(subject), vec!["did:test"], vec![Algorithm::EdDSA]).unwrap();
+
+        // The certificate chain must be issued by a trust anchor.
+        assert!(provider
+            .validate_request(request_url("client.example.org"))
+            .await
+            .is_err());
+
+        let mut trust_anchors = TrustAnchors::default();
+        trust_anchors.add(root.der()).unwrap();
+        provider.trust_anchors = trust_anchors;
+        let authorization_request = provider
+            .validate_request(request_url("client.example.org"))
+            .await
+            .unwrap();
+        assert_eq!(authorization_request.body.client_id, "client.example.org");
+
+        // The `client_id` must be a DNS name of the certificate.
+        assert_eq!(
+            provider
+                .validate_request(request_url("other.example.org"))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The `client_id` `other.example.org` is not a DNS name of the certificate."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verifier_attestation() {
+        use crate::{relying_party::RelyingParty, test_utils::TEST_KEYPAIR};
+        use oid4vc_core::authorization_request::ByValue;
+        use rcgen::{KeyPair, PKCS_ECDSA_P256_SHA256};
+
+        struct AttestationIssuer(Vec<u8>);
+
+        #[async_trait::async_trait]
+        impl Verify for AttestationIssuer {
+            async fn public_key(&self, _kid: &str) -> Result<Vec<u8>> {
+                Ok(self.0.clone())
+            }
+        }
+
+        async fn request_url(relying_party: &RelyingParty, redirect_uri: &str) -> String {
+            let authorization_request: AuthorizationRequest<Object> = serde_json::from_value(serde_json::json!({
+                "scope": "openid",
+                "response_type": "id_token",
+                "client_id": "client",
+                "client_id_scheme": "verifier_attestation",
+                "redirect_uri": redirect_uri,
+                "nonce": "n-0S6_WzA2Mj"
+            }))
+            .unwrap();
+            AuthorizationRequest {
+                custom_url_scheme: "siopv2".to_string(),
+                body: ByValue {
+                    client_id: "client".to_string(),
+                    request: relying_party
+                        .encode(&authorization_request, Algorithm::EdDSA)
+                        .await
+                        .unwrap(),
+                },
+            }
+            .to_string()
+        }
+
+        // The attestation issuer attests the key with which the Relying Party signs its request objects.
+        let issuer_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let attest = |client_id: &str, key: &str| {
+            let mut header = jsonwebtoken::Header::new(Algorithm::ES256);
+            header.typ = Some("verifier-attestation+jwt".to_string());
+            header.kid = Some("https://attestation.example.org#key".to_string());
+            jsonwebtoken::encode(
+                &header,
+                &serde_json::json!({
+                    "iss": "https://attestation.example.org",
+                    "sub": client_id,
+                    "exp": 9999999999i64,
+                    "cnf": { "jwk": { "kty": "OKP", "crv": "Ed25519", "x": key } },
+                    "redirect_uris": ["https://client.example.org/cb"]
+                }),
+                &jsonwebtoken::EncodingKey::from_ec_pem(issuer_key.serialize_pem().as_bytes()).unwrap(),
+            )
+            .unwrap()
+        };
+        let key = base64_url::encode(TEST_KEYPAIR.verifying_key().as_bytes());
+
+        let subject = Arc::new(TestSubject::new("did:test:123".to_string(), "key_id".to_string()).unwrap());
+        let mut relying_party = RelyingParty::new(subject.clone(), "did:test").unwrap();
+        let mut provider = Provider::new(subject, vec!["did:test"], vec![Algorithm::EdDSA]).unwrap();
+
+        // A request object without a Verifier Attestation is rejected.
+        assert_eq!(
+            provider
+                .validate_request(request_url(&relying_party, "https://client.example.org/cb").await)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "No Verifier Attestation found in the header."
+        );
+
+        // The Verifier Attestation must be issued by a trusted issuer.
+        relying_party.verifier_attestation = Some(attest("client", &key));
+        assert_eq!(
+            provider
+                .validate_request(request_url(&relying_party, "https://client.example.org/cb").await)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The Verifier Attestation issuer `https://attestation.example.org` is not trusted."
+        );
+
+        provider.attestation_issuers.insert(
+            "https://attestation.example.org".to_string(),
+            Arc::new(AttestationIssuer(issuer_key.public_key_raw().to_vec())),
+        );
+        assert!(provider
+            .validate_request(request_url(&relying_party, "https://client.example.org/cb").await)
+            .await
+            .is_ok());
+
+        // Only the attested redirect URIs are accepted.
+        assert_eq!(
+            provider
+                .validate_request(request_url(&relying_party, "https://other.example.org/cb").await)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The redirect URI is not attested for the Client `client`."
+        );
+
+        // The request object must be signed with the attested key.
+        let other_key = base64_url::encode(&[0; 32]);
+        relying_party.verifier_attestation = Some(attest("client", &other_key));
+        assert!(provider
+            .validate_request(request_url(&relying_party, "https://client.example.org/cb").await)
+            .await
+            .is_err());
+    }
 }
@@ -5,11 +5,14 @@ use oid4vc_core::{
     authentication::subject::SigningSubject,
     authorization_request::{AuthorizationRequest, Object},
     authorization_response::AuthorizationResponse,
+    jwe::EncryptionKey,
     jwt,
-    openid4vc_extension::{Extension, ResponseHandle},
+    openid4vc_extension::{Extension, ResponseHandle, VerificationContext},
+    resolver::ReferenceResolver,
+    status_list::StatusListTransport,
     SubjectSyntaxType, Validator,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 pub struct RelyingParty {
     // TODO: Strictly speaking a relying party doesn't need to have a [`Subject`]. It just needs methods to
@@ -17,6 +20,17 @@ pub struct RelyingParty {
     pub subject: SigningSubject,
     pub default_subject_syntax_type: SubjectSyntaxType,
     pub sessions: HashMap<(String, String), AuthorizationRequest<Object<SIOPv2>>>,
+    /// Passed on as the [`VerificationContext::status_list_transport`] when validating responses.
+    pub status_list_transport: Option<Arc<dyn StatusListTransport>>,
+    /// Resolves the parameters of the Authorization Request that are passed by reference, such as a
+    /// `presentation_definition_uri`, before a response is validated against it.
+    pub resolver: ReferenceResolver,
+    /// A Verifier Attestation JWT that is added as the `jwt` header parameter of the request objects, so that Providers
+    /// can authenticate the Relying Party with the `verifier_attestation` Client ID Scheme.
+    pub verifier_attestation: Option<String>,
+    /// Decrypts the Authorization Responses of the `direct_post.jwt` Response Mode. Its public key is to be included in
+    /// the `jwks` of the Client Metadata.
+    pub encryption_key: Option<EncryptionKey>,
 }
 
 impl RelyingParty {
@@ -28,6 +42,10 @@ impl RelyingParty {
                 .try_into()
                 .map_err(|_| anyhow::anyhow!("Invalid did method."))?,
             sessions: HashMap::new(),
+            status_list_transport: None,
+            resolver: ReferenceResolver::default(),
+            verifier_attestation: None,
+            encryption_key: None,
         })
     }
 
@@ -36,13 +54,19 @@ impl RelyingParty {
         authorization_request: &AuthorizationRequest<Object<E>>,
         signing_algorithm: impl TryInto<Algorithm>,
     ) -> Result<String> {
-        jwt::encode(
+        let parameters = self
+            .verifier_attestation
+            .iter()
+            .map(|verifier_attestation| ("jwt".to_string(), verifier_attestation.clone().into()))
+            .collect();
+        jwt::encode_with_parameters(
             self.subject.clone(),
             Header::new(
                 signing_algorithm
                     .try_into()
                     .map_err(|_| anyhow::anyhow!("Invalid signing algorithm."))?,
             ),
+            parameters,
             authorization_request,
             &self.default_subject_syntax_type.to_string(),
         )
@@ -51,10 +75,43 @@ impl RelyingParty {
 
     /// Validates a [`AuthorizationResponse`] by decoding the header of the id_token, fetching the public key corresponding to
     /// the key identifier and finally decoding the id_token using the public key and by validating the signature.
+    /// The response is not compared with its Authorization Request, so it is only verified to be authentic: the checks
+    /// of the `nonce`, the audience and the requested Credentials are skipped. Use
+    /// [`RelyingParty::validate_response_to_request`] to verify that the response answers a request.
     pub async fn validate_response<E: Extension>(
         &self,
         authorization_response: &AuthorizationResponse<E>,
     ) -> Result<<E::ResponseHandle as ResponseHandle>::ResponseItem> {
-        E::decode_authorization_response(Validator::Subject(self.subject.clone()), authorization_response).await
+        self.decode_response(authorization_response, None).await
+    }
+
+    /// Validates a [`AuthorizationResponse`] like [`RelyingParty::validate_response`], but additionally checks that it
+    /// is a response to the given [`AuthorizationRequest`].
+    pub async fn validate_response_to_request<E: Extension>(
+        &self,
+        authorization_request: &AuthorizationRequest<Object<E>>,
+        authorization_response: &AuthorizationResponse<E>,
+    ) -> Result<<E::ResponseHandle as ResponseHandle>::ResponseItem> {
+        let mut authorization_request = authorization_request.body.clone();
+        E::resolve_references(&mut authorization_request.extension, &self.resolver).await?;
+        self.decode_response(authorization_response, Some(authorization_request))
+            .await
+    }
+
+    async fn decode_response<E: Extension>(
+        &self,
+        authorization_response: &AuthorizationResponse<E>,
+        authorization_request: Option<Object<E>>,
+    ) -> Result<<E::ResponseHandle as ResponseHandle>::ResponseItem> {
+        E::decode_authorization_response(
+            Validator::Subject(self.subject.clone()),
+            authorization_response,
+            &VerificationContext {
+                authorization_request,
+                status_list_transport: self.status_list_transport.clone(),
+                encryption_key: self.encryption_key.clone(),
+            },
+        )
+        .await
     }
 }
@@ -6,12 +6,10 @@ use crate::token::id_token::IdToken;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{Algorithm, Header};
 use oid4vc_core::client_metadata::ClientMetadataResource;
-use oid4vc_core::openid4vc_extension::{OpenID4VC, RequestHandle, ResponseHandle};
+use oid4vc_core::openid4vc_extension::{OpenID4VC, RequestHandle, ResponseHandle, VerificationContext};
+use oid4vc_core::resolver::ReferenceResolver;
 use oid4vc_core::{authorization_response::AuthorizationResponse, jwt, openid4vc_extension::Extension, Subject};
 use oid4vc_core::{SubjectSyntaxType, Validator};
-use reqwest_middleware::ClientBuilder;
-use reqwest_retry::policies::ExponentialBackoff;
-use reqwest_retry::RetryTransientMiddleware;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -28,6 +26,7 @@ impl RequestHandle for RequestHandler {
 pub struct ResponseHandler {}
 impl ResponseHandle for ResponseHandler {
     type Input = StandardClaimsValues;
+    type Tokens = Vec<String>;
     type Parameters = AuthorizationResponseParameters;
     type ResponseItem = IdToken;
 }
@@ -83,29 +82,24 @@ impl Extension for SIOPv2 {
         Ok(vec![jwt])
     }
 
-    // TODO: combine this function with `get_relying_party_supported_syntax_types`.
+    async fn resolve_references(
+        extension_parameters: &mut <Self::RequestHandle as RequestHandle>::Parameters,
+        resolver: &ReferenceResolver,
+    ) -> anyhow::Result<()> {
+        extension_parameters.client_metadata = extension_parameters.client_metadata.resolve(resolver).await?;
+        Ok(())
+    }
+
     async fn get_relying_party_supported_algorithms(
         authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
     ) -> anyhow::Result<Vec<Algorithm>> {
-        let client_metadata = match &authorization_request.client_metadata {
-            // Fetch the client metadata from the given URI.
-            ClientMetadataResource::ClientMetadataUri(client_metadata_uri) => {
-                let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
-                let client = ClientBuilder::new(reqwest::Client::new())
-                    .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-                    .build();
-                let client_metadata: ClientMetadataResource<ClientMetadataParameters> =
-                    client.get(client_metadata_uri).send().await?.json().await?;
-                client_metadata
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => {
+                Err(anyhow::anyhow!("The `client_metadata_uri` is not resolved."))
             }
-            client_metadata => client_metadata.clone(),
-        };
-
-        match client_metadata {
-            ClientMetadataResource::ClientMetadataUri(_) => unreachable!(),
             ClientMetadataResource::ClientMetadata { extension, .. } => {
-                match extension.id_token_signed_response_alg {
-                    Some(alg) => Ok(vec![alg]),
+                match &extension.id_token_signed_response_alg {
+                    Some(alg) => Ok(vec![*alg]),
                     // TODO: default to RS256
                     None => Ok(vec![Algorithm::EdDSA]),
                 }
@@ -116,22 +110,10 @@ impl Extension for SIOPv2 {
     async fn get_relying_party_supported_syntax_types(
         authorization_request: &<Self::RequestHandle as RequestHandle>::Parameters,
     ) -> anyhow::Result<Vec<SubjectSyntaxType>> {
-        let client_metadata = match &authorization_request.client_metadata {
-            // Fetch the client metadata from the given URI.
-            ClientMetadataResource::ClientMetadataUri(client_metadata_uri) => {
-                let retry_policy = ExponentialBackoff::builder().build_with_max_retries(5);
-                let client = ClientBuilder::new(reqwest::Client::new())
-                    .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-                    .build();
-                let client_metadata: ClientMetadataResource<ClientMetadataParameters> =
-                    client.get(client_metadata_uri).send().await?.json().await?;
-                client_metadata
+        match &authorization_request.client_metadata {
+            ClientMetadataResource::ClientMetadataUri(_) => {
+                Err(anyhow::anyhow!("The `client_metadata_uri` is not resolved."))
             }
-            client_metadata => client_metadata.clone(),
-        };
-
-        match client_metadata {
-            ClientMetadataResource::ClientMetadataUri(_) => unreachable!(),
             ClientMetadataResource::ClientMetadata {
                 extension:
                     ClientMetadataParameters {
@@ -139,13 +121,14 @@ impl Extension for SIOPv2 {
                         ..
                     },
                 ..
-            } => Ok(subject_syntax_types_supported),
+            } => Ok(subject_syntax_types_supported.clone()),
         }
     }
 
     fn build_authorization_response(
         jwts: Vec<String>,
         _user_input: <Self::ResponseHandle as ResponseHandle>::Input,
+        _extension_parameters: &<Self::RequestHandle as RequestHandle>::Parameters,
         redirect_uri: String,
         state: Option<String>,
     ) -> anyhow::Result<AuthorizationResponse<Self>> {
@@ -163,6 +146,7 @@ impl Extension for SIOPv2 {
     async fn decode_authorization_response(
         validator: Validator,
         authorization_response: &AuthorizationResponse<Self>,
+        _verification_context: &VerificationContext<Self>,
     ) -> anyhow::Result<<Self::ResponseHandle as ResponseHandle>::ResponseItem> {
         let token = authorization_response.extension.id_token.clone();
         validator.decode(token).await